@@ -0,0 +1,925 @@
+use either::Either;
+use hir::{HirDisplay, Local, Semantics, Type};
+use ide_db::{
+    defs::Definition,
+    search::{Reference, SearchScope},
+    RootDatabase,
+};
+use syntax::{
+    algo::{find_node_at_offset, SyntaxRewriter},
+    ast::{
+        self,
+        edit::{AstNodeEdit, IndentLevel},
+        make, ArgListOwner, AstNode, AstToken, GenericParamsOwner, NameOwner,
+    },
+    SyntaxKind, TextRange, TextSize, T,
+};
+
+use crate::{
+    assist_context::AssistBuilder, utils::vis_offset, AssistContext, AssistId, AssistKind, Assists,
+    GroupLabel,
+};
+
+// Assist: convert_closure_to_fn
+//
+// Converts a closure into a function, passing the captured variables as parameters.
+//
+// ```
+// struct Counter { count: u32 }
+//
+// fn main() {
+//     let counter = Counter { count: 0 };
+//     let add = <|>|a: u32| a + counter.count;
+//     add(1);
+// }
+// ```
+// ->
+// ```
+// struct Counter { count: u32 }
+//
+// fn main() {
+//     let counter = Counter { count: 0 };
+//     fn add(a: u32, counter: &Counter) -> u32 {
+//         a + counter.count
+//     }
+//     add(1, &counter);
+// }
+// ```
+pub(crate) fn convert_closure_to_fn(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let closure = ctx.find_node_at_offset::<ast::ClosureExpr>()?;
+    let body = closure.body()?;
+    if body.syntax().text_range().contains(ctx.offset()) {
+        return None;
+    }
+    let module = ctx.sema.scope(closure.syntax()).module()?;
+
+    let callable = ctx.sema.type_of_expr(&closure.clone().into())?.as_callable(ctx.db())?;
+    let mut params = Vec::new();
+    for (param, (_, ty)) in closure.param_list()?.params().zip(callable.params(ctx.db())) {
+        let pat = param.pat()?;
+        let ty = match param.ty() {
+            Some(it) => it.to_string(),
+            None => ty.display_source_code(ctx.db(), module.into()).ok()?,
+        };
+        params.push((pat, ty));
+    }
+    let ret_type = match closure.ret_type() {
+        Some(it) => Some(it.ty()?.to_string()),
+        None => {
+            let ty = callable.return_type();
+            if ty.is_unit() {
+                None
+            } else {
+                Some(ty.display_source_code(ctx.db(), module.into()).ok()?)
+            }
+        }
+    };
+    let captures = captured_locals(ctx, &closure)?;
+
+    let binding = closure
+        .syntax()
+        .parent()
+        .and_then(ast::LetStmt::cast)
+        .filter(|let_stmt| {
+            let_stmt.initializer().map(|it| it.syntax().clone()).as_ref() == Some(closure.syntax())
+        })
+        .and_then(|let_stmt| match let_stmt.pat()? {
+            ast::Pat::IdentPat(pat) => Some((let_stmt, pat)),
+            _ => None,
+        });
+    let call_sites = match &binding {
+        Some((_, pat)) => {
+            let local = ctx.sema.to_def(pat)?;
+            let call_sites = call_sites(ctx, local);
+            if call_sites.is_none() && !captures.is_empty() {
+                // The closure is used as a value, we can't pass the captures there.
+                return None;
+            }
+            call_sites.unwrap_or_default()
+        }
+        None => {
+            if !captures.is_empty()
+                && !params.iter().all(|(pat, _)| matches!(pat, ast::Pat::IdentPat(_)))
+            {
+                return None;
+            }
+            Vec::new()
+        }
+    };
+
+    let anchor_stmt = closure
+        .syntax()
+        .ancestors()
+        .find(|it| it.parent().and_then(ast::BlockExpr::cast).is_some())?;
+    let anchor_item = closure.syntax().ancestors().find(|it| {
+        let parent_kind = it.parent().map(|parent| parent.kind());
+        ast::Item::can_cast(it.kind())
+            && matches!(parent_kind, Some(SyntaxKind::SOURCE_FILE) | Some(SyntaxKind::ITEM_LIST))
+    })?;
+
+    let fn_name = match &binding {
+        Some((_, pat)) => pat.name()?.text().to_string(),
+        None => "fun_name".to_string(),
+    };
+    let signature = FnSignature { name: fn_name, params, ret_type, captures, body };
+
+    let group = GroupLabel("Convert closure to function".to_string());
+    let target = closure.syntax().text_range();
+    acc.add_group(
+        &group,
+        AssistId("convert_closure_to_fn", AssistKind::RefactorRewrite),
+        "Convert closure to local function",
+        target,
+        |builder| {
+            let indent = IndentLevel::from_node(&anchor_stmt);
+            let fn_text = signature.to_fn(&ctx.sema, indent).to_string();
+            match &binding {
+                Some((let_stmt, _)) => {
+                    builder.replace(let_stmt.syntax().text_range(), fn_text);
+                }
+                None => {
+                    builder.insert(
+                        anchor_stmt.text_range().start(),
+                        format!("{}\n{}", fn_text, indent),
+                    );
+                    builder.replace(target, signature.replacement_for_closure());
+                }
+            }
+            signature.update_call_sites(builder, &call_sites);
+        },
+    );
+    acc.add_group(
+        &group,
+        AssistId("convert_closure_to_fn", AssistKind::RefactorRewrite),
+        "Convert closure to module-level function",
+        target,
+        |builder| {
+            let indent = IndentLevel::from_node(&anchor_item);
+            let fn_text = signature.to_fn(&ctx.sema, indent).to_string();
+            builder.insert(anchor_item.text_range().end(), format!("\n\n{}{}", indent, fn_text));
+            match &binding {
+                Some((let_stmt, _)) => {
+                    let stmt = let_stmt.syntax();
+                    let range = match stmt.prev_sibling_or_token() {
+                        Some(ws) if ws.kind() == SyntaxKind::WHITESPACE => {
+                            TextRange::new(ws.text_range().start(), stmt.text_range().end())
+                        }
+                        _ => stmt.text_range(),
+                    };
+                    builder.delete(range);
+                }
+                None => builder.replace(target, signature.replacement_for_closure()),
+            }
+            signature.update_call_sites(builder, &call_sites);
+        },
+    )
+}
+
+struct Capture {
+    local: Local,
+    name: String,
+    ty: String,
+    pass_by: PassBy,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PassBy {
+    Value,
+    Ref,
+    MutRef,
+}
+
+struct FnSignature {
+    name: String,
+    params: Vec<(ast::Pat, String)>,
+    ret_type: Option<String>,
+    captures: Vec<Capture>,
+    body: ast::Expr,
+}
+
+impl FnSignature {
+    fn to_fn(&self, sema: &Semantics<RootDatabase>, indent: IndentLevel) -> ast::Fn {
+        let params =
+            self.params.iter().map(|(pat, ty)| make::param(pat.to_string(), ty.clone())).chain(
+                self.captures.iter().map(|capture| {
+                    let ty = match capture.pass_by {
+                        PassBy::Value => capture.ty.clone(),
+                        PassBy::Ref => format!("&{}", capture.ty),
+                        PassBy::MutRef => format!("&mut {}", capture.ty),
+                    };
+                    make::param(capture.name.clone(), ty)
+                }),
+            );
+
+        let body = self.rewrite_captures(sema);
+        let body = match body {
+            ast::Expr::BlockExpr(block) => {
+                let closure_indent = IndentLevel::from_node(self.body.syntax());
+                block.dedent(closure_indent)
+            }
+            expr => make::block_expr(None, Some(expr)),
+        };
+        let ret_type = self.ret_type.as_deref().map(|it| make::ret_type(make::ty(it)));
+
+        make::fn_(None, make::name(&self.name), None, make::param_list(params), body, ret_type)
+            .indent(indent)
+    }
+
+    /// References captured by reference have to be dereferenced explicitly, unless they
+    /// are auto-dereferenced anyway.
+    fn rewrite_captures(&self, sema: &Semantics<RootDatabase>) -> ast::Expr {
+        let mut rewriter = SyntaxRewriter::default();
+        for path_expr in self.body.syntax().descendants().filter_map(ast::PathExpr::cast) {
+            let local = match path_expr.path().and_then(|it| sema.resolve_path(&it)) {
+                Some(hir::PathResolution::Local(it)) => it,
+                _ => continue,
+            };
+            let by_ref = self
+                .captures
+                .iter()
+                .any(|capture| capture.local == local && capture.pass_by != PassBy::Value);
+            if !by_ref {
+                continue;
+            }
+            let parent_kind = path_expr.syntax().parent().map(|it| it.kind());
+            let auto_derefs = matches!(
+                parent_kind,
+                Some(SyntaxKind::METHOD_CALL_EXPR) | Some(SyntaxKind::FIELD_EXPR)
+            );
+            if auto_derefs {
+                continue;
+            }
+            let deref = make::expr_prefix(T![*], path_expr.clone().into());
+            rewriter.replace_ast(&ast::Expr::from(path_expr), &deref);
+        }
+        rewriter.rewrite_ast(&self.body)
+    }
+
+    fn replacement_for_closure(&self) -> String {
+        if self.captures.is_empty() {
+            return self.name.clone();
+        }
+        let params: Vec<String> = self.params.iter().map(|(pat, _)| pat.to_string()).collect();
+        let args: Vec<String> = params
+            .iter()
+            .cloned()
+            .chain(self.captures.iter().map(|capture| capture.as_arg()))
+            .collect();
+        format!("|{}| {}({})", params.join(", "), self.name, args.join(", "))
+    }
+
+    fn update_call_sites(&self, builder: &mut AssistBuilder, calls: &[ast::CallExpr]) {
+        if self.captures.is_empty() {
+            return;
+        }
+        let extra_args: Vec<String> = self.captures.iter().map(|it| it.as_arg()).collect();
+        let extra_args = extra_args.join(", ");
+        for call in calls {
+            let arg_list = match call.arg_list() {
+                Some(it) => it,
+                None => continue,
+            };
+            let r_paren = match arg_list.r_paren_token() {
+                Some(it) => it,
+                None => continue,
+            };
+            let has_trailing_comma = r_paren
+                .siblings_with_tokens(syntax::Direction::Prev)
+                .skip(1)
+                .find(|it| it.kind() != SyntaxKind::WHITESPACE)
+                .map(|it| it.kind())
+                == Some(T![,]);
+            let text = if arg_list.args().next().is_none() {
+                extra_args.clone()
+            } else if has_trailing_comma {
+                format!(" {}", extra_args)
+            } else {
+                format!(", {}", extra_args)
+            };
+            builder.insert(r_paren.text_range().start(), text);
+        }
+    }
+}
+
+impl Capture {
+    fn as_arg(&self) -> String {
+        match self.pass_by {
+            PassBy::Value => self.name.clone(),
+            PassBy::Ref => format!("&{}", self.name),
+            PassBy::MutRef => format!("&mut {}", self.name),
+        }
+    }
+}
+
+/// Collects the locals defined outside of the closure and used inside of it.
+fn captured_locals(ctx: &AssistContext, closure: &ast::ClosureExpr) -> Option<Vec<Capture>> {
+    let closure_range = closure.syntax().text_range();
+    let module = ctx.sema.scope(closure.syntax()).module()?;
+    let mut res: Vec<Capture> = Vec::new();
+    for path in closure.syntax().descendants().filter_map(ast::Path::cast) {
+        let local = match ctx.sema.resolve_path(&path) {
+            Some(hir::PathResolution::Local(it)) => it,
+            _ => continue,
+        };
+        if res.iter().any(|it| it.local == local) {
+            continue;
+        }
+        let source = local.source(ctx.db());
+        let pat = match source.value {
+            Either::Left(pat) => pat,
+            // There's no way to pass `self` to a function.
+            Either::Right(_) => return None,
+        };
+        if source.file_id.original_file(ctx.db()) == ctx.frange.file_id
+            && closure_range.contains_range(pat.syntax().text_range())
+        {
+            continue;
+        }
+        let ty: Type = local.ty(ctx.db());
+        if ty.is_mutable_reference() {
+            // The closure reborrows these, which can't be expressed with a plain argument.
+            return None;
+        }
+        let pass_by = pass_mode(ctx, closure, local, is_copy(ctx, &ty))?;
+        let ty = ty.display_source_code(ctx.db(), module.into()).ok()?;
+        res.push(Capture { local, name: local.name(ctx.db())?.to_string(), ty, pass_by });
+    }
+    Some(res)
+}
+
+/// Figures out how `local` has to be passed to the function from the way the closure uses it,
+/// or returns `None` if that can't be determined.
+fn pass_mode(
+    ctx: &AssistContext,
+    closure: &ast::ClosureExpr,
+    local: Local,
+    is_copy: bool,
+) -> Option<PassBy> {
+    let mut mutated = false;
+    let mut moved = false;
+    for path_expr in closure.syntax().descendants().filter_map(ast::PathExpr::cast) {
+        match path_expr.path().and_then(|it| ctx.sema.resolve_path(&it)) {
+            Some(hir::PathResolution::Local(it)) if it == local => (),
+            _ => continue,
+        }
+        match use_kind(ctx, ast::Expr::from(path_expr))? {
+            PassBy::MutRef => mutated = true,
+            PassBy::Value => moved = true,
+            PassBy::Ref => (),
+        }
+    }
+    let res = match (mutated, moved) {
+        // Moving a value and mutating it requires a `mut` parameter.
+        (true, true) => return None,
+        (true, _) => PassBy::MutRef,
+        (false, _) if is_copy || moved => PassBy::Value,
+        (false, _) => PassBy::Ref,
+    };
+    Some(res)
+}
+
+/// Classifies a single use of a captured local, following field accesses to the place that is
+/// actually used.
+fn use_kind(ctx: &AssistContext, expr: ast::Expr) -> Option<PassBy> {
+    let mut place = expr.clone();
+    loop {
+        let parent = place.syntax().parent()?;
+        if let Some(field) = ast::FieldExpr::cast(parent.clone()) {
+            place = field.into();
+            continue;
+        }
+        if let Some(call) = ast::MethodCallExpr::cast(parent.clone()) {
+            if call.receiver().as_ref() == Some(&place) {
+                let self_param = ctx.sema.resolve_method_call(&call)?.self_param(ctx.db())?;
+                return match self_param.access(ctx.db()) {
+                    hir::Access::Shared => Some(PassBy::Ref),
+                    hir::Access::Exclusive => Some(PassBy::MutRef),
+                    hir::Access::Owned => moved_place(ctx, &expr, &place),
+                };
+            }
+        }
+        if let Some(ref_expr) = ast::RefExpr::cast(parent.clone()) {
+            let pass_by = if ref_expr.mut_token().is_some() { PassBy::MutRef } else { PassBy::Ref };
+            return Some(pass_by);
+        }
+        if let Some(bin_expr) = ast::BinExpr::cast(parent) {
+            let is_assignment = bin_expr.op_kind().map_or(false, |op| op.is_assignment());
+            if is_assignment && bin_expr.lhs().as_ref() == Some(&place) {
+                return Some(PassBy::MutRef);
+            }
+        }
+        return moved_place(ctx, &expr, &place);
+    }
+}
+
+/// A place used by value is copied if it can be, and moved otherwise. Moving out of a field
+/// isn't supported.
+fn moved_place(ctx: &AssistContext, local: &ast::Expr, place: &ast::Expr) -> Option<PassBy> {
+    if is_copy(ctx, &ctx.sema.type_of_expr(place)?) {
+        Some(PassBy::Ref)
+    } else if place == local {
+        Some(PassBy::Value)
+    } else {
+        None
+    }
+}
+
+/// Scalars are checked separately, since they are `Copy` even when the `Copy` trait can't be
+/// resolved.
+fn is_copy(ctx: &AssistContext, ty: &Type) -> bool {
+    ty.is_scalar() || ty.is_copy(ctx.db())
+}
+
+/// Returns all the calls of `local`, or `None` if it is used in any other way.
+fn call_sites(ctx: &AssistContext, local: Local) -> Option<Vec<ast::CallExpr>> {
+    let source_file = ctx.sema.parse(ctx.frange.file_id);
+    let usages: Vec<Reference> = Definition::Local(local)
+        .usages(&ctx.sema)
+        .in_scope(SearchScope::single_file(ctx.frange.file_id))
+        .all();
+    let mut res = Vec::new();
+    for usage in usages {
+        let call = find_node_at_offset::<ast::PathExpr>(
+            source_file.syntax(),
+            usage.file_range.range.start(),
+        )
+        .and_then(|path_expr| {
+            let call = path_expr.syntax().parent().and_then(ast::CallExpr::cast)?;
+            if call.expr()?.syntax() == path_expr.syntax() {
+                Some(call)
+            } else {
+                None
+            }
+        });
+        match call {
+            Some(call) => res.push(call),
+            None => return None,
+        }
+    }
+    Some(res)
+}
+
+// Assist: convert_fn_to_closure
+//
+// Converts a local function into a closure bound to a variable.
+//
+// ```
+// fn main() {
+//     fn <|>add(a: i32, b: i32) -> i32 {
+//         a + b
+//     }
+//     add(1, 2);
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let add = |a: i32, b: i32| -> i32 {
+//         a + b
+//     };
+//     add(1, 2);
+// }
+// ```
+pub(crate) fn convert_fn_to_closure(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let name = ctx.find_node_at_offset::<ast::Name>()?;
+    let fn_ = name.syntax().parent().and_then(ast::Fn::cast)?;
+    let block = fn_.syntax().parent().and_then(ast::BlockExpr::cast)?;
+    if fn_.generic_param_list().is_some()
+        || fn_.where_clause().is_some()
+        || fn_.async_token().is_some()
+        || fn_.const_token().is_some()
+        || fn_.unsafe_token().is_some()
+        || fn_.abi().is_some()
+    {
+        return None;
+    }
+    let param_list = fn_.param_list()?;
+    if param_list.self_param().is_some() {
+        return None;
+    }
+    let body = fn_.body()?;
+
+    // Local functions are not in scope of their own body, so we can't rely on name resolution
+    // to detect recursion.
+    let is_recursive = body
+        .syntax()
+        .descendants()
+        .filter_map(ast::NameRef::cast)
+        .any(|name_ref| name_ref.text() == name.text());
+    if is_recursive {
+        return None;
+    }
+
+    let func = ctx.sema.to_def(&fn_)?;
+    let fn_range = fn_.syntax().text_range();
+    let usages = Definition::ModuleDef(func.into())
+        .usages(&ctx.sema)
+        .in_scope(SearchScope::single_file(ctx.frange.file_id))
+        .all();
+    let block_range = block.syntax().text_range();
+    let convertible = usages.iter().all(|reference| {
+        let range = reference.file_range.range;
+        // A `let` binding is only visible after it is declared, and can't refer to itself.
+        block_range.contains_range(range) && range.start() >= fn_range.end()
+    });
+    if !convertible {
+        return None;
+    }
+
+    acc.add(
+        AssistId("convert_fn_to_closure", AssistKind::RefactorRewrite),
+        "Convert function to closure",
+        fn_range,
+        |builder| {
+            let params: Vec<String> = param_list.params().map(|it| it.to_string()).collect();
+            let closure_body = match (fn_.ret_type(), trivial_tail_expr(&body)) {
+                (None, Some(expr)) => expr.to_string(),
+                _ => body.to_string(),
+            };
+            let ret_type = match fn_.ret_type() {
+                Some(it) => format!(" {}", it),
+                None => String::new(),
+            };
+            let closure =
+                format!("let {} = |{}|{} {};", name, params.join(", "), ret_type, closure_body);
+            let start: TextSize = vis_offset(fn_.syntax());
+            builder.replace(TextRange::new(start, fn_range.end()), closure);
+            // Doc comments on statements are unused, turn them into regular ones.
+            let doc_comments = fn_
+                .syntax()
+                .children_with_tokens()
+                .filter_map(|it| it.into_token().and_then(ast::Comment::cast))
+                .filter(|it| it.kind().doc.is_some());
+            for comment in doc_comments {
+                let prefix = comment.prefix();
+                let range =
+                    TextRange::at(comment.syntax().text_range().start(), TextSize::of(prefix));
+                builder.replace(range, &prefix[..prefix.len() - 1]);
+            }
+        },
+    )
+}
+
+fn trivial_tail_expr(block: &ast::BlockExpr) -> Option<ast::Expr> {
+    if block.statements().next().is_some() {
+        return None;
+    }
+    block.expr().filter(|it| !it.syntax().text().contains_char('\n'))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn closure_without_captures_to_local_fn() {
+        check_assist_by_label(
+            convert_closure_to_fn,
+            r#"
+fn main() {
+    let double = <|>|x: u32| x * 2;
+    double(2);
+}
+"#,
+            r#"
+fn main() {
+    fn double(x: u32) -> u32 {
+        x * 2
+    }
+    double(2);
+}
+"#,
+            "Convert closure to local function",
+        );
+    }
+
+    #[test]
+    fn closure_infers_param_types() {
+        check_assist_by_label(
+            convert_closure_to_fn,
+            r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for i32 {}
+impl Copy for u32 {}
+
+fn main() {
+    let bias = 2;
+    let add = <|>|a: i32, b| {
+        let sum = a + b;
+        sum + bias
+    };
+    add(1, 2);
+    add(3, 4,);
+}
+"#,
+            r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for i32 {}
+impl Copy for u32 {}
+
+fn main() {
+    let bias = 2;
+    fn add(a: i32, b: i32, bias: i32) -> i32 {
+        let sum = a + b;
+        sum + bias
+    }
+    add(1, 2, bias);
+    add(3, 4, bias);
+}
+"#,
+            "Convert closure to local function",
+        );
+    }
+
+    #[test]
+    fn closure_with_non_copy_and_mutated_captures() {
+        check_assist_by_label(
+            convert_closure_to_fn,
+            r#"
+struct Log { len: usize }
+impl Log { fn len(&self) -> usize { self.len } }
+
+fn main() {
+    let log = Log { len: 0 };
+    let mut count = 0usize;
+    let mut tick = <|>|| {
+        count += log.len();
+    };
+    tick();
+}
+"#,
+            r#"
+struct Log { len: usize }
+impl Log { fn len(&self) -> usize { self.len } }
+
+fn main() {
+    let log = Log { len: 0 };
+    let mut count = 0usize;
+    fn tick(count: &mut usize, log: &Log) {
+        *count += log.len();
+    }
+    tick(&mut count, &log);
+}
+"#,
+            "Convert closure to local function",
+        );
+    }
+
+    #[test]
+    fn closure_with_autoref_and_moved_captures() {
+        check_assist_by_label(
+            convert_closure_to_fn,
+            r#"
+struct Line;
+struct Log { len: usize }
+impl Log { fn push(&mut self, line: Line) { self.len += 1 } }
+
+fn main() {
+    let mut log = Log { len: 0 };
+    let line = Line;
+    let write = <|>|| log.push(line);
+    write();
+}
+"#,
+            r#"
+struct Line;
+struct Log { len: usize }
+impl Log { fn push(&mut self, line: Line) { self.len += 1 } }
+
+fn main() {
+    let mut log = Log { len: 0 };
+    let line = Line;
+    fn write(log: &mut Log, line: Line) {
+        log.push(line)
+    }
+    write(&mut log, line);
+}
+"#,
+            "Convert closure to local function",
+        );
+    }
+
+    #[test]
+    fn closure_moving_out_of_field_not_applicable() {
+        check_assist_not_applicable(
+            convert_closure_to_fn,
+            r#"
+struct Line;
+struct Log { line: Line }
+
+fn main() {
+    let log = Log { line: Line };
+    let take = <|>|| log.line;
+    take();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_closure_to_local_fn() {
+        check_assist_by_label(
+            convert_closure_to_fn,
+            r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for i32 {}
+impl Copy for u32 {}
+
+fn apply<F>(f: F) {}
+
+fn main() {
+    let bias = 1u32;
+    apply(<|>|x: u32| x + bias);
+}
+"#,
+            r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for i32 {}
+impl Copy for u32 {}
+
+fn apply<F>(f: F) {}
+
+fn main() {
+    let bias = 1u32;
+    fn fun_name(x: u32, bias: u32) -> u32 {
+        x + bias
+    }
+    apply(|x| fun_name(x, bias));
+}
+"#,
+            "Convert closure to local function",
+        );
+    }
+
+    #[test]
+    fn closure_to_module_level_fn() {
+        check_assist_by_label(
+            convert_closure_to_fn,
+            r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for i32 {}
+impl Copy for u32 {}
+
+fn main() {
+    let bias = 2;
+    let add = <|>|a: i32| a + bias;
+    add(1);
+}
+"#,
+            r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for i32 {}
+impl Copy for u32 {}
+
+fn main() {
+    let bias = 2;
+    add(1, bias);
+}
+
+fn add(a: i32, bias: i32) -> i32 {
+    a + bias
+}
+"#,
+            "Convert closure to module-level function",
+        );
+    }
+
+    #[test]
+    fn closure_used_as_value_with_captures_not_applicable() {
+        check_assist_not_applicable(
+            convert_closure_to_fn,
+            r#"
+fn apply(f: impl Fn(u32) -> u32) {}
+
+fn main() {
+    let bias = 1u32;
+    let add = <|>|x: u32| x + bias;
+    apply(add);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closure_capturing_self_not_applicable() {
+        check_assist_not_applicable(
+            convert_closure_to_fn,
+            r#"
+struct S(u32);
+impl S {
+    fn f(&self) {
+        let g = <|>|| self.0;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closure_not_applicable_in_body() {
+        check_assist_not_applicable(
+            convert_closure_to_fn,
+            r#"
+fn main() {
+    let f = |x: u32| x <|>+ 1;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fn_to_closure_trivial_body() {
+        check_assist(
+            convert_fn_to_closure,
+            r#"
+fn main() {
+    fn <|>double(x: u32) {
+        x * 2;
+    }
+    double(2);
+}
+"#,
+            r#"
+fn main() {
+    let double = |x: u32| {
+        x * 2;
+    };
+    double(2);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fn_to_closure_unwraps_tail_expr() {
+        check_assist(
+            convert_fn_to_closure,
+            r#"
+fn main() {
+    /// Doubles.
+    fn <|>double(x: u32) {
+        foo(x)
+    }
+    double(2);
+}
+"#,
+            r#"
+fn main() {
+    // Doubles.
+    let double = |x: u32| foo(x);
+    double(2);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fn_to_closure_not_applicable_when_used_before() {
+        check_assist_not_applicable(
+            convert_fn_to_closure,
+            r#"
+fn main() {
+    double(2);
+    fn <|>double(x: u32) -> u32 {
+        x * 2
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fn_to_closure_not_applicable_for_recursive_fn() {
+        check_assist_not_applicable(
+            convert_fn_to_closure,
+            r#"
+fn main() {
+    fn <|>fact(x: u32) -> u32 {
+        if x == 0 { 1 } else { x * fact(x - 1) }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fn_to_closure_not_applicable_for_generic_and_top_level_fns() {
+        check_assist_not_applicable(convert_fn_to_closure, "fn <|>foo() {}");
+        check_assist_not_applicable(
+            convert_fn_to_closure,
+            r#"
+fn main() {
+    fn <|>id<T>(x: T) -> T { x }
+}
+"#,
+        );
+    }
+}
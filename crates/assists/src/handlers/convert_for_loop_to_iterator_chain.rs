@@ -0,0 +1,504 @@
+use hir::HirDisplay;
+use ide_db::{
+    defs::Definition,
+    search::{ReferenceAccess, SearchScope},
+};
+use syntax::{
+    ast::{self, ArgListOwner, LoopBodyOwner, NameOwner},
+    AstNode,
+    SyntaxKind::*,
+    SyntaxNode, TextRange,
+};
+
+use crate::{
+    utils::{escaping_jumps, for_iterable_to_iterator, invert_boolean_expression},
+    AssistContext, AssistId, AssistKind, Assists,
+};
+
+// Assist: convert_for_loop_to_iterator_chain
+//
+// Converts a `for` loop which filters and pushes into a `Vec`, or sums up
+// values, into an iterator chain.
+//
+// ```
+// fn main() {
+//     let xs = vec![1, 2, 3];
+//     let mut evens = Vec::new();
+//     for<|> x in xs {
+//         if x % 2 == 0 {
+//             evens.push(x * 10);
+//         }
+//     }
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let xs = vec![1, 2, 3];
+//     let evens: Vec<_> = xs.into_iter().filter(|x| x % 2 == 0).map(|x| x * 10).collect();
+// }
+// ```
+pub(crate) fn convert_for_loop_to_iterator_chain(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+) -> Option<()> {
+    let for_loop = ctx.find_node_at_offset::<ast::ForExpr>()?;
+    let body = for_loop.loop_body()?;
+    if body.syntax().text_range().contains(ctx.offset()) || for_loop.label().is_some() {
+        return None;
+    }
+    let pat = for_loop.pat()?;
+    let iterable = for_loop.iterable()?;
+
+    let mut filters = Vec::new();
+    let accumulation = accumulation_in(body, &mut filters)?;
+    let accumulator = accumulation.accumulator();
+    let mentions_accumulator = |expr: &ast::Expr| {
+        expr.syntax()
+            .descendants()
+            .filter_map(ast::NameRef::cast)
+            .any(|it| accumulator.syntax().text() == it.text().as_str())
+    };
+    for expr in filters.iter().chain(Some(accumulation.item())) {
+        if !escaping_jumps(expr.syntax()).is_empty() || mentions_accumulator(expr) {
+            return None;
+        }
+    }
+
+    let stmt = match for_loop.syntax().parent() {
+        Some(parent) if parent.kind() == EXPR_STMT => parent,
+        _ => for_loop.syntax().clone(),
+    };
+    let init = stmt
+        .prev_sibling()
+        .and_then(ast::LetStmt::cast)
+        .filter(|it| initializes(it, &accumulation));
+
+    let item_is_copy = ctx.sema.type_of_pat(&pat).map(|it| it.is_copy(ctx.db())).unwrap_or(false);
+    let filter_pat = if item_is_copy { format!("&{}", pat) } else { pat.to_string() };
+    let mut chain = for_iterable_to_iterator(&ctx.sema, &iterable);
+    for filter in &filters {
+        chain += &format!(".filter(|{}| {})", filter_pat, filter);
+    }
+    let item = accumulation.item();
+    if item.syntax().text() != pat.syntax().text() {
+        chain += &format!(".map(|{}| {})", pat, item);
+    }
+
+    let module = ctx.sema.scope(for_loop.syntax()).module()?;
+    let (range, replacement) = match (&accumulation, init) {
+        (Accumulation::Push { .. }, Some(init)) => {
+            let ty = init.ty().map_or_else(|| "Vec<_>".to_string(), |it| it.to_string());
+            let pat = binding(ctx, &init, &stmt)?;
+            (init_range(&init, &stmt), format!("let {}: {} = {}.collect();", pat, ty, chain))
+        }
+        (Accumulation::Sum { .. }, Some(init)) => {
+            let ty = match init.ty() {
+                Some(it) => it.to_string(),
+                None => ctx
+                    .sema
+                    .type_of_pat(&init.pat()?)?
+                    .display_source_code(ctx.db(), module.into())
+                    .ok()?,
+            };
+            let pat = binding(ctx, &init, &stmt)?;
+            (init_range(&init, &stmt), format!("let {}: {} = {}.sum();", pat, ty, chain))
+        }
+        (Accumulation::Push { .. }, None) => {
+            (stmt.text_range(), format!("{}.extend({});", accumulator, chain))
+        }
+        (Accumulation::Sum { .. }, None) => {
+            let ty = ctx
+                .sema
+                .type_of_expr(&accumulator.clone().into())?
+                .display_source_code(ctx.db(), module.into())
+                .ok()?;
+            (stmt.text_range(), format!("{} += {}.sum::<{}>();", accumulator, chain, ty))
+        }
+    };
+
+    acc.add(
+        AssistId("convert_for_loop_to_iterator_chain", AssistKind::RefactorRewrite),
+        "Convert for loop into iterator chain",
+        for_loop.syntax().text_range(),
+        |builder| builder.replace(range, replacement),
+    )
+}
+
+enum Accumulation {
+    /// `acc.push(item);`
+    Push { accumulator: ast::PathExpr, item: ast::Expr },
+    /// `acc += item;`
+    Sum { accumulator: ast::PathExpr, item: ast::Expr },
+}
+
+impl Accumulation {
+    fn accumulator(&self) -> &ast::PathExpr {
+        match self {
+            Accumulation::Push { accumulator, .. } | Accumulation::Sum { accumulator, .. } => {
+                accumulator
+            }
+        }
+    }
+
+    fn item(&self) -> &ast::Expr {
+        match self {
+            Accumulation::Push { item, .. } | Accumulation::Sum { item, .. } => item,
+        }
+    }
+}
+
+/// Matches the loop body against `if cond { continue; } ... acc.push(item);`,
+/// descending into `if cond { ... }` blocks, and records the conditions an
+/// item has to satisfy in `filters`.
+fn accumulation_in(block: ast::BlockExpr, filters: &mut Vec<ast::Expr>) -> Option<Accumulation> {
+    let mut exprs = Vec::new();
+    for stmt in block.statements() {
+        match stmt {
+            ast::Stmt::ExprStmt(it) => exprs.push(it.expr()?),
+            ast::Stmt::LetStmt(_) | ast::Stmt::Item(_) => return None,
+        }
+    }
+    exprs.extend(block.expr());
+
+    let (last, guards) = exprs.split_last()?;
+    for guard in guards {
+        let if_expr = match guard {
+            ast::Expr::IfExpr(it) if it.else_branch().is_none() => it,
+            _ => return None,
+        };
+        let then_branch = if_expr.then_branch()?;
+        let is_continue = match (then_branch.statements().next(), then_branch.expr()) {
+            (Some(ast::Stmt::ExprStmt(stmt)), None) => {
+                then_branch.statements().nth(1).is_none() && is_plain_continue(stmt.expr()?)
+            }
+            (None, Some(expr)) => is_plain_continue(expr),
+            _ => false,
+        };
+        if !is_continue {
+            return None;
+        }
+        filters.push(invert_boolean_expression(condition(if_expr)?));
+    }
+
+    match last {
+        ast::Expr::IfExpr(if_expr) if if_expr.else_branch().is_none() => {
+            filters.push(condition(if_expr)?);
+            accumulation_in(if_expr.then_branch()?, filters)
+        }
+        ast::Expr::MethodCallExpr(call) if call.name_ref()?.text() == "push" => {
+            let accumulator = local_path(call.receiver()?)?;
+            let mut args = call.arg_list()?.args();
+            match (args.next()?, args.next()) {
+                (item, None) => Some(Accumulation::Push { accumulator, item }),
+                _ => None,
+            }
+        }
+        ast::Expr::BinExpr(bin) if bin.op_kind()? == ast::BinOp::AddAssign => {
+            let accumulator = local_path(bin.lhs()?)?;
+            Some(Accumulation::Sum { accumulator, item: bin.rhs()? })
+        }
+        _ => None,
+    }
+}
+
+fn condition(if_expr: &ast::IfExpr) -> Option<ast::Expr> {
+    let cond = if_expr.condition()?;
+    if cond.pat().is_some() {
+        return None;
+    }
+    cond.expr()
+}
+
+fn is_plain_continue(expr: ast::Expr) -> bool {
+    matches!(expr, ast::Expr::ContinueExpr(it) if it.lifetime().is_none())
+}
+
+fn local_path(expr: ast::Expr) -> Option<ast::PathExpr> {
+    match expr {
+        ast::Expr::PathExpr(it) if it.path()?.qualifier().is_none() => Some(it),
+        _ => None,
+    }
+}
+
+/// Checks whether `let_stmt` declares the accumulator with an empty initial value.
+fn initializes(let_stmt: &ast::LetStmt, accumulation: &Accumulation) -> bool {
+    let name = match let_stmt.pat() {
+        Some(ast::Pat::IdentPat(it)) => it.name(),
+        _ => None,
+    };
+    if name.map(|it| it.text().to_string()) != Some(accumulation.accumulator().to_string()) {
+        return false;
+    }
+    match (accumulation, let_stmt.initializer()) {
+        (Accumulation::Push { .. }, Some(ast::Expr::CallExpr(call))) => {
+            let is_new = call.expr().map(|it| it.syntax().text() == "Vec::new").unwrap_or(false);
+            is_new && call.arg_list().and_then(|it| it.args().next()).is_none()
+        }
+        (Accumulation::Push { .. }, Some(ast::Expr::MacroCall(mac))) => {
+            let is_vec = mac.path().map(|it| it.syntax().text() == "vec").unwrap_or(false);
+            let tt = mac.token_tree().map(|it| it.syntax().text().to_string());
+            is_vec && matches!(tt.as_deref(), Some("[]") | Some("()"))
+        }
+        (Accumulation::Sum { .. }, Some(ast::Expr::Literal(lit))) => is_zero(&lit),
+        _ => false,
+    }
+}
+
+fn is_zero(lit: &ast::Literal) -> bool {
+    let text = lit.syntax().text().to_string();
+    let (digits, suffix) = match text.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(idx) => text.split_at(idx),
+        None => (text.as_str(), ""),
+    };
+    let valid_suffix = suffix.is_empty() || suffix.starts_with(&['i', 'u', 'f'][..]);
+    digits.starts_with('0') && digits.chars().all(|c| matches!(c, '0' | '.' | '_')) && valid_suffix
+}
+
+/// Renders the pattern of the new binding, keeping `mut` only if the
+/// accumulator is still modified after the loop.
+fn binding(ctx: &AssistContext, init: &ast::LetStmt, stmt: &SyntaxNode) -> Option<String> {
+    let ident = match init.pat()? {
+        ast::Pat::IdentPat(it) => it,
+        _ => return None,
+    };
+    let local = ctx.sema.to_def(&ident)?;
+    let file_id = ctx.frange.file_id;
+    let is_mutated_later = Definition::Local(local)
+        .usages(&ctx.sema)
+        .in_scope(SearchScope::single_file(file_id))
+        .all()
+        .into_iter()
+        .any(|it| {
+            it.access == Some(ReferenceAccess::Write)
+                && it.file_range.range.start() >= stmt.text_range().end()
+        });
+    let name = ident.name()?;
+    Some(if is_mutated_later { format!("mut {}", name) } else { name.to_string() })
+}
+
+fn init_range(init: &ast::LetStmt, stmt: &SyntaxNode) -> TextRange {
+    TextRange::new(init.syntax().text_range().start(), stmt.text_range().end())
+}
+
+#[cfg(test)]
+mod tests {
+    use ide_db::helpers::FamousDefs;
+
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn push_with_filter() {
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            r"
+fn main() {
+    let xs = vec![1, 2, 3];
+    let mut evens = Vec::new();
+    for<|> x in xs {
+        if x % 2 == 0 {
+            evens.push(x * 10);
+        }
+    }
+    foo(evens);
+}
+",
+            r"
+fn main() {
+    let xs = vec![1, 2, 3];
+    let evens: Vec<_> = xs.into_iter().filter(|x| x % 2 == 0).map(|x| x * 10).collect();
+    foo(evens);
+}
+",
+        )
+    }
+
+    #[test]
+    fn push_with_continue_guards() {
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            r"
+fn main() {
+    let mut names: Vec<String> = vec![];
+    for<|> user in &users {
+        if user.is_admin() {
+            continue;
+        }
+        if user.name.is_empty() { continue }
+        names.push(user.name.clone());
+    }
+}
+",
+            r"
+fn main() {
+    let names: Vec<String> = users.iter().filter(|user| !user.is_admin()).filter(|user| !user.name.is_empty()).map(|user| user.name.clone()).collect();
+}
+",
+        )
+    }
+
+    #[test]
+    fn push_without_init_extends() {
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            r"
+fn main() {
+    let mut out = Vec::new();
+    foo(&mut out);
+    for<|> x in 0..10 {
+        out.push(x);
+    }
+}
+",
+            r"
+fn main() {
+    let mut out = Vec::new();
+    foo(&mut out);
+    out.extend((0..10).into_iter());
+}
+",
+        )
+    }
+
+    #[test]
+    fn sum_of_copy_items() {
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            &format!(
+                "//- /main.rs crate:main deps:core{}{}",
+                r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for i32 {}
+
+fn main() {
+    let mut total = 0;
+    for<|> x in core::iter::repeat(3).take(5) {
+        if x > 2 {
+            total += x * x;
+        }
+    }
+    total += 1;
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for i32 {}
+
+fn main() {
+    let mut total: i32 = core::iter::repeat(3).take(5).filter(|&x| x > 2).map(|x| x * x).sum();
+    total += 1;
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn sum_into_existing_accumulator() {
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            r"
+fn main(mut total: u64, xs: Vec<u64>) {
+    for<|> x in xs {
+        total += x;
+    }
+}
+",
+            r"
+fn main(mut total: u64, xs: Vec<u64>) {
+    total += xs.into_iter().sum::<u64>();
+}
+",
+        )
+    }
+
+    #[test]
+    fn not_applicable_with_escaping_jumps() {
+        check_assist_not_applicable(
+            convert_for_loop_to_iterator_chain,
+            r"
+fn main() {
+    let mut out = Vec::new();
+    for<|> x in xs {
+        if x == 0 {
+            break;
+        }
+        out.push(x);
+    }
+}
+",
+        );
+        check_assist_not_applicable(
+            convert_for_loop_to_iterator_chain,
+            r"
+fn main() -> Option<()> {
+    let mut out = Vec::new();
+    for<|> x in xs {
+        out.push(x.parse()?);
+    }
+    None
+}
+",
+        );
+        check_assist_not_applicable(
+            convert_for_loop_to_iterator_chain,
+            r"
+fn main() {
+    let mut out = Vec::new();
+    for<|> x in xs {
+        if x == 0 {
+            return;
+        }
+        out.push(x);
+    }
+}
+",
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_other_loops() {
+        check_assist_not_applicable(
+            convert_for_loop_to_iterator_chain,
+            r"
+fn main() {
+    let mut out = Vec::new();
+    for<|> x in xs {
+        let y = x * 2;
+        out.push(y);
+    }
+}
+",
+        );
+        check_assist_not_applicable(
+            convert_for_loop_to_iterator_chain,
+            r"
+fn main() {
+    let mut out = Vec::new();
+    for<|> x in xs {
+        out.push(out.len() + x);
+    }
+}
+",
+        );
+        check_assist_not_applicable(
+            convert_for_loop_to_iterator_chain,
+            r"
+fn main() {
+    for<|> x in xs {
+        if x > 0 {
+            foo(x);
+        } else {
+            out.push(x);
+        }
+    }
+}
+",
+        );
+    }
+}
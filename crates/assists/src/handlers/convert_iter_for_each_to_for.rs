@@ -0,0 +1,511 @@
+use syntax::{
+    algo::SyntaxRewriter,
+    ast::{self, edit::AstNodeEdit, make, ArgListOwner, LoopBodyOwner},
+    AstNode,
+    SyntaxKind::*,
+};
+
+use crate::{
+    utils::{escaping_jumps, for_iterable_to_iterator, Jump},
+    AssistContext, AssistId, AssistKind, Assists,
+};
+
+// Assist: convert_for_to_iter_for_each
+//
+// Converts a for loop into a `for_each` call on the iterator.
+//
+// ```
+// fn main() {
+//     let x = vec![1, 2, 3];
+//     for<|> v in x {
+//         let y = v * 2;
+//     }
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let x = vec![1, 2, 3];
+//     x.into_iter().for_each(|v| {
+//         let y = v * 2;
+//     });
+// }
+// ```
+pub(crate) fn convert_for_to_iter_for_each(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let for_loop = ctx.find_node_at_offset::<ast::ForExpr>()?;
+    let body = for_loop.loop_body()?;
+    if body.syntax().text_range().contains(ctx.offset()) || for_loop.label().is_some() {
+        return None;
+    }
+    let pat = for_loop.pat()?;
+    let iterable = for_loop.iterable()?;
+
+    // Only plain `continue`s can be expressed inside the closure, as `return`s.
+    let mut rewriter = SyntaxRewriter::default();
+    for jump in escaping_jumps(body.syntax()) {
+        match jump {
+            Jump::Continue(it) if it.lifetime().is_none() => {
+                rewriter.replace(it.syntax(), make::expr_return().syntax())
+            }
+            _ => return None,
+        }
+    }
+
+    acc.add(
+        AssistId("convert_for_to_iter_for_each", AssistKind::RefactorRewrite),
+        "Convert a for loop into an Iterator::for_each",
+        for_loop.syntax().text_range(),
+        |builder| {
+            let receiver = for_iterable_to_iterator(&ctx.sema, &iterable);
+            let body = rewriter.rewrite(body.syntax());
+            let parent = for_loop.syntax().parent();
+            let needs_semicolon = match parent.clone().and_then(ast::ExprStmt::cast) {
+                Some(stmt) => stmt.semicolon_token().is_none(),
+                None => parent.map(|it| it.kind()) == Some(BLOCK_EXPR),
+            };
+            let semicolon = if needs_semicolon { ";" } else { "" };
+            let replacement = format!("{}.for_each(|{}| {}){}", receiver, pat, body, semicolon);
+            builder.replace(for_loop.syntax().text_range(), replacement)
+        },
+    )
+}
+
+// Assist: convert_iter_for_each_to_for
+//
+// Converts an `Iterator::for_each` call into a for loop.
+//
+// ```
+// fn main() {
+//     let x = vec![1, 2, 3];
+//     x.iter().for_each<|>(|v| {
+//         let y = v * 2;
+//     });
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let x = vec![1, 2, 3];
+//     for v in &x {
+//         let y = v * 2;
+//     }
+// }
+// ```
+pub(crate) fn convert_iter_for_each_to_for(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let method_call = ctx.find_node_at_offset::<ast::MethodCallExpr>()?;
+    if method_call.name_ref()?.text() != "for_each" {
+        return None;
+    }
+    let stmt = method_call.syntax().parent().and_then(ast::ExprStmt::cast);
+    if stmt.is_none() && method_call.syntax().parent().and_then(ast::BlockExpr::cast).is_none() {
+        return None;
+    }
+
+    let mut args = method_call.arg_list()?.args();
+    let closure = match (args.next()?, args.next()) {
+        (ast::Expr::ClosureExpr(closure), None) => closure,
+        _ => return None,
+    };
+    let body = closure.body()?;
+    if body.syntax().text_range().contains(ctx.offset()) {
+        return None;
+    }
+    let mut params = closure.param_list()?.params();
+    let pat = match (params.next()?.pat()?, params.next()) {
+        (pat, None) => pat,
+        _ => return None,
+    };
+
+    // Returning from the closure moves on to the next iteration of the loop, unless the
+    // `return` sits inside another loop which would catch the `continue`.
+    let mut rewriter = SyntaxRewriter::default();
+    for jump in escaping_jumps(body.syntax()) {
+        match jump {
+            Jump::Return(it) if it.expr().is_none() && !is_in_loop(it.syntax(), &body) => {
+                rewriter.replace(it.syntax(), make::expr_continue().syntax())
+            }
+            _ => return None,
+        }
+    }
+
+    let receiver = method_call.receiver()?;
+    let target = stmt.as_ref().map_or(method_call.syntax(), |it| it.syntax()).text_range();
+    acc.add(
+        AssistId("convert_iter_for_each_to_for", AssistKind::RefactorRewrite),
+        "Convert an Iterator::for_each into a for loop",
+        target,
+        |builder| {
+            let iterable = iterable_from_receiver(receiver);
+            let body = match rewriter.rewrite_ast(&body) {
+                ast::Expr::BlockExpr(block) => block,
+                expr => make::block_expr(Some(make::expr_stmt(expr).into()), None)
+                    .indent(method_call.indent_level()),
+            };
+            builder.replace(target, format!("for {} in {} {}", pat, iterable, body))
+        },
+    )
+}
+
+fn is_in_loop(node: &syntax::SyntaxNode, body: &ast::Expr) -> bool {
+    node.ancestors()
+        .take_while(|it| it != body.syntax())
+        .any(|it| matches!(it.kind(), LOOP_EXPR | WHILE_EXPR | FOR_EXPR))
+}
+
+/// Undoes the iterator adapters a `for` loop applies on its own.
+fn iterable_from_receiver(receiver: ast::Expr) -> String {
+    if let ast::Expr::MethodCallExpr(call) = &receiver {
+        let no_args = call.arg_list().and_then(|it| it.args().next()).is_none();
+        if let (Some(inner), Some(name), true) = (call.receiver(), call.name_ref(), no_args) {
+            match name.text().as_str() {
+                "iter" => return format!("&{}", inner),
+                "iter_mut" => return format!("&mut {}", inner),
+                "into_iter" => return inner.to_string(),
+                _ => (),
+            }
+        }
+    }
+    receiver.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use ide_db::helpers::FamousDefs;
+
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn for_to_for_each_into_iter() {
+        check_assist(
+            convert_for_to_iter_for_each,
+            r"
+fn main() {
+    let x = vec![1, 2, 3];
+    for <|>v in x {
+        let y = v * 2;
+    }
+}
+",
+            r"
+fn main() {
+    let x = vec![1, 2, 3];
+    x.into_iter().for_each(|v| {
+        let y = v * 2;
+    });
+}
+",
+        )
+    }
+
+    #[test]
+    fn for_to_for_each_by_ref() {
+        check_assist(
+            convert_for_to_iter_for_each,
+            r"
+fn main() {
+    let x = vec![1, 2, 3];
+    for<|> v in &x {
+        let y = v * 2;
+    }
+    for v in &mut x {
+        *v *= 2;
+    }
+}
+",
+            r"
+fn main() {
+    let x = vec![1, 2, 3];
+    x.iter().for_each(|v| {
+        let y = v * 2;
+    });
+    for v in &mut x {
+        *v *= 2;
+    }
+}
+",
+        );
+        check_assist(
+            convert_for_to_iter_for_each,
+            r"
+fn main() {
+    let x = vec![1, 2, 3];
+    for<|> v in &mut x {
+        *v *= 2;
+    }
+}
+",
+            r"
+fn main() {
+    let x = vec![1, 2, 3];
+    x.iter_mut().for_each(|v| {
+        *v *= 2;
+    });
+}
+",
+        );
+    }
+
+    #[test]
+    fn for_to_for_each_iterator() {
+        check_assist(
+            convert_for_to_iter_for_each,
+            &format!(
+                "//- /main.rs crate:main deps:core{}{}",
+                r"
+use core::iter::repeat;
+fn main() {
+    for<|> v in repeat(92).take(3) {
+        let y = v * 2;
+    }
+}
+",
+                FamousDefs::FIXTURE
+            ),
+            r"
+use core::iter::repeat;
+fn main() {
+    repeat(92).take(3).for_each(|v| {
+        let y = v * 2;
+    });
+}
+",
+        )
+    }
+
+    #[test]
+    fn for_to_for_each_parenthesizes_ranges() {
+        check_assist(
+            convert_for_to_iter_for_each,
+            r"
+fn main() {
+    for<|> i in 0..10 {
+        foo(i);
+    }
+}
+",
+            r"
+fn main() {
+    (0..10).into_iter().for_each(|i| {
+        foo(i);
+    });
+}
+",
+        )
+    }
+
+    #[test]
+    fn for_to_for_each_rewrites_continue() {
+        check_assist(
+            convert_for_to_iter_for_each,
+            r"
+fn main() {
+    for<|> v in x {
+        if v == 0 {
+            continue;
+        }
+        for w in y {
+            if w == v {
+                continue;
+            }
+        }
+    }
+}
+",
+            r"
+fn main() {
+    x.into_iter().for_each(|v| {
+        if v == 0 {
+            return;
+        }
+        for w in y {
+            if w == v {
+                continue;
+            }
+        }
+    });
+}
+",
+        )
+    }
+
+    #[test]
+    fn for_to_for_each_not_applicable_with_escaping_jumps() {
+        check_assist_not_applicable(
+            convert_for_to_iter_for_each,
+            r"
+fn main() {
+    for<|> v in x {
+        if v == 0 {
+            break;
+        }
+    }
+}
+",
+        );
+        check_assist_not_applicable(
+            convert_for_to_iter_for_each,
+            r"
+fn main() -> Option<()> {
+    for<|> v in x {
+        foo(v)?;
+    }
+    None
+}
+",
+        );
+        check_assist_not_applicable(
+            convert_for_to_iter_for_each,
+            r"
+fn main() {
+    'outer: loop {
+        for<|> v in x {
+            continue 'outer;
+        }
+    }
+}
+",
+        );
+    }
+
+    #[test]
+    fn for_to_for_each_ignores_nested_closures() {
+        check_assist(
+            convert_for_to_iter_for_each,
+            r"
+fn main() {
+    for<|> v in x {
+        let f = || return v;
+        loop { break; }
+    }
+}
+",
+            r"
+fn main() {
+    x.into_iter().for_each(|v| {
+        let f = || return v;
+        loop { break; }
+    });
+}
+",
+        )
+    }
+
+    #[test]
+    fn for_to_for_each_not_applicable_in_body() {
+        check_assist_not_applicable(
+            convert_for_to_iter_for_each,
+            r"
+fn main() {
+    for v in x {
+        let y = v <|>* 2;
+    }
+}
+",
+        );
+    }
+
+    #[test]
+    fn for_each_to_for() {
+        check_assist(
+            convert_iter_for_each_to_for,
+            r"
+fn main() {
+    let x = vec![1, 2, 3];
+    x.iter_mut().for_each<|>(|v| {
+        if *v == 0 {
+            return;
+        }
+        *v *= 2;
+    });
+}
+",
+            r"
+fn main() {
+    let x = vec![1, 2, 3];
+    for v in &mut x {
+        if *v == 0 {
+            continue;
+        }
+        *v *= 2;
+    }
+}
+",
+        )
+    }
+
+    #[test]
+    fn for_each_to_for_expression_body() {
+        check_assist(
+            convert_iter_for_each_to_for,
+            r"
+fn main() {
+    if true {
+        x.into_iter().<|>for_each(|v| println!({}, v));
+    }
+}
+",
+            r"
+fn main() {
+    if true {
+        for v in x {
+            println!({}, v);
+        }
+    }
+}
+",
+        )
+    }
+
+    #[test]
+    fn for_each_to_for_keeps_other_receivers() {
+        check_assist(
+            convert_iter_for_each_to_for,
+            r"
+fn main() {
+    x.chars().rev().for_each<|>(|(a, b)| { foo(a, b) })
+}
+",
+            r"
+fn main() {
+    for (a, b) in x.chars().rev() { foo(a, b) }
+}
+",
+        )
+    }
+
+    #[test]
+    fn for_each_to_for_not_applicable() {
+        check_assist_not_applicable(
+            convert_iter_for_each_to_for,
+            r"
+fn main() {
+    let n = x.iter().for_each<|>(|v| foo(v));
+}
+",
+        );
+        check_assist_not_applicable(
+            convert_iter_for_each_to_for,
+            r"
+fn main() {
+    x.iter().for_each<|>(|v| {
+        for w in y {
+            if w == v {
+                return;
+            }
+        }
+    });
+}
+",
+        );
+        check_assist_not_applicable(
+            convert_iter_for_each_to_for,
+            r"
+fn main() {
+    x.iter().for_each<|>(foo);
+}
+",
+        );
+    }
+}
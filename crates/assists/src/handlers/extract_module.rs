@@ -0,0 +1,781 @@
+use std::collections::BTreeSet;
+
+use hir::{AsAssocItem, AssocItemContainer, ModuleDef, Name, ScopeDef};
+use ide_db::{
+    base_db::FileId,
+    defs::Definition,
+    helpers::insert_use::{insert_use, ImportScope},
+    search::Reference,
+};
+use rustc_hash::FxHashMap;
+use stdx::format_to;
+use syntax::{
+    algo::find_node_at_offset,
+    ast::{
+        self,
+        edit::{AstNodeEdit, IndentLevel},
+        make, AstNode, PathSegmentKind, VisibilityOwner,
+    },
+    NodeOrToken, SyntaxKind, SyntaxNode, TextSize,
+};
+use text_edit::{TextEdit, TextEditBuilder};
+
+use crate::{utils::vis_offset, AssistContext, AssistId, AssistKind, Assists};
+
+const MODULE_NAME: &str = "modname";
+
+// Assist: extract_module
+//
+// Extracts the selected items into a new child module.
+//
+// ```
+// <|>fn foo(name: i32) -> i32 {
+//     name + 1
+// }<|>
+//
+// fn bar(name: i32) -> i32 {
+//     foo(name) + 2
+// }
+// ```
+// ->
+// ```
+// use self::modname::foo;
+//
+// mod $0modname {
+//     pub(super) fn foo(name: i32) -> i32 {
+//         name + 1
+//     }
+// }
+//
+// fn bar(name: i32) -> i32 {
+//     foo(name) + 2
+// }
+// ```
+pub(crate) fn extract_module(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let selection = ctx.frange.range;
+    if selection.is_empty() {
+        return None;
+    }
+    let container = match ctx.covering_element() {
+        NodeOrToken::Node(node) => node,
+        NodeOrToken::Token(token) => token.parent(),
+    };
+    let container = container
+        .ancestors()
+        .find(|it| matches!(it.kind(), SyntaxKind::SOURCE_FILE | SyntaxKind::ITEM_LIST))?;
+
+    let mut items = Vec::new();
+    for item in container.children().filter_map(ast::Item::cast) {
+        let range = item.syntax().text_range();
+        if selection.contains_range(range) {
+            items.push(item);
+        } else if let Some(intersection) = selection.intersect(range) {
+            // The selection cuts through an item, moving it would produce garbage.
+            if !intersection.is_empty() {
+                return None;
+            }
+        }
+    }
+    let first = items.first()?;
+    let last = items.last()?;
+    let items_range = first.syntax().text_range().cover(last.syntax().text_range());
+
+    let parent_module = ctx.sema.scope(first.syntax()).module()?;
+    let moved_defs: Vec<ModuleDef> = items.iter().filter_map(|it| item_to_def(ctx, it)).collect();
+
+    acc.add(
+        AssistId("extract_module", AssistKind::RefactorExtract),
+        "Extract module",
+        items_range,
+        |builder| {
+            let db = ctx.db();
+            let parent_scope = parent_module.scope(db, None);
+            let current_file = ctx.frange.file_id;
+
+            let mut inner_edit = TextEdit::builder();
+            let mut reexports = Vec::new();
+            let mut parent_imports = BTreeSet::new();
+            let mut outer_inserts: FxHashMap<FileId, Vec<TextSize>> = FxHashMap::default();
+
+            let is_external = |reference: &Reference| {
+                reference.file_range.file_id != current_file
+                    || !items_range.contains_range(reference.file_range.range)
+            };
+            let make_visible = |node: &SyntaxNode, inner_edit: &mut TextEditBuilder| {
+                let offset = vis_offset(node) - items_range.start();
+                inner_edit.insert(offset, "pub(super) ".to_string());
+            };
+
+            for item in &items {
+                if let ast::Item::Impl(impl_) = item {
+                    // Trait impl items inherit the visibility of the trait.
+                    if impl_.trait_().is_some() {
+                        continue;
+                    }
+                    let assoc_items =
+                        impl_.assoc_item_list().into_iter().flat_map(|it| it.assoc_items());
+                    for assoc_item in assoc_items {
+                        let (node, def) = match &assoc_item {
+                            ast::AssocItem::Fn(it) if needs_visibility(it) => {
+                                (it.syntax(), ctx.sema.to_def(it).map(ModuleDef::from))
+                            }
+                            ast::AssocItem::Const(it) if needs_visibility(it) => {
+                                (it.syntax(), ctx.sema.to_def(it).map(ModuleDef::from))
+                            }
+                            _ => continue,
+                        };
+                        let usages = match def {
+                            Some(def) => Definition::ModuleDef(def).usages(&ctx.sema).all(),
+                            None => continue,
+                        };
+                        if usages.iter().any(is_external) {
+                            make_visible(node, &mut inner_edit);
+                        }
+                    }
+                    continue;
+                }
+
+                if let ast::Item::Struct(strukt) = item {
+                    for field in record_or_tuple_fields(strukt) {
+                        let def = match &field {
+                            Field::Record(it) => ctx.sema.to_def(it),
+                            Field::Tuple(it) => ctx.sema.to_def(it),
+                        };
+                        let usages = match def {
+                            Some(def) => Definition::Field(def).usages(&ctx.sema).all(),
+                            None => continue,
+                        };
+                        if usages.iter().any(is_external) {
+                            make_visible(field.syntax(), &mut inner_edit);
+                        }
+                    }
+                }
+
+                let def = match item_to_def(ctx, item) {
+                    Some(it) => it,
+                    None => continue,
+                };
+                let name = match def.name(db) {
+                    Some(it) => it,
+                    None => continue,
+                };
+                if let Some(vis) = visibility_of(item) {
+                    // Already visible outside of the parent module, re-export it so that all
+                    // the existing paths keep working, whether or not they are used in this
+                    // crate. Relative visibilities now start one module deeper, so they have
+                    // to be adjusted for the re-export to not be more visible than the item
+                    // itself.
+                    if let Some(adjusted) = deepen_visibility(&vis) {
+                        let range = vis.syntax().text_range() - items_range.start();
+                        inner_edit.replace(range, adjusted);
+                    }
+                    reexports.push(format!("{} use self::{}::{};", vis, MODULE_NAME, name));
+                    continue;
+                }
+
+                let external: Vec<Reference> = Definition::ModuleDef(def)
+                    .usages(&ctx.sema)
+                    .all()
+                    .into_iter()
+                    .filter(is_external)
+                    .collect();
+                if external.is_empty() {
+                    continue;
+                }
+                make_visible(item.syntax(), &mut inner_edit);
+                for reference in external {
+                    let file_id = reference.file_range.file_id;
+                    let source_file = ctx.sema.parse(file_id);
+                    let offset = reference.file_range.range.start();
+                    let name_ref: ast::NameRef =
+                        match find_node_at_offset(source_file.syntax(), offset) {
+                            Some(it) => it,
+                            None => continue,
+                        };
+                    let path = match name_ref
+                        .syntax()
+                        .parent()
+                        .and_then(|it| it.parent())
+                        .and_then(ast::Path::cast)
+                    {
+                        Some(it) => it,
+                        None => continue,
+                    };
+                    let in_use_tree = path.syntax().parent().and_then(ast::UseTree::cast).is_some();
+                    if path.qualifier().is_some() || in_use_tree {
+                        outer_inserts
+                            .entry(file_id)
+                            .or_default()
+                            .push(name_ref.syntax().text_range().start());
+                    } else {
+                        parent_imports.insert(name.to_string());
+                    }
+                }
+            }
+
+            let mut imports = BTreeSet::new();
+            for item in &items {
+                for path in item.syntax().descendants().filter_map(ast::Path::cast) {
+                    if path.qualifier().is_some()
+                        || path.syntax().ancestors().any(|it| ast::Visibility::can_cast(it.kind()))
+                    {
+                        continue;
+                    }
+                    let segment = match path.segment() {
+                        Some(it) => it,
+                        None => continue,
+                    };
+                    let parent_path = path.syntax().parent().and_then(ast::Path::cast);
+                    match segment.kind() {
+                        Some(PathSegmentKind::SelfKw) => {
+                            // `self::foo` now refers to the new module.
+                            let resolution =
+                                parent_path.as_ref().and_then(|it| ctx.sema.resolve_path(it));
+                            let stays_local = match resolution {
+                                Some(hir::PathResolution::Def(def)) => moved_defs.contains(&def),
+                                Some(_) => false,
+                                None => true,
+                            };
+                            if !stays_local {
+                                let range = path.syntax().text_range() - items_range.start();
+                                inner_edit.replace(range, "super".to_string());
+                            }
+                        }
+                        Some(PathSegmentKind::SuperKw) if parent_path.is_some() => {
+                            let offset = path.syntax().text_range().start() - items_range.start();
+                            inner_edit.insert(offset, "super::".to_string());
+                        }
+                        Some(PathSegmentKind::Name(name_ref)) => {
+                            if path.syntax().ancestors().any(|it| ast::UseTree::can_cast(it.kind()))
+                            {
+                                continue;
+                            }
+                            let def = match ctx.sema.resolve_path(&path) {
+                                Some(hir::PathResolution::Def(def)) => def,
+                                _ => continue,
+                            };
+                            if moved_defs.contains(&def) {
+                                continue;
+                            }
+                            // Items coming from the prelude are not in the module scope, and
+                            // are visible from the new module anyway.
+                            if name_in_scope(&parent_scope, def).is_some() {
+                                imports.insert(name_ref.text().to_string());
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                for call in item.syntax().descendants().filter_map(ast::MethodCallExpr::cast) {
+                    let trait_ = match ctx
+                        .sema
+                        .resolve_method_call(&call)
+                        .and_then(|it| it.as_assoc_item(db))
+                        .map(|it| it.container(db))
+                    {
+                        Some(AssocItemContainer::Trait(it)) => ModuleDef::Trait(it),
+                        _ => continue,
+                    };
+                    if moved_defs.contains(&trait_) {
+                        continue;
+                    }
+                    if let Some(name) = name_in_scope(&parent_scope, trait_) {
+                        imports.insert(name.to_string());
+                    }
+                }
+            }
+
+            let source_file = ctx.sema.parse(current_file);
+            let mut body = source_file.syntax().text().slice(items_range).to_string();
+            inner_edit.finish().apply(&mut body);
+
+            let indent = IndentLevel::from_node(first.syntax());
+            let module = build_module(&body, indent, imports, ctx);
+
+            let mut replacement = String::new();
+            let outer_uses: Vec<String> = reexports
+                .into_iter()
+                .chain(
+                    parent_imports
+                        .iter()
+                        .map(|name| format!("use self::{}::{};", MODULE_NAME, name)),
+                )
+                .collect();
+            if !outer_uses.is_empty() {
+                let separator = format!("\n{}", indent);
+                format_to!(replacement, "{}\n{}", outer_uses.join(&separator), separator);
+            }
+            replacement.push_str(&module);
+
+            let inserts_in_current_file = outer_inserts.remove(&current_file).unwrap_or_default();
+            for offset in inserts_in_current_file {
+                builder.insert(offset, format!("{}::", MODULE_NAME));
+            }
+            match ctx.config.snippet_cap {
+                Some(cap) => {
+                    let module_decl = format!("mod {}", MODULE_NAME);
+                    let snippet =
+                        replacement.replacen(&module_decl, &format!("mod $0{}", MODULE_NAME), 1);
+                    builder.replace_snippet(cap, items_range, snippet)
+                }
+                None => builder.replace(items_range, replacement),
+            }
+            for (file_id, offsets) in outer_inserts {
+                builder.edit_file(file_id);
+                for offset in offsets {
+                    builder.insert(offset, format!("{}::", MODULE_NAME));
+                }
+            }
+        },
+    )
+}
+
+fn name_in_scope(scope: &[(Name, ScopeDef)], def: ModuleDef) -> Option<&Name> {
+    scope.iter().find_map(|(name, scope_def)| match scope_def {
+        ScopeDef::ModuleDef(it) if *it == def => Some(name),
+        _ => None,
+    })
+}
+
+fn item_to_def(ctx: &AssistContext, item: &ast::Item) -> Option<ModuleDef> {
+    let def = match item {
+        ast::Item::Fn(it) => ctx.sema.to_def(it)?.into(),
+        ast::Item::Struct(it) => ModuleDef::Adt(ctx.sema.to_def(it)?.into()),
+        ast::Item::Enum(it) => ModuleDef::Adt(ctx.sema.to_def(it)?.into()),
+        ast::Item::Union(it) => ModuleDef::Adt(ctx.sema.to_def(it)?.into()),
+        ast::Item::Trait(it) => ctx.sema.to_def(it)?.into(),
+        ast::Item::TypeAlias(it) => ctx.sema.to_def(it)?.into(),
+        ast::Item::Const(it) => ctx.sema.to_def(it)?.into(),
+        ast::Item::Static(it) => ctx.sema.to_def(it)?.into(),
+        ast::Item::Module(it) => ctx.sema.to_def(it)?.into(),
+        _ => return None,
+    };
+    Some(def)
+}
+
+/// Returns the visibility of the item, unless it is private.
+fn visibility_of(item: &ast::Item) -> Option<ast::Visibility> {
+    let vis = match item {
+        ast::Item::Fn(it) => it.visibility(),
+        ast::Item::Struct(it) => it.visibility(),
+        ast::Item::Enum(it) => it.visibility(),
+        ast::Item::Union(it) => it.visibility(),
+        ast::Item::Trait(it) => it.visibility(),
+        ast::Item::TypeAlias(it) => it.visibility(),
+        ast::Item::Const(it) => it.visibility(),
+        ast::Item::Static(it) => it.visibility(),
+        ast::Item::Module(it) => it.visibility(),
+        _ => None,
+    }?;
+    if vis.syntax().text() == "pub(self)" {
+        return None;
+    }
+    Some(vis)
+}
+
+/// Returns the visibility that denotes the same scope as `vis` once the item is moved into a
+/// child module, if it has to change.
+fn deepen_visibility(vis: &ast::Visibility) -> Option<String> {
+    match vis.kind() {
+        ast::VisibilityKind::PubSuper => Some("pub(in super::super)".to_string()),
+        ast::VisibilityKind::In(path) => {
+            let path = path.syntax().to_string();
+            if path == "crate" || path.starts_with("crate::") {
+                return None;
+            }
+            let path = match path.strip_prefix("self") {
+                Some(rest) => format!("super{}", rest),
+                None => format!("super::{}", path),
+            };
+            Some(format!("pub(in {})", path))
+        }
+        ast::VisibilityKind::Pub | ast::VisibilityKind::PubCrate | ast::VisibilityKind::PubSelf => {
+            None
+        }
+    }
+}
+
+fn needs_visibility(node: &impl VisibilityOwner) -> bool {
+    node.visibility().is_none()
+}
+
+enum Field {
+    Record(ast::RecordField),
+    Tuple(ast::TupleField),
+}
+
+impl Field {
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Field::Record(it) => it.syntax(),
+            Field::Tuple(it) => it.syntax(),
+        }
+    }
+}
+
+fn record_or_tuple_fields(strukt: &ast::Struct) -> Vec<Field> {
+    match strukt.field_list() {
+        Some(ast::FieldList::RecordFieldList(it)) => {
+            it.fields().filter(needs_visibility).map(Field::Record).collect()
+        }
+        Some(ast::FieldList::TupleFieldList(it)) => {
+            it.fields().filter(needs_visibility).map(Field::Tuple).collect()
+        }
+        None => Vec::new(),
+    }
+}
+
+fn build_module(
+    body: &str,
+    indent: IndentLevel,
+    imports: BTreeSet<String>,
+    ctx: &AssistContext,
+) -> String {
+    // The first line of `body` starts at the item, the rest carry the indentation of the
+    // surrounding code, which we strip before nesting everything one level deeper.
+    let outer_indent = indent.to_string();
+    let inner_indent = IndentLevel(1).to_string();
+    let mut text = format!("mod {} {{\n", MODULE_NAME);
+    for (idx, line) in body.lines().enumerate() {
+        let line = if idx == 0 { line } else { line.strip_prefix(&outer_indent).unwrap_or(line) };
+        if !line.trim().is_empty() {
+            text.push_str(&inner_indent);
+            text.push_str(line);
+        }
+        text.push('\n');
+    }
+    text.push('}');
+
+    let mut module = match ast::SourceFile::parse(&text)
+        .tree()
+        .syntax()
+        .descendants()
+        .find_map(ast::Module::cast)
+    {
+        Some(it) => it,
+        None => return text,
+    };
+    for name in imports {
+        let scope = match ImportScope::from(module.syntax().clone()) {
+            Some(it) => it,
+            None => break,
+        };
+        let path = make::path_qualified(
+            make::path_unqualified(make::path_segment_super()),
+            make::path_segment(make::name_ref(&name)),
+        );
+        let rewriter = insert_use(&scope, path, ctx.config.insert_use.merge);
+        module = match ast::Module::cast(rewriter.rewrite(module.syntax())) {
+            Some(it) => it,
+            None => break,
+        };
+    }
+    module.indent(indent).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn extract_unreferenced_item() {
+        check_assist(
+            extract_module,
+            r#"
+<|>struct Foo;<|>
+"#,
+            r#"
+mod $0modname {
+    struct Foo;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_adds_imports_for_outer_items() {
+        check_assist(
+            extract_module,
+            r#"
+mod collections {
+    pub struct HashMap<K, V>(K, V);
+}
+use collections::HashMap;
+
+struct Config;
+
+<|>fn load(config: &Config) -> HashMap<u32, u32> {
+    unimplemented!()
+}
+
+fn store(config: &Config) {}<|>
+"#,
+            r#"
+mod collections {
+    pub struct HashMap<K, V>(K, V);
+}
+use collections::HashMap;
+
+struct Config;
+
+mod $0modname {
+    use super::{Config, HashMap};
+
+    fn load(config: &Config) -> HashMap<u32, u32> {
+        unimplemented!()
+    }
+
+    fn store(config: &Config) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_imports_traits_of_method_calls() {
+        check_assist(
+            extract_module,
+            r#"
+trait Frobnicate {
+    fn frobnicate(&self);
+}
+impl Frobnicate for u32 {
+    fn frobnicate(&self) {}
+}
+
+<|>fn go(x: u32) {
+    x.frobnicate();
+}<|>
+"#,
+            r#"
+trait Frobnicate {
+    fn frobnicate(&self);
+}
+impl Frobnicate for u32 {
+    fn frobnicate(&self) {}
+}
+
+mod $0modname {
+    use super::Frobnicate;
+
+    fn go(x: u32) {
+        x.frobnicate();
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_bumps_visibility_of_used_items() {
+        check_assist(
+            extract_module,
+            r#"
+<|>struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    fn new() -> Point {
+        Point { x: 0, y: 0 }
+    }
+
+    fn helper(&self) {}
+}<|>
+
+fn main() {
+    let p = Point::new();
+    let x = p.x;
+    self::Point::new();
+}
+"#,
+            r#"
+use self::modname::Point;
+
+mod $0modname {
+    pub(super) struct Point {
+        pub(super) x: i32,
+        y: i32,
+    }
+
+    impl Point {
+        pub(super) fn new() -> Point {
+            Point { x: 0, y: 0 }
+        }
+
+        fn helper(&self) {}
+    }
+}
+
+fn main() {
+    let p = Point::new();
+    let x = p.x;
+    self::modname::Point::new();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_reexports_public_items() {
+        check_assist(
+            extract_module,
+            r#"
+mod outer {
+    <|>pub fn foo() {}
+
+    pub(crate) struct Bar;<|>
+
+    fn bar() {
+        foo();
+    }
+}
+
+fn main() {
+    outer::foo();
+    let _ = outer::Bar;
+}
+"#,
+            r#"
+mod outer {
+    pub use self::modname::foo;
+    pub(crate) use self::modname::Bar;
+
+    mod $0modname {
+        pub fn foo() {}
+
+        pub(crate) struct Bar;
+    }
+
+    fn bar() {
+        foo();
+    }
+}
+
+fn main() {
+    outer::foo();
+    let _ = outer::Bar;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_reexports_restricted_items() {
+        check_assist(
+            extract_module,
+            r#"
+mod outer {
+    <|>pub(super) fn foo() {}
+
+    pub(in super::outer) struct Bar;<|>
+
+    fn bar() {
+        foo();
+    }
+}
+
+fn main() {
+    outer::foo();
+}
+"#,
+            r#"
+mod outer {
+    pub(super) use self::modname::foo;
+    pub(in super::outer) use self::modname::Bar;
+
+    mod $0modname {
+        pub(in super::super) fn foo() {}
+
+        pub(in super::super::outer) struct Bar;
+    }
+
+    fn bar() {
+        foo();
+    }
+}
+
+fn main() {
+    outer::foo();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_adjusts_relative_paths() {
+        check_assist(
+            extract_module,
+            r#"
+mod a {
+    pub(crate) fn x() {}
+}
+
+mod b {
+    fn y() {}
+
+    <|>fn moved() {
+        super::a::x();
+        self::y();
+        self::z();
+    }
+
+    fn z() {}<|>
+}
+"#,
+            r#"
+mod a {
+    pub(crate) fn x() {}
+}
+
+mod b {
+    fn y() {}
+
+    mod $0modname {
+        fn moved() {
+            super::super::a::x();
+            super::y();
+            self::z();
+        }
+
+        fn z() {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_selection() {
+        check_assist_not_applicable(extract_module, "fn <|>foo() {}");
+    }
+
+    #[test]
+    fn not_applicable_when_cutting_an_item() {
+        check_assist_not_applicable(
+            extract_module,
+            r#"
+<|>fn foo() {}
+
+fn bar() {<|>
+    foo();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_inside_function_body() {
+        check_assist_not_applicable(
+            extract_module,
+            r#"
+fn foo() {
+    <|>let x = 92;<|>
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,485 @@
+use hir::{AsAssocItem, AssocItemContainer, ModuleDef, PathKind, Visibility};
+use stdx::{format_to, to_lower_snake_case};
+use syntax::{
+    ast::{self, edit::IndentLevel, DocCommentsOwner},
+    AstNode,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: generate_documentation_template
+//
+// Adds a documentation template above a public function, struct or trait
+// definition.
+//
+// ```
+// pub fn my_<|>func(a: i32, b: i32) -> Result<(), std::io::Error> {
+//     Ok(())
+// }
+// ```
+// ->
+// ```
+// /// $0.
+// ///
+// /// # Arguments
+// ///
+// /// * `a` -
+// /// * `b` -
+// ///
+// /// # Errors
+// ///
+// /// This function will return an error if .
+// ///
+// /// # Examples
+// ///
+// /// ```
+// /// use test::my_func;
+// ///
+// /// assert_eq!(my_func(a, b), );
+// /// ```
+// pub fn my_func(a: i32, b: i32) -> Result<(), std::io::Error> {
+//     Ok(())
+// }
+// ```
+pub(crate) fn generate_documentation_template(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+) -> Option<()> {
+    let name = ctx.find_node_at_offset::<ast::Name>()?;
+    let item = name.syntax().parent()?;
+    let (def, sections, example) = if let Some(fn_) = ast::Fn::cast(item.clone()) {
+        if fn_.doc_comment_text().is_some() {
+            return None;
+        }
+        let def = ctx.sema.to_def(&fn_)?;
+        (def.into(), fn_sections(&fn_), fn_example(ctx, &fn_)?)
+    } else if let Some(strukt) = ast::Struct::cast(item.clone()) {
+        if strukt.doc_comment_text().is_some() {
+            return None;
+        }
+        let def = hir::Adt::from(ctx.sema.to_def(&strukt)?).into();
+        (def, Vec::new(), use_line(ctx, def).into_iter().collect())
+    } else if let Some(trait_) = ast::Trait::cast(item.clone()) {
+        if trait_.doc_comment_text().is_some() {
+            return None;
+        }
+        let def = ctx.sema.to_def(&trait_)?.into();
+        (def, Vec::new(), use_line(ctx, def).into_iter().collect())
+    } else {
+        return None;
+    };
+    // Like the `missing_docs` lint, private items are left alone.
+    if def.definition_visibility(ctx.db()) != Some(Visibility::Public) {
+        return None;
+    }
+
+    acc.add(
+        AssistId("generate_documentation_template", AssistKind::Generate),
+        "Generate a documentation template",
+        name.syntax().text_range(),
+        |builder| {
+            let summary = if ctx.config.snippet_cap.is_some() { "$0." } else { "." };
+            let mut lines = vec![summary.to_string()];
+            for (title, body) in sections {
+                lines.extend(vec![String::new(), format!("# {}", title), String::new()]);
+                lines.extend(body);
+            }
+            if !example.is_empty() {
+                lines.extend(vec![String::new(), "# Examples".to_string(), String::new()]);
+                lines.push("```".to_string());
+                lines.extend(example);
+                lines.push("```".to_string());
+            }
+
+            let indent = IndentLevel::from_node(&item);
+            let mut buf = String::new();
+            for line in lines {
+                if line.is_empty() {
+                    format_to!(buf, "///\n{}", indent);
+                } else {
+                    format_to!(buf, "/// {}\n{}", line, indent);
+                }
+            }
+            let offset = item.text_range().start();
+            match ctx.config.snippet_cap {
+                Some(cap) => builder.insert_snippet(cap, offset, buf),
+                None => builder.insert(offset, buf),
+            }
+        },
+    )
+}
+
+fn fn_sections(fn_: &ast::Fn) -> Vec<(&'static str, Vec<String>)> {
+    let mut sections = Vec::new();
+    let args: Vec<String> = fn_
+        .param_list()
+        .into_iter()
+        .flat_map(|it| it.params())
+        .filter_map(|param| param.pat())
+        .map(|pat| format!("* `{}` -", pat))
+        .collect();
+    if !args.is_empty() {
+        sections.push(("Arguments", args));
+    }
+    if returns_result(fn_) {
+        sections.push(("Errors", vec!["This function will return an error if .".to_string()]));
+    }
+    if can_panic(fn_) {
+        sections.push(("Panics", vec!["Panics if .".to_string()]));
+    }
+    if fn_.unsafe_token().is_some() {
+        sections.push(("Safety", vec!["The caller must ensure that .".to_string()]));
+    }
+    sections
+}
+
+fn returns_result(fn_: &ast::Fn) -> bool {
+    let path = match fn_.ret_type().and_then(|it| it.ty()) {
+        Some(ast::Type::PathType(it)) => it.path(),
+        _ => None,
+    };
+    let segment = path.and_then(|it| it.segment()).and_then(|it| it.name_ref());
+    segment.map(|it| it.text() == "Result").unwrap_or(false)
+}
+
+fn can_panic(fn_: &ast::Fn) -> bool {
+    let body = match fn_.body() {
+        Some(it) => it,
+        None => return false,
+    };
+    body.syntax().descendants().any(|node| {
+        if let Some(call) = ast::MethodCallExpr::cast(node.clone()) {
+            let name = call.name_ref();
+            return matches!(
+                name.as_ref().map(|it| it.text().as_str()),
+                Some("unwrap") | Some("expect")
+            );
+        }
+        if let Some(mac) = ast::MacroCall::cast(node) {
+            let name = mac.path().and_then(|it| it.segment()).and_then(|it| it.name_ref());
+            return name.map(|it| it.text() == "panic").unwrap_or(false);
+        }
+        false
+    })
+}
+
+/// Builds the doctest calling `fn_`, preceded by the `use` bringing it, or the
+/// type it belongs to, into scope.
+fn fn_example(ctx: &AssistContext, fn_: &ast::Fn) -> Option<Vec<String>> {
+    let db = ctx.db();
+    let def = ctx.sema.to_def(fn_)?;
+    let name = def.name(db);
+    let args = fn_
+        .param_list()
+        .into_iter()
+        .flat_map(|it| it.params())
+        .filter_map(|param| param.pat())
+        .map(|pat| pat.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let self_param = fn_.param_list().and_then(|it| it.self_param());
+
+    let mut lines = Vec::new();
+    let (owner, owner_name) = match def.as_assoc_item(db).map(|it| it.container(db)) {
+        None => (Some(ModuleDef::from(def)), None),
+        Some(AssocItemContainer::Trait(trait_)) => (Some(trait_.into()), Some(trait_.name(db))),
+        Some(AssocItemContainer::Impl(impl_)) => match impl_.target_ty(db).as_adt() {
+            Some(adt) => (Some(adt.into()), Some(adt.name(db))),
+            None => (None, None),
+        },
+    };
+    if let Some(use_line) = owner.and_then(|it| use_line(ctx, it)) {
+        lines.push(use_line);
+        lines.push(String::new());
+    }
+
+    let call = match (owner_name, self_param) {
+        (None, _) => format!("{}({})", name, args),
+        (Some(owner), None) => format!("{}::{}({})", owner, name, args),
+        (Some(owner), Some(self_param)) => {
+            let receiver = to_lower_snake_case(&owner.to_string());
+            let is_mut = self_param.amp_token().is_some() && self_param.mut_token().is_some();
+            let mutability = if is_mut { "mut " } else { "" };
+            lines.push(format!("let {}{} = ;", mutability, receiver));
+            format!("{}.{}({})", receiver, name, args)
+        }
+    };
+    let call = if fn_.unsafe_token().is_some() { format!("unsafe {{ {} }}", call) } else { call };
+    let returns_unit = match fn_.ret_type().and_then(|it| it.ty()) {
+        None => true,
+        Some(ast::Type::TupleType(it)) => it.fields().next().is_none(),
+        Some(_) => false,
+    };
+    if returns_unit {
+        lines.push(format!("{};", call));
+    } else {
+        lines.push(format!("assert_eq!({}, );", call));
+    }
+    Some(lines)
+}
+
+/// Renders the `use` item a doctest needs to refer to `def`, which lives
+/// outside of the crate in doctests.
+fn use_line(ctx: &AssistContext, def: ModuleDef) -> Option<String> {
+    let krate = def.module(ctx.db())?.krate();
+    let path = krate.root_module(ctx.db()).find_use_path(ctx.db(), def)?;
+    let path = match (&path.kind, krate.display_name(ctx.db())) {
+        (PathKind::Plain, Some(crate_name)) => format!("{}::{}", crate_name, path),
+        _ => path.to_string(),
+    };
+    Some(format!("use {};", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn not_applicable_to_private_items() {
+        check_assist_not_applicable(
+            generate_documentation_template,
+            r#"
+fn ma<|>in() {}
+"#,
+        );
+        check_assist_not_applicable(
+            generate_documentation_template,
+            r#"
+struct Fo<|>o;
+"#,
+        );
+        check_assist_not_applicable(
+            generate_documentation_template,
+            r#"
+pub(crate) trait Sh<|>ape {}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_docs() {
+        check_assist_not_applicable(
+            generate_documentation_template,
+            r#"
+/// Does nothing.
+pub fn no<|>op() {}
+"#,
+        );
+        check_assist_not_applicable(
+            generate_documentation_template,
+            r#"
+fn main() {
+    let x<|> = 92;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn free_function() {
+        check_assist(
+            generate_documentation_template,
+            r#"
+//- /lib.rs crate:mylib
+pub mod ops {
+    pub fn a<|>dd(a: i32, b: i32) -> i32 {
+        a + b
+    }
+}
+"#,
+            r#"
+pub mod ops {
+    /// $0.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` -
+    /// * `b` -
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mylib::ops::add;
+    ///
+    /// assert_eq!(add(a, b), );
+    /// ```
+    pub fn add(a: i32, b: i32) -> i32 {
+        a + b
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn errors_panics_and_safety() {
+        check_assist(
+            generate_documentation_template,
+            r#"
+//- /lib.rs crate:mylib
+#[inline]
+pub unsafe fn re<|>ad(ptr: *const u8) -> Result<u8, ()> {
+    let value = ptr.as_ref().unwrap();
+    Ok(*value)
+}
+"#,
+            r#"
+/// $0.
+///
+/// # Arguments
+///
+/// * `ptr` -
+///
+/// # Errors
+///
+/// This function will return an error if .
+///
+/// # Panics
+///
+/// Panics if .
+///
+/// # Safety
+///
+/// The caller must ensure that .
+///
+/// # Examples
+///
+/// ```
+/// use mylib::read;
+///
+/// assert_eq!(unsafe { read(ptr) }, );
+/// ```
+#[inline]
+pub unsafe fn read(ptr: *const u8) -> Result<u8, ()> {
+    let value = ptr.as_ref().unwrap();
+    Ok(*value)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn methods() {
+        check_assist(
+            generate_documentation_template,
+            r#"
+//- /lib.rs crate:mylib
+pub struct MyStruct;
+impl MyStruct {
+    pub fn pu<|>sh(&mut self, x: u32) {
+        if x == 0 {
+            panic!("zero");
+        }
+    }
+}
+"#,
+            r#"
+pub struct MyStruct;
+impl MyStruct {
+    /// $0.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` -
+    ///
+    /// # Panics
+    ///
+    /// Panics if .
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mylib::MyStruct;
+    ///
+    /// let mut my_struct = ;
+    /// my_struct.push(x);
+    /// ```
+    pub fn push(&mut self, x: u32) {
+        if x == 0 {
+            panic!("zero");
+        }
+    }
+}
+"#,
+        );
+        check_assist(
+            generate_documentation_template,
+            r#"
+//- /lib.rs crate:mylib
+pub struct MyStruct;
+impl MyStruct {
+    pub fn n<|>ew() -> Self {
+        MyStruct
+    }
+}
+"#,
+            r#"
+pub struct MyStruct;
+impl MyStruct {
+    /// $0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mylib::MyStruct;
+    ///
+    /// assert_eq!(MyStruct::new(), );
+    /// ```
+    pub fn new() -> Self {
+        MyStruct
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn structs_and_traits() {
+        check_assist(
+            generate_documentation_template,
+            r#"
+//- /lib.rs crate:mylib
+mod inner {
+    pub struct Fo<|>o;
+}
+pub use inner::Foo;
+"#,
+            r#"
+mod inner {
+    /// $0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mylib::Foo;
+    /// ```
+    pub struct Foo;
+}
+pub use inner::Foo;
+"#,
+        );
+        check_assist(
+            generate_documentation_template,
+            r#"
+//- /lib.rs crate:mylib
+pub trait Sh<|>ape {
+    fn area(&self) -> f64;
+}
+"#,
+            r#"
+/// $0.
+///
+/// # Examples
+///
+/// ```
+/// use mylib::Shape;
+/// ```
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+"#,
+        );
+    }
+}
@@ -2,10 +2,13 @@ use syntax::{
     ast::{self, edit::AstNodeEdit, make, AstNode, NameOwner, TypeBoundsOwner},
     match_ast,
     SyntaxKind::*,
-    T,
+    SyntaxNode, T,
 };
 
-use crate::{AssistContext, AssistId, AssistKind, Assists};
+use crate::{
+    utils::{delete_from_list, delete_where_clause},
+    AssistContext, AssistId, AssistKind, Assists,
+};
 
 // Assist: move_bounds_to_where_clause
 //
@@ -92,11 +95,90 @@ fn build_predicate(param: ast::TypeParam) -> Option<ast::WherePred> {
     Some(predicate)
 }
 
+// Assist: move_where_clause_to_bounds
+//
+// Moves where clause predicates on generic parameters to inline type bounds.
+//
+// ```
+// fn apply<T, U, F>(f: F, x: T) -> U where <|>F: FnOnce(T) -> U {
+//     f(x)
+// }
+// ```
+// ->
+// ```
+// fn apply<T, U, F: FnOnce(T) -> U>(f: F, x: T) -> U {
+//     f(x)
+// }
+// ```
+pub(crate) fn move_where_clause_to_bounds(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let where_clause = ctx.find_node_at_offset::<ast::WhereClause>()?;
+    let parent = where_clause.syntax().parent()?;
+    let type_param_list = parent.children().find_map(ast::GenericParamList::cast)?;
+    let type_params: Vec<ast::TypeParam> = type_param_list.type_params().collect();
+
+    let mut moved: Vec<(ast::TypeParam, Vec<ast::TypeBound>)> = Vec::new();
+    let mut removed = Vec::new();
+    for pred in where_clause.predicates() {
+        // Higher-ranked bounds can't be written inline.
+        if pred.generic_param_list().is_some() {
+            continue;
+        }
+        let ty = match pred.ty() {
+            Some(ast::Type::PathType(it)) => it,
+            _ => continue,
+        };
+        let param = type_params.iter().find(|param| {
+            param.name().map(|name| ty.syntax().text() == name.text().as_str()).unwrap_or(false)
+        });
+        let (param, bounds) = match (param, pred.type_bound_list()) {
+            (Some(param), Some(bounds)) if bounds.bounds().next().is_some() => (param, bounds),
+            _ => continue,
+        };
+        match moved.iter_mut().find(|(it, _)| it == param) {
+            Some((_, moved_bounds)) => moved_bounds.extend(bounds.bounds()),
+            None => moved.push((param.clone(), bounds.bounds().collect())),
+        }
+        removed.push(pred.syntax().clone());
+    }
+    if moved.is_empty() {
+        return None;
+    }
+
+    let target = where_clause.syntax().text_range();
+    acc.add(
+        AssistId("move_where_clause_to_bounds", AssistKind::RefactorRewrite),
+        "Move to generic parameter bounds",
+        target,
+        |edit| {
+            for (param, bounds) in moved {
+                let bounds = bounds.iter().map(|it| it.to_string()).collect::<Vec<_>>().join(" + ");
+                match (param.type_bound_list(), param.name()) {
+                    (Some(list), _) => {
+                        edit.insert(list.syntax().text_range().end(), format!(" + {}", bounds))
+                    }
+                    (None, Some(name)) => {
+                        edit.insert(name.syntax().text_range().end(), format!(": {}", bounds))
+                    }
+                    (None, None) => (),
+                }
+            }
+
+            let predicates: Vec<SyntaxNode> =
+                where_clause.predicates().map(|it| it.syntax().clone()).collect();
+            if predicates.len() == removed.len() {
+                delete_where_clause(edit, &where_clause);
+            } else {
+                delete_from_list(edit, &predicates, &removed);
+            }
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use crate::tests::check_assist;
+    use crate::tests::{check_assist, check_assist_not_applicable};
 
     #[test]
     fn move_bounds_to_where_clause_fn() {
@@ -149,4 +231,78 @@ mod tests {
             "#,
         );
     }
+
+    #[test]
+    fn move_where_clause_to_bounds_fn() {
+        check_assist(
+            move_where_clause_to_bounds,
+            r#"
+            fn foo<T: Copy, F>() where <|>T: Clone, F: FnOnce(T) -> T, F: Send {}
+            "#,
+            r#"
+            fn foo<T: Copy + Clone, F: FnOnce(T) -> T + Send>() {}
+            "#,
+        );
+    }
+
+    #[test]
+    fn move_where_clause_to_bounds_keeps_other_predicates() {
+        check_assist(
+            move_where_clause_to_bounds,
+            r#"
+fn foo<'a, T>(t: &'a T)
+where
+    <|>T: Clone,
+    Vec<T>: Debug,
+    for<'b> T: Fn(&'b u8),
+{
+}
+"#,
+            r#"
+fn foo<'a, T: Clone>(t: &'a T)
+where
+    Vec<T>: Debug,
+    for<'b> T: Fn(&'b u8),
+{
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn move_where_clause_to_bounds_struct() {
+        check_assist(
+            move_where_clause_to_bounds,
+            r#"
+            struct Pair<T>(T, T) where <|>T: u32;
+            "#,
+            r#"
+            struct Pair<T: u32>(T, T);
+            "#,
+        );
+        check_assist(
+            move_where_clause_to_bounds,
+            r#"
+            impl<T> Foo for Bar<T>
+            where
+                <|>T: Foo,
+            {
+            }
+            "#,
+            r#"
+            impl<T: Foo> Foo for Bar<T> {
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn move_where_clause_to_bounds_not_applicable() {
+        check_assist_not_applicable(
+            move_where_clause_to_bounds,
+            r#"
+            fn foo<T>() where <|>Vec<T>: Debug {}
+            "#,
+        );
+    }
 }
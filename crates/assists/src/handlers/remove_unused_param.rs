@@ -2,13 +2,13 @@ use ide_db::{defs::Definition, search::Reference};
 use syntax::{
     algo::find_node_at_range,
     ast::{self, ArgListOwner},
-    AstNode, SyntaxKind, SyntaxNode, TextRange, T,
+    AstNode,
 };
 use test_utils::mark;
-use SyntaxKind::WHITESPACE;
 
 use crate::{
-    assist_context::AssistBuilder, utils::next_prev, AssistContext, AssistId, AssistKind, Assists,
+    assist_context::AssistBuilder, utils::range_to_remove, AssistContext, AssistId, AssistKind,
+    Assists,
 };
 
 // Assist: remove_unused_param
@@ -86,31 +86,6 @@ fn process_usage(
     Some(())
 }
 
-fn range_to_remove(node: &SyntaxNode) -> TextRange {
-    let up_to_comma = next_prev().find_map(|dir| {
-        node.siblings_with_tokens(dir)
-            .filter_map(|it| it.into_token())
-            .find(|it| it.kind() == T![,])
-            .map(|it| (dir, it))
-    });
-    if let Some((dir, token)) = up_to_comma {
-        if node.next_sibling().is_some() {
-            let up_to_space = token
-                .siblings_with_tokens(dir)
-                .skip(1)
-                .take_while(|it| it.kind() == WHITESPACE)
-                .last()
-                .and_then(|it| it.into_token());
-            return node
-                .text_range()
-                .cover(up_to_space.map_or(token.text_range(), |it| it.text_range()));
-        }
-        node.text_range().cover(token.text_range())
-    } else {
-        node.text_range()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use crate::tests::{check_assist, check_assist_not_applicable};
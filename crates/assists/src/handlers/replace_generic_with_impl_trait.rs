@@ -0,0 +1,425 @@
+use syntax::{
+    ast::{self, AstNode, GenericParamsOwner, NameOwner, TypeBoundsOwner},
+    SyntaxKind::*,
+    SyntaxNode,
+};
+
+use crate::{
+    assist_context::AssistBuilder,
+    utils::{delete_from_list, delete_where_clause, range_to_remove, turbofish_usages},
+    AssistContext, AssistId, AssistKind, Assists,
+};
+
+// Assist: replace_generic_with_impl_trait
+//
+// Replaces a generic type parameter which is only used by a single function
+// argument with `impl Trait`.
+//
+// ```
+// fn foo<<|>B>(bar: B) where B: Bar + Clone {}
+// ```
+// ->
+// ```
+// fn foo(bar: impl Bar + Clone) {}
+// ```
+pub(crate) fn replace_generic_with_impl_trait(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+) -> Option<()> {
+    let single_use = SingleUse::find(ctx)?;
+    if single_use.bounds.iter().any(|it| it.question_mark_token().is_some()) {
+        return None;
+    }
+
+    let bounds = match single_use.bounds_text() {
+        bounds if bounds.is_empty() => "Sized".to_string(),
+        bounds => bounds,
+    };
+    let needs_parens = single_use.bounds.len() > 1 && single_use.is_pointee();
+    let replacement =
+        if needs_parens { format!("(impl {})", bounds) } else { format!("impl {}", bounds) };
+
+    let target = single_use.type_param.syntax().text_range();
+    acc.add(
+        AssistId("replace_generic_with_impl_trait", AssistKind::RefactorRewrite),
+        "Replace generic with impl trait",
+        target,
+        |builder| single_use.replace(ctx, builder, replacement),
+    )
+}
+
+// Assist: replace_generic_with_dyn_trait
+//
+// Replaces a generic type parameter which is only used behind a pointer by a
+// single function argument with `dyn Trait`.
+//
+// ```
+// fn foo<<|>B: Bar + ?Sized>(bar: Box<B>) {}
+// ```
+// ->
+// ```
+// fn foo(bar: Box<dyn Bar>) {}
+// ```
+pub(crate) fn replace_generic_with_dyn_trait(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let mut single_use = SingleUse::find(ctx)?;
+    if !single_use.is_pointee() && !single_use.is_boxed() {
+        return None;
+    }
+    single_use.bounds.retain(|it| it.question_mark_token().is_none());
+    // `dyn` needs a trait to start with, not a lifetime.
+    single_use.bounds.first()?.ty()?;
+
+    let bounds = single_use.bounds_text();
+    let replacement = if single_use.bounds.len() > 1 && single_use.is_pointee() {
+        format!("(dyn {})", bounds)
+    } else {
+        format!("dyn {}", bounds)
+    };
+
+    let target = single_use.type_param.syntax().text_range();
+    acc.add(
+        AssistId("replace_generic_with_dyn_trait", AssistKind::RefactorRewrite),
+        "Replace generic with dyn trait",
+        target,
+        |builder| single_use.replace(ctx, builder, replacement),
+    )
+}
+
+/// A type parameter of a function which is mentioned by exactly one argument type.
+struct SingleUse {
+    type_fn: ast::Fn,
+    fn_def: hir::Function,
+    type_param: ast::TypeParam,
+    usage: ast::PathType,
+    /// Inline bounds followed by the ones from the `where` clause.
+    bounds: Vec<ast::TypeBound>,
+    where_preds: Vec<ast::WherePred>,
+}
+
+impl SingleUse {
+    fn find(ctx: &AssistContext) -> Option<SingleUse> {
+        let type_param = ctx.find_node_at_offset::<ast::TypeParam>()?;
+        let param_list = type_param.syntax().parent().and_then(ast::GenericParamList::cast)?;
+        let type_fn = param_list.syntax().parent().and_then(ast::Fn::cast)?;
+        if type_param.default_type().is_some() {
+            return None;
+        }
+        let name = type_param.name()?;
+        let is_param = |ty: Option<ast::Type>| match ty {
+            Some(ast::Type::PathType(it)) => it.syntax().text() == name.text().as_str(),
+            _ => false,
+        };
+
+        let mut bounds: Vec<ast::TypeBound> =
+            type_param.type_bound_list().into_iter().flat_map(|it| it.bounds()).collect();
+        let mut where_preds = Vec::new();
+        for pred in type_fn.where_clause().into_iter().flat_map(|it| it.predicates()) {
+            if !is_param(pred.ty()) {
+                continue;
+            }
+            // `for<'a> T: Trait<'a>` has no `impl Trait` equivalent.
+            if pred.generic_param_list().is_some() {
+                return None;
+            }
+            bounds.extend(pred.type_bound_list().into_iter().flat_map(|it| it.bounds()));
+            where_preds.push(pred);
+        }
+
+        let mut mentions = type_fn
+            .syntax()
+            .descendants()
+            .filter_map(ast::NameRef::cast)
+            .filter(|it| it.text() == name.text())
+            .filter(|it| {
+                let range = it.syntax().text_range();
+                !where_preds
+                    .iter()
+                    .filter_map(|pred| pred.ty())
+                    .any(|ty| ty.syntax().text_range().contains_range(range))
+            });
+        let usage = match (mentions.next(), mentions.next()) {
+            (Some(usage), None) => usage,
+            _ => return None,
+        };
+        let usage = usage.syntax().ancestors().find_map(ast::PathType::cast)?;
+        if usage.syntax().text() != name.text().as_str() {
+            return None;
+        }
+        let param = usage.syntax().ancestors().find_map(ast::Param::cast)?;
+        if param.syntax().parent()?.parent()? != *type_fn.syntax() {
+            return None;
+        }
+
+        let fn_def = ctx.sema.to_def(&type_fn)?;
+        Some(SingleUse { type_fn, fn_def, type_param, usage, bounds, where_preds })
+    }
+
+    fn bounds_text(&self) -> String {
+        self.bounds.iter().map(|it| it.to_string()).collect::<Vec<_>>().join(" + ")
+    }
+
+    /// Whether the parameter is used as `&T` or `*const T`.
+    fn is_pointee(&self) -> bool {
+        matches!(self.usage.syntax().parent().map(|it| it.kind()), Some(REF_TYPE) | Some(PTR_TYPE))
+    }
+
+    /// Whether the parameter is used as `Box<T>`, `Rc<T>` or `Arc<T>`.
+    fn is_boxed(&self) -> bool {
+        let segment = self
+            .usage
+            .syntax()
+            .parent()
+            .filter(|it| it.kind() == TYPE_ARG)
+            .and_then(|it| it.parent())
+            .and_then(ast::GenericArgList::cast)
+            .and_then(|it| it.syntax().parent())
+            .and_then(ast::PathSegment::cast);
+        let name = segment.and_then(|it| it.name_ref());
+        matches!(name.as_ref().map(|it| it.text().as_str()), Some("Box") | Some("Rc") | Some("Arc"))
+    }
+
+    fn replace(&self, ctx: &AssistContext, builder: &mut AssistBuilder, replacement: String) {
+        builder.replace(self.usage.syntax().text_range(), replacement);
+
+        let generic_params: Vec<ast::GenericParam> = self
+            .type_fn
+            .generic_param_list()
+            .into_iter()
+            .flat_map(|it| it.generic_params())
+            .collect();
+        match self.type_fn.generic_param_list() {
+            Some(list) if generic_params.len() == 1 => builder.delete(list.syntax().text_range()),
+            _ => builder.delete(range_to_remove(self.type_param.syntax())),
+        }
+
+        if let Some(where_clause) = self.type_fn.where_clause() {
+            if where_clause.predicates().count() == self.where_preds.len() {
+                delete_where_clause(builder, &where_clause);
+            } else {
+                let items: Vec<SyntaxNode> =
+                    where_clause.predicates().map(|it| it.syntax().clone()).collect();
+                let removed: Vec<SyntaxNode> =
+                    self.where_preds.iter().map(|it| it.syntax().clone()).collect();
+                delete_from_list(builder, &items, &removed);
+            }
+        }
+
+        let index = generic_params
+            .iter()
+            .filter(|it| !matches!(it, ast::GenericParam::LifetimeParam(_)))
+            .position(|it| it.syntax() == self.type_param.syntax());
+        let index = match index {
+            Some(it) => it,
+            None => return,
+        };
+        for (file_id, generic_arg_list) in turbofish_usages(&ctx.sema, self.fn_def) {
+            let mut args = generic_arg_list
+                .generic_args()
+                .filter(|it| !matches!(it, ast::GenericArg::LifetimeArg(_)));
+            let arg = match args.nth(index) {
+                Some(it) => it,
+                None => continue,
+            };
+            builder.edit_file(file_id);
+            if generic_arg_list.generic_args().count() == 1 {
+                builder.delete(generic_arg_list.syntax().text_range());
+            } else {
+                builder.delete(range_to_remove(arg.syntax()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn generic_to_impl_trait_inline_bounds() {
+        check_assist(
+            replace_generic_with_impl_trait,
+            r#"
+fn foo<G, <|>B: Bar>(g: G, bar: B) {}
+"#,
+            r#"
+fn foo<G>(g: G, bar: impl Bar) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn generic_to_impl_trait_merges_where_clause() {
+        check_assist(
+            replace_generic_with_impl_trait,
+            r#"
+fn foo<<|>B: Bar, G>(bar: &B, g: G)
+where
+    B: Clone,
+    G: Copy,
+{
+}
+"#,
+            r#"
+fn foo<G>(bar: &(impl Bar + Clone), g: G)
+where
+    G: Copy,
+{
+}
+"#,
+        );
+        check_assist(
+            replace_generic_with_impl_trait,
+            r#"
+fn foo<G, <|>B>(g: G, bar: Vec<B>) where G: Copy, B: Bar, B: Clone {}
+"#,
+            r#"
+fn foo<G>(g: G, bar: Vec<impl Bar + Clone>) where G: Copy {}
+"#,
+        );
+    }
+
+    #[test]
+    fn generic_to_impl_trait_unbounded() {
+        check_assist(
+            replace_generic_with_impl_trait,
+            r#"
+fn foo<<|>T>(t: T) {}
+"#,
+            r#"
+fn foo(t: impl Sized) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn generic_to_impl_trait_updates_turbofish() {
+        check_assist(
+            replace_generic_with_impl_trait,
+            r#"
+struct S;
+impl S {
+    fn foo<'a, <|>B: Bar, C>(&self, bar: B, c: &'a C) {}
+}
+fn main() {
+    S.foo::<'static, Baz, i32>(Baz, &92);
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn foo<'a, C>(&self, bar: impl Bar, c: &'a C) {}
+}
+fn main() {
+    S.foo::<'static, i32>(Baz, &92);
+}
+"#,
+        );
+        check_assist(
+            replace_generic_with_impl_trait,
+            r#"
+fn only<<|>T: Bar>(t: T) {}
+fn main() {
+    only::<Baz>(Baz);
+}
+"#,
+            r#"
+fn only(t: impl Bar) {}
+fn main() {
+    only(Baz);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generic_to_impl_trait_not_applicable() {
+        // Used twice.
+        check_assist_not_applicable(
+            replace_generic_with_impl_trait,
+            r#"
+fn foo<<|>B: Bar>(a: B, b: B) {}
+"#,
+        );
+        // Used in the body and the return type.
+        check_assist_not_applicable(
+            replace_generic_with_impl_trait,
+            r#"
+fn foo<<|>B: Bar>(a: B) { let b: B = a; }
+"#,
+        );
+        check_assist_not_applicable(
+            replace_generic_with_impl_trait,
+            r#"
+fn foo<<|>B: Bar>(a: B) -> B { a }
+"#,
+        );
+        // Mentioned by its own bounds.
+        check_assist_not_applicable(
+            replace_generic_with_impl_trait,
+            r#"
+fn foo<<|>B>(a: B) where B: PartialEq<B> {}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_generic_with_impl_trait,
+            r#"
+fn foo<<|>B: ?Sized>(a: &B) {}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_generic_with_impl_trait,
+            r#"
+struct S<<|>B>(B);
+"#,
+        );
+    }
+
+    #[test]
+    fn generic_to_dyn_trait() {
+        check_assist(
+            replace_generic_with_dyn_trait,
+            r#"
+fn foo<<|>B: Bar + ?Sized>(bar: &B) {}
+"#,
+            r#"
+fn foo(bar: &dyn Bar) {}
+"#,
+        );
+        check_assist(
+            replace_generic_with_dyn_trait,
+            r#"
+fn foo<'a, <|>B>(bar: Box<B>) where B: Bar + Send + 'a {}
+"#,
+            r#"
+fn foo<'a>(bar: Box<dyn Bar + Send + 'a>) {}
+"#,
+        );
+        check_assist(
+            replace_generic_with_dyn_trait,
+            r#"
+fn foo<<|>B: Bar + Send>(bar: &mut B) {}
+"#,
+            r#"
+fn foo(bar: &mut (dyn Bar + Send)) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn generic_to_dyn_trait_not_applicable() {
+        check_assist_not_applicable(
+            replace_generic_with_dyn_trait,
+            r#"
+fn foo<<|>B: Bar>(bar: B) {}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_generic_with_dyn_trait,
+            r#"
+fn foo<<|>B: 'static>(bar: &B) {}
+"#,
+        );
+    }
+}
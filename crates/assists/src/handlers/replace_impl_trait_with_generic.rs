@@ -1,10 +1,18 @@
-use syntax::ast::{self, edit::AstNodeEdit, make, AstNode, GenericParamsOwner};
+use syntax::{
+    ast::{self, AstNode, GenericParamsOwner, NameOwner},
+    SyntaxKind::*,
+    T,
+};
 
-use crate::{AssistContext, AssistId, AssistKind, Assists};
+use crate::{
+    assist_context::AssistBuilder,
+    utils::{fresh_type_param_name, turbofish_usages},
+    AssistContext, AssistId, AssistKind, Assists,
+};
 
 // Assist: replace_impl_trait_with_generic
 //
-// Replaces `impl Trait` function argument with the named generic.
+// Replaces `impl Trait` in a function argument type with the named generic.
 //
 // ```
 // fn foo(bar: <|>impl Bar) {}
@@ -18,10 +26,34 @@ pub(crate) fn replace_impl_trait_with_generic(
     ctx: &AssistContext,
 ) -> Option<()> {
     let type_impl_trait = ctx.find_node_at_offset::<ast::ImplTraitType>()?;
-    let type_param = type_impl_trait.syntax().parent().and_then(ast::Param::cast)?;
-    let type_fn = type_param.syntax().ancestors().find_map(ast::Fn::cast)?;
+    // The `impl Trait` may be nested in the argument type, like in `&impl Bar` or
+    // `Vec<impl Bar>`, but not in another `impl Trait`, `dyn Trait` or `fn` type.
+    let type_param = type_impl_trait
+        .syntax()
+        .ancestors()
+        .skip(1)
+        .take_while(|it| {
+            matches!(
+                it.kind(),
+                PARAM
+                    | REF_TYPE
+                    | PTR_TYPE
+                    | PAREN_TYPE
+                    | TUPLE_TYPE
+                    | ARRAY_TYPE
+                    | SLICE_TYPE
+                    | PATH_TYPE
+                    | PATH
+                    | PATH_SEGMENT
+                    | GENERIC_ARG_LIST
+                    | TYPE_ARG
+            )
+        })
+        .find_map(ast::Param::cast)?;
+    let type_fn = type_param.syntax().parent()?.parent().and_then(ast::Fn::cast)?;
 
     let impl_trait_ty = type_impl_trait.type_bound_list()?;
+    let fn_def = ctx.sema.to_def(&type_fn)?;
 
     let target = type_fn.syntax().text_range();
     acc.add(
@@ -29,27 +61,172 @@ pub(crate) fn replace_impl_trait_with_generic(
         "Replace impl trait with generic",
         target,
         |edit| {
-            let generic_letter = impl_trait_ty.to_string().chars().next().unwrap().to_string();
+            let generic_letter = fresh_type_param_name(
+                type_fn.generic_param_list().as_ref(),
+                &impl_trait_ty.to_string(),
+            );
+            let generic_param = format!("{}: {}", generic_letter, impl_trait_ty);
+            replace_with_type_param(
+                ctx,
+                edit,
+                &type_fn,
+                fn_def,
+                type_impl_trait.syntax(),
+                &generic_letter,
+                &generic_param,
+            );
+        },
+    )
+}
 
-            let generic_param_list = type_fn
-                .generic_param_list()
-                .unwrap_or_else(|| make::generic_param_list(None))
-                .append_param(make::generic_param(generic_letter.clone(), Some(impl_trait_ty)));
+// Assist: replace_dyn_trait_with_generic
+//
+// Replaces a `dyn Trait` in a function argument with the named generic.
+//
+// ```
+// fn foo(bar: Box<dyn <|>Bar>) {}
+// ```
+// ->
+// ```
+// fn foo<B: Bar + ?Sized + 'static>(bar: Box<B>) {}
+// ```
+pub(crate) fn replace_dyn_trait_with_generic(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let dyn_trait = ctx.find_node_at_offset::<ast::DynTraitType>()?;
+    let param = dyn_trait.syntax().ancestors().find_map(ast::Param::cast)?;
+    let type_fn = param.syntax().parent()?.parent().and_then(ast::Fn::cast)?;
+    let bounds = dyn_trait.type_bound_list()?;
+    let fn_def = ctx.sema.to_def(&type_fn)?;
 
-            let new_type_fn = type_fn
-                .replace_descendant::<ast::Type>(type_impl_trait.into(), make::ty(&generic_letter))
-                .with_generic_param_list(generic_param_list);
+    // `&(dyn A + B)` becomes `&T` rather than `&(T)`.
+    let replaced = match dyn_trait.syntax().parent() {
+        Some(parent) if parent.kind() == PAREN_TYPE => parent,
+        _ => dyn_trait.syntax().clone(),
+    };
+    // Without a lifetime, `Box<dyn Bar>` is `Box<dyn Bar + 'static>`, while
+    // `&dyn Bar` borrows for the lifetime of the reference.
+    let needs_static = !bounds.bounds().any(|it| it.lifetime().is_some())
+        && replaced
+            .ancestors()
+            .skip(1)
+            .find(|it| {
+                !matches!(it.kind(), TYPE_ARG | GENERIC_ARG_LIST | PATH_SEGMENT | PATH | PAREN_TYPE)
+            })
+            .map_or(false, |it| it.kind() == PATH_TYPE);
 
-            edit.replace_ast(type_fn.clone(), new_type_fn);
+    let target = dyn_trait.syntax().text_range();
+    acc.add(
+        AssistId("replace_dyn_trait_with_generic", AssistKind::RefactorRewrite),
+        "Replace dyn trait with generic",
+        target,
+        |edit| {
+            let generic_letter =
+                fresh_type_param_name(type_fn.generic_param_list().as_ref(), &bounds.to_string());
+            let mut generic_param = format!("{}: {} + ?Sized", generic_letter, bounds);
+            if needs_static {
+                generic_param.push_str(" + 'static");
+            }
+            replace_with_type_param(
+                ctx,
+                edit,
+                &type_fn,
+                fn_def,
+                &replaced,
+                &generic_letter,
+                &generic_param,
+            );
         },
     )
 }
 
+/// Declares `generic_param` on `type_fn`, replaces `ty` with its name and
+/// passes `_` for it at the call sites which spell out the generic arguments.
+fn replace_with_type_param(
+    ctx: &AssistContext,
+    edit: &mut AssistBuilder,
+    type_fn: &ast::Fn,
+    fn_def: hir::Function,
+    ty: &syntax::SyntaxNode,
+    generic_letter: &str,
+    generic_param: &str,
+) {
+    let params: Vec<ast::GenericParam> =
+        type_fn.generic_param_list().into_iter().flat_map(|it| it.generic_params()).collect();
+    // Type parameters have to precede const parameters.
+    let first_const = params.iter().position(|it| matches!(it, ast::GenericParam::ConstParam(_)));
+    let index = params
+        .iter()
+        .take(first_const.unwrap_or(params.len()))
+        .filter(|it| !matches!(it, ast::GenericParam::LifetimeParam(_)))
+        .count();
+
+    match (type_fn.generic_param_list(), first_const) {
+        (Some(_), Some(first_const)) => {
+            let offset = params[first_const].syntax().text_range().start();
+            edit.insert(offset, format!("{}, ", generic_param))
+        }
+        (Some(list), None) => match params.last() {
+            Some(last) => {
+                let trailing_comma = last
+                    .syntax()
+                    .siblings_with_tokens(syntax::Direction::Next)
+                    .skip(1)
+                    .find(|it| it.kind() != WHITESPACE)
+                    .filter(|it| it.kind() == T![,]);
+                match trailing_comma {
+                    Some(comma) => {
+                        edit.insert(comma.text_range().end(), format!(" {}", generic_param))
+                    }
+                    None => edit
+                        .insert(last.syntax().text_range().end(), format!(", {}", generic_param)),
+                }
+            }
+            None => {
+                let offset = list
+                    .l_angle_token()
+                    .map_or_else(|| list.syntax().text_range().start(), |it| it.text_range().end());
+                edit.insert(offset, generic_param)
+            }
+        },
+        (None, _) => {
+            if let Some(name) = type_fn.name() {
+                edit.insert(name.syntax().text_range().end(), format!("<{}>", generic_param))
+            }
+        }
+    }
+    edit.replace(ty.text_range(), generic_letter);
+
+    for (file_id, generic_arg_list) in turbofish_usages(&ctx.sema, fn_def) {
+        edit.edit_file(file_id);
+        insert_generic_arg(edit, &generic_arg_list, index, "_");
+    }
+}
+
+fn insert_generic_arg(
+    edit: &mut AssistBuilder,
+    generic_arg_list: &ast::GenericArgList,
+    index: usize,
+    arg: &str,
+) {
+    let args: Vec<ast::GenericArg> = generic_arg_list
+        .generic_args()
+        .filter(|it| !matches!(it, ast::GenericArg::LifetimeArg(_)))
+        .collect();
+    match (args.get(index), generic_arg_list.generic_args().last()) {
+        (Some(next), _) => edit.insert(next.syntax().text_range().start(), format!("{}, ", arg)),
+        (None, Some(last)) => edit.insert(last.syntax().text_range().end(), format!(", {}", arg)),
+        (None, None) => {
+            if let Some(r_angle) = generic_arg_list.r_angle_token() {
+                edit.insert(r_angle.text_range().start(), arg)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use crate::tests::check_assist;
+    use crate::tests::{check_assist, check_assist_not_applicable};
 
     #[test]
     fn replace_impl_trait_with_generic_params() {
@@ -90,6 +267,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn replace_nested_impl_trait_with_generic() {
+        check_assist(
+            replace_impl_trait_with_generic,
+            r#"
+            fn foo(bar: &mut Vec<<|>impl Bar>) {}
+            "#,
+            r#"
+            fn foo<B: Bar>(bar: &mut Vec<B>) {}
+            "#,
+        );
+    }
+
+    #[test]
+    fn replace_impl_trait_in_fn_type_not_applicable() {
+        check_assist_not_applicable(
+            replace_impl_trait_with_generic,
+            r#"
+            fn foo(bar: fn(<|>impl Bar)) {}
+            "#,
+        );
+    }
+
     #[test]
     fn replace_impl_trait_with_empty_generic_params() {
         check_assist(
@@ -119,7 +319,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "This case is very rare but there is no simple solutions to fix it."]
     fn replace_impl_trait_with_exist_generic_letter() {
         check_assist(
             replace_impl_trait_with_generic,
@@ -165,4 +364,108 @@ mod tests {
             "#,
         );
     }
+
+    #[test]
+    fn replace_impl_trait_updates_turbofish() {
+        check_assist(
+            replace_impl_trait_with_generic,
+            r#"
+fn foo<T>(x: T, bar: <|>impl Bar) {}
+fn main() {
+    foo::<i32>(92, baz);
+}
+"#,
+            r#"
+fn foo<T, B: Bar>(x: T, bar: B) {}
+fn main() {
+    foo::<i32, _>(92, baz);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_dyn_trait_with_generic_param() {
+        check_assist(
+            replace_dyn_trait_with_generic,
+            r#"
+            fn foo<G>(bar: Box<dyn <|>Bar>) {}
+            "#,
+            r#"
+            fn foo<G, B: Bar + ?Sized + 'static>(bar: Box<B>) {}
+            "#,
+        );
+    }
+
+    #[test]
+    fn replace_dyn_trait_keeps_explicit_lifetime() {
+        check_assist(
+            replace_dyn_trait_with_generic,
+            r#"
+            fn foo<'a>(bar: Box<dyn <|>Bar + 'a>, baz: Rc<dyn Baz>) {}
+            "#,
+            r#"
+            fn foo<'a, B: Bar + 'a + ?Sized>(bar: Box<B>, baz: Rc<dyn Baz>) {}
+            "#,
+        );
+        check_assist(
+            replace_dyn_trait_with_generic,
+            r#"
+            fn foo(bar: Rc<(dyn <|>Bar + Send)>) {}
+            "#,
+            r#"
+            fn foo<B: Bar + Send + ?Sized + 'static>(bar: Rc<B>) {}
+            "#,
+        );
+    }
+
+    #[test]
+    fn replace_dyn_trait_with_multiple_bounds() {
+        check_assist(
+            replace_dyn_trait_with_generic,
+            r#"
+            fn foo(bar: &(<|>dyn Bar + Send)) {}
+            "#,
+            r#"
+            fn foo<B: Bar + Send + ?Sized>(bar: &B) {}
+            "#,
+        );
+    }
+
+    #[test]
+    fn replace_dyn_trait_before_const_params() {
+        check_assist(
+            replace_dyn_trait_with_generic,
+            r#"
+fn foo<'a, const N: usize>(bar: &'a dyn <|>Bar) {}
+fn main() {
+    foo::<3>(&baz);
+}
+"#,
+            r#"
+fn foo<'a, B: Bar + ?Sized, const N: usize>(bar: &'a B) {}
+fn main() {
+    foo::<_, 3>(&baz);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_dyn_trait_not_applicable_outside_params() {
+        check_assist_not_applicable(
+            replace_dyn_trait_with_generic,
+            r#"
+            fn foo() -> Box<dyn <|>Bar> {}
+            "#,
+        );
+        check_assist_not_applicable(
+            replace_dyn_trait_with_generic,
+            r#"
+            fn foo() {
+                let f = |bar: &dyn <|>Bar| ();
+            }
+            "#,
+        );
+    }
 }
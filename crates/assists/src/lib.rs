@@ -126,9 +126,13 @@ mod handlers {
     mod apply_demorgan;
     mod auto_import;
     mod change_visibility;
+    mod convert_closure_to_fn;
+    mod convert_for_loop_to_iterator_chain;
     mod convert_integer_literal;
+    mod convert_iter_for_each_to_for;
     mod early_return;
     mod expand_glob_import;
+    mod extract_module;
     mod extract_struct_from_enum_variant;
     mod extract_variable;
     mod fill_match_arms;
@@ -138,6 +142,7 @@ mod handlers {
     mod flip_trait_bound;
     mod generate_derive;
     mod generate_default_from_enum_variant;
+    mod generate_documentation_template;
     mod generate_from_impl_for_enum;
     mod generate_function;
     mod generate_impl;
@@ -157,6 +162,7 @@ mod handlers {
     mod remove_unused_param;
     mod reorder_fields;
     mod replace_derive_with_manual_impl;
+    mod replace_generic_with_impl_trait;
     mod replace_if_let_with_match;
     mod replace_impl_trait_with_generic;
     mod replace_let_with_if_let;
@@ -176,9 +182,15 @@ mod handlers {
             apply_demorgan::apply_demorgan,
             auto_import::auto_import,
             change_visibility::change_visibility,
+            convert_closure_to_fn::convert_closure_to_fn,
+            convert_closure_to_fn::convert_fn_to_closure,
+            convert_for_loop_to_iterator_chain::convert_for_loop_to_iterator_chain,
             convert_integer_literal::convert_integer_literal,
+            convert_iter_for_each_to_for::convert_for_to_iter_for_each,
+            convert_iter_for_each_to_for::convert_iter_for_each_to_for,
             early_return::convert_to_guarded_return,
             expand_glob_import::expand_glob_import,
+            extract_module::extract_module,
             extract_struct_from_enum_variant::extract_struct_from_enum_variant,
             extract_variable::extract_variable,
             fill_match_arms::fill_match_arms,
@@ -188,6 +200,7 @@ mod handlers {
             flip_trait_bound::flip_trait_bound,
             generate_derive::generate_derive,
             generate_default_from_enum_variant::generate_default_from_enum_variant,
+            generate_documentation_template::generate_documentation_template,
             generate_from_impl_for_enum::generate_from_impl_for_enum,
             generate_function::generate_function,
             generate_impl::generate_impl,
@@ -199,6 +212,7 @@ mod handlers {
             merge_imports::merge_imports,
             merge_match_arms::merge_match_arms,
             move_bounds::move_bounds_to_where_clause,
+            move_bounds::move_where_clause_to_bounds,
             move_guard::move_arm_cond_to_match_guard,
             move_guard::move_guard_to_arm_body,
             qualify_path::qualify_path,
@@ -210,8 +224,11 @@ mod handlers {
             remove_unused_param::remove_unused_param,
             reorder_fields::reorder_fields,
             replace_derive_with_manual_impl::replace_derive_with_manual_impl,
+            replace_generic_with_impl_trait::replace_generic_with_dyn_trait,
+            replace_generic_with_impl_trait::replace_generic_with_impl_trait,
             replace_if_let_with_match::replace_if_let_with_match,
             replace_if_let_with_match::replace_match_with_if_let,
+            replace_impl_trait_with_generic::replace_dyn_trait_with_generic,
             replace_impl_trait_with_generic::replace_impl_trait_with_generic,
             replace_let_with_if_let::replace_let_with_if_let,
             replace_qualified_name_with_use::replace_qualified_name_with_use,
@@ -171,6 +171,103 @@ pub(crate) fn frobnicate() {}
     )
 }
 
+#[test]
+fn doctest_convert_closure_to_fn() {
+    check_doc_test(
+        "convert_closure_to_fn",
+        r#####"
+struct Counter { count: u32 }
+
+fn main() {
+    let counter = Counter { count: 0 };
+    let add = <|>|a: u32| a + counter.count;
+    add(1);
+}
+"#####,
+        r#####"
+struct Counter { count: u32 }
+
+fn main() {
+    let counter = Counter { count: 0 };
+    fn add(a: u32, counter: &Counter) -> u32 {
+        a + counter.count
+    }
+    add(1, &counter);
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_convert_fn_to_closure() {
+    check_doc_test(
+        "convert_fn_to_closure",
+        r#####"
+fn main() {
+    fn <|>add(a: i32, b: i32) -> i32 {
+        a + b
+    }
+    add(1, 2);
+}
+"#####,
+        r#####"
+fn main() {
+    let add = |a: i32, b: i32| -> i32 {
+        a + b
+    };
+    add(1, 2);
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_convert_for_loop_to_iterator_chain() {
+    check_doc_test(
+        "convert_for_loop_to_iterator_chain",
+        r#####"
+fn main() {
+    let xs = vec![1, 2, 3];
+    let mut evens = Vec::new();
+    for<|> x in xs {
+        if x % 2 == 0 {
+            evens.push(x * 10);
+        }
+    }
+}
+"#####,
+        r#####"
+fn main() {
+    let xs = vec![1, 2, 3];
+    let evens: Vec<_> = xs.into_iter().filter(|x| x % 2 == 0).map(|x| x * 10).collect();
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_convert_for_to_iter_for_each() {
+    check_doc_test(
+        "convert_for_to_iter_for_each",
+        r#####"
+fn main() {
+    let x = vec![1, 2, 3];
+    for<|> v in x {
+        let y = v * 2;
+    }
+}
+"#####,
+        r#####"
+fn main() {
+    let x = vec![1, 2, 3];
+    x.into_iter().for_each(|v| {
+        let y = v * 2;
+    });
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_integer_literal() {
     check_doc_test(
@@ -184,6 +281,29 @@ const _: i32 = 0b1010;
     )
 }
 
+#[test]
+fn doctest_convert_iter_for_each_to_for() {
+    check_doc_test(
+        "convert_iter_for_each_to_for",
+        r#####"
+fn main() {
+    let x = vec![1, 2, 3];
+    x.iter().for_each<|>(|v| {
+        let y = v * 2;
+    });
+}
+"#####,
+        r#####"
+fn main() {
+    let x = vec![1, 2, 3];
+    for v in &x {
+        let y = v * 2;
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_to_guarded_return() {
     check_doc_test(
@@ -235,6 +355,35 @@ fn qux(bar: Bar, baz: Baz) {}
     )
 }
 
+#[test]
+fn doctest_extract_module() {
+    check_doc_test(
+        "extract_module",
+        r#####"
+<|>fn foo(name: i32) -> i32 {
+    name + 1
+}<|>
+
+fn bar(name: i32) -> i32 {
+    foo(name) + 2
+}
+"#####,
+        r#####"
+use self::modname::foo;
+
+mod $0modname {
+    pub(super) fn foo(name: i32) -> i32 {
+        name + 1
+    }
+}
+
+fn bar(name: i32) -> i32 {
+    foo(name) + 2
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_extract_struct_from_enum_variant() {
     check_doc_test(
@@ -411,6 +560,41 @@ struct Point {
     )
 }
 
+#[test]
+fn doctest_generate_documentation_template() {
+    check_doc_test(
+        "generate_documentation_template",
+        r#####"
+pub fn my_<|>func(a: i32, b: i32) -> Result<(), std::io::Error> {
+    Ok(())
+}
+"#####,
+        r#####"
+/// $0.
+///
+/// # Arguments
+///
+/// * `a` -
+/// * `b` -
+///
+/// # Errors
+///
+/// This function will return an error if .
+///
+/// # Examples
+///
+/// ```
+/// use test::my_func;
+///
+/// assert_eq!(my_func(a, b), );
+/// ```
+pub fn my_func(a: i32, b: i32) -> Result<(), std::io::Error> {
+    Ok(())
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_generate_from_impl_for_enum() {
     check_doc_test(
@@ -720,6 +904,23 @@ fn handle(action: Action) {
     )
 }
 
+#[test]
+fn doctest_move_where_clause_to_bounds() {
+    check_doc_test(
+        "move_where_clause_to_bounds",
+        r#####"
+fn apply<T, U, F>(f: F, x: T) -> U where <|>F: FnOnce(T) -> U {
+    f(x)
+}
+"#####,
+        r#####"
+fn apply<T, U, F: FnOnce(T) -> U>(f: F, x: T) -> U {
+    f(x)
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_qualify_path() {
     check_doc_test(
@@ -849,6 +1050,45 @@ impl Debug for S {
     )
 }
 
+#[test]
+fn doctest_replace_dyn_trait_with_generic() {
+    check_doc_test(
+        "replace_dyn_trait_with_generic",
+        r#####"
+fn foo(bar: Box<dyn <|>Bar>) {}
+"#####,
+        r#####"
+fn foo<B: Bar + ?Sized + 'static>(bar: Box<B>) {}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_replace_generic_with_dyn_trait() {
+    check_doc_test(
+        "replace_generic_with_dyn_trait",
+        r#####"
+fn foo<<|>B: Bar + ?Sized>(bar: Box<B>) {}
+"#####,
+        r#####"
+fn foo(bar: Box<dyn Bar>) {}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_replace_generic_with_impl_trait() {
+    check_doc_test(
+        "replace_generic_with_impl_trait",
+        r#####"
+fn foo<<|>B>(bar: B) where B: Bar + Clone {}
+"#####,
+        r#####"
+fn foo(bar: impl Bar + Clone) {}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_replace_if_let_with_match() {
     check_doc_test(
@@ -3,21 +3,23 @@ pub(crate) mod import_assets;
 
 use std::ops;
 
-use hir::HasSource;
-use ide_db::RootDatabase;
+use hir::{HasSource, Semantics};
+use ide_db::{base_db::FileId, defs::Definition, helpers::FamousDefs, RootDatabase};
 use itertools::Itertools;
 use syntax::{
+    algo::find_node_at_range,
     ast::edit::AstNodeEdit,
     ast::AttrsOwner,
     ast::NameOwner,
     ast::{self, edit, make, ArgListOwner},
-    AstNode, Direction,
+    match_ast, AstNode, Direction,
     SyntaxKind::*,
-    SyntaxNode, TextSize, T,
+    SyntaxNode, TextRange, TextSize, T,
 };
 
 use crate::{
     assist_config::SnippetCap,
+    assist_context::AssistBuilder,
     ast_transform::{self, AstTransform, QualifyPaths, SubstituteTypeParams},
 };
 
@@ -248,3 +250,246 @@ fn invert_special_case(expr: &ast::Expr) -> Option<ast::Expr> {
 pub(crate) fn next_prev() -> impl Iterator<Item = Direction> {
     [Direction::Next, Direction::Prev].iter().copied()
 }
+
+/// Returns the range covering `node` together with the comma separating it from its
+/// neighbours in a list.
+pub(crate) fn range_to_remove(node: &SyntaxNode) -> TextRange {
+    let up_to_comma = next_prev().find_map(|dir| {
+        node.siblings_with_tokens(dir)
+            .filter_map(|it| it.into_token())
+            .find(|it| it.kind() == T![,])
+            .map(|it| (dir, it))
+    });
+    if let Some((dir, token)) = up_to_comma {
+        if node.next_sibling().is_some() {
+            let up_to_space = token
+                .siblings_with_tokens(dir)
+                .skip(1)
+                .take_while(|it| it.kind() == WHITESPACE)
+                .last()
+                .and_then(|it| it.into_token());
+            return node
+                .text_range()
+                .cover(up_to_space.map_or(token.text_range(), |it| it.text_range()));
+        }
+        node.text_range().cover(token.text_range())
+    } else {
+        node.text_range()
+    }
+}
+
+/// A `return`, `?`, `break` or `continue` expression.
+#[derive(Debug, Clone)]
+pub(crate) enum Jump {
+    Return(ast::ReturnExpr),
+    Try(ast::TryExpr),
+    Break(ast::BreakExpr),
+    Continue(ast::ContinueExpr),
+}
+
+impl Jump {
+    pub(crate) fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Jump::Return(it) => it.syntax(),
+            Jump::Try(it) => it.syntax(),
+            Jump::Break(it) => it.syntax(),
+            Jump::Continue(it) => it.syntax(),
+        }
+    }
+
+    fn lifetime(&self) -> Option<ast::Lifetime> {
+        match self {
+            Jump::Break(it) => it.lifetime(),
+            Jump::Continue(it) => it.lifetime(),
+            Jump::Return(_) | Jump::Try(_) => None,
+        }
+    }
+}
+
+/// Collects the jumps which transfer control out of `body`.
+///
+/// `return` and `?` inside nested closures, functions, async and try blocks are
+/// ignored, as are `break` and `continue` targeting a loop nested in `body`.
+pub(crate) fn escaping_jumps(body: &SyntaxNode) -> Vec<Jump> {
+    body.descendants()
+        .filter_map(|node| {
+            let jump = match_ast! {
+                match node {
+                    ast::ReturnExpr(it) => Jump::Return(it),
+                    ast::TryExpr(it) => Jump::Try(it),
+                    ast::BreakExpr(it) => Jump::Break(it),
+                    ast::ContinueExpr(it) => Jump::Continue(it),
+                    _ => return None,
+                }
+            };
+            let label = jump.lifetime().map(|it| it.syntax().text().to_string());
+            let mut enclosing = jump.syntax().ancestors().take_while(|it| it != body).skip(1);
+            let is_contained = enclosing.any(|it| match it.kind() {
+                CLOSURE_EXPR | FN => true,
+                EFFECT_EXPR => ast::EffectExpr::cast(it)
+                    .map(|it| it.async_token().is_some() || it.try_token().is_some())
+                    .unwrap_or(false),
+                LOOP_EXPR | WHILE_EXPR | FOR_EXPR => match (&jump, &label) {
+                    (Jump::Return(_), _) | (Jump::Try(_), _) => false,
+                    (_, None) => true,
+                    (_, Some(label)) => it
+                        .children()
+                        .find_map(ast::Label::cast)
+                        .and_then(|it| it.lifetime())
+                        .map(|it| it.syntax().text() == label.as_str())
+                        .unwrap_or(false),
+                },
+                _ => false,
+            });
+            if is_contained {
+                None
+            } else {
+                Some(jump)
+            }
+        })
+        .collect()
+}
+
+/// Turns the iterable of a `for` loop into an iterator suitable for a method
+/// chain: `&v` becomes `v.iter()`, `&mut v` becomes `v.iter_mut()` and
+/// anything that is not an `Iterator` already gets `.into_iter()` appended.
+pub(crate) fn for_iterable_to_iterator(
+    sema: &Semantics<RootDatabase>,
+    iterable: &ast::Expr,
+) -> String {
+    if let ast::Expr::RefExpr(ref_expr) = iterable {
+        if let Some(inner) = ref_expr.expr() {
+            let method = if ref_expr.mut_token().is_some() { "iter_mut" } else { "iter" };
+            return format!("{}.{}()", method_receiver(&inner), method);
+        }
+    }
+    let is_iterator = (|| {
+        let krate = sema.scope(iterable.syntax()).module()?.krate();
+        let iter_trait = FamousDefs(sema, Some(krate)).core_iter_Iterator()?;
+        let ty = sema.type_of_expr(iterable)?;
+        Some(ty.impls_trait(sema.db, iter_trait, &[]))
+    })()
+    .unwrap_or(false);
+    if is_iterator {
+        method_receiver(iterable)
+    } else {
+        format!("{}.into_iter()", method_receiver(iterable))
+    }
+}
+
+/// Renders `expr` so that a method call can be appended to it.
+pub(crate) fn method_receiver(expr: &ast::Expr) -> String {
+    match expr {
+        ast::Expr::PathExpr(_)
+        | ast::Expr::MethodCallExpr(_)
+        | ast::Expr::CallExpr(_)
+        | ast::Expr::FieldExpr(_)
+        | ast::Expr::IndexExpr(_)
+        | ast::Expr::ParenExpr(_)
+        | ast::Expr::MacroCall(_)
+        | ast::Expr::Literal(_)
+        | ast::Expr::TupleExpr(_)
+        | ast::Expr::ArrayExpr(_) => expr.syntax().text().to_string(),
+        _ => format!("({})", expr.syntax()),
+    }
+}
+
+/// Collects the explicit generic argument lists, like the `::<u32>` of
+/// `foo::<u32>()`, of all references to `func`.
+pub(crate) fn turbofish_usages(
+    sema: &Semantics<RootDatabase>,
+    func: hir::Function,
+) -> Vec<(FileId, ast::GenericArgList)> {
+    Definition::ModuleDef(func.into())
+        .usages(sema)
+        .all()
+        .into_iter()
+        .filter_map(|usage| {
+            let file_id = usage.file_range.file_id;
+            let source_file = sema.parse(file_id);
+            let name_ref: ast::NameRef =
+                find_node_at_range(source_file.syntax(), usage.file_range.range)?;
+            let parent = name_ref.syntax().parent()?;
+            let generic_arg_list = match_ast! {
+                match parent {
+                    ast::PathSegment(it) => it.generic_arg_list()?,
+                    ast::MethodCallExpr(it) => it.generic_arg_list()?,
+                    _ => return None,
+                }
+            };
+            Some((file_id, generic_arg_list))
+        })
+        .collect()
+}
+
+/// Removes `where_clause`, keeping a single space before a following `{`.
+pub(crate) fn delete_where_clause(builder: &mut AssistBuilder, where_clause: &ast::WhereClause) {
+    let node = where_clause.syntax();
+    let before = node.prev_sibling_or_token().filter(|it| it.kind() == WHITESPACE);
+    let after = node.next_sibling_or_token().filter(|it| it.kind() == WHITESPACE);
+    let start = before.map_or(node.text_range().start(), |it| it.text_range().start());
+    let end = after.map_or(node.text_range().end(), |it| it.text_range().end());
+    let next =
+        node.siblings_with_tokens(Direction::Next).skip(1).find(|it| it.kind() != WHITESPACE);
+    let space = match next {
+        Some(it) if matches!(it.kind(), T![;] | T![,]) => "",
+        Some(_) => " ",
+        None => "",
+    };
+    builder.replace(TextRange::new(start, end), space);
+}
+
+/// Picks a name for a new type parameter of an item with `generic_params`,
+/// starting from the first letter of `hint`.
+pub(crate) fn fresh_type_param_name(
+    generic_params: Option<&ast::GenericParamList>,
+    hint: &str,
+) -> String {
+    let taken: Vec<String> = generic_params
+        .into_iter()
+        .flat_map(|it| it.generic_params())
+        .filter_map(|param| match param {
+            ast::GenericParam::TypeParam(it) => it.name(),
+            ast::GenericParam::ConstParam(it) => it.name(),
+            ast::GenericParam::LifetimeParam(_) => None,
+        })
+        .map(|it| it.text().to_string())
+        .collect();
+    let first = hint.chars().find(|it| it.is_ascii_alphabetic()).unwrap_or('T');
+    let first = first.to_ascii_uppercase() as u8 - b'A';
+    (0..26u8)
+        .map(|offset| ((b'A' + (first + offset) % 26) as char).to_string())
+        .chain((1..).map(|idx| format!("T{}", idx)))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap()
+}
+
+/// Deletes the `removed` elements of a comma separated list of `items`,
+/// together with the separators.
+pub(crate) fn delete_from_list(
+    builder: &mut AssistBuilder,
+    items: &[SyntaxNode],
+    removed: &[SyntaxNode],
+) {
+    let mut idx = 0;
+    while idx < items.len() {
+        if !removed.contains(&items[idx]) {
+            idx += 1;
+            continue;
+        }
+        let first = idx;
+        while idx < items.len() && removed.contains(&items[idx]) {
+            idx += 1;
+        }
+        let range = match (items.get(idx), first.checked_sub(1)) {
+            (Some(next), _) => {
+                TextRange::new(items[first].text_range().start(), next.text_range().start())
+            }
+            (None, Some(prev)) => {
+                TextRange::new(items[prev].text_range().end(), items[idx - 1].text_range().end())
+            }
+            (None, None) => items[first].text_range().cover(items[idx - 1].text_range()),
+        };
+        builder.delete(range);
+    }
+}
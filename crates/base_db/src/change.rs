@@ -7,7 +7,7 @@ use rustc_hash::FxHashSet;
 use salsa::Durability;
 use vfs::FileId;
 
-use crate::{CrateGraph, SourceDatabaseExt, SourceRoot, SourceRootId};
+use crate::{CrateGraph, PackageIndex, SourceDatabaseExt, SourceRoot, SourceRootId};
 
 /// Encapsulate a bunch of raw `.set` calls on the database.
 #[derive(Default)]
@@ -15,6 +15,7 @@ pub struct Change {
     pub roots: Option<Vec<SourceRoot>>,
     pub files_changed: Vec<(FileId, Option<Arc<String>>)>,
     pub crate_graph: Option<CrateGraph>,
+    pub package_index: Option<PackageIndex>,
 }
 
 impl fmt::Debug for Change {
@@ -29,6 +30,9 @@ impl fmt::Debug for Change {
         if self.crate_graph.is_some() {
             d.field("crate_graph", &self.crate_graph);
         }
+        if let Some(package_index) = &self.package_index {
+            d.field("package_index", &package_index.features.len());
+        }
         d.finish()
     }
 }
@@ -50,6 +54,10 @@ impl Change {
         self.crate_graph = Some(graph);
     }
 
+    pub fn set_package_index(&mut self, package_index: PackageIndex) {
+        self.package_index = Some(package_index);
+    }
+
     pub fn apply(self, db: &mut dyn SourceDatabaseExt) {
         let _p = profile::span("RootDatabase::apply_change");
         // db.request_cancellation();
@@ -85,6 +93,9 @@ impl Change {
         if let Some(crate_graph) = self.crate_graph {
             db.set_crate_graph_with_durability(Arc::new(crate_graph), Durability::HIGH)
         }
+        if let Some(package_index) = self.package_index {
+            db.set_package_index_with_durability(Arc::new(package_index), Durability::HIGH)
+        }
     }
 }
 
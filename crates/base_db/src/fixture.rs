@@ -48,6 +48,8 @@
 //! - crate names via `crate:cratename`
 //! - dependencies via `deps:dep1,dep2`
 //! - configuration settings via `cfg:dbg=false,opt_level=2`
+//! - an additional instance of the crate analyzed with other configuration
+//!   settings via `alt_cfg:windows`
 //! - environment variables via `env:PATH=/bin,RUST_LOG=debug`
 //!
 //! Example using all available metadata:
@@ -66,7 +68,7 @@ use vfs::{file_set::FileSet, VfsPath};
 
 use crate::{
     input::CrateName, Change, CrateGraph, CrateId, Edition, Env, FileId, FilePosition,
-    SourceDatabaseExt, SourceRoot, SourceRootId,
+    PackageIndex, SourceDatabaseExt, SourceRoot, SourceRootId,
 };
 
 pub const WORKSPACE: SourceRootId = SourceRootId(0);
@@ -131,6 +133,7 @@ impl ChangeFixture {
         let mut crate_graph = CrateGraph::default();
         let mut crates = FxHashMap::default();
         let mut crate_deps = Vec::new();
+        let mut alt_crates = Vec::new();
         let mut default_crate_root: Option<FileId> = None;
         let mut default_cfg = CfgOptions::default();
 
@@ -160,11 +163,23 @@ impl ChangeFixture {
                     meta.edition,
                     Some(crate_name.clone().into()),
                     meta.cfg,
-                    meta.env,
+                    meta.env.clone(),
                     Default::default(),
                 );
                 let prev = crates.insert(crate_name.clone(), crate_id);
                 assert!(prev.is_none());
+                if let Some(alt_cfg) = meta.alt_cfg {
+                    let alt_id = crate_graph.add_crate_root(
+                        file_id,
+                        meta.edition,
+                        Some(crate_name.clone().into()),
+                        alt_cfg,
+                        meta.env,
+                        Default::default(),
+                    );
+                    crate_graph.set_alternative_of(alt_id, crate_id);
+                    alt_crates.push((crate_name.clone(), alt_id));
+                }
                 for dep in meta.deps {
                     let dep = CrateName::normalize_dashes(&dep);
                     crate_deps.push((crate_name.clone(), dep))
@@ -194,14 +209,30 @@ impl ChangeFixture {
             );
         } else {
             for (from, to) in crate_deps {
-                let from_id = crates[&from];
                 let to_id = crates[&to];
-                crate_graph.add_dep(from_id, CrateName::new(&to).unwrap(), to_id).unwrap();
+                let alt_ids =
+                    alt_crates.iter().filter(|(name, _)| *name == from).map(|&(_, id)| id);
+                for from_id in std::iter::once(crates[&from]).chain(alt_ids) {
+                    crate_graph.add_dep(from_id, CrateName::new(&to).unwrap(), to_id).unwrap();
+                }
+            }
+        }
+
+        // Each crate is a package declaring the features it's compiled with.
+        let mut package_index = PackageIndex::default();
+        for krate in crate_graph.iter() {
+            let data = &crate_graph[krate];
+            if let Some(name) = &data.display_name {
+                let mut features: Vec<String> =
+                    data.cfg_options.get_cfg_values("feature").map(|it| it.to_string()).collect();
+                features.sort();
+                package_index.features.insert(name.to_string(), features);
             }
         }
 
         change.set_roots(vec![SourceRoot::new_local(file_set)]);
         change.set_crate_graph(crate_graph);
+        change.set_package_index(package_index);
 
         ChangeFixture { file_position, files, change }
     }
@@ -212,6 +243,7 @@ struct FileMeta {
     krate: Option<String>,
     deps: Vec<String>,
     cfg: CfgOptions,
+    alt_cfg: Option<CfgOptions>,
     edition: Edition,
     env: Env,
 }
@@ -221,12 +253,23 @@ impl From<Fixture> for FileMeta {
         let mut cfg = CfgOptions::default();
         f.cfg_atoms.iter().for_each(|it| cfg.insert_atom(it.into()));
         f.cfg_key_values.iter().for_each(|(k, v)| cfg.insert_key_value(k.into(), v.into()));
+        let alt_cfg = if f.alt_cfg_atoms.is_empty() && f.alt_cfg_key_values.is_empty() {
+            None
+        } else {
+            let mut alt_cfg = CfgOptions::default();
+            f.alt_cfg_atoms.iter().for_each(|it| alt_cfg.insert_atom(it.into()));
+            f.alt_cfg_key_values
+                .iter()
+                .for_each(|(k, v)| alt_cfg.insert_key_value(k.into(), v.into()));
+            Some(alt_cfg)
+        };
 
         FileMeta {
             path: f.path,
             krate: f.krate,
             deps: f.deps,
             cfg,
+            alt_cfg,
             edition: f
                 .edition
                 .as_ref()
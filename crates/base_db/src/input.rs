@@ -143,7 +143,7 @@ impl CrateDisplayName {
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct ProcMacroId(pub u32);
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub enum ProcMacroKind {
     CustomDerive,
     FuncLike,
@@ -176,15 +176,34 @@ pub struct CrateData {
     /// `Dependency` matters), this name should only be used for UI.
     pub display_name: Option<CrateDisplayName>,
     pub cfg_options: CfgOptions,
+    /// All the cfg options the crate could be compiled with, like every
+    /// feature declared in `Cargo.toml`, enabled or not.
+    pub potential_cfg_options: CfgOptions,
     pub env: Env,
     pub dependencies: Vec<Dependency>,
     pub proc_macro: Vec<ProcMacro>,
+    /// For an additional instance of a crate, analyzed with an alternative set
+    /// of cfgs, the crate it is an alternative of.
+    pub alternative_of: Option<CrateId>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The packages a `Cargo.toml` can refer to, known from the project model and
+/// from the local registry cache.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageIndex {
+    /// Features declared by the packages loaded with `cargo metadata`, by
+    /// package name.
+    pub features: FxHashMap<String, Vec<String>>,
+    /// Versions of the crates in the local registry cache, by crate name,
+    /// oldest first.
+    pub registry: FxHashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Edition {
-    Edition2018,
     Edition2015,
+    Edition2018,
+    Edition2021,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -212,10 +231,12 @@ impl CrateGraph {
             root_file_id: file_id,
             edition,
             display_name,
+            potential_cfg_options: cfg_options.clone(),
             cfg_options,
             env,
             proc_macro,
             dependencies: Vec::new(),
+            alternative_of: None,
         };
         let crate_id = CrateId(self.arena.len() as u32);
         let prev = self.arena.insert(crate_id, data);
@@ -223,6 +244,17 @@ impl CrateGraph {
         crate_id
     }
 
+    /// Sets the cfg options the crate could be compiled with, which default to
+    /// the enabled ones.
+    pub fn set_potential_cfg_options(&mut self, crate_id: CrateId, cfg_options: CfgOptions) {
+        self.arena.get_mut(&crate_id).unwrap().potential_cfg_options = cfg_options;
+    }
+
+    /// Marks `crate_id` as an instance of `original` with alternative cfgs.
+    pub fn set_alternative_of(&mut self, crate_id: CrateId, original: CrateId) {
+        self.arena.get_mut(&crate_id).unwrap().alternative_of = Some(original);
+    }
+
     pub fn add_dep(
         &mut self,
         from: CrateId,
@@ -311,6 +343,7 @@ impl CrateGraph {
             for dep in &mut data.dependencies {
                 dep.crate_id = dep.crate_id.shift(start);
             }
+            data.alternative_of = data.alternative_of.map(|it| it.shift(start));
             (new_id, data)
         }));
         start
@@ -384,6 +417,7 @@ impl FromStr for Edition {
         let res = match s {
             "2015" => Edition::Edition2015,
             "2018" => Edition::Edition2018,
+            "2021" => Edition::Edition2021,
             _ => return Err(ParseEditionError { invalid_input: s.to_string() }),
         };
         Ok(res)
@@ -395,6 +429,7 @@ impl fmt::Display for Edition {
         f.write_str(match self {
             Edition::Edition2015 => "2015",
             Edition::Edition2018 => "2018",
+            Edition::Edition2021 => "2021",
         })
     }
 }
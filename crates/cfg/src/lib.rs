@@ -41,6 +41,18 @@ impl CfgOptions {
         self.enabled.insert(CfgAtom::KeyValue { key, value });
     }
 
+    /// Returns the values of the key-value options with the given key, like
+    /// all the enabled features for `feature`.
+    pub fn get_cfg_values<'a>(
+        &'a self,
+        cfg_key: &'a str,
+    ) -> impl Iterator<Item = &'a SmolStr> + 'a {
+        self.enabled.iter().filter_map(move |atom| match atom {
+            CfgAtom::KeyValue { key, value } if key == cfg_key => Some(value),
+            _ => None,
+        })
+    }
+
     pub fn apply_diff(&mut self, diff: CfgDiff) {
         for atom in diff.enable {
             self.enabled.insert(atom);
@@ -52,6 +64,7 @@ impl CfgOptions {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CfgDiff {
     // Invariants: No duplicates, no atom that's both in `enable` and `disable`.
     enable: Vec<CfgAtom>,
@@ -59,6 +72,16 @@ pub struct CfgDiff {
 }
 
 impl CfgDiff {
+    /// Creates a diff enabling `enable` and disabling `disable`, or `None` if
+    /// an atom appears twice.
+    pub fn new(enable: Vec<CfgAtom>, disable: Vec<CfgAtom>) -> Option<CfgDiff> {
+        let mut atoms = FxHashSet::default();
+        if !enable.iter().chain(disable.iter()).all(|atom| atoms.insert(atom)) {
+            return None;
+        }
+        Some(CfgDiff { enable, disable })
+    }
+
     /// Returns the total number of atoms changed by this diff.
     pub fn len(&self) -> usize {
         self.enable.len() + self.disable.len()
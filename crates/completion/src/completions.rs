@@ -1,11 +1,15 @@
 //! This module defines an accumulator for completions which are going to be presented to user.
 
 pub(crate) mod attribute;
+pub(crate) mod cargo_toml;
 pub(crate) mod dot;
+pub(crate) mod expected_type;
+pub(crate) mod format_string;
 pub(crate) mod record;
 pub(crate) mod pattern;
 pub(crate) mod fn_param;
 pub(crate) mod keyword;
+pub(crate) mod lifetime;
 pub(crate) mod snippet;
 pub(crate) mod qualified_path;
 pub(crate) mod unqualified_path;
@@ -20,8 +24,9 @@ use crate::{
     item::Builder,
     render::{
         const_::render_const, enum_variant::render_variant, function::render_fn,
-        macro_::render_macro, render_field, render_resolution, render_tuple_field,
-        type_alias::render_type_alias, RenderContext,
+        macro_::render_macro, pattern::render_struct_pat, render_field, render_resolution,
+        render_tuple_field, struct_literal::render_struct_literal, type_alias::render_type_alias,
+        RenderContext,
     },
     CompletionContext, CompletionItem,
 };
@@ -127,6 +132,28 @@ impl Completions {
         self.add(item);
     }
 
+    pub(crate) fn add_struct_literal(
+        &mut self,
+        ctx: &CompletionContext,
+        def: hir::VariantDef,
+        path: String,
+    ) {
+        if let Some(item) = render_struct_literal(RenderContext::new(ctx), def, path) {
+            self.add(item);
+        }
+    }
+
+    pub(crate) fn add_struct_pat(
+        &mut self,
+        ctx: &CompletionContext,
+        def: hir::VariantDef,
+        path: String,
+    ) {
+        if let Some(item) = render_struct_pat(RenderContext::new(ctx), def, path) {
+            self.add(item);
+        }
+    }
+
     pub(crate) fn add_enum_variant(
         &mut self,
         ctx: &CompletionContext,
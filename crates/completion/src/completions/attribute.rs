@@ -1,17 +1,20 @@
 //! Completion for attributes
 //!
 //! This module uses a bit of static metadata to provide completions
-//! for built-in attributes.
+//! for built-in attributes, and completes the derive and attribute macros
+//! and the Cargo features known to the crate graph.
 
+use hir::ScopeDef;
+use ide_db::helpers::insert_use::ImportScope;
 use itertools::Itertools;
 use rustc_hash::FxHashSet;
-use syntax::{ast, AstNode, SyntaxKind};
+use syntax::{ast, AstNode, SyntaxKind, SyntaxToken, TextRange, TextSize};
 
 use crate::{
     context::CompletionContext,
     generated_lint_completions::{CLIPPY_LINTS, FEATURES},
     item::{CompletionItem, CompletionItemKind, CompletionKind},
-    Completions,
+    Completions, ImportEdit,
 };
 
 pub(crate) fn complete_attribute(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
@@ -35,6 +38,11 @@ pub(crate) fn complete_attribute(acc: &mut Completions, ctx: &CompletionContext)
             complete_lint(acc, ctx, token_tree.clone(), DEFAULT_LINT_COMPLETIONS);
             complete_lint(acc, ctx, token_tree, CLIPPY_LINTS);
         }
+        (Some(path), Some(_token_tree))
+            if path.to_string() == "cfg" || path.to_string() == "cfg_attr" =>
+        {
+            complete_cfg_feature(acc, ctx);
+        }
         (_, Some(_token_tree)) => {}
         _ => complete_attribute_start(acc, ctx, attribute),
     }
@@ -65,6 +73,16 @@ fn complete_attribute_start(acc: &mut Completions, ctx: &CompletionContext, attr
             acc.add(item.build());
         }
     }
+
+    ctx.scope.process_all_names(&mut |name, scope_def| {
+        if let ScopeDef::MacroDef(mac) = scope_def {
+            if mac.is_attr_macro() {
+                CompletionItem::new(CompletionKind::Attribute, ctx.source_range(), name.to_string())
+                    .kind(CompletionItemKind::Attribute)
+                    .add_to(acc)
+            }
+        }
+    });
 }
 
 struct AttrCompletion {
@@ -181,14 +199,98 @@ fn complete_derive(acc: &mut Completions, ctx: &CompletionContext, derive_input:
                 .add_to(acc)
         }
 
-        for custom_derive_name in get_derive_names_in_scope(ctx).difference(&existing_derives) {
+        let derives_in_scope = get_derive_names_in_scope(ctx);
+        for custom_derive_name in derives_in_scope.difference(&existing_derives) {
             CompletionItem::new(CompletionKind::Attribute, ctx.source_range(), custom_derive_name)
                 .kind(CompletionItemKind::Attribute)
                 .add_to(acc)
         }
+
+        if ctx.config.enable_autoimport_completions && ctx.config.resolve_additional_edits_lazily()
+        {
+            complete_derives_to_import(acc, ctx, &derives_in_scope, &existing_derives);
+        }
     }
 }
 
+/// Completes the derive macros exported by the dependencies of the crate, like
+/// `serde::Serialize`, importing the macro on completion.
+fn complete_derives_to_import(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    derives_in_scope: &FxHashSet<String>,
+    existing_derives: &FxHashSet<String>,
+) -> Option<()> {
+    let current_module = ctx.scope.module()?;
+    let import_scope =
+        ImportScope::find_insert_use_container(&ctx.original_token.parent(), &ctx.sema)?;
+    for dep in ctx.krate?.dependencies(ctx.db) {
+        for (name, def) in dep.krate.root_module(ctx.db).scope(ctx.db, None) {
+            let mac = match def {
+                ScopeDef::MacroDef(mac) if mac.is_derive_macro() => mac,
+                _ => continue,
+            };
+            let name = name.to_string();
+            if derives_in_scope.contains(&name) || existing_derives.contains(&name) {
+                continue;
+            }
+            let import_path = match current_module.find_use_path(ctx.db, mac) {
+                Some(it) => it,
+                None => continue,
+            };
+            CompletionItem::new(CompletionKind::Attribute, ctx.source_range(), name)
+                .kind(CompletionItemKind::Attribute)
+                .add_import(Some(ImportEdit { import_path, import_scope: import_scope.clone() }))
+                .add_to(acc)
+        }
+    }
+    Some(())
+}
+
+/// Completes the Cargo features of the crate in `#[cfg(feature = "...")]`.
+fn complete_cfg_feature(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
+    let token = &ctx.original_token;
+    let (range, quote) = if token.kind() == SyntaxKind::STRING {
+        // Replace the contents of the string, keeping the quotes.
+        let range = token.text_range();
+        if range.len() < TextSize::of("\"\"") || !token.text().ends_with('"') {
+            return None;
+        }
+        let range =
+            TextRange::new(range.start() + TextSize::of('"'), range.end() - TextSize::of('"'));
+        (range, false)
+    } else {
+        (TextRange::empty(ctx.position.offset), true)
+    };
+    let eq = match token.kind() {
+        SyntaxKind::STRING | SyntaxKind::WHITESPACE => prev_non_trivia_token(token)?,
+        _ => token.clone(),
+    };
+    if eq.kind() != SyntaxKind::EQ || prev_non_trivia_token(&eq)?.text() != "feature" {
+        return None;
+    }
+
+    for feature in ctx.krate?.potential_cfg_values(ctx.db, "feature") {
+        let insert = if quote { format!("\"{}\"", feature) } else { feature.clone() };
+        CompletionItem::new(CompletionKind::Attribute, range, feature)
+            .kind(CompletionItemKind::Attribute)
+            .insert_text(insert)
+            .add_to(acc)
+    }
+    Some(())
+}
+
+fn prev_non_trivia_token(token: &SyntaxToken) -> Option<SyntaxToken> {
+    let mut token = token.prev_token();
+    while let Some(inner) = token {
+        if !inner.kind().is_trivia() {
+            return Some(inner);
+        }
+        token = inner.prev_token();
+    }
+    None
+}
+
 fn complete_lint(
     acc: &mut Completions,
     ctx: &CompletionContext,
@@ -406,7 +508,10 @@ const DEFAULT_LINT_COMPLETIONS: &[LintCompletion] = &[
 mod tests {
     use expect_test::{expect, Expect};
 
-    use crate::{test_utils::completion_list, CompletionKind};
+    use crate::{
+        test_utils::{check_edit, check_edit_with_config, completion_list},
+        CompletionConfig, CompletionKind, CompletionResolveCapability,
+    };
 
     fn check(ra_fixture: &str, expect: Expect) {
         let actual = completion_list(ra_fixture, CompletionKind::Attribute);
@@ -562,4 +667,89 @@ struct Test {}
             "#]],
         );
     }
+
+    #[test]
+    fn completes_derives_of_dependencies() {
+        let mut config = CompletionConfig::default();
+        config.active_resolve_capabilities.insert(CompletionResolveCapability::AdditionalTextEdits);
+        check_edit_with_config(
+            config,
+            "Serialize",
+            r#"
+//- /main.rs crate:main deps:serde_derive
+#[derive(Debug, Ser<|>)]
+struct Config;
+//- /lib.rs crate:serde_derive
+#[proc_macro_derive(Serialize)]
+pub fn derive_serialize(input: TokenStream) -> TokenStream {}
+#[proc_macro_attribute]
+pub fn serde(attr: TokenStream, input: TokenStream) -> TokenStream {}
+"#,
+            r#"
+use serde_derive::Serialize;
+
+#[derive(Debug, Serialize)]
+struct Config;
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_attribute_macros_in_scope() {
+        let completions = completion_list(
+            r#"
+//- /main.rs crate:main deps:tokio_macros
+use tokio_macros::{main, Spawn};
+#[<|>]
+fn main() {}
+//- /lib.rs crate:tokio_macros
+#[proc_macro_attribute]
+pub fn main(attr: TokenStream, input: TokenStream) -> TokenStream {}
+#[proc_macro_derive(Spawn)]
+pub fn derive_spawn(input: TokenStream) -> TokenStream {}
+"#,
+            CompletionKind::Attribute,
+        );
+        assert!(completions.contains("at main\n"));
+        assert!(!completions.contains("at Spawn\n"));
+    }
+
+    #[test]
+    fn completes_cargo_features() {
+        check(
+            r#"
+//- /main.rs crate:main cfg:feature=std,feature=serde
+#[cfg(feature = "<|>")]
+mod io {}
+"#,
+            expect![[r#"
+                at serde
+                at std
+            "#]],
+        );
+        check_edit(
+            "std",
+            r#"
+//- /main.rs crate:main cfg:feature=std,feature=serde
+#[cfg(feature = "s<|>")]
+mod io {}
+"#,
+            r#"
+#[cfg(feature = "std")]
+mod io {}
+"#,
+        );
+        check_edit(
+            "serde",
+            r#"
+//- /main.rs crate:main cfg:feature=std,feature=serde
+#[cfg_attr(feature = <|>, derive(Debug))]
+struct Config;
+"#,
+            r#"
+#[cfg_attr(feature = "serde", derive(Debug))]
+struct Config;
+"#,
+        );
+    }
 }
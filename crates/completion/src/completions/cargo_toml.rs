@@ -0,0 +1,208 @@
+//! Completes the tables, keys, dependencies and features of `Cargo.toml`
+//! files.
+
+use ide_db::{
+    base_db::{FilePosition, SourceDatabase, SourceDatabaseExt},
+    cargo_toml::{manifest_position, ManifestContext, Table, TABLES},
+    RootDatabase,
+};
+
+use crate::{
+    item::{CompletionItem, CompletionItemKind, CompletionKind},
+    Completions,
+};
+
+pub(crate) fn complete_cargo_toml(
+    acc: &mut Completions,
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<()> {
+    let text = db.file_text(position.file_id);
+    let manifest_position = manifest_position(&text, position.offset)?;
+    let range = manifest_position.range;
+    let in_string = manifest_position.in_string;
+    let quote = |it: &str| {
+        if in_string {
+            it.to_string()
+        } else {
+            format!("\"{}\"", it)
+        }
+    };
+    let package_index = db.package_index();
+
+    match manifest_position.context {
+        ManifestContext::TableHeader => {
+            for table in TABLES {
+                CompletionItem::new(CompletionKind::Manifest, range, table.name)
+                    .kind(CompletionItemKind::Module)
+                    .detail(table.description)
+                    .add_to(acc)
+            }
+        }
+        ManifestContext::Key(table) => {
+            for key in table.keys() {
+                CompletionItem::new(CompletionKind::Manifest, range, key.name)
+                    .kind(CompletionItemKind::Field)
+                    .detail(key.description)
+                    .add_to(acc)
+            }
+            if table == Table::Dependencies {
+                let mut crates: Vec<_> = package_index.registry.iter().collect();
+                crates.sort_by_key(|&(name, _)| name);
+                for (name, versions) in crates {
+                    let latest = match versions.last() {
+                        Some(it) => it,
+                        None => continue,
+                    };
+                    CompletionItem::new(CompletionKind::Manifest, range, name.as_str())
+                        .kind(CompletionItemKind::Module)
+                        .detail(latest.as_str())
+                        .insert_text(format!("{} = \"{}\"", name, latest))
+                        .add_to(acc)
+                }
+            }
+        }
+        ManifestContext::DependencyVersion(name) => {
+            let versions = package_index.registry.get(&name)?;
+            for version in versions.iter().rev() {
+                CompletionItem::new(CompletionKind::Manifest, range, version.as_str())
+                    .kind(CompletionItemKind::Const)
+                    .insert_text(quote(version))
+                    .add_to(acc)
+            }
+        }
+        ManifestContext::DependencyFeature(name) => {
+            let features = package_index
+                .features
+                .get(&name)
+                .or_else(|| package_index.features.get(&name.replace('_', "-")))?;
+            for feature in features {
+                CompletionItem::new(CompletionKind::Manifest, range, feature.as_str())
+                    .kind(CompletionItemKind::Attribute)
+                    .insert_text(quote(feature))
+                    .add_to(acc)
+            }
+        }
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use expect_test::{expect, Expect};
+    use ide_db::{
+        base_db::{fixture::WithFixture, PackageIndex, SourceDatabase},
+        RootDatabase,
+    };
+
+    use crate::{
+        test_utils::{check_edit, completion_list},
+        CompletionConfig, CompletionKind,
+    };
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Manifest);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_tables_and_keys() {
+        check(
+            r#"
+//- /Cargo.toml
+[profile.release]
+opt<|>
+//- /lib.rs
+"#,
+            expect![[r#"
+                fd opt-level        The optimization level, `0` to `3`, `"s"` or `"z"`.
+                fd debug            The amount of debug information, `0`, `1` or `2`.
+                fd debug-assertions Whether `cfg(debug_assertions)` is enabled.
+                fd overflow-checks  Whether integer overflows panic.
+                fd lto              Link time optimizations, `false`, `true`, `"thin"` or `"off"`.
+                fd panic            The panic strategy, `"unwind"` or `"abort"`.
+                fd incremental      Whether incremental compilation is enabled.
+                fd codegen-units    How many code generation units a crate is split into.
+                fd rpath            Whether rpath is enabled.
+            "#]],
+        );
+        check_edit(
+            "dev-dependencies",
+            r#"
+//- /Cargo.toml
+[dev-<|>]
+//- /lib.rs
+"#,
+            r#"
+[dev-dependencies]
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_features_of_dependencies() {
+        check(
+            r#"
+//- /Cargo.toml
+[dependencies]
+serde = { version = "1.0", features = ["<|>"] }
+//- /lib.rs crate:main deps:serde
+//- /serde.rs crate:serde cfg:feature=derive,feature=std
+"#,
+            expect![[r#"
+                at derive
+                at std
+            "#]],
+        );
+        check_edit(
+            "derive",
+            r#"
+//- /Cargo.toml
+[dependencies.serde]
+features = [<|>]
+//- /lib.rs crate:main deps:serde
+//- /serde.rs crate:serde cfg:feature=derive,feature=std
+"#,
+            r#"
+[dependencies.serde]
+features = ["derive"]
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_crates_of_the_local_registry() {
+        let (mut db, position) = RootDatabase::with_position(
+            r#"
+//- /Cargo.toml
+[dependencies]
+rand = "<|>"
+ser
+//- /lib.rs
+"#,
+        );
+        let mut package_index = PackageIndex::default();
+        package_index.registry.insert("rand".to_string(), vec!["0.7.3".into(), "0.8.0".into()]);
+        package_index.registry.insert("serde".to_string(), vec!["1.0.118".into()]);
+        db.set_package_index(Arc::new(package_index));
+
+        let completions: Vec<_> =
+            crate::completions(&db, &CompletionConfig::default(), position).unwrap().into();
+        let versions: Vec<_> = completions.iter().map(|it| it.label()).collect();
+        assert_eq!(versions, vec!["0.8.0", "0.7.3"]);
+
+        let position = ide_db::base_db::FilePosition {
+            offset: position.offset + syntax::TextSize::of("\"\nser"),
+            ..position
+        };
+        let completions: Vec<_> =
+            crate::completions(&db, &CompletionConfig::default(), position).unwrap().into();
+        let crates: Vec<_> = completions
+            .iter()
+            .map(|it| format!("{} {:?}", it.label(), it.text_edit().iter().next().unwrap().insert))
+            .collect();
+        assert_eq!(crates, vec![r#"rand "rand = \"0.8.0\"""#, r#"serde "serde = \"1.0.118\"""#]);
+    }
+}
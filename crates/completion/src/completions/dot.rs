@@ -1,10 +1,14 @@
 //! Completes references after dot (fields and method calls).
 
-use hir::{HasVisibility, Type};
+use hir::{AsAssocItem, AssocItemContainer, Crate, HasVisibility, Name, Type};
 use rustc_hash::FxHashSet;
 use test_utils::mark;
 
-use crate::{context::CompletionContext, Completions};
+use crate::{
+    context::CompletionContext,
+    render::{function::render_fn, RenderContext},
+    Completions,
+};
 
 /// Complete dot accesses, i.e. fields or methods.
 pub(crate) fn complete_dot(acc: &mut Completions, ctx: &CompletionContext) {
@@ -56,7 +60,46 @@ fn complete_methods(acc: &mut Completions, ctx: &CompletionContext, receiver: &T
             }
             None::<()>
         });
+
+        if ctx.config.enable_autoimport_completions && ctx.config.resolve_additional_edits_lazily()
+        {
+            complete_methods_to_import(acc, ctx, receiver, krate, seen_methods);
+        }
+    }
+}
+
+/// Completes the methods of traits which are implemented for the receiver, but
+/// are not in scope yet, importing the trait on completion.
+fn complete_methods_to_import(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    receiver: &Type,
+    krate: Crate,
+    mut seen_methods: FxHashSet<Name>,
+) {
+    let traits_to_import = ctx.traits_to_import();
+    if traits_to_import.is_empty() {
+        return;
     }
+    let mut traits = ctx.scope.traits_in_scope();
+    for &(trait_, _) in traits_to_import.iter() {
+        traits.insert(trait_.into());
+    }
+
+    receiver.iterate_method_candidates(ctx.db, krate, &traits, None, |_ty, func| {
+        let trait_ = match func.as_assoc_item(ctx.db).map(|it| it.container(ctx.db)) {
+            Some(AssocItemContainer::Trait(it)) => it,
+            _ => return None,
+        };
+        let import_edit = traits_to_import.iter().find(|(it, _)| *it == trait_)?.1.clone();
+        if func.self_param(ctx.db).is_some()
+            && ctx.scope.module().map(|m| func.is_visible_from(ctx.db, m)).unwrap_or(true)
+            && seen_methods.insert(func.name(ctx.db))
+        {
+            acc.add(render_fn(RenderContext::new(ctx), Some(import_edit), None, func));
+        }
+        None::<()>
+    });
 }
 
 #[cfg(test)]
@@ -64,13 +107,39 @@ mod tests {
     use expect_test::{expect, Expect};
     use test_utils::mark;
 
-    use crate::{test_utils::completion_list, CompletionKind};
+    use crate::{
+        test_utils::{check_edit_with_config, completion_list, completion_list_with_config},
+        CompletionConfig, CompletionKind, CompletionResolveCapability,
+    };
 
     fn check(ra_fixture: &str, expect: Expect) {
         let actual = completion_list(ra_fixture, CompletionKind::Reference);
         expect.assert_eq(&actual);
     }
 
+    fn autoimport_config() -> CompletionConfig {
+        let mut config = CompletionConfig::default();
+        config.active_resolve_capabilities.insert(CompletionResolveCapability::AdditionalTextEdits);
+        config
+    }
+
+    const DEP: &str = r#"
+//- /lib.rs crate:dep
+pub mod io {
+    pub trait Write {
+        fn write(&mut self, data: &[u8]);
+        fn flush(&mut self) {}
+    }
+    pub trait Read {
+        fn read(&mut self) -> u8;
+    }
+}
+pub struct Buffer;
+impl io::Write for Buffer {
+    fn write(&mut self, data: &[u8]) {}
+}
+"#;
+
     #[test]
     fn test_struct_field_and_method_completion() {
         check(
@@ -428,4 +497,70 @@ fn main() { make_s!().f<|>; }
             "#]],
         )
     }
+
+    #[test]
+    fn completes_methods_of_traits_to_import() {
+        let check = |ra_fixture: &str, expect: Expect| {
+            let fixture = format!("{}{}", DEP, ra_fixture);
+            let actual = completion_list_with_config(
+                autoimport_config(),
+                &fixture,
+                CompletionKind::Reference,
+            );
+            expect.assert_eq(&actual);
+        };
+        check(
+            r#"
+//- /main.rs crate:main deps:dep
+fn main(buf: dep::Buffer) { buf.wr<|> }
+"#,
+            expect![[r#"
+                me write(…) (use dep::io::Write) fn write(&mut self, data: &[u8])
+                me flush() (use dep::io::Write) fn flush(&mut self)
+            "#]],
+        );
+        check(
+            r#"
+//- /main.rs crate:main deps:dep
+use dep::io::Write;
+fn main(buf: dep::Buffer) { buf.wr<|> }
+"#,
+            expect![[r#"
+                me write(…) fn write(&mut self, data: &[u8])
+                me flush()  fn flush(&mut self)
+            "#]],
+        );
+        check(
+            r#"
+//- /main.rs crate:main deps:dep
+fn main(buf: dep::Buffer) { buf.<|> }
+"#,
+            expect![[""]],
+        );
+    }
+
+    #[test]
+    fn imports_trait_of_completed_method() {
+        check_edit_with_config(
+            autoimport_config(),
+            "write",
+            &format!(
+                "{}{}",
+                DEP,
+                r#"
+//- /main.rs crate:main deps:dep
+fn main(mut buf: dep::Buffer) {
+    buf.wr<|>
+}
+"#
+            ),
+            r#"
+use dep::io::Write;
+
+fn main(mut buf: dep::Buffer) {
+    buf.write(${1:data})$0
+}
+"#,
+        );
+    }
 }
@@ -0,0 +1,466 @@
+//! Completes whole expressions of the type expected at the cursor, like
+//! `Foo::new()`, `Default::default()` or `self.config.timeout`.
+//!
+//! Enum variants of the expected type are completed by `unqualified_path`.
+
+use hir::{Adt, Function, HasVisibility, HirDisplay, ModuleDef, Type};
+use ide_db::helpers::FamousDefs;
+use rustc_hash::FxHashSet;
+use syntax::SyntaxKind::IDENT;
+
+use crate::{
+    item::{CompletionItemKind, CompletionKind, Locality},
+    CompletionContext, CompletionItem, CompletionRelevance, Completions,
+};
+
+/// How many fields deep we look into the locals.
+const MAX_FIELD_DEPTH: usize = 2;
+/// How many methods can be chained on a local.
+const MAX_METHOD_CHAIN: usize = 2;
+
+pub(crate) fn complete_expected_type(acc: &mut Completions, ctx: &CompletionContext) {
+    if !ctx.is_trivial_path || !ctx.is_expr || ctx.record_lit_syntax.is_some() {
+        return;
+    }
+    let expected = match expected_type(ctx) {
+        Some(it) if !it.is_unknown() && !it.is_unit() => it,
+        _ => return,
+    };
+    let module = match ctx.scope.module() {
+        Some(it) => it,
+        None => return,
+    };
+
+    if let Some(adt) = expected.as_adt() {
+        complete_constructors(acc, ctx, module, adt, &expected);
+    }
+
+    let mut field_paths = Vec::new();
+    for (name, local) in ctx.locals.iter() {
+        let field_path = FieldPath {
+            path: name.clone(),
+            ty: local.ty(ctx.db),
+            depth: 0,
+            behind_ref: false,
+            mutable: local.is_mut(ctx.db),
+        };
+        collect_field_paths(ctx, module, field_path, &mut field_paths);
+    }
+    for field_path in field_paths.iter() {
+        // Locals themselves are completed as usual.
+        if field_path.depth > 0 {
+            complete_path(acc, ctx, &expected, field_path);
+        }
+        complete_cloned_path(acc, ctx, &expected, field_path);
+    }
+
+    for (name, local) in ctx.locals.iter() {
+        complete_method_chains(acc, ctx, module, &expected, name.clone(), local.ty(ctx.db));
+    }
+}
+
+/// `expected_type` is the type of the innermost expression around the token
+/// before the cursor, which is the completed expression only if there's an
+/// identifier under the cursor. Record fields and call arguments know better.
+fn expected_type(ctx: &CompletionContext) -> Option<Type> {
+    if let Some(record_field) = &ctx.record_field_syntax {
+        let (field, _local) = ctx.sema.resolve_record_field(record_field)?;
+        return Some(field.signature_ty(ctx.db));
+    }
+    if let Some(active_parameter) = &ctx.active_parameter {
+        return Some(active_parameter.ty.clone());
+    }
+    if ctx.token.kind() == IDENT {
+        return ctx.expected_type.clone();
+    }
+    None
+}
+
+fn relevance(expected: &Type, ty: &Type) -> CompletionRelevance {
+    CompletionRelevance {
+        exact_type_match: expected == ty,
+        locality: Some(Locality::Local),
+        ..CompletionRelevance::default()
+    }
+}
+
+/// Completes associated functions of `adt` returning the expected type, and
+/// `Default::default()`.
+fn complete_constructors(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    module: hir::Module,
+    adt: Adt,
+    expected: &Type,
+) -> Option<()> {
+    let adt_path = module.find_use_path(ctx.db, ModuleDef::Adt(adt))?;
+    let krate = ctx.krate?;
+
+    let mut constructors = Vec::new();
+    expected.clone().iterate_assoc_items(ctx.db, krate, |item| {
+        if let hir::AssocItem::Function(func) = item {
+            if func.self_param(ctx.db).is_none()
+                && func.is_visible_from(ctx.db, module)
+                && func.ret_type_could_unify_with(ctx.db, expected)
+            {
+                constructors.push(func);
+            }
+        }
+        None::<()>
+    });
+    for func in constructors {
+        let ret_type = func.ret_type(ctx.db);
+        let path = format!("{}::{}", adt_path, func.name(ctx.db));
+        add_call(acc, ctx, path, !func.assoc_fn_params(ctx.db).is_empty(), expected, &ret_type);
+    }
+
+    if let Some(default_trait) = FamousDefs(&ctx.sema, ctx.krate).core_default_Default() {
+        if expected.impls_trait(ctx.db, default_trait, &[]) {
+            let trait_path = module.find_use_path(ctx.db, ModuleDef::Trait(default_trait))?;
+            let path = format!("{}::default", trait_path);
+            add_call(acc, ctx, path, false, expected, expected);
+        }
+    }
+    Some(())
+}
+
+fn add_call(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    path: String,
+    has_params: bool,
+    expected: &Type,
+    ty: &Type,
+) {
+    let label = format!("{}({})", path, if has_params { "…" } else { "" });
+    let mut builder = CompletionItem::new(CompletionKind::Magic, ctx.source_range(), label)
+        .kind(CompletionItemKind::Function)
+        .lookup_by(path.clone())
+        .detail(ty.display(ctx.db).to_string())
+        .set_relevance(CompletionRelevance {
+            exact_type_match: expected == ty,
+            ..CompletionRelevance::default()
+        });
+    builder = match ctx.config.snippet_cap {
+        Some(cap) if has_params => {
+            builder.insert_snippet(cap, format!("{}($0)", path)).trigger_call_info()
+        }
+        _ => builder.insert_text(format!("{}()", path)),
+    };
+    builder.add_to(acc);
+}
+
+/// A local, or a field reachable from it like `local.field.other_field`.
+struct FieldPath {
+    path: String,
+    ty: Type,
+    depth: usize,
+    /// Whether the path goes through a reference, so that the value can't be
+    /// moved out of it.
+    behind_ref: bool,
+    /// Whether the value can be borrowed mutably.
+    mutable: bool,
+}
+
+fn collect_field_paths(
+    ctx: &CompletionContext,
+    module: hir::Module,
+    field_path: FieldPath,
+    acc: &mut Vec<FieldPath>,
+) {
+    if field_path.ty.is_unknown() {
+        return;
+    }
+    if field_path.depth < MAX_FIELD_DEPTH {
+        let derefed = field_path.ty.remove_ref();
+        let behind_ref = field_path.behind_ref || derefed.is_some();
+        let mutable = match derefed {
+            Some(_) => field_path.ty.is_mutable_reference(),
+            None => field_path.mutable,
+        };
+        let derefed = derefed.unwrap_or_else(|| field_path.ty.clone());
+        for (field, ty) in derefed.fields(ctx.db) {
+            if !field.is_visible_from(ctx.db, module) {
+                continue;
+            }
+            let path = format!("{}.{}", field_path.path, field.name(ctx.db));
+            let depth = field_path.depth + 1;
+            let field_path = FieldPath { path, ty, depth, behind_ref, mutable };
+            collect_field_paths(ctx, module, field_path, acc);
+        }
+    }
+    acc.push(field_path);
+}
+
+fn complete_path(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    expected: &Type,
+    field_path: &FieldPath,
+) {
+    let FieldPath { path, ty, behind_ref, mutable, .. } = field_path;
+    let can_move = !behind_ref || ty.is_copy(ctx.db);
+    let (label, target) = if ty.could_unify_with(expected) && can_move {
+        (path.to_string(), expected.clone())
+    } else {
+        match expected.remove_ref() {
+            Some(derefed) if ty.could_unify_with(&derefed) => {
+                let ref_ = match expected.is_mutable_reference() {
+                    true if !mutable => return,
+                    true => "&mut ",
+                    false => "&",
+                };
+                (format!("{}{}", ref_, path), derefed)
+            }
+            _ => return,
+        }
+    };
+    CompletionItem::new(CompletionKind::Magic, ctx.source_range(), label)
+        .kind(CompletionItemKind::Field)
+        .lookup_by(path)
+        .detail(ty.display(ctx.db).to_string())
+        .set_relevance(relevance(&target, ty))
+        .add_to(acc);
+}
+
+/// Completes `path.clone()` if `path` is a reference to the expected type, or
+/// a value of the expected type which can't be moved.
+fn complete_cloned_path(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    expected: &Type,
+    field_path: &FieldPath,
+) -> Option<()> {
+    let FieldPath { path, ty, behind_ref, .. } = field_path;
+    let value_ty = match ty.remove_ref() {
+        Some(it) => it,
+        None if *behind_ref && !ty.is_copy(ctx.db) => ty.clone(),
+        None => return None,
+    };
+    if !value_ty.could_unify_with(expected) {
+        return None;
+    }
+    let krate = ctx.krate?;
+    let traits_in_scope = ctx.scope.traits_in_scope();
+    value_ty.iterate_method_candidates(ctx.db, krate, &traits_in_scope, None, |_, func| {
+        if func.name(ctx.db).to_string() == "clone" {
+            Some(())
+        } else {
+            None
+        }
+    })?;
+
+    CompletionItem::new(CompletionKind::Magic, ctx.source_range(), format!("{}.clone()", path))
+        .kind(CompletionItemKind::Method)
+        .lookup_by(path)
+        .detail(value_ty.display(ctx.db).to_string())
+        .set_relevance(relevance(expected, &value_ty))
+        .add_to(acc);
+    Some(())
+}
+
+/// Completes `receiver.foo()` and `receiver.foo().bar()`, for methods without
+/// arguments returning the expected type.
+fn complete_method_chains(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    module: hir::Module,
+    expected: &Type,
+    receiver: String,
+    receiver_ty: Type,
+) -> Option<()> {
+    let krate = ctx.krate?;
+    let traits_in_scope = ctx.scope.traits_in_scope();
+    let mut receivers = vec![(receiver, receiver_ty)];
+    for _ in 0..MAX_METHOD_CHAIN {
+        let mut next_receivers = Vec::new();
+        for (receiver, receiver_ty) in receivers {
+            if receiver_ty.is_unknown() || receiver_ty.is_unit() {
+                continue;
+            }
+            let mut methods: Vec<Function> = Vec::new();
+            let mut seen = FxHashSet::default();
+            receiver_ty.iterate_method_candidates(
+                ctx.db,
+                krate,
+                &traits_in_scope,
+                None,
+                |_, func| {
+                    let takes_no_args =
+                        func.method_params(ctx.db).map(|it| it.is_empty()).unwrap_or(false);
+                    if takes_no_args
+                        && func.is_visible_from(ctx.db, module)
+                        && seen.insert(func.name(ctx.db))
+                    {
+                        methods.push(func);
+                    }
+                    None::<()>
+                },
+            );
+
+            for func in methods {
+                let ret_type = func.ret_type(ctx.db);
+                let chain = format!("{}.{}()", receiver, func.name(ctx.db));
+                if ret_type.is_unknown() || !ret_type.could_unify_with(expected) {
+                    next_receivers.push((chain, ret_type));
+                    continue;
+                }
+                CompletionItem::new(CompletionKind::Magic, ctx.source_range(), chain.clone())
+                    .kind(CompletionItemKind::Method)
+                    .lookup_by(chain)
+                    .detail(ret_type.display(ctx.db).to_string())
+                    .set_relevance(relevance(expected, &ret_type))
+                    .add_to(acc);
+            }
+        }
+        receivers = next_receivers;
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use ide_db::helpers::FamousDefs;
+
+    use crate::{test_utils::completion_list, CompletionKind};
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Magic);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_record_variants_and_constructors() {
+        check(
+            &format!(
+                "//- /main.rs crate:main deps:core\n{}\n{}",
+                r#"
+mod config {
+    pub enum Timeout { Fixed { millis: u32, retries: u8 }, Unlimited }
+    pub struct Config { timeout: Timeout }
+    impl Config {
+        pub fn new() -> Config { Config { timeout: Timeout::Unlimited } }
+        pub fn with_timeout(timeout: Timeout) -> Self { Config { timeout } }
+        pub fn timeout(&self) -> &Timeout { &self.timeout }
+        fn private() -> Config { Config::new() }
+    }
+    impl Default for Config { fn default() -> Self { Config::new() } }
+}
+use config::{Config, Timeout};
+
+fn run(config: Config, timeout: Timeout) {}
+fn main() {
+    let millis: u32 = 92;
+    run(<|>)
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            expect![[r#"
+                fn Config::new()    Config
+                fn Config::with_timeout(…) Config
+                fn Default::default() Config
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_generic_constructors() {
+        check(
+            r#"
+struct Wrapper<T> { value: T }
+impl<T> Wrapper<T> {
+    fn new() -> Wrapper<T> { loop {} }
+    fn wrapping(value: T) -> Self { Wrapper { value } }
+    fn value(self) -> T { self.value }
+}
+impl Wrapper<u8> {
+    fn bytes() -> Wrapper<u8> { loop {} }
+}
+fn run(wrapper: Wrapper<u32>) {}
+fn main() { run(<|>) }
+"#,
+            expect![[r#"
+                st Wrapper { value } { value: T }
+                fn Wrapper::new()   Wrapper<T>
+                fn Wrapper::wrapping(…) Wrapper<T>
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_fields_of_locals() {
+        check(
+            r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for u32 {}
+struct Config { timeout: u32, name: Name }
+struct Name;
+struct Server { config: Config, port: u32 }
+impl Server {
+    fn run(&self) { sleep(<|>) }
+}
+fn sleep(millis: u32) {}
+"#,
+            expect![[r#"
+                st Config { timeout, name } { timeout: u32, name: Name }
+                st Server { config, port } { config: Config, port: u32 }
+                fd self.config.timeout u32
+                fd self.port        u32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_references_and_clones() {
+        check(
+            r#"
+trait Clone { fn clone(&self) -> Self; }
+struct Name;
+impl Clone for Name { fn clone(&self) -> Self { Name } }
+struct Person { name: Name }
+fn greet(name: Name) {}
+fn main(person: &Person, other: &Name) { greet(<|>) }
+"#,
+            expect![[r#"
+                st Person { name }  { name: Name }
+                me person.name.clone() Name
+                me other.clone()    Name
+            "#]],
+        );
+        check(
+            r#"
+struct Name;
+struct Person { name: Name }
+fn greet(name: &mut Name) {}
+fn main(person: Person, mut other: Person, borrowed: &Person) { greet(<|>) }
+"#,
+            expect![[r#"
+                st Person { name } { name: Name }
+                fd &mut other.name Name
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_method_chains() {
+        check(
+            r#"
+struct Server;
+struct Config;
+impl Server { fn config(&self) -> Config { Config } }
+impl Config {
+    fn timeout(&self) -> u32 { 92 }
+    fn scaled_timeout(&self, factor: u32) -> u32 { 92 * factor }
+}
+fn sleep(millis: u32) {}
+fn main(server: Server) { sleep(<|>) }
+"#,
+            expect![[r#"
+                me server.config().timeout() u32
+            "#]],
+        );
+    }
+}
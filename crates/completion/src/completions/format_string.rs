@@ -0,0 +1,264 @@
+//! Completes the `{}` placeholders of the format strings of `format!`-like
+//! macros: variables captured by name, fields passed as arguments and format
+//! specs.
+
+use hir::{HasVisibility, HirDisplay};
+use ide_db::helpers::format_string::{open_placeholder, placeholders, FormatCall, PlaceholderArg};
+use syntax::{
+    ast::{self, AstToken},
+    TextRange, TextSize,
+};
+use text_edit::TextEdit;
+
+use crate::{CompletionContext, CompletionItem, CompletionItemKind, CompletionKind, Completions};
+
+/// Format specs, with their descriptions.
+const FORMAT_SPECS: &[(&str, &str)] = &[
+    (":?", "Debug"),
+    (":#?", "Debug, pretty-printed"),
+    (":x", "LowerHex"),
+    (":X", "UpperHex"),
+    (":#x", "LowerHex, with a `0x` prefix"),
+    (":b", "Binary"),
+    (":o", "Octal"),
+    (":e", "LowerExp"),
+    (":>8", "right-aligned, 8 wide"),
+    (":<8", "left-aligned, 8 wide"),
+    (":^8", "centered, 8 wide"),
+    (":08", "zero-padded, 8 wide"),
+    (":+", "always with a sign"),
+    (":.3", "3 digits of precision"),
+];
+
+pub(crate) fn complete_format_string(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
+    let string = ast::String::cast(ctx.original_token.clone())?;
+    let call = FormatCall::from_string(&string)?;
+    let string_start = string.syntax().text_range().start();
+    let prefix = string.text().get(..usize::from(ctx.position.offset - string_start))?;
+    let placeholder_start = open_placeholder(prefix)?;
+    let contents = &prefix[placeholder_start..];
+    let range_of = |start: usize| {
+        TextRange::new(string_start + TextSize::from(start as u32), ctx.position.offset)
+    };
+
+    if let Some(colon) = contents.find(':') {
+        let range = range_of(placeholder_start + colon);
+        for &(spec, description) in FORMAT_SPECS {
+            CompletionItem::new(CompletionKind::Magic, range, spec)
+                .kind(CompletionItemKind::Keyword)
+                .detail(description)
+                .add_to(acc);
+        }
+        return Some(());
+    }
+    if !contents.chars().all(|c| c == '_' || c == '.' || c.is_alphanumeric()) {
+        return None;
+    }
+
+    let range = range_of(placeholder_start);
+    for (name, local) in &ctx.locals {
+        let ty = local.ty(ctx.db);
+        CompletionItem::new(CompletionKind::Reference, range, name.as_str())
+            .kind(CompletionItemKind::Binding)
+            .detail(ty.display(ctx.db).to_string())
+            .add_to(acc);
+    }
+
+    // Fields can't be captured by name, so they are passed as positional
+    // arguments, after the ones of the placeholders before the cursor.
+    let preceding = placeholders(&prefix[..placeholder_start - 1])?
+        .iter()
+        .filter(|it| it.arg == PlaceholderArg::Next)
+        .count();
+    let positional: Vec<_> = call.args.iter().filter(|it| it.name.is_none()).collect();
+    for (name, local) in &ctx.locals {
+        for (field, ty) in local.ty(ctx.db).fields(ctx.db) {
+            if ctx.scope.module().map(|m| field.is_visible_from(ctx.db, m)) == Some(false) {
+                continue;
+            }
+            let arg = format!("{}.{}", name, field.name(ctx.db));
+            let mut builder = TextEdit::builder();
+            builder.delete(range);
+            match positional.get(preceding) {
+                Some(next) => builder.insert(next.range.start(), format!("{}, ", arg)),
+                None => {
+                    let end = positional
+                        .last()
+                        .map_or(string.syntax().text_range().end(), |it| it.range.end());
+                    builder.insert(end, format!(", {}", arg))
+                }
+            }
+            CompletionItem::new(CompletionKind::Reference, range, arg)
+                .kind(CompletionItemKind::Field)
+                .detail(ty.display(ctx.db).to_string())
+                .text_edit(builder.finish())
+                .add_to(acc);
+        }
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::{
+        test_utils::{check_edit, completion_list},
+        CompletionKind,
+    };
+
+    fn check(ra_fixture: &str, kind: CompletionKind, expect: Expect) {
+        let actual = completion_list(ra_fixture, kind);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_variables_and_fields() {
+        check(
+            r#"
+macro_rules! println { ($($t:tt)*) => { 0 } }
+struct Point { x: i32, y: i32 }
+fn main() {
+    let count = 92;
+    let point = Point { x: 1, y: 2 };
+    println!("{{}} {} {c<|>", count);
+}
+"#,
+            CompletionKind::Reference,
+            expect![[r#"
+                bn point   Point
+                bn count   i32
+                fd point.x i32
+                fd point.y i32
+            "#]],
+        );
+        check_edit(
+            "count",
+            r#"
+macro_rules! println { ($($t:tt)*) => { 0 } }
+fn main() {
+    let count = 92;
+    println!("{co<|>");
+}
+"#,
+            r#"
+macro_rules! println { ($($t:tt)*) => { 0 } }
+fn main() {
+    let count = 92;
+    println!("{count");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn passes_fields_as_arguments() {
+        check_edit(
+            "point.y",
+            r#"
+macro_rules! println { ($($t:tt)*) => { 0 } }
+struct Point { x: i32, y: i32 }
+fn main() {
+    let point = Point { x: 1, y: 2 };
+    println!("{} {<|>} {}", point.x, 3, sep = ",");
+}
+"#,
+            r#"
+macro_rules! println { ($($t:tt)*) => { 0 } }
+struct Point { x: i32, y: i32 }
+fn main() {
+    let point = Point { x: 1, y: 2 };
+    println!("{} {} {}", point.x, point.y, 3, sep = ",");
+}
+"#,
+        );
+        check_edit(
+            "point.x",
+            r#"
+macro_rules! println { ($($t:tt)*) => { 0 } }
+struct Point { x: i32, y: i32 }
+fn main() {
+    let point = Point { x: 1, y: 2 };
+    println!("{count} {po<|>}");
+}
+"#,
+            r#"
+macro_rules! println { ($($t:tt)*) => { 0 } }
+struct Point { x: i32, y: i32 }
+fn main() {
+    let point = Point { x: 1, y: 2 };
+    println!("{count} {}", point.x);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_format_specs() {
+        check(
+            r#"
+macro_rules! format { ($($t:tt)*) => { 0 } }
+fn main() {
+    let s = format!("{value:<|>}");
+}
+"#,
+            CompletionKind::Magic,
+            expect![[r##"
+                kw :?  Debug
+                kw :#? Debug, pretty-printed
+                kw :x  LowerHex
+                kw :X  UpperHex
+                kw :#x LowerHex, with a `0x` prefix
+                kw :b  Binary
+                kw :o  Octal
+                kw :e  LowerExp
+                kw :>8 right-aligned, 8 wide
+                kw :<8 left-aligned, 8 wide
+                kw :^8 centered, 8 wide
+                kw :08 zero-padded, 8 wide
+                kw :+  always with a sign
+                kw :.3 3 digits of precision
+            "##]],
+        );
+        check_edit(
+            ":#?",
+            r#"
+macro_rules! format { ($($t:tt)*) => { 0 } }
+fn main() {
+    let s = format!("{value:#<|>}");
+}
+"#,
+            r#"
+macro_rules! format { ($($t:tt)*) => { 0 } }
+fn main() {
+    let s = format!("{value:#?}");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_completions_outside_of_placeholders() {
+        check(
+            r#"
+macro_rules! println { ($($t:tt)*) => { 0 } }
+fn main() {
+    let count = 92;
+    println!("{count} c<|>");
+}
+"#,
+            CompletionKind::Reference,
+            expect![[r#""#]],
+        );
+        check(
+            r#"
+fn main() {
+    let count = 92;
+    let s = "{c<|>";
+}
+"#,
+            CompletionKind::Reference,
+            expect![[r#""#]],
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Completes lifetimes and labels.
+
+use hir::ScopeDef;
+use syntax::{ast, AstNode};
+
+use crate::{CompletionContext, CompletionItem, CompletionItemKind, CompletionKind, Completions};
+
+/// Completes lifetimes in scope, as in `&'<|> str`.
+pub(crate) fn complete_lifetime(acc: &mut Completions, ctx: &CompletionContext) {
+    if !ctx.lifetime_allowed {
+        return;
+    }
+    let param_lifetime = ctx
+        .lifetime_param_syntax
+        .as_ref()
+        .and_then(|param| param.lifetime())
+        .map(|lifetime| lifetime.text().to_string());
+
+    ctx.scope.process_all_names(&mut |name, res| {
+        if let ScopeDef::GenericParam(hir::GenericParam::LifetimeParam(_)) = res {
+            // `'a: 'a` is not useful.
+            if param_lifetime.as_deref() != Some(name.to_string().as_str()) {
+                acc.add_resolution(ctx, name.to_string(), &res);
+            }
+        }
+    });
+
+    // Lifetimes bound by `for<'a>` are not part of the generics of the item.
+    if let Some(lifetime) = &ctx.lifetime_syntax {
+        for node in lifetime.syntax().ancestors() {
+            let params = if let Some(for_type) = ast::ForType::cast(node.clone()) {
+                for_type.generic_param_list()
+            } else if let Some(where_pred) = ast::WherePred::cast(node) {
+                where_pred.generic_param_list()
+            } else {
+                continue;
+            };
+            for param in params.iter().flat_map(|it| it.lifetime_params()) {
+                if let Some(lifetime) = param.lifetime() {
+                    CompletionItem::new(
+                        CompletionKind::Reference,
+                        ctx.source_range(),
+                        lifetime.text().to_string(),
+                    )
+                    .kind(CompletionItemKind::LifetimeParam)
+                    .add_to(acc);
+                }
+            }
+        }
+    }
+
+    CompletionItem::new(CompletionKind::Reference, ctx.source_range(), "'static")
+        .kind(CompletionItemKind::LifetimeParam)
+        .add_to(acc);
+}
+
+/// Completes labels in scope, as in `break '<|>`.
+pub(crate) fn complete_label(acc: &mut Completions, ctx: &CompletionContext) {
+    if !ctx.is_label_ref {
+        return;
+    }
+    ctx.scope.process_all_names(&mut |name, res| {
+        if let ScopeDef::Label(_) = res {
+            acc.add_resolution(ctx, name.to_string(), &res);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use test_utils::mark;
+
+    use crate::{
+        test_utils::{check_edit, completion_list},
+        CompletionKind,
+    };
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Reference);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_lifetimes_in_types() {
+        check(
+            r#"
+fn foo<'lifetime, T>(x: &'<|> T) {}
+"#,
+            expect![[r#"
+                lt 'lifetime
+                lt 'static
+            "#]],
+        );
+        check_edit(
+            "'lifetime",
+            r#"
+fn foo<'lifetime>(x: &'li<|> str) {}
+"#,
+            r#"
+fn foo<'lifetime>(x: &'lifetime str) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_lifetimes_of_impls_and_binders() {
+        check(
+            r#"
+struct Foo<'a>(&'a ());
+impl<'impl_> Foo<'impl_> {
+    fn foo<'fn_>(f: impl for<'hr> Fn(&'<|> ())) {}
+}
+"#,
+            expect![[r#"
+                lt 'fn_
+                lt 'impl_
+                lt 'hr
+                lt 'static
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_lifetimes_in_bounds() {
+        check(
+            r#"
+fn foo<'lifetime, 'b: 'li<|>>() {}
+"#,
+            expect![[r#"
+                lt 'lifetime
+                lt 'static
+            "#]],
+        );
+        check(
+            r#"
+fn foo<'a, '<|>>() {}
+"#,
+            expect![[r#""#]],
+        );
+    }
+
+    #[test]
+    fn completes_labels() {
+        check(
+            r#"
+fn foo() {
+    'outer: loop {
+        'inner: for _ in 0..10 {
+            break '<|>
+        }
+    }
+    'after: loop {}
+}
+"#,
+            expect![[r#"
+                lb 'inner
+                lb 'outer
+            "#]],
+        );
+        check(
+            r#"
+fn foo() {
+    'outer: while true {
+        let f = || { 'inner: loop { continue '<|> } };
+    }
+}
+"#,
+            expect![[r#"
+                lb 'inner
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_generic_params_in_types() {
+        mark::check!(skip_lifetime_completion);
+        check(
+            r#"
+struct Foo<'a, T, const N: usize>(&'a [T; N]);
+impl<'a, T, const N: usize> Foo<'a, T, N> {
+    fn foo(x: <|>) {}
+}
+"#,
+            expect![[r#"
+                bn x
+                tp Self
+                tp T
+                cp N
+                st Foo<…>
+            "#]],
+        );
+    }
+}
@@ -34,10 +34,27 @@ pub(crate) fn complete_pattern(acc: &mut Completions, ctx: &CompletionContext) {
         };
         if add_resolution {
             acc.add_resolution(ctx, name.to_string(), &res);
+            if !ctx.is_pattern_call {
+                add_struct_pat(acc, ctx, name.to_string(), &res);
+            }
         }
     });
 }
 
+fn add_struct_pat(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    name: String,
+    resolution: &hir::ScopeDef,
+) {
+    let def = match resolution {
+        hir::ScopeDef::ModuleDef(hir::ModuleDef::Adt(hir::Adt::Struct(it))) => (*it).into(),
+        hir::ScopeDef::ModuleDef(hir::ModuleDef::Variant(it)) => (*it).into(),
+        _ => return,
+    };
+    acc.add_struct_pat(ctx, def, name);
+}
+
 #[cfg(test)]
 mod tests {
     use expect_test::{expect, Expect};
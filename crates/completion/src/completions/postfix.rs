@@ -1,6 +1,7 @@
 //! Postfix completions, like `Ok(10).ifl<|>` => `if let Ok() = Ok(10) { <|> }`.
 
 mod format_like;
+mod type_directed;
 
 use ide_db::ty_filter::TryEnum;
 use syntax::{
@@ -10,12 +11,15 @@ use syntax::{
 };
 use text_edit::TextEdit;
 
-use self::format_like::add_format_like_completions;
+use self::{
+    format_like::add_format_like_completions,
+    type_directed::{add_method_like_completions, add_wrapping_completions},
+};
 use crate::{
     config::SnippetCap,
     context::CompletionContext,
     item::{Builder, CompletionKind},
-    CompletionItem, CompletionItemKind, Completions,
+    CompletionItem, CompletionItemKind, Completions, SnippetScope,
 };
 
 pub(crate) fn complete_postfix(acc: &mut Completions, ctx: &CompletionContext) {
@@ -109,6 +113,8 @@ pub(crate) fn complete_postfix(acc: &mut Completions, ctx: &CompletionContext) {
             .add_to(acc);
     }
 
+    add_method_like_completions(acc, ctx, cap, dot_receiver, &receiver_text, &receiver_ty);
+
     postfix_snippet(ctx, cap, &dot_receiver, "ref", "&expr", &format!("&{}", receiver_text))
         .add_to(acc);
     postfix_snippet(
@@ -221,6 +227,8 @@ pub(crate) fn complete_postfix(acc: &mut Completions, ctx: &CompletionContext) {
     )
     .add_to(acc);
 
+    add_wrapping_completions(acc, ctx, cap, &dot_receiver, &receiver_text, &receiver_ty);
+
     if let Some(parent) = dot_receiver.syntax().parent().and_then(|p| p.parent()) {
         if matches!(parent.kind(), BLOCK_EXPR | EXPR_STMT) {
             postfix_snippet(
@@ -249,6 +257,30 @@ pub(crate) fn complete_postfix(acc: &mut Completions, ctx: &CompletionContext) {
             add_format_like_completions(acc, ctx, &dot_receiver, cap, &literal_text);
         }
     }
+
+    add_custom_postfix_completions(acc, ctx, cap, &dot_receiver, &receiver_text);
+}
+
+fn add_custom_postfix_completions(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    cap: SnippetCap,
+    receiver: &ast::Expr,
+    receiver_text: &str,
+) {
+    for user_snippet in ctx.config.snippets.iter().filter(|it| it.scope == SnippetScope::Expr) {
+        let imports = match user_snippet.imports(ctx) {
+            Some(it) => it,
+            None => continue,
+        };
+        let body = user_snippet.postfix_snippet(receiver_text);
+        for trigger in user_snippet.postfix_triggers.iter() {
+            postfix_snippet(ctx, cap, receiver, trigger, "", &body)
+                .set_detail(user_snippet.description.clone())
+                .insert_imports(&imports, ctx.config.merge)
+                .add_to(acc);
+        }
+    }
 }
 
 fn get_receiver_text(receiver: &ast::Expr, receiver_is_ambiguous_float_literal: bool) -> String {
@@ -296,8 +328,8 @@ mod tests {
     use expect_test::{expect, Expect};
 
     use crate::{
-        test_utils::{check_edit, completion_list},
-        CompletionKind,
+        test_utils::{check_edit, check_edit_with_config, completion_list},
+        CompletionConfig, CompletionKind, Snippet, SnippetScope,
     };
 
     fn check(ra_fixture: &str, expect: Expect) {
@@ -547,4 +579,40 @@ fn main() {
             r#"fn main() { log::error!("{}", 2+2) }"#,
         );
     }
+
+    #[test]
+    fn user_postfix_snippets() {
+        let snippet = Snippet::new(
+            &[],
+            &["arcm".to_string()],
+            &["Arc::new(Mutex::new(${receiver}))".to_string()],
+            "Arc<Mutex<T>>",
+            &["std::sync::Arc".to_string(), "std::sync::Mutex".to_string()],
+            SnippetScope::Expr,
+        )
+        .unwrap();
+        let config = CompletionConfig { snippets: vec![snippet], ..CompletionConfig::default() };
+        check_edit_with_config(
+            config,
+            "arcm",
+            r#"
+//- /main.rs crate:main deps:std
+fn main() {
+    let data = 92.<|>
+}
+//- /std/lib.rs crate:std
+pub mod sync {
+    pub struct Arc<T>(T);
+    pub struct Mutex<T>(T);
+}
+"#,
+            r#"
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let data = Arc::new(Mutex::new(92))
+}
+"#,
+        );
+    }
 }
@@ -0,0 +1,382 @@
+// Feature: Type-Directed Postfix Completion.
+//
+// Some postfix snippets are only offered for receivers of a fitting type:
+//
+// - `unwrap_or`, `unwrap_or_else` and `unwrap_or_default` on `Option` and `Result`
+// - `ok_or` on `Option`
+// - `try` -> `expr?`, in functions returning the same kind of `Option` or `Result`
+// - `lock` -> `expr.lock().unwrap()` on `Mutex`
+// - `iter` -> `for x in expr {}` on `IntoIterator` types
+// - `arc` and `rc`, when `std::sync::Arc` and `std::rc::Rc` are available
+// - `return`, for values of the return type of the function
+
+use hir::Type;
+use ide_db::{helpers::FamousDefs, ty_filter::TryEnum};
+use syntax::ast::{self, AstNode};
+
+use crate::{
+    completions::postfix::postfix_snippet, config::SnippetCap, context::CompletionContext,
+    snippet::import_edits, Completions,
+};
+
+/// Adds the completions which call a method on the receiver, or use it as is.
+pub(crate) fn add_method_like_completions(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    cap: SnippetCap,
+    receiver: &ast::Expr,
+    receiver_text: &str,
+    receiver_ty: &Type,
+) {
+    let famous_defs = FamousDefs(&ctx.sema, ctx.krate);
+
+    if let Some(try_enum) = TryEnum::from_ty(&ctx.sema, receiver_ty) {
+        postfix_snippet(
+            ctx,
+            cap,
+            receiver,
+            "unwrap_or",
+            "expr.unwrap_or(default)",
+            &format!("{}.unwrap_or($0)", receiver_text),
+        )
+        .add_to(acc);
+        let closure_params = match try_enum {
+            TryEnum::Option => "||",
+            TryEnum::Result => "|${1:err}|",
+        };
+        postfix_snippet(
+            ctx,
+            cap,
+            receiver,
+            "unwrap_or_else",
+            "expr.unwrap_or_else(|| default)",
+            &format!("{}.unwrap_or_else({} $0)", receiver_text, closure_params),
+        )
+        .add_to(acc);
+        let inner_is_default =
+            match (famous_defs.core_default_Default(), receiver_ty.type_arguments().first()) {
+                (Some(default_trait), Some(inner)) => inner.impls_trait(ctx.db, default_trait, &[]),
+                _ => false,
+            };
+        if inner_is_default {
+            postfix_snippet(
+                ctx,
+                cap,
+                receiver,
+                "unwrap_or_default",
+                "expr.unwrap_or_default()",
+                &format!("{}.unwrap_or_default()", receiver_text),
+            )
+            .add_to(acc);
+        }
+        if let TryEnum::Option = try_enum {
+            postfix_snippet(
+                ctx,
+                cap,
+                receiver,
+                "ok_or",
+                "expr.ok_or(err)",
+                &format!("{}.ok_or($0)", receiver_text),
+            )
+            .add_to(acc);
+        }
+
+        let fn_try_enum =
+            enclosing_fn_ret_type(ctx).and_then(|ty| TryEnum::from_ty(&ctx.sema, &ty));
+        if fn_try_enum == Some(try_enum) {
+            postfix_snippet(ctx, cap, receiver, "try", "expr?", &format!("{}?", receiver_text))
+                .add_to(acc);
+        }
+    }
+
+    if let Some(mutex) = famous_defs.std_sync_Mutex() {
+        if receiver_ty.autoderef(ctx.db).any(|ty| ty.as_adt() == Some(mutex.into())) {
+            postfix_snippet(
+                ctx,
+                cap,
+                receiver,
+                "lock",
+                "expr.lock().unwrap()",
+                &format!("{}.lock().unwrap()", receiver_text),
+            )
+            .add_to(acc);
+        }
+    }
+}
+
+/// Adds the completions which move the receiver into a new expression.
+pub(crate) fn add_wrapping_completions(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    cap: SnippetCap,
+    receiver: &ast::Expr,
+    receiver_text: &str,
+    receiver_ty: &Type,
+) {
+    let into_iterator = FamousDefs(&ctx.sema, ctx.krate).core_iter_IntoIterator();
+    if let Some(into_iterator) = into_iterator {
+        if receiver_ty.impls_trait(ctx.db, into_iterator, &[]) {
+            postfix_snippet(
+                ctx,
+                cap,
+                receiver,
+                "iter",
+                "for x in expr {}",
+                &format!("for ${{1:x}} in {} {{\n    $0\n}}", receiver_text),
+            )
+            .add_to(acc);
+        }
+    }
+
+    for &(label, path, name) in &[("arc", "std::sync::Arc", "Arc"), ("rc", "std::rc::Rc", "Rc")] {
+        let imports = match import_edits(ctx, &[path]) {
+            Some(it) => it,
+            None => continue,
+        };
+        postfix_snippet(
+            ctx,
+            cap,
+            receiver,
+            label,
+            &format!("{}::new(expr)", name),
+            &format!("{}::new({})", name, receiver_text),
+        )
+        .insert_imports(&imports, ctx.config.merge)
+        .add_to(acc);
+    }
+
+    if let Some(ret_ty) = enclosing_fn_ret_type(ctx) {
+        if !receiver_ty.is_unknown() && receiver_ty.could_unify_with(&ret_ty) {
+            postfix_snippet(
+                ctx,
+                cap,
+                receiver,
+                "return",
+                "return expr",
+                &format!("return {}", receiver_text),
+            )
+            .add_to(acc);
+        }
+    }
+}
+
+/// The return type of the function around the completion, unless the
+/// completion is inside of a closure.
+fn enclosing_fn_ret_type(ctx: &CompletionContext) -> Option<Type> {
+    let fn_ = ctx
+        .sema
+        .ancestors_with_macros(ctx.original_token.parent())
+        .take_while(|it| !ast::ClosureExpr::can_cast(it.kind()))
+        .find_map(ast::Fn::cast)?;
+    Some(ctx.sema.to_def(&fn_)?.ret_type(ctx.db))
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use ide_db::helpers::FamousDefs;
+
+    use crate::{
+        test_utils::{check_edit, completion_list},
+        CompletionKind,
+    };
+
+    const STD_FIXTURE: &str = r#"
+//- /std.rs crate:std deps:core
+pub mod sync {
+    pub struct Mutex<T>(T);
+    pub struct Arc<T>(T);
+}
+pub mod rc {
+    pub struct Rc<T>(T);
+}
+"#;
+
+    fn with_std(ra_fixture: &str) -> String {
+        format!(
+            "//- /main.rs crate:main deps:core,std\n{}{}{}",
+            ra_fixture.trim_start(),
+            FamousDefs::FIXTURE,
+            STD_FIXTURE
+        )
+    }
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(&with_std(ra_fixture), CompletionKind::Postfix);
+        expect.assert_eq(&actual)
+    }
+
+    #[test]
+    fn completes_option_methods() {
+        check(
+            r#"
+struct Config;
+impl Default for Config { fn default() -> Self { Config } }
+fn foo() -> Option<u32> {
+    let bar = Some(Config);
+    bar.<|>
+}
+"#,
+            expect![[r#"
+                sn ifl              if let Some {}
+                sn while            while let Some {}
+                sn unwrap_or        expr.unwrap_or(default)
+                sn unwrap_or_else   expr.unwrap_or_else(|| default)
+                sn unwrap_or_default expr.unwrap_or_default()
+                sn ok_or            expr.ok_or(err)
+                sn try              expr?
+                sn ref              &expr
+                sn refm             &mut expr
+                sn match            match expr {}
+                sn box              Box::new(expr)
+                sn ok               Ok(expr)
+                sn some             Some(expr)
+                sn dbg              dbg!(expr)
+                sn dbgr             dbg!(&expr)
+                sn call             function(expr)
+                sn arc              Arc::new(expr)
+                sn rc               Rc::new(expr)
+                sn let              let
+                sn letm             let mut
+            "#]],
+        );
+        check_edit(
+            "ok_or",
+            &with_std(
+                r#"
+fn main() {
+    let bar = Some(92u32);
+    bar.<|>
+}
+"#,
+            ),
+            r#"fn main() {
+    let bar = Some(92u32);
+    bar.ok_or($0)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_try_in_functions_returning_result() {
+        check_edit(
+            "try",
+            r#"
+enum Result<T, E> { Ok(T), Err(E) }
+fn parse() -> Result<u32, ()> { Result::Ok(92) }
+fn main() -> Result<(), ()> {
+    let x = parse().<|>
+}
+"#,
+            r#"
+enum Result<T, E> { Ok(T), Err(E) }
+fn parse() -> Result<u32, ()> { Result::Ok(92) }
+fn main() -> Result<(), ()> {
+    let x = parse()?
+}
+"#,
+        );
+        check_edit(
+            "unwrap_or_else",
+            r#"
+enum Result<T, E> { Ok(T), Err(E) }
+fn parse() -> Result<u32, ()> { Result::Ok(92) }
+fn main() {
+    let x = parse().<|>
+}
+"#,
+            r#"
+enum Result<T, E> { Ok(T), Err(E) }
+fn parse() -> Result<u32, ()> { Result::Ok(92) }
+fn main() {
+    let x = parse().unwrap_or_else(|${1:err}| $0)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_iter_lock_and_wrappers() {
+        check(
+            r#"
+use std::sync::Mutex;
+struct Counter;
+fn main(counter: Mutex<Counter>) {
+    counter.<|>
+}
+"#,
+            expect![[r#"
+                sn lock  expr.lock().unwrap()
+                sn ref   &expr
+                sn refm  &mut expr
+                sn match match expr {}
+                sn box   Box::new(expr)
+                sn ok    Ok(expr)
+                sn some  Some(expr)
+                sn dbg   dbg!(expr)
+                sn dbgr  dbg!(&expr)
+                sn call  function(expr)
+                sn arc   Arc::new(expr)
+                sn rc    Rc::new(expr)
+                sn let   let
+                sn letm  let mut
+            "#]],
+        );
+        check_edit(
+            "iter",
+            &with_std(
+                r#"
+fn main() {
+    core::iter::repeat(92).<|>
+}
+"#,
+            ),
+            r#"fn main() {
+    for ${1:x} in core::iter::repeat(92) {
+    $0
+}
+}
+"#,
+        );
+        check_edit(
+            "rc",
+            &with_std(
+                r#"
+fn main() {
+    let x = 92.<|>
+}
+"#,
+            ),
+            r#"use std::rc::Rc;
+
+fn main() {
+    let x = Rc::new(92)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_return_of_the_return_type() {
+        check_edit(
+            "return",
+            r#"
+fn foo(x: u32) -> u32 {
+    if x > 92 {
+        x.<|>
+    }
+    0
+}
+"#,
+            r#"
+fn foo(x: u32) -> u32 {
+    if x > 92 {
+        return x
+    }
+    0
+}
+"#,
+        );
+    }
+}
@@ -1,11 +1,14 @@
 //! Completion of paths, i.e. `some::prefix::<|>`.
 
-use hir::{Adt, HasVisibility, PathResolution, ScopeDef};
+use hir::{Adt, AsAssocItem, AssocItemContainer, HasVisibility, PathResolution, ScopeDef, Type};
 use rustc_hash::FxHashSet;
 use syntax::AstNode;
 use test_utils::mark;
 
-use crate::{CompletionContext, Completions};
+use crate::{
+    render::{function::render_fn, RenderContext},
+    CompletionContext, Completions,
+};
 
 pub(crate) fn complete_qualified_path(acc: &mut Completions, ctx: &CompletionContext) {
     let path = match &ctx.path_qual {
@@ -82,6 +85,12 @@ pub(crate) fn complete_qualified_path(acc: &mut Completions, ctx: &CompletionCon
                     None::<()>
                 });
 
+                if ctx.config.enable_autoimport_completions
+                    && ctx.config.resolve_additional_edits_lazily()
+                {
+                    complete_assoc_fns_to_import(acc, ctx, &ty, krate);
+                }
+
                 // Iterate assoc types separately
                 ty.iterate_assoc_items(ctx.db, krate, |item| {
                     if context_module.map_or(false, |m| !item.is_visible_from(ctx.db, m)) {
@@ -144,14 +153,51 @@ pub(crate) fn complete_qualified_path(acc: &mut Completions, ctx: &CompletionCon
     }
 }
 
+/// Completes the associated functions of traits which are implemented for `ty`,
+/// but are not in scope yet, importing the trait on completion.
+fn complete_assoc_fns_to_import(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    ty: &Type,
+    krate: hir::Crate,
+) {
+    let traits_to_import = ctx.traits_to_import();
+    if traits_to_import.is_empty() {
+        return;
+    }
+    let mut traits = ctx.scope.traits_in_scope();
+    for &(trait_, _) in traits_to_import.iter() {
+        traits.insert(trait_.into());
+    }
+
+    let context_module = ctx.scope.module();
+    let mut seen = FxHashSet::default();
+    ty.iterate_path_candidates(ctx.db, krate, &traits, None, |_ty, item| {
+        let func = match item {
+            hir::AssocItem::Function(it) => it,
+            hir::AssocItem::Const(_) | hir::AssocItem::TypeAlias(_) => return None,
+        };
+        let trait_ = match func.as_assoc_item(ctx.db).map(|it| it.container(ctx.db)) {
+            Some(AssocItemContainer::Trait(it)) => it,
+            _ => return None,
+        };
+        let import_edit = traits_to_import.iter().find(|(it, _)| *it == trait_)?.1.clone();
+        let is_visible = context_module.map(|m| func.is_visible_from(ctx.db, m)).unwrap_or(true);
+        if is_visible && seen.insert(func) {
+            acc.add(render_fn(RenderContext::new(ctx), Some(import_edit), None, func));
+        }
+        None::<()>
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use expect_test::{expect, Expect};
     use test_utils::mark;
 
     use crate::{
-        test_utils::{check_edit, completion_list},
-        CompletionKind,
+        test_utils::{check_edit, check_edit_with_config, completion_list},
+        CompletionConfig, CompletionKind, CompletionResolveCapability,
     };
 
     fn check(ra_fixture: &str, expect: Expect) {
@@ -752,4 +798,39 @@ fn main() {
             "#]],
         );
     }
+
+    #[test]
+    fn imports_trait_of_completed_assoc_fn() {
+        let mut config = CompletionConfig::default();
+        config.active_resolve_capabilities.insert(CompletionResolveCapability::AdditionalTextEdits);
+        check_edit_with_config(
+            config,
+            "from_bytes",
+            r#"
+//- /lib.rs crate:dep
+pub mod convert {
+    pub trait FromBytes {
+        fn from_bytes(bytes: &[u8]) -> Self;
+    }
+}
+pub struct Buffer;
+impl convert::FromBytes for Buffer {
+    fn from_bytes(bytes: &[u8]) -> Self { Buffer }
+}
+//- /main.rs crate:main deps:dep
+use dep::Buffer;
+
+fn main() {
+    Buffer::fro<|>
+}
+"#,
+            r#"
+use dep::{Buffer, convert::FromBytes};
+
+fn main() {
+    Buffer::from_bytes(${1:bytes})$0
+}
+"#,
+        );
+    }
 }
@@ -2,7 +2,7 @@
 
 use crate::{
     config::SnippetCap, item::Builder, CompletionContext, CompletionItem, CompletionItemKind,
-    CompletionKind, Completions,
+    CompletionKind, Completions, SnippetScope,
 };
 
 fn snippet(ctx: &CompletionContext, cap: SnippetCap, label: &str, snippet: &str) -> Builder {
@@ -22,6 +22,10 @@ pub(crate) fn complete_expr_snippet(acc: &mut Completions, ctx: &CompletionConte
 
     snippet(ctx, cap, "pd", "eprintln!(\"$0 = {:?}\", $0);").add_to(acc);
     snippet(ctx, cap, "ppd", "eprintln!(\"$0 = {:#?}\", $0);").add_to(acc);
+
+    if ctx.is_expr {
+        add_custom_completions(acc, ctx, cap, SnippetScope::Expr);
+    }
 }
 
 pub(crate) fn complete_item_snippet(acc: &mut Completions, ctx: &CompletionContext) {
@@ -65,19 +69,88 @@ fn ${1:feature}() {
     .add_to(acc);
 
     snippet(ctx, cap, "macro_rules", "macro_rules! $1 {\n\t($2) => {\n\t\t$0\n\t};\n}").add_to(acc);
+
+    add_custom_completions(acc, ctx, cap, SnippetScope::Item);
+}
+
+pub(crate) fn complete_type_snippet(acc: &mut Completions, ctx: &CompletionContext) {
+    if !(ctx.is_trivial_path && ctx.is_path_type) {
+        return;
+    }
+    let cap = match ctx.config.snippet_cap {
+        Some(it) => it,
+        None => return,
+    };
+
+    add_custom_completions(acc, ctx, cap, SnippetScope::Type);
+}
+
+fn add_custom_completions(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    cap: SnippetCap,
+    scope: SnippetScope,
+) {
+    for user_snippet in ctx.config.snippets.iter().filter(|it| it.scope == scope) {
+        let imports = match user_snippet.imports(ctx) {
+            Some(it) => it,
+            None => continue,
+        };
+        for trigger in user_snippet.prefix_triggers.iter() {
+            snippet(ctx, cap, trigger, user_snippet.snippet())
+                .set_detail(user_snippet.description.clone())
+                .insert_imports(&imports, ctx.config.merge)
+                .add_to(acc);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use expect_test::{expect, Expect};
 
-    use crate::{test_utils::completion_list, CompletionKind};
+    use crate::{
+        test_utils::{check_edit_with_config, completion_list, completion_list_with_config},
+        CompletionConfig, CompletionKind, Snippet, SnippetScope,
+    };
 
     fn check(ra_fixture: &str, expect: Expect) {
         let actual = completion_list(ra_fixture, CompletionKind::Snippet);
         expect.assert_eq(&actual)
     }
 
+    fn user_snippets_config() -> CompletionConfig {
+        let snippet = |prefix: &str, body: &str, requires: &[&str], scope| {
+            let requires = requires.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+            Snippet::new(&[prefix.to_string()], &[], &[body.to_string()], "", &requires, scope)
+                .unwrap()
+        };
+        CompletionConfig {
+            snippets: vec![
+                snippet(
+                    "arcm",
+                    "Arc::new(Mutex::new($0))",
+                    &["std::sync::Arc", "std::sync::Mutex"],
+                    SnippetScope::Expr,
+                ),
+                snippet("ordering", "Ordering", &["std::cmp::Ordering"], SnippetScope::Type),
+                snippet("derived", "#[derive($0)]", &[], SnippetScope::Item),
+                snippet("unresolved", "Foo::new()", &["std::foo::Foo"], SnippetScope::Expr),
+            ],
+            ..CompletionConfig::default()
+        }
+    }
+
+    const STD: &str = r#"//- /std/lib.rs crate:std
+pub mod sync {
+    pub struct Arc<T>(T);
+    pub struct Mutex<T>(T);
+}
+pub mod cmp {
+    pub enum Ordering { Less, Equal, Greater }
+}
+"#;
+
     #[test]
     fn completes_snippets_in_expressions() {
         check(
@@ -111,4 +184,86 @@ mod tests {
             "#]],
         )
     }
+
+    #[test]
+    fn user_snippets_are_scoped() {
+        let check = |ra_fixture: &str, expect: Expect| {
+            let fixture = format!("{}\n{}", ra_fixture, STD);
+            let actual = completion_list_with_config(
+                user_snippets_config(),
+                &fixture,
+                CompletionKind::Snippet,
+            );
+            expect.assert_eq(&actual)
+        };
+        check(
+            "//- /main.rs crate:main deps:std\nfn foo() { <|> }",
+            expect![[r#"
+                sn pd
+                sn ppd
+                sn arcm
+            "#]],
+        );
+        check(
+            "//- /main.rs crate:main deps:std\nfn foo(x: <|>) {}",
+            expect![[r#"
+                sn pd
+                sn ppd
+                sn ordering
+            "#]],
+        );
+        check(
+            "//- /main.rs crate:main deps:std\nmod m { <|> }",
+            expect![[r#"
+                sn tmod (Test module)
+                sn tfn (Test function)
+                sn macro_rules
+                sn derived
+            "#]],
+        );
+    }
+
+    #[test]
+    fn user_snippets_import_required_paths() {
+        check_edit_with_config(
+            user_snippets_config(),
+            "arcm",
+            &format!(
+                "{}{}",
+                r#"
+//- /main.rs crate:main deps:std
+use std::sync::Mutex;
+
+fn main() {
+    let x = <|>
+}
+"#,
+                STD
+            ),
+            r#"
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let x = Arc::new(Mutex::new($0))
+}
+"#,
+        );
+        check_edit_with_config(
+            user_snippets_config(),
+            "ordering",
+            &format!(
+                "{}{}",
+                r#"
+//- /main.rs crate:main deps:std
+fn cmp(a: i32, b: i32) -> <|> {}
+"#,
+                STD
+            ),
+            r#"
+use std::cmp::Ordering;
+
+fn cmp(a: i32, b: i32) -> Ordering {}
+"#,
+        );
+    }
 }
@@ -1,7 +1,7 @@
 //! Completion of names from the current scope, e.g. locals and imported items.
 
 use either::Either;
-use hir::{Adt, ModPath, ModuleDef, ScopeDef, Type};
+use hir::{Adt, ModPath, ModuleDef, ScopeDef, Type, VariantDef};
 use ide_db::helpers::insert_use::ImportScope;
 use ide_db::imports_locator;
 use syntax::AstNode;
@@ -33,6 +33,12 @@ pub(crate) fn complete_unqualified_path(acc: &mut Completions, ctx: &CompletionC
     }
 
     ctx.scope.process_all_names(&mut |name, res| {
+        if let ScopeDef::GenericParam(hir::GenericParam::LifetimeParam(_)) | ScopeDef::Label(_) =
+            res
+        {
+            mark::hit!(skip_lifetime_completion);
+            return;
+        }
         if ctx.use_item_syntax.is_some() {
             if let (ScopeDef::Unknown, Some(name_ref)) = (&res, &ctx.name_ref_syntax) {
                 if name_ref.syntax().text() == name.to_string().as_str() {
@@ -41,7 +47,12 @@ pub(crate) fn complete_unqualified_path(acc: &mut Completions, ctx: &CompletionC
                 }
             }
         }
-        acc.add_resolution(ctx, name.to_string(), &res)
+        acc.add_resolution(ctx, name.to_string(), &res);
+        if ctx.is_expr && !ctx.is_call {
+            if let Some(def) = variant_def(&res) {
+                acc.add_struct_literal(ctx, def, name.to_string());
+            }
+        }
     });
 
     if ctx.config.enable_autoimport_completions && ctx.config.resolve_additional_edits_lazily() {
@@ -49,6 +60,14 @@ pub(crate) fn complete_unqualified_path(acc: &mut Completions, ctx: &CompletionC
     }
 }
 
+fn variant_def(resolution: &ScopeDef) -> Option<VariantDef> {
+    match resolution {
+        ScopeDef::ModuleDef(ModuleDef::Adt(Adt::Struct(it))) => Some((*it).into()),
+        ScopeDef::ModuleDef(ModuleDef::Variant(it)) => Some((*it).into()),
+        _ => None,
+    }
+}
+
 fn complete_enum_variants(acc: &mut Completions, ctx: &CompletionContext, ty: &Type) {
     if let Some(Adt::Enum(enum_data)) = ty.as_adt() {
         let variants = enum_data.variants(ctx.db);
@@ -66,7 +85,13 @@ fn complete_enum_variants(acc: &mut Completions, ctx: &CompletionContext, ty: &T
                 // Variants with trivial paths are already added by the existing completion logic,
                 // so we should avoid adding these twice
                 if path.segments.len() > 1 {
+                    let path_text = path.to_string();
                     acc.add_qualified_enum_variant(ctx, variant, path);
+                    if ctx.is_pat_binding_or_const {
+                        acc.add_struct_pat(ctx, variant.into(), path_text);
+                    } else if ctx.is_expr && !ctx.is_call {
+                        acc.add_struct_literal(ctx, variant.into(), path_text);
+                    }
                 }
             }
         }
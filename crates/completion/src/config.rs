@@ -7,6 +7,8 @@
 use ide_db::helpers::insert_use::MergeBehavior;
 use rustc_hash::FxHashSet;
 
+use crate::snippet::Snippet;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CompletionConfig {
     pub enable_postfix_completions: bool,
@@ -17,6 +19,8 @@ pub struct CompletionConfig {
     pub merge: Option<MergeBehavior>,
     /// A set of capabilities, enabled on the client and supported on the server.
     pub active_resolve_capabilities: FxHashSet<CompletionResolveCapability>,
+    /// User-defined snippets, in addition to the built-in ones.
+    pub snippets: Vec<Snippet>,
 }
 
 /// A resolve capability, supported on the server.
@@ -58,6 +62,7 @@ impl Default for CompletionConfig {
             snippet_cap: Some(SnippetCap { _private: () }),
             merge: Some(MergeBehavior::Full),
             active_resolve_capabilities: FxHashSet::default(),
+            snippets: Vec::new(),
         }
     }
 }
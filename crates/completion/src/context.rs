@@ -1,8 +1,11 @@
 //! See `CompletionContext` structure.
 
-use hir::{Local, ScopeDef, Semantics, SemanticsScope, Type};
+use hir::{AssocItemContainer, Local, ModuleDef, ScopeDef, Semantics, SemanticsScope, Type};
 use ide_db::base_db::{FilePosition, SourceDatabase};
-use ide_db::{call_info::ActiveParameter, RootDatabase};
+use ide_db::{
+    call_info::ActiveParameter, helpers::insert_use::ImportScope, imports_locator, RootDatabase,
+};
+use rustc_hash::FxHashSet;
 use syntax::{
     algo::{find_covering_element, find_node_at_offset},
     ast, match_ast, AstNode, NodeOrToken,
@@ -20,7 +23,7 @@ use crate::{
         has_trait_parent, if_is_prev, inside_impl_trait_block, is_in_loop_body, is_match_arm,
         unsafe_is_prev,
     },
-    CompletionConfig,
+    CompletionConfig, ImportEdit,
 };
 
 /// `CompletionContext` is created early during completion to figure out, where
@@ -39,6 +42,13 @@ pub(crate) struct CompletionContext<'a> {
     pub(super) krate: Option<hir::Crate>,
     pub(super) expected_type: Option<Type>,
     pub(super) name_ref_syntax: Option<ast::NameRef>,
+    pub(super) lifetime_syntax: Option<ast::Lifetime>,
+    /// The lifetime parameter whose bounds are being completed, as in `'a: '<|>`.
+    pub(super) lifetime_param_syntax: Option<ast::LifetimeParam>,
+    /// If a lifetime is expected, as in `&'<|> str`.
+    pub(super) lifetime_allowed: bool,
+    /// If a label is expected, as in `break '<|>`.
+    pub(super) is_label_ref: bool,
     pub(super) function_syntax: Option<ast::Fn>,
     pub(super) use_item_syntax: Option<ast::Use>,
     pub(super) record_lit_syntax: Option<ast::RecordExpr>,
@@ -138,6 +148,10 @@ impl<'a> CompletionContext<'a> {
             krate,
             expected_type: None,
             name_ref_syntax: None,
+            lifetime_syntax: None,
+            lifetime_param_syntax: None,
+            lifetime_allowed: false,
+            is_label_ref: false,
             function_syntax: None,
             use_item_syntax: None,
             record_lit_syntax: None,
@@ -239,7 +253,7 @@ impl<'a> CompletionContext<'a> {
     pub(crate) fn source_range(&self) -> TextRange {
         // check kind of macro-expanded token, but use range of original token
         let kind = self.token.kind();
-        if kind == IDENT || kind == UNDERSCORE || kind.is_keyword() {
+        if kind == IDENT || kind == LIFETIME_IDENT || kind == UNDERSCORE || kind.is_keyword() {
             mark::hit!(completes_if_prefix_is_keyword);
             self.original_token.text_range()
         } else {
@@ -247,6 +261,46 @@ impl<'a> CompletionContext<'a> {
         }
     }
 
+    /// Traits which are not in scope, but have associated items named similarly
+    /// to the identifier being completed, with the imports bringing them in scope.
+    pub(crate) fn traits_to_import(&self) -> Vec<(hir::Trait, ImportEdit)> {
+        let _p = profile::span("traits_to_import");
+        if self.token.kind() != IDENT {
+            return Vec::new();
+        }
+        let (krate, current_module) = match (self.krate, self.scope.module()) {
+            (Some(krate), Some(module)) => (krate, module),
+            _ => return Vec::new(),
+        };
+        let anchor = match &self.name_ref_syntax {
+            Some(it) => it.syntax(),
+            None => return Vec::new(),
+        };
+        let import_scope = match ImportScope::find_insert_use_container(anchor, &self.sema) {
+            Some(it) => it,
+            None => return Vec::new(),
+        };
+
+        let traits_in_scope = self.scope.traits_in_scope();
+        let mut seen = FxHashSet::default();
+        imports_locator::find_similar_trait_assoc_items(
+            &self.sema,
+            krate,
+            Some(40),
+            self.token.text(),
+        )
+        .filter_map(|item| match item.container(self.db) {
+            AssocItemContainer::Trait(trait_) => Some(trait_),
+            AssocItemContainer::Impl(_) => None,
+        })
+        .filter(|&trait_| !traits_in_scope.contains(&trait_.into()) && seen.insert(trait_))
+        .filter_map(|trait_| {
+            let import_path = current_module.find_use_path(self.db, ModuleDef::from(trait_))?;
+            Some((trait_, ImportEdit { import_path, import_scope: import_scope.clone() }))
+        })
+        .collect()
+    }
+
     fn fill_keyword_patterns(&mut self, file_with_fake_ident: &SyntaxNode, offset: TextSize) {
         let fake_ident_token = file_with_fake_ident.token_at_offset(offset).right_biased().unwrap();
         let syntax_element = NodeOrToken::Token(fake_ident_token);
@@ -278,25 +332,36 @@ impl<'a> CompletionContext<'a> {
         file_with_fake_ident: SyntaxNode,
         offset: TextSize,
     ) {
-        // FIXME: this is wrong in at least two cases:
-        //  * when there's no token `foo(<|>)`
-        //  * when there is a token, but it happens to have type of it's own
-        self.expected_type = self
-            .token
-            .ancestors()
-            .find_map(|node| {
-                let ty = match_ast! {
-                    match node {
-                        ast::Pat(it) => self.sema.type_of_pat(&it),
-                        ast::Expr(it) => self.sema.type_of_expr(&it),
-                        _ => return None,
-                    }
-                };
-                Some(ty)
-            })
-            .flatten();
+        // FIXME: this is wrong when there is a token, but it happens to have
+        // type of it's own
+        self.expected_type = match &self.active_parameter {
+            // Without a token, like in `foo(<|>)`, the ancestors are the call.
+            Some(active_parameter) if self.token.kind() != IDENT => {
+                Some(active_parameter.ty.clone())
+            }
+            _ => self
+                .token
+                .ancestors()
+                .find_map(|node| {
+                    let ty = match_ast! {
+                        match node {
+                            ast::Pat(it) => self.sema.type_of_pat(&it),
+                            ast::Expr(it) => self.sema.type_of_expr(&it),
+                            _ => return None,
+                        }
+                    };
+                    Some(ty)
+                })
+                .flatten(),
+        };
         self.attribute_under_caret = find_node_at_offset(&file_with_fake_ident, offset);
 
+        if let Some(lifetime) = find_node_at_offset::<ast::Lifetime>(&file_with_fake_ident, offset)
+        {
+            self.classify_lifetime(lifetime);
+            return;
+        }
+
         // First, let's try to complete a reference to some declaration.
         if let Some(name_ref) = find_node_at_offset::<ast::NameRef>(&file_with_fake_ident, offset) {
             // Special case, `trait T { fn foo(i_am_a_name_ref) {} }`.
@@ -349,6 +414,29 @@ impl<'a> CompletionContext<'a> {
         }
     }
 
+    fn classify_lifetime(&mut self, lifetime: ast::Lifetime) {
+        let parent = match lifetime.syntax().parent() {
+            Some(it) => it,
+            None => return,
+        };
+        self.lifetime_syntax = Some(lifetime.clone());
+        match_ast! {
+            match parent {
+                ast::LifetimeParam(param) => {
+                    // Only the bounds of a lifetime parameter refer to other lifetimes.
+                    if param.lifetime().as_ref() != Some(&lifetime) {
+                        self.lifetime_param_syntax = Some(param);
+                        self.lifetime_allowed = true;
+                    }
+                },
+                ast::BreakExpr(_it) => self.is_label_ref = true,
+                ast::ContinueExpr(_it) => self.is_label_ref = true,
+                ast::Label(_it) => (),
+                _ => self.lifetime_allowed = true,
+            }
+        }
+    }
+
     fn classify_name_ref(
         &mut self,
         original_file: &SyntaxNode,
@@ -60,12 +60,13 @@ pub struct CompletionItem {
     /// after completion.
     trigger_call_info: bool,
 
-    /// Score is useful to pre select or display in better order completion items
-    score: Option<CompletionScore>,
+    /// How relevant the item is at the completion site, used to pre select
+    /// and to order completion items.
+    relevance: CompletionRelevance,
 
     /// Indicates that a reference or mutable reference to this variable is a
     /// possible match.
-    ref_match: Option<(Mutability, CompletionScore)>,
+    ref_match: Option<(Mutability, CompletionRelevance)>,
 
     /// The import data to add to completion's edits.
     import_to_add: Option<ImportEdit>,
@@ -98,8 +99,8 @@ impl fmt::Debug for CompletionItem {
         if self.deprecated {
             s.field("deprecated", &true);
         }
-        if let Some(score) = &self.score {
-            s.field("score", score);
+        if self.relevance.is_relevant() {
+            s.field("relevance", &self.relevance);
         }
         if self.trigger_call_info {
             s.field("trigger_call_info", &true);
@@ -108,12 +109,73 @@ impl fmt::Debug for CompletionItem {
     }
 }
 
+/// The facts that make a completion item more or less relevant at the
+/// completion site. See `CompletionRelevance::score` for how they are weighed.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct CompletionRelevance {
+    /// The type of the item is the type expected at the completion site.
+    pub exact_type_match: bool,
+    /// The name of the item is the name expected at the completion site, for
+    /// example the name of the record field or of the function parameter.
+    pub exact_name_match: bool,
+    /// Where the item is defined, relative to the completion site.
+    pub locality: Option<Locality>,
+    pub is_deprecated: bool,
+    /// The item is a method (or an associated function) coming from a trait,
+    /// rather than from an inherent impl.
+    pub is_trait_method: bool,
+    /// The item was accepted by the user in one of the recent completions.
+    pub recently_accepted: bool,
+}
+
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
-pub enum CompletionScore {
-    /// If only type match
-    TypeMatch,
-    /// If type and name match
-    TypeAndNameMatch,
+pub enum Locality {
+    /// Defined in another crate.
+    Dependency,
+    /// Defined in the current crate, in a different module.
+    Crate,
+    /// Defined in the current module.
+    Module,
+    /// A local binding.
+    Local,
+}
+
+impl CompletionRelevance {
+    /// Combines the facts into a single number, bigger meaning more relevant.
+    ///
+    /// The facts are ordered by their importance: a deprecated item is always
+    /// ranked below a non-deprecated one, a type match trumps a name match and
+    /// so on, down to inherent methods being preferred to trait methods.
+    pub fn score(&self) -> u32 {
+        let mut score = 0;
+        if !self.is_deprecated {
+            score += 1 << 6;
+        }
+        if self.exact_type_match {
+            score += 1 << 5;
+        }
+        if self.exact_name_match {
+            score += 1 << 4;
+        }
+        if self.recently_accepted {
+            score += 1 << 3;
+        }
+        score += match self.locality {
+            Some(Locality::Local) => 3 << 1,
+            Some(Locality::Module) => 2 << 1,
+            Some(Locality::Crate) => 1 << 1,
+            Some(Locality::Dependency) | None => 0,
+        };
+        if !self.is_trait_method {
+            score += 1;
+        }
+        score
+    }
+
+    /// Whether the item is relevant enough to be pre selected in the editor.
+    pub fn is_relevant(&self) -> bool {
+        self.exact_type_match
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -134,6 +196,9 @@ pub enum CompletionItemKind {
     TypeAlias,
     Method,
     TypeParam,
+    LifetimeParam,
+    ConstParam,
+    Label,
     Macro,
     Attribute,
     UnresolvedReference,
@@ -147,11 +212,14 @@ impl CompletionItemKind {
             CompletionItemKind::Binding => "bn",
             CompletionItemKind::BuiltinType => "bt",
             CompletionItemKind::Const => "ct",
+            CompletionItemKind::ConstParam => "cp",
             CompletionItemKind::Enum => "en",
             CompletionItemKind::EnumVariant => "ev",
             CompletionItemKind::Field => "fd",
             CompletionItemKind::Function => "fn",
             CompletionItemKind::Keyword => "kw",
+            CompletionItemKind::Label => "lb",
+            CompletionItemKind::LifetimeParam => "lt",
             CompletionItemKind::Macro => "ma",
             CompletionItemKind::Method => "me",
             CompletionItemKind::Module => "md",
@@ -178,6 +246,8 @@ pub(crate) enum CompletionKind {
     Postfix,
     BuiltinType,
     Attribute,
+    /// Keys and values of `Cargo.toml` files.
+    Manifest,
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -206,9 +276,10 @@ impl CompletionItem {
             text_edit: None,
             deprecated: None,
             trigger_call_info: None,
-            score: None,
+            relevance: CompletionRelevance::default(),
             ref_match: None,
             import_to_add: None,
+            import_is_trait: false,
         }
     }
 
@@ -249,15 +320,24 @@ impl CompletionItem {
         self.deprecated
     }
 
-    pub fn score(&self) -> Option<CompletionScore> {
-        self.score
+    pub fn relevance(&self) -> CompletionRelevance {
+        self.relevance
+    }
+
+    /// Marks the item as recently accepted, which is only known to the client
+    /// of the completion engine.
+    pub fn set_recently_accepted(&mut self, recently_accepted: bool) {
+        self.relevance.recently_accepted = recently_accepted;
+        if let Some((_, relevance)) = &mut self.ref_match {
+            relevance.recently_accepted = recently_accepted;
+        }
     }
 
     pub fn trigger_call_info(&self) -> bool {
         self.trigger_call_info
     }
 
-    pub fn ref_match(&self) -> Option<(Mutability, CompletionScore)> {
+    pub fn ref_match(&self) -> Option<(Mutability, CompletionRelevance)> {
         self.ref_match
     }
 
@@ -290,6 +370,31 @@ impl ImportEdit {
 
         Some(import_insert.finish())
     }
+
+    /// Inserts several imports into their common scope, one after another, so
+    /// that the imports get merged with each other too.
+    pub(crate) fn to_combined_text_edit(
+        imports: &[ImportEdit],
+        merge_behavior: Option<MergeBehavior>,
+    ) -> Option<TextEdit> {
+        let _p = profile::span("ImportEdit::to_combined_text_edit");
+
+        let original_scope = imports.first()?.import_scope.clone();
+        let mut scope = original_scope.clone();
+        for import in imports {
+            let rewriter = insert_use::insert_use(
+                &scope,
+                mod_path_to_ast(&import.import_path),
+                merge_behavior,
+            );
+            scope = ImportScope::from(rewriter.rewrite(scope.as_syntax_node()))?;
+        }
+        let mut import_insert = TextEdit::builder();
+        algo::diff(original_scope.as_syntax_node(), scope.as_syntax_node())
+            .into_text_edit(&mut import_insert);
+
+        Some(import_insert.finish())
+    }
 }
 
 /// A helper to make `CompletionItem`s.
@@ -299,6 +404,9 @@ pub(crate) struct Builder {
     source_range: TextRange,
     completion_kind: CompletionKind,
     import_to_add: Option<ImportEdit>,
+    /// Whether `import_to_add` brings the trait of the completed item into
+    /// scope, rather than the item itself.
+    import_is_trait: bool,
     label: String,
     insert_text: Option<String>,
     insert_text_format: InsertTextFormat,
@@ -309,8 +417,8 @@ pub(crate) struct Builder {
     text_edit: Option<TextEdit>,
     deprecated: Option<bool>,
     trigger_call_info: Option<bool>,
-    score: Option<CompletionScore>,
-    ref_match: Option<(Mutability, CompletionScore)>,
+    relevance: CompletionRelevance,
+    ref_match: Option<(Mutability, CompletionRelevance)>,
 }
 
 impl Builder {
@@ -321,7 +429,15 @@ impl Builder {
         let mut lookup = self.lookup;
         let mut insert_text = self.insert_text;
 
-        if let Some(import_to_add) = self.import_to_add.as_ref() {
+        if let (Some(import_to_add), true) = (self.import_to_add.as_ref(), self.import_is_trait) {
+            if lookup.is_none() {
+                lookup = Some(label.clone());
+            }
+            if insert_text.is_none() {
+                insert_text = Some(label.clone());
+            }
+            label = format!("{} (use {})", label, import_to_add.import_path);
+        } else if let Some(import_to_add) = self.import_to_add.as_ref() {
             let mut import_path_without_last_segment = import_to_add.import_path.to_owned();
             let _ = import_path_without_last_segment.segments.pop();
 
@@ -343,6 +459,7 @@ impl Builder {
             }
         };
 
+        let deprecated = self.deprecated.unwrap_or(false);
         CompletionItem {
             source_range: self.source_range,
             label,
@@ -353,10 +470,12 @@ impl Builder {
             lookup,
             kind: self.kind,
             completion_kind: self.completion_kind,
-            deprecated: self.deprecated.unwrap_or(false),
+            deprecated,
             trigger_call_info: self.trigger_call_info.unwrap_or(false),
-            score: self.score,
-            ref_match: self.ref_match,
+            relevance: CompletionRelevance { is_deprecated: deprecated, ..self.relevance },
+            ref_match: self.ref_match.map(|(mutability, relevance)| {
+                (mutability, CompletionRelevance { is_deprecated: deprecated, ..relevance })
+            }),
             import_to_add: self.import_to_add,
         }
     }
@@ -412,8 +531,8 @@ impl Builder {
         self.deprecated = Some(deprecated);
         self
     }
-    pub(crate) fn set_score(mut self, score: CompletionScore) -> Builder {
-        self.score = Some(score);
+    pub(crate) fn set_relevance(mut self, relevance: CompletionRelevance) -> Builder {
+        self.relevance = relevance;
         self
     }
     pub(crate) fn trigger_call_info(mut self) -> Builder {
@@ -424,9 +543,43 @@ impl Builder {
         self.import_to_add = import_to_add;
         self
     }
+    pub(crate) fn set_import_is_trait(mut self, import_is_trait: bool) -> Builder {
+        self.import_is_trait = import_is_trait;
+        self
+    }
+    /// Inserts the imports right away, as part of the completion's own edit.
+    pub(crate) fn insert_imports(
+        mut self,
+        imports: &[ImportEdit],
+        merge_behavior: Option<MergeBehavior>,
+    ) -> Builder {
+        let import_edit = match ImportEdit::to_combined_text_edit(imports, merge_behavior) {
+            Some(it) => it,
+            None => return self,
+        };
+        let mut text_edit = match self.text_edit.take() {
+            Some(it) => it,
+            None => {
+                let insert_text = self.insert_text.clone().unwrap_or_else(|| self.label.clone());
+                TextEdit::replace(self.source_range, insert_text)
+            }
+        };
+        // Overlapping edits can't be applied together, completing the item itself is what
+        // matters then.
+        if text_edit.union(import_edit).is_err() {
+            let paths: Vec<String> = imports.iter().map(|it| it.import_path.to_string()).collect();
+            log::warn!(
+                "imports {:?} of completion {:?} overlap with its edit and were not added",
+                paths,
+                self.label
+            );
+        }
+        self.text_edit = Some(text_edit);
+        self
+    }
     pub(crate) fn set_ref_match(
         mut self,
-        ref_match: Option<(Mutability, CompletionScore)>,
+        ref_match: Option<(Mutability, CompletionRelevance)>,
     ) -> Builder {
         self.ref_match = ref_match;
         self
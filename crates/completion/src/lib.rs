@@ -8,6 +8,7 @@ mod generated_lint_completions;
 #[cfg(test)]
 mod test_utils;
 mod render;
+mod snippet;
 
 mod completions;
 
@@ -21,7 +22,11 @@ use crate::{completions::Completions, context::CompletionContext, item::Completi
 
 pub use crate::{
     config::{CompletionConfig, CompletionResolveCapability},
-    item::{CompletionItem, CompletionItemKind, CompletionScore, ImportEdit, InsertTextFormat},
+    item::{
+        CompletionItem, CompletionItemKind, CompletionRelevance, ImportEdit, InsertTextFormat,
+        Locality,
+    },
+    snippet::{Snippet, SnippetScope},
 };
 
 //FIXME: split the following feature into fine-grained features.
@@ -39,6 +44,16 @@ pub use crate::{
 // When completing a function call, `()` are automatically inserted. If a function
 // takes arguments, the cursor is positioned inside the parenthesis.
 //
+// Structs and record variants are also completed as whole literals, like
+// `Foo { a, b: $2 }`, and as patterns destructuring all the visible fields.
+// Arguments and fields are filled in with the locals of the same name.
+//
+// When the type of the expression being completed is known, for example in a
+// function argument, whole expressions of that type are completed: record enum
+// variants with their fields, constructors like `Foo::new()` and
+// `Default::default()`, fields of locals like `self.config.timeout` (borrowed or
+// cloned if needed) and chains of up to two methods without arguments.
+//
 // There are postfix completions, which can be triggered by typing something like
 // `foo().if`. The word after `.` determines postfix completion. Possible variants are:
 //
@@ -73,9 +88,14 @@ pub use crate::{
 // }
 // ```
 //
+// More snippets, including postfix ones, can be defined with the `rust-analyzer.completion.snippets`
+// setting. Paths a snippet `requires` are imported when it is completed.
+//
 // And the auto import completions, enabled with the `rust-analyzer.completion.autoimport.enable` setting and the corresponding LSP client capabilities.
 // Those are the additional completion options with automatic `use` import and options from all project importable items,
 // fuzzy matched agains the completion imput.
+// Methods and associated functions of traits implemented for a type are offered the same way,
+// when the trait is not in scope yet: completing them imports the trait.
 
 /// Main entry point for completion. We run completion as a two-phase process.
 ///
@@ -104,6 +124,12 @@ pub fn completions(
     config: &CompletionConfig,
     position: FilePosition,
 ) -> Option<Completions> {
+    if ide_db::cargo_toml::is_cargo_toml(db, position.file_id) {
+        let mut acc = Completions::default();
+        completions::cargo_toml::complete_cargo_toml(&mut acc, db, position);
+        return Some(acc);
+    }
+
     let ctx = CompletionContext::new(db, position, config)?;
 
     if ctx.no_completion_required() {
@@ -118,9 +144,14 @@ pub fn completions(
     completions::keyword::complete_use_tree_keyword(&mut acc, &ctx);
     completions::snippet::complete_expr_snippet(&mut acc, &ctx);
     completions::snippet::complete_item_snippet(&mut acc, &ctx);
+    completions::snippet::complete_type_snippet(&mut acc, &ctx);
     completions::qualified_path::complete_qualified_path(&mut acc, &ctx);
     completions::unqualified_path::complete_unqualified_path(&mut acc, &ctx);
     completions::dot::complete_dot(&mut acc, &ctx);
+    completions::lifetime::complete_lifetime(&mut acc, &ctx);
+    completions::lifetime::complete_label(&mut acc, &ctx);
+    completions::expected_type::complete_expected_type(&mut acc, &ctx);
+    completions::format_string::complete_format_string(&mut acc, &ctx);
     completions::record::complete_record(&mut acc, &ctx);
     completions::pattern::complete_pattern(&mut acc, &ctx);
     completions::postfix::complete_postfix(&mut acc, &ctx);
@@ -140,10 +171,14 @@ pub fn resolve_completion_edits(
     imported_name: &str,
 ) -> Option<Vec<TextEdit>> {
     let ctx = CompletionContext::new(db, position, config)?;
-    let anchor = ctx.name_ref_syntax.as_ref()?;
-    let import_scope = ImportScope::find_insert_use_container(anchor.syntax(), &ctx.sema)?;
+    // Derives are completed inside of a token tree, without a name reference.
+    let anchor = match &ctx.name_ref_syntax {
+        Some(name_ref) => name_ref.syntax().clone(),
+        None => ctx.original_token.parent(),
+    };
+    let import_scope = ImportScope::find_insert_use_container(&anchor, &ctx.sema)?;
 
-    let current_module = ctx.sema.scope(anchor.syntax()).module()?;
+    let current_module = ctx.sema.scope(&anchor).module()?;
     let current_crate = current_module.krate();
 
     let import_path = imports_locator::find_exact_imports(&ctx.sema, current_crate, imported_name)
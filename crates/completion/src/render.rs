@@ -6,6 +6,8 @@ pub(crate) mod function;
 pub(crate) mod enum_variant;
 pub(crate) mod const_;
 pub(crate) mod type_alias;
+pub(crate) mod struct_literal;
+pub(crate) mod pattern;
 
 mod builder_ext;
 
@@ -15,8 +17,9 @@ use syntax::TextRange;
 use test_utils::mark;
 
 use crate::{
-    config::SnippetCap, item::ImportEdit, CompletionContext, CompletionItem, CompletionItemKind,
-    CompletionKind, CompletionScore,
+    config::SnippetCap,
+    item::{ImportEdit, Locality},
+    CompletionContext, CompletionItem, CompletionItemKind, CompletionKind, CompletionRelevance,
 };
 
 use crate::render::{enum_variant::render_variant, function::render_fn, macro_::render_macro};
@@ -88,6 +91,37 @@ impl<'a> RenderContext<'a> {
         node.docs(self.db())
     }
 
+    /// The expression to use for a parameter or a field called `name`, if there
+    /// is a local of the same name fitting `ty`, possibly by reference.
+    fn local_for(&self, name: &str, ty: &Type) -> Option<String> {
+        let (_, local) =
+            self.completion.locals.iter().find(|(local_name, _)| local_name == name)?;
+        let local_ty = local.ty(self.db());
+        if &local_ty == ty {
+            return Some(name.to_string());
+        }
+        let derefed_ty = ty.remove_ref()?;
+        if local_ty != derefed_ty {
+            return None;
+        }
+        let ref_ = if ty.is_mutable_reference() { "&mut " } else { "&" };
+        Some(format!("{}{}", ref_, name))
+    }
+
+    /// Where an item defined in `module` is, relative to the completion site.
+    fn locality(&self, module: Option<hir::Module>) -> Option<Locality> {
+        let module = module?;
+        let current_module = self.completion.scope.module()?;
+        let locality = if module == current_module {
+            Locality::Module
+        } else if module.krate() == current_module.krate() {
+            Locality::Crate
+        } else {
+            Locality::Dependency
+        };
+        Some(locality)
+    }
+
     fn active_name_and_type(&self) -> Option<(String, Type)> {
         if let Some(record_field) = &self.completion.record_field_syntax {
             mark::hit!(record_field_type_match);
@@ -126,9 +160,10 @@ impl<'a> Render<'a> {
         .set_documentation(field.docs(self.ctx.db()))
         .set_deprecated(is_deprecated);
 
-        if let Some(score) = compute_score(&self.ctx, &ty, &name.to_string()) {
-            item = item.set_score(score);
-        }
+        let mut relevance = compute_relevance(&self.ctx, &ty, &name.to_string());
+        relevance.locality =
+            self.ctx.locality(Some(field.parent_def(self.ctx.db()).module(self.ctx.db())));
+        item = item.set_relevance(relevance);
 
         item.build()
     }
@@ -178,8 +213,13 @@ impl<'a> Render<'a> {
             ScopeDef::ModuleDef(Trait(..)) => CompletionItemKind::Trait,
             ScopeDef::ModuleDef(TypeAlias(..)) => CompletionItemKind::TypeAlias,
             ScopeDef::ModuleDef(BuiltinType(..)) => CompletionItemKind::BuiltinType,
-            ScopeDef::GenericParam(..) => CompletionItemKind::TypeParam,
+            ScopeDef::GenericParam(param) => match param {
+                hir::GenericParam::TypeParam(_) => CompletionItemKind::TypeParam,
+                hir::GenericParam::LifetimeParam(_) => CompletionItemKind::LifetimeParam,
+                hir::GenericParam::ConstParam(_) => CompletionItemKind::ConstParam,
+            },
             ScopeDef::Local(..) => CompletionItemKind::Binding,
+            ScopeDef::Label(..) => CompletionItemKind::Label,
             // (does this need its own kind?)
             ScopeDef::AdtSelfType(..) | ScopeDef::ImplSelfType(..) => CompletionItemKind::TypeParam,
             ScopeDef::Unknown => {
@@ -206,17 +246,28 @@ impl<'a> Render<'a> {
             }
         };
 
+        let mut relevance = CompletionRelevance::default();
         let mut ref_match = None;
-        if let ScopeDef::Local(local) = resolution {
-            if let Some((active_name, active_type)) = self.ctx.active_name_and_type() {
-                let ty = local.ty(self.ctx.db());
-                if let Some(score) =
-                    compute_score_from_active(&active_type, &active_name, &ty, &local_name)
-                {
-                    item = item.set_score(score);
+        match resolution {
+            ScopeDef::Local(local) => {
+                relevance.locality = Some(Locality::Local);
+                if let Some((active_name, active_type)) = self.ctx.active_name_and_type() {
+                    let ty = local.ty(self.ctx.db());
+                    compute_relevance_from_active(
+                        &mut relevance,
+                        &active_type,
+                        &active_name,
+                        &ty,
+                        &local_name,
+                    );
+                    ref_match =
+                        refed_type_matches(relevance, &active_type, &active_name, &ty, &local_name);
                 }
-                ref_match = refed_type_matches(&active_type, &active_name, &ty, &local_name);
             }
+            ScopeDef::ModuleDef(def) => {
+                relevance.locality = self.ctx.locality(def.module(self.ctx.db()))
+            }
+            _ => (),
         }
 
         // Add `<>` for generic types
@@ -246,6 +297,7 @@ impl<'a> Render<'a> {
             .kind(kind)
             .add_import(import_to_add)
             .set_documentation(docs)
+            .set_relevance(relevance)
             .set_ref_match(ref_match)
             .build();
         Some(item)
@@ -266,44 +318,47 @@ impl<'a> Render<'a> {
     }
 }
 
-fn compute_score_from_active(
+/// Fills in the type and name matches of an item of type `ty` named `name`.
+fn compute_relevance_from_active(
+    relevance: &mut CompletionRelevance,
     active_type: &Type,
     active_name: &str,
     ty: &Type,
     name: &str,
-) -> Option<CompletionScore> {
-    // Compute score
-    // For the same type
+) {
     if active_type != ty {
-        return None;
+        return;
     }
-
-    let mut res = CompletionScore::TypeMatch;
-
-    // If same type + same name then go top position
-    if active_name == name {
-        res = CompletionScore::TypeAndNameMatch
-    }
-
-    Some(res)
+    relevance.exact_type_match = true;
+    relevance.exact_name_match = active_name == name;
 }
+
 fn refed_type_matches(
+    relevance: CompletionRelevance,
     active_type: &Type,
     active_name: &str,
     ty: &Type,
     name: &str,
-) -> Option<(Mutability, CompletionScore)> {
+) -> Option<(Mutability, CompletionRelevance)> {
     let derefed_active = active_type.remove_ref()?;
-    let score = compute_score_from_active(&derefed_active, &active_name, &ty, &name)?;
+    let mut relevance =
+        CompletionRelevance { exact_type_match: false, exact_name_match: false, ..relevance };
+    compute_relevance_from_active(&mut relevance, &derefed_active, &active_name, &ty, &name);
+    if !relevance.exact_type_match {
+        return None;
+    }
     Some((
         if active_type.is_mutable_reference() { Mutability::Mut } else { Mutability::Shared },
-        score,
+        relevance,
     ))
 }
 
-fn compute_score(ctx: &RenderContext, ty: &Type, name: &str) -> Option<CompletionScore> {
-    let (active_name, active_type) = ctx.active_name_and_type()?;
-    compute_score_from_active(&active_type, &active_name, ty, name)
+fn compute_relevance(ctx: &RenderContext, ty: &Type, name: &str) -> CompletionRelevance {
+    let mut relevance = CompletionRelevance::default();
+    if let Some((active_name, active_type)) = ctx.active_name_and_type() {
+        compute_relevance_from_active(&mut relevance, &active_type, &active_name, ty, name);
+    }
+    relevance
 }
 
 #[cfg(test)]
@@ -315,7 +370,7 @@ mod tests {
 
     use crate::{
         test_utils::{check_edit, do_completion, get_all_items},
-        CompletionConfig, CompletionKind, CompletionScore,
+        CompletionConfig, CompletionKind, CompletionRelevance,
     };
 
     fn check(ra_fixture: &str, expect: Expect) {
@@ -323,24 +378,36 @@ mod tests {
         expect.assert_debug_eq(&actual);
     }
 
-    fn check_scores(ra_fixture: &str, expect: Expect) {
-        fn display_score(score: Option<CompletionScore>) -> &'static str {
-            match score {
-                Some(CompletionScore::TypeMatch) => "[type]",
-                Some(CompletionScore::TypeAndNameMatch) => "[type+name]",
-                None => "[]".into(),
+    fn check_relevance(ra_fixture: &str, expect: Expect) {
+        fn display_relevance(relevance: CompletionRelevance) -> String {
+            let mut res = Vec::new();
+            if relevance.exact_type_match {
+                res.push("type".to_string());
+            }
+            if relevance.exact_name_match {
+                res.push("name".to_string());
+            }
+            if let Some(locality) = relevance.locality {
+                res.push(format!("{:?}", locality).to_lowercase());
             }
+            if relevance.is_trait_method {
+                res.push("trait".to_string());
+            }
+            if relevance.is_deprecated {
+                res.push("deprecated".to_string());
+            }
+            format!("[{}]", res.join("+"))
         }
 
         let mut completions = get_all_items(CompletionConfig::default(), ra_fixture);
-        completions.sort_by_key(|it| (Reverse(it.score()), it.label().to_string()));
+        completions.sort_by_key(|it| (Reverse(it.relevance().score()), it.label().to_string()));
         let actual = completions
             .into_iter()
             .filter(|it| it.completion_kind == CompletionKind::Reference)
             .map(|it| {
                 let tag = it.kind().unwrap().tag();
-                let score = display_score(it.score());
-                format!("{} {} {}\n", tag, it.label(), score)
+                let relevance = display_relevance(it.relevance());
+                format!("{} {} {}\n", tag, it.label(), relevance)
             })
             .collect::<String>();
         expect.assert_eq(&actual);
@@ -775,16 +842,16 @@ fn foo(xs: Vec<i128>)
     #[test]
     fn active_param_score() {
         mark::check!(active_param_type_match);
-        check_scores(
+        check_relevance(
             r#"
 struct S { foo: i64, bar: u32, baz: u32 }
 fn test(bar: u32) { }
 fn foo(s: S) { test(s.<|>) }
 "#,
             expect![[r#"
-                fd bar [type+name]
-                fd baz [type]
-                fd foo []
+                fd bar [type+name+module]
+                fd baz [type+module]
+                fd foo [module]
             "#]],
         );
     }
@@ -792,23 +859,23 @@ fn foo(s: S) { test(s.<|>) }
     #[test]
     fn record_field_scores() {
         mark::check!(record_field_type_match);
-        check_scores(
+        check_relevance(
             r#"
 struct A { foo: i64, bar: u32, baz: u32 }
 struct B { x: (), y: f32, bar: u32 }
 fn foo(a: A) { B { bar: a.<|> }; }
 "#,
             expect![[r#"
-                fd bar [type+name]
-                fd baz [type]
-                fd foo []
+                fd bar [type+name+module]
+                fd baz [type+module]
+                fd foo [module]
             "#]],
         )
     }
 
     #[test]
     fn record_field_and_call_scores() {
-        check_scores(
+        check_relevance(
             r#"
 struct A { foo: i64, bar: u32, baz: u32 }
 struct B { x: (), y: f32, bar: u32 }
@@ -816,12 +883,12 @@ fn f(foo: i64) {  }
 fn foo(a: A) { B { bar: f(a.<|>) }; }
 "#,
             expect![[r#"
-                fd foo [type+name]
-                fd bar []
-                fd baz []
+                fd foo [type+name+module]
+                fd bar [module]
+                fd baz [module]
             "#]],
         );
-        check_scores(
+        check_relevance(
             r#"
 struct A { foo: i64, bar: u32, baz: u32 }
 struct B { x: (), y: f32, bar: u32 }
@@ -829,39 +896,82 @@ fn f(foo: i64) {  }
 fn foo(a: A) { f(B { bar: a.<|> }); }
 "#,
             expect![[r#"
-                fd bar [type+name]
-                fd baz [type]
-                fd foo []
+                fd bar [type+name+module]
+                fd baz [type+module]
+                fd foo [module]
             "#]],
         );
     }
 
     #[test]
     fn prioritize_exact_ref_match() {
-        check_scores(
+        check_relevance(
             r#"
 struct WorldSnapshot { _f: () };
 fn go(world: &WorldSnapshot) { go(w<|>) }
 "#,
             expect![[r#"
-                bn world [type+name]
-                st WorldSnapshot []
-                fn go(…) []
+                bn world [type+name+local]
+                st WorldSnapshot [module]
+                fn go(…) [module]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn relevance_prefers_local_and_non_deprecated_items() {
+        check_relevance(
+            r#"
+//- /lib.rs crate:dep
+pub fn dep_fn() {}
+//- /main.rs crate:main deps:dep
+mod inner { pub fn inner_fn() {} }
+use inner::inner_fn;
+use dep::dep_fn;
+#[deprecated]
+fn old_fn() {}
+fn main() { let local = 92; l<|> }
+"#,
+            expect![[r#"
+                bn local [local]
+                md inner [module]
+                fn main() [module]
+                fn inner_fn() [crate]
+                md dep []
+                fn dep_fn() [dependency]
+                fn old_fn() [module+deprecated]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn relevance_prefers_inherent_methods() {
+        check_relevance(
+            r#"
+struct S;
+impl S { fn inherent(&self) {} }
+trait Trait { fn from_trait(&self) {} }
+impl Trait for S {}
+fn foo(s: S) { s.<|> }
+"#,
+            expect![[r#"
+                me inherent() [module]
+                me from_trait() [module+trait]
             "#]],
         );
     }
 
     #[test]
     fn too_many_arguments() {
-        check_scores(
+        check_relevance(
             r#"
 struct Foo;
 fn f(foo: &Foo) { f(foo, w<|>) }
 "#,
             expect![[r#"
-                st Foo []
-                fn f(…) []
-                bn foo []
+                bn foo [local]
+                st Foo [module]
+                fn f(…) [module]
             "#]],
         );
     }
@@ -7,7 +7,7 @@ use syntax::{
 };
 
 use crate::{
-    item::{CompletionItem, CompletionItemKind, CompletionKind},
+    item::{CompletionItem, CompletionItemKind, CompletionKind, CompletionRelevance},
     render::RenderContext,
 };
 
@@ -39,6 +39,10 @@ impl<'a> ConstRender<'a> {
             .kind(CompletionItemKind::Const)
             .set_documentation(self.ctx.docs(self.const_))
             .set_deprecated(self.ctx.is_deprecated(self.const_))
+            .set_relevance(CompletionRelevance {
+                locality: self.ctx.locality(Some(self.const_.module(self.ctx.db()))),
+                ..CompletionRelevance::default()
+            })
             .detail(detail)
             .build();
 
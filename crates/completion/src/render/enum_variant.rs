@@ -5,7 +5,7 @@ use itertools::Itertools;
 use test_utils::mark;
 
 use crate::{
-    item::{CompletionItem, CompletionItemKind, CompletionKind, ImportEdit},
+    item::{CompletionItem, CompletionItemKind, CompletionKind, CompletionRelevance, ImportEdit},
     render::{builder_ext::Params, RenderContext},
 };
 
@@ -63,6 +63,10 @@ impl<'a> EnumRender<'a> {
         .kind(CompletionItemKind::EnumVariant)
         .set_documentation(self.variant.docs(self.ctx.db()))
         .set_deprecated(self.ctx.is_deprecated(self.variant))
+        .set_relevance(CompletionRelevance {
+            locality: self.ctx.locality(Some(self.variant.module(self.ctx.db()))),
+            ..CompletionRelevance::default()
+        })
         .add_import(import_to_add)
         .detail(self.detail());
 
@@ -1,11 +1,11 @@
 //! Renderer for function calls.
 
-use hir::{HasSource, Type};
+use hir::{AsAssocItem, AssocItemContainer, HasSource, Type};
 use syntax::{ast::Fn, display::function_declaration};
 use test_utils::mark;
 
 use crate::{
-    item::{CompletionItem, CompletionItemKind, CompletionKind, ImportEdit},
+    item::{CompletionItem, CompletionItemKind, CompletionKind, CompletionRelevance, ImportEdit},
     render::{builder_ext::Params, RenderContext},
 };
 
@@ -41,30 +41,36 @@ impl<'a> FunctionRender<'a> {
 
     fn render(self, import_to_add: Option<ImportEdit>) -> CompletionItem {
         let params = self.params();
+        let is_trait_item = self.is_trait_item();
         CompletionItem::new(CompletionKind::Reference, self.ctx.source_range(), self.name.clone())
             .kind(self.kind())
             .set_documentation(self.ctx.docs(self.func))
             .set_deprecated(self.ctx.is_deprecated(self.func))
+            .set_relevance(CompletionRelevance {
+                locality: self.ctx.locality(Some(self.func.module(self.ctx.db()))),
+                is_trait_method: is_trait_item,
+                ..CompletionRelevance::default()
+            })
             .detail(self.detail())
             .add_call_parens(self.ctx.completion, self.name, params)
             .add_import(import_to_add)
+            // Trait items can't be imported by themselves, their trait gets imported instead.
+            .set_import_is_trait(is_trait_item)
             .build()
     }
 
+    fn is_trait_item(&self) -> bool {
+        let container =
+            self.func.as_assoc_item(self.ctx.db()).map(|it| it.container(self.ctx.db()));
+        matches!(container, Some(AssocItemContainer::Trait(_)))
+    }
+
     fn detail(&self) -> String {
         function_declaration(&self.ast_node)
     }
 
     fn add_arg(&self, arg: &str, ty: &Type) -> String {
-        if let Some(derefed_ty) = ty.remove_ref() {
-            for (name, local) in self.ctx.completion.locals.iter() {
-                if name == arg && local.ty(self.ctx.db()) == derefed_ty {
-                    let mutability = if ty.is_mutable_reference() { "&mut " } else { "&" };
-                    return format!("{}{}", mutability, arg);
-                }
-            }
-        }
-        arg.to_string()
+        self.ctx.local_for(arg, ty).unwrap_or_else(|| arg.to_string())
     }
 
     fn params(&self) -> Params {
@@ -5,7 +5,7 @@ use syntax::display::macro_label;
 use test_utils::mark;
 
 use crate::{
-    item::{CompletionItem, CompletionItemKind, CompletionKind, ImportEdit},
+    item::{CompletionItem, CompletionItemKind, CompletionKind, CompletionRelevance, ImportEdit},
     render::RenderContext,
 };
 
@@ -51,6 +51,10 @@ impl<'a> MacroRender<'a> {
                 .kind(CompletionItemKind::Macro)
                 .set_documentation(self.docs.clone())
                 .set_deprecated(self.ctx.is_deprecated(self.macro_))
+                .set_relevance(CompletionRelevance {
+                    locality: self.ctx.locality(self.macro_.module(self.ctx.db())),
+                    ..CompletionRelevance::default()
+                })
                 .add_import(import_to_add)
                 .detail(self.detail());
 
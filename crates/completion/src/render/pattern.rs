@@ -0,0 +1,105 @@
+//! Renderer for struct and enum variant patterns.
+
+use hir::{HasVisibility, StructKind, VariantDef};
+use itertools::Itertools;
+
+use crate::{
+    item::{CompletionItem, CompletionItemKind, CompletionKind},
+    render::RenderContext,
+    CompletionRelevance,
+};
+
+/// Renders a pattern destructuring all the fields of a record, like
+/// `Foo { a, b }$0`. Private fields are skipped with `..`.
+///
+/// Tuple structs and variants are already completed with parentheses.
+pub(crate) fn render_struct_pat<'a>(
+    ctx: RenderContext<'a>,
+    def: VariantDef,
+    path: String,
+) -> Option<CompletionItem> {
+    let _p = profile::span("render_struct_pat");
+    let cap = ctx.snippet_cap()?;
+    let db = ctx.db();
+    let fields = def.fields(db);
+    if def.kind(db) != StructKind::Record || fields.is_empty() {
+        return None;
+    }
+    let module = ctx.completion.scope.module()?;
+    let visible_fields: Vec<_> =
+        fields.iter().filter(|field| field.is_visible_from(db, module)).collect();
+    let has_private_fields = visible_fields.len() < fields.len();
+
+    let mut pat_fields = visible_fields.iter().map(|field| field.name(db).to_string());
+    let pat_fields = if has_private_fields {
+        pat_fields.chain(Some("..".to_string())).join(", ")
+    } else {
+        pat_fields.join(", ")
+    };
+    let pat = format!("{} {{ {} }}", path, pat_fields);
+
+    let item_kind = match def {
+        VariantDef::Variant(_) => CompletionItemKind::EnumVariant,
+        VariantDef::Struct(_) | VariantDef::Union(_) => CompletionItemKind::Struct,
+    };
+    let item = CompletionItem::new(CompletionKind::Magic, ctx.source_range(), pat.clone())
+        .kind(item_kind)
+        .set_relevance(CompletionRelevance {
+            locality: ctx.locality(Some(def.module(db))),
+            ..CompletionRelevance::default()
+        })
+        .insert_snippet(cap, format!("{}$0", pat))
+        .build();
+    Some(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::{
+        test_utils::{check_edit, completion_list},
+        CompletionKind,
+    };
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Magic);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_struct_patterns() {
+        check(
+            r#"
+mod m { pub struct Private { pub visible: u32, hidden: u32 } }
+use m::Private;
+struct Point { x: i32, y: i32 }
+struct Meters(f64);
+fn main(p: Point) { let P<|> = p; }
+"#,
+            expect![[r#"
+                st Point { x, y }
+                st Private { visible, .. }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_record_variant_patterns() {
+        check_edit(
+            "Shape::Circle { radius }",
+            r#"
+enum Shape { Circle { radius: f64 }, Square(f64) }
+fn area(shape: Shape) -> f64 {
+    match shape { C<|> }
+}
+"#,
+            r#"
+enum Shape { Circle { radius: f64 }, Square(f64) }
+fn area(shape: Shape) -> f64 {
+    match shape { Shape::Circle { radius }$0 }
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Renderer for struct and enum variant literals.
+
+use hir::{Adt, HasVisibility, HirDisplay, StructKind, VariantDef};
+use itertools::Itertools;
+
+use crate::{
+    item::{CompletionItem, CompletionItemKind, CompletionKind},
+    render::RenderContext,
+    CompletionRelevance,
+};
+
+/// Renders a literal with all the fields, like `Foo { a, b: $2 }$0` or
+/// `Foo($1, $2)$0`. Fields with a local of the same name and type are filled
+/// in with that local.
+pub(crate) fn render_struct_literal<'a>(
+    ctx: RenderContext<'a>,
+    def: VariantDef,
+    path: String,
+) -> Option<CompletionItem> {
+    let _p = profile::span("render_struct_literal");
+    let cap = ctx.snippet_cap()?;
+    let db = ctx.db();
+    let kind = def.kind(db);
+    let fields = def.fields(db);
+    if kind == StructKind::Unit || fields.is_empty() {
+        return None;
+    }
+    // Tuple variants are already completed with parentheses, like functions.
+    if kind == StructKind::Tuple && matches!(def, VariantDef::Variant(_)) {
+        return None;
+    }
+    // A literal can't be written if one of the fields is private.
+    let module = ctx.completion.scope.module()?;
+    if !fields.iter().all(|field| field.is_visible_from(db, module)) {
+        return None;
+    }
+
+    let (label, snippet, detail) = match kind {
+        StructKind::Record => {
+            let names = fields.iter().map(|field| field.name(db)).format(", ");
+            let snippet_fields = fields.iter().enumerate().format_with(", ", |(idx, field), f| {
+                let name = field.name(db).to_string();
+                match ctx.local_for(&name, &field.signature_ty(db)) {
+                    Some(local) if local == name => f(&name),
+                    Some(local) => f(&format_args!("{}: {}", name, local)),
+                    None => f(&format_args!("{}: ${}", name, idx + 1)),
+                }
+            });
+            let detail = fields
+                .iter()
+                .format_with(", ", |field, f| {
+                    f(&format_args!("{}: {}", field.name(db), field.signature_ty(db).display(db)))
+                })
+                .to_string();
+            (
+                format!("{} {{ {} }}", path, names),
+                format!("{} {{ {} }}$0", path, snippet_fields),
+                format!("{{ {} }}", detail),
+            )
+        }
+        _ => {
+            let placeholders =
+                (1..=fields.len()).format_with(", ", |idx, f| f(&format_args!("${}", idx)));
+            let detail = fields
+                .iter()
+                .map(|field| field.signature_ty(db).display(db).to_string())
+                .join(", ");
+            (
+                format!("{}(…)", path),
+                format!("{}({})$0", path, placeholders),
+                format!("({})", detail),
+            )
+        }
+    };
+
+    let (item_kind, adt) = match def {
+        VariantDef::Variant(it) => (CompletionItemKind::EnumVariant, it.parent_enum(db).into()),
+        VariantDef::Struct(it) => (CompletionItemKind::Struct, Adt::from(it)),
+        VariantDef::Union(it) => (CompletionItemKind::Struct, it.into()),
+    };
+    let expected_adt = ctx.completion.expected_type.as_ref().and_then(|ty| ty.as_adt());
+    let item = CompletionItem::new(CompletionKind::Magic, ctx.source_range(), label)
+        .kind(item_kind)
+        .detail(detail)
+        .set_relevance(CompletionRelevance {
+            exact_type_match: expected_adt == Some(adt),
+            locality: ctx.locality(Some(def.module(db))),
+            ..CompletionRelevance::default()
+        })
+        .insert_snippet(cap, snippet)
+        .build();
+    Some(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::{
+        test_utils::{check_edit, completion_list, get_all_items},
+        CompletionConfig, CompletionKind,
+    };
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture, CompletionKind::Magic);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_struct_literals() {
+        check(
+            r#"
+struct Point { x: i32, y: i32 }
+struct Meters(f64);
+struct Unit;
+mod m { pub struct Private { field: u32 } }
+fn main() { let p = P<|> }
+"#,
+            expect![[r#"
+                st Point { x, y } { x: i32, y: i32 }
+                st Meters(…)      (f64)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn fills_fields_from_locals() {
+        check_edit(
+            "Point { x, y }",
+            r#"
+struct Point { x: i32, y: i32 }
+fn main() {
+    let x: i32 = 92;
+    let p = P<|>
+}
+"#,
+            r#"
+struct Point { x: i32, y: i32 }
+fn main() {
+    let x: i32 = 92;
+    let p = Point { x, y: $2 }$0
+}
+"#,
+        );
+        check_edit(
+            "Meters(…)",
+            r#"
+struct Meters(f64);
+fn main() { let m = M<|> }
+"#,
+            r#"
+struct Meters(f64);
+fn main() { let m = Meters($1)$0 }
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_record_variant_literals() {
+        check_edit(
+            "Timeout::Fixed { millis, retries }",
+            r#"
+enum Timeout { Fixed { millis: u32, retries: u8 }, Unlimited }
+fn run(timeout: Timeout) {}
+fn main() {
+    let millis: u32 = 92;
+    run(T<|>)
+}
+"#,
+            r#"
+enum Timeout { Fixed { millis: u32, retries: u8 }, Unlimited }
+fn run(timeout: Timeout) {}
+fn main() {
+    let millis: u32 = 92;
+    run(Timeout::Fixed { millis, retries: $2 }$0)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fills_record_variant_fields() {
+        let before = r#"
+enum Timeout { Fixed { millis: u32, retries: u8 }, Unlimited }
+fn run(timeout: Timeout) {}
+fn main() {
+    let millis: u32 = 92;
+    run(<|>)
+}
+"#;
+        check_edit(
+            "Timeout::Fixed { millis, retries }",
+            before,
+            r#"
+enum Timeout { Fixed { millis: u32, retries: u8 }, Unlimited }
+fn run(timeout: Timeout) {}
+fn main() {
+    let millis: u32 = 92;
+    run(Timeout::Fixed { millis, retries: $2 }$0)
+}
+"#,
+        );
+        let items = get_all_items(CompletionConfig::default(), before);
+        let item =
+            items.iter().find(|it| it.label() == "Timeout::Fixed { millis, retries }").unwrap();
+        assert!(item.relevance().exact_type_match);
+    }
+}
@@ -7,7 +7,7 @@ use syntax::{
 };
 
 use crate::{
-    item::{CompletionItem, CompletionItemKind, CompletionKind},
+    item::{CompletionItem, CompletionItemKind, CompletionKind, CompletionRelevance},
     render::RenderContext,
 };
 
@@ -39,6 +39,10 @@ impl<'a> TypeAliasRender<'a> {
             .kind(CompletionItemKind::TypeAlias)
             .set_documentation(self.ctx.docs(self.type_alias))
             .set_deprecated(self.ctx.is_deprecated(self.type_alias))
+            .set_relevance(CompletionRelevance {
+                locality: self.ctx.locality(Some(self.type_alias.module(self.ctx.db()))),
+                ..CompletionRelevance::default()
+            })
             .detail(detail)
             .build();
 
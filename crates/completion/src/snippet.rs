@@ -0,0 +1,119 @@
+//! User-defined (postfix) snippets, configured through the server settings.
+//!
+//! The completions themselves are produced by `completions::snippet` and
+//! `completions::postfix`.
+
+use hir::PathResolution;
+use ide_db::helpers::insert_use::ImportScope;
+use syntax::{ast, AstNode, SourceFile};
+
+use crate::{context::CompletionContext, ImportEdit};
+
+/// Where a user-defined snippet may be completed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnippetScope {
+    Item,
+    Expr,
+    Type,
+}
+
+/// A user-defined snippet.
+///
+/// Postfix snippets refer to the receiver expression as `${receiver}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snippet {
+    pub prefix_triggers: Vec<String>,
+    pub postfix_triggers: Vec<String>,
+    pub scope: SnippetScope,
+    pub description: Option<String>,
+    snippet: String,
+    /// Paths which have to resolve at the completion site, and which get
+    /// imported when the snippet is accepted.
+    requires: Vec<String>,
+}
+
+impl Snippet {
+    /// Returns `None` if the snippet has no triggers, or if one of the required
+    /// paths is not a valid path.
+    pub fn new(
+        prefix_triggers: &[String],
+        postfix_triggers: &[String],
+        snippet: &[String],
+        description: &str,
+        requires: &[String],
+        scope: SnippetScope,
+    ) -> Option<Snippet> {
+        if prefix_triggers.is_empty() && postfix_triggers.is_empty() {
+            return None;
+        }
+        if requires.iter().any(|path| parse_path(path).is_none()) {
+            return None;
+        }
+        let description = if description.is_empty() { None } else { Some(description.to_string()) };
+        Some(Snippet {
+            prefix_triggers: prefix_triggers.to_vec(),
+            postfix_triggers: postfix_triggers.to_vec(),
+            scope,
+            description,
+            snippet: snippet.join("\n"),
+            requires: requires.to_vec(),
+        })
+    }
+
+    pub(crate) fn snippet(&self) -> &str {
+        &self.snippet
+    }
+
+    pub(crate) fn postfix_snippet(&self, receiver: &str) -> String {
+        self.snippet.replace("${receiver}", receiver)
+    }
+
+    /// Computes the imports the snippet needs at the completion site.
+    ///
+    /// Returns `None` if some required path does not resolve there, in which
+    /// case the snippet should not be offered at all.
+    pub(crate) fn imports(&self, ctx: &CompletionContext) -> Option<Vec<ImportEdit>> {
+        import_edits(ctx, &self.requires)
+    }
+}
+
+/// Computes the imports of `requires` at the completion site, or `None` if
+/// some path does not resolve there.
+pub(crate) fn import_edits<S: AsRef<str>>(
+    ctx: &CompletionContext,
+    requires: &[S],
+) -> Option<Vec<ImportEdit>> {
+    if requires.is_empty() {
+        return Some(Vec::new());
+    }
+    let import_scope =
+        ImportScope::find_insert_use_container(&ctx.original_token.parent(), &ctx.sema)?;
+    let current_module = ctx.scope.module()?;
+
+    let mut res = Vec::new();
+    for path in requires.iter() {
+        let path = parse_path(path.as_ref())?;
+        let import_path = match ctx.scope.speculative_resolve(&path)? {
+            PathResolution::Def(def) => current_module.find_use_path(ctx.db, def)?,
+            PathResolution::Macro(mac) => current_module.find_use_path(ctx.db, mac)?,
+            _ => return None,
+        };
+        // A single segment path is already in scope.
+        if import_path.len() > 1 {
+            res.push(ImportEdit { import_path, import_scope: import_scope.clone() });
+        }
+    }
+    Some(res)
+}
+
+fn parse_path(text: &str) -> Option<ast::Path> {
+    let parse = SourceFile::parse(&format!("use {};", text));
+    if !parse.errors().is_empty() {
+        return None;
+    }
+    let path = parse.tree().syntax().descendants().find_map(ast::Path::cast)?;
+    if path.syntax().text() != text.trim() {
+        return None;
+    }
+    Some(path)
+}
@@ -2,9 +2,12 @@
 //! another compatible command (f.x. clippy) in a background thread and provide
 //! LSP diagnostics based on the output of the command.
 
+mod rustc;
+
 use std::{
+    collections::VecDeque,
     fmt,
-    io::{self, BufReader},
+    io::{self, BufRead, BufReader, Read},
     ops,
     path::PathBuf,
     process::{self, Command, Stdio},
@@ -13,6 +16,9 @@ use std::{
 
 use crossbeam_channel::{never, select, unbounded, Receiver, Sender};
 
+use crate::rustc::RustcRun;
+
+pub use crate::rustc::{RustcCrate, RustcCrateKind};
 pub use cargo_metadata::diagnostic::{
     Applicability, Diagnostic, DiagnosticCode, DiagnosticLevel, DiagnosticSpan,
     DiagnosticSpanMacroExpansion,
@@ -28,11 +34,30 @@ pub enum FlycheckConfig {
         all_features: bool,
         features: Vec<String>,
         extra_args: Vec<String>,
+        /// Packages checked with their own features or target.
+        package_overrides: Vec<CargoPackageOverride>,
     },
     CustomCommand {
         command: String,
         args: Vec<String>,
     },
+    /// Check each of the crates with `rustc`, for projects not using cargo.
+    RustcCommand {
+        crates: Vec<RustcCrate>,
+        target_triple: Option<String>,
+    },
+}
+
+/// Features and target of a single package replacing the ones of the
+/// `CargoCommand`. Such a package is checked by a `cargo` invocation of its
+/// own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CargoPackageOverride {
+    /// The `-p` spec of the package.
+    pub package: String,
+    pub features: Option<Vec<String>>,
+    pub no_default_features: bool,
+    pub target_triple: Option<String>,
 }
 
 impl fmt::Display for FlycheckConfig {
@@ -42,6 +67,7 @@ impl fmt::Display for FlycheckConfig {
             FlycheckConfig::CustomCommand { command, args } => {
                 write!(f, "{} {}", command, args.join(" "))
             }
+            FlycheckConfig::RustcCommand { .. } => write!(f, "rustc"),
         }
     }
 }
@@ -52,8 +78,9 @@ impl fmt::Display for FlycheckConfig {
 /// The spawned thread is shut down when this struct is dropped.
 #[derive(Debug)]
 pub struct FlycheckHandle {
+    id: usize,
     // XXX: drop order is significant
-    sender: Sender<Restart>,
+    sender: Sender<StateChange>,
     thread: jod_thread::JoinHandle,
 }
 
@@ -65,21 +92,39 @@ impl FlycheckHandle {
         workspace_root: PathBuf,
     ) -> FlycheckHandle {
         let actor = FlycheckActor::new(id, sender, config, workspace_root);
-        let (sender, receiver) = unbounded::<Restart>();
+        let (sender, receiver) = unbounded::<StateChange>();
         let thread = jod_thread::spawn(move || actor.run(receiver));
-        FlycheckHandle { sender, thread }
+        FlycheckHandle { id, sender, thread }
     }
 
-    /// Schedule a re-start of the cargo check worker.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Schedule a re-start of the cargo check worker for the whole workspace.
     pub fn update(&self) {
-        self.sender.send(Restart).unwrap();
+        self.sender.send(StateChange::Restart(None)).unwrap();
+    }
+
+    /// Schedule a re-start of the cargo check worker for some packages only,
+    /// given as `-p` specs, or as crate names when checking with `rustc`.
+    ///
+    /// Custom check commands can't be restricted to packages, and check the
+    /// whole workspace instead.
+    pub fn update_packages(&self, packages: Vec<String>) {
+        self.sender.send(StateChange::Restart(Some(packages))).unwrap();
+    }
+
+    /// Stop the check currently in progress, if any.
+    pub fn cancel(&self) {
+        self.sender.send(StateChange::Cancel).unwrap();
     }
 }
 
 #[derive(Debug)]
 pub enum Message {
     /// Request adding a diagnostic with fixes included to a file
-    AddDiagnostic { workspace_root: PathBuf, diagnostic: Diagnostic },
+    AddDiagnostic { workspace_root: PathBuf, package_id: String, diagnostic: Diagnostic },
 
     /// Request check progress notification to client
     Progress {
@@ -91,13 +136,21 @@ pub enum Message {
 
 #[derive(Debug)]
 pub enum Progress {
-    DidStart,
+    /// A check started, for the given `-p` specs or, if `None`, for the whole
+    /// workspace.
+    DidStart {
+        packages: Option<Vec<String>>,
+    },
     DidCheckCrate(String),
     DidFinish(io::Result<()>),
     DidCancel,
 }
 
-struct Restart;
+enum StateChange {
+    /// Restart the check, for the given `-p` specs or the whole workspace.
+    Restart(Option<Vec<String>>),
+    Cancel,
+}
 
 struct FlycheckActor {
     id: usize,
@@ -110,11 +163,27 @@ struct FlycheckActor {
     /// have to wrap sub-processes output handling in a thread and pass messages
     /// back over a channel.
     cargo_handle: Option<CargoHandle>,
+    /// The packages checked by the running command, `None` meaning the whole
+    /// workspace.
+    checked_packages: Option<Vec<String>>,
+    /// When checking with `rustc`, the crates left to check and the crates
+    /// whose metadata is up to date.
+    rustc_run: Option<RustcRun>,
+    /// The commands left to run after the running one, when the check takes
+    /// several `cargo` invocations.
+    pending_commands: VecDeque<Command>,
 }
 
 enum Event {
-    Restart(Restart),
-    CheckEvent(Option<cargo_metadata::Message>),
+    StateChange(StateChange),
+    CheckEvent(Option<CheckMessage>),
+}
+
+/// A message read from the output of the check command.
+enum CheckMessage {
+    Cargo(cargo_metadata::Message),
+    /// A diagnostic emitted by `rustc --error-format=json`.
+    Rustc(Diagnostic),
 }
 
 impl FlycheckActor {
@@ -124,32 +193,74 @@ impl FlycheckActor {
         config: FlycheckConfig,
         workspace_root: PathBuf,
     ) -> FlycheckActor {
-        FlycheckActor { id, sender, config, workspace_root, cargo_handle: None }
+        FlycheckActor {
+            id,
+            sender,
+            config,
+            workspace_root,
+            cargo_handle: None,
+            checked_packages: None,
+            rustc_run: None,
+            pending_commands: VecDeque::new(),
+        }
     }
     fn progress(&self, progress: Progress) {
         self.send(Message::Progress { id: self.id, progress });
     }
-    fn next_event(&self, inbox: &Receiver<Restart>) -> Option<Event> {
+    fn next_event(&self, inbox: &Receiver<StateChange>) -> Option<Event> {
         let check_chan = self.cargo_handle.as_ref().map(|cargo| &cargo.receiver);
         select! {
-            recv(inbox) -> msg => msg.ok().map(Event::Restart),
+            recv(inbox) -> msg => msg.ok().map(Event::StateChange),
             recv(check_chan.unwrap_or(&never())) -> msg => Some(Event::CheckEvent(msg.ok())),
         }
     }
-    fn run(mut self, inbox: Receiver<Restart>) {
+    fn run(mut self, inbox: Receiver<StateChange>) {
         while let Some(event) = self.next_event(&inbox) {
             match event {
-                Event::Restart(Restart) => {
-                    while let Ok(Restart) = inbox.recv_timeout(Duration::from_millis(50)) {}
+                Event::StateChange(StateChange::Cancel) => self.cancel_check_process(),
+                Event::StateChange(StateChange::Restart(packages)) => {
+                    // Packages of an interrupted check still need to be checked.
+                    // `rustc` runs keep track of the crates left to check
+                    // themselves.
+                    let mut packages = match (&self.cargo_handle, &self.config) {
+                        (_, FlycheckConfig::RustcCommand { .. }) | (None, _) => packages,
+                        (Some(_), _) => merge_packages(self.checked_packages.take(), packages),
+                    };
+                    let mut cancelled = false;
+                    while let Ok(change) = inbox.recv_timeout(Duration::from_millis(50)) {
+                        match change {
+                            StateChange::Restart(more) => {
+                                packages = merge_packages(packages, more);
+                                cancelled = false;
+                            }
+                            StateChange::Cancel => cancelled = true,
+                        }
+                    }
 
                     self.cancel_check_process();
+                    if cancelled {
+                        continue;
+                    }
+                    if let FlycheckConfig::RustcCommand { crates, .. } = &self.config {
+                        let (id, workspace_root) = (self.id, &self.workspace_root);
+                        let rustc_run = self.rustc_run.get_or_insert_with(|| {
+                            RustcRun::new(crates, rustc::out_dir(id, workspace_root))
+                        });
+                        let packages = rustc_run.start(crates, packages.as_deref());
+                        self.progress(Progress::DidStart { packages });
+                        if !self.check_next_crate() {
+                            self.progress(Progress::DidFinish(Ok(())));
+                        }
+                        continue;
+                    }
+                    if let FlycheckConfig::CustomCommand { .. } = self.config {
+                        packages = None;
+                    }
 
-                    let mut command = self.check_command();
-                    log::info!("restart flycheck {:?}", command);
-                    command.stdout(Stdio::piped()).stderr(Stdio::null()).stdin(Stdio::null());
-                    if let Ok(child) = command.spawn().map(JodChild) {
-                        self.cargo_handle = Some(CargoHandle::spawn(child));
-                        self.progress(Progress::DidStart);
+                    self.pending_commands = self.check_commands(packages.as_deref()).into();
+                    if self.run_next_command() {
+                        self.checked_packages = packages.clone();
+                        self.progress(Progress::DidStart { packages });
                     }
                 }
                 Event::CheckEvent(None) => {
@@ -157,15 +268,31 @@ impl FlycheckActor {
                     // avoid busy-waiting.
                     let cargo_handle = self.cargo_handle.take().unwrap();
                     let res = cargo_handle.join();
-                    if res.is_err() {
-                        log::error!(
-                            "Flycheck failed to run the following command: {:?}",
-                            self.check_command()
-                        )
+                    if let Some(rustc_run) = &mut self.rustc_run {
+                        rustc_run.finish_current(matches!(res, Ok(true)));
+                        if self.check_next_crate() {
+                            continue;
+                        }
+                        self.progress(Progress::DidFinish(res.map(drop)));
+                        continue;
                     }
-                    self.progress(Progress::DidFinish(res));
+                    if let Err(err) = &res {
+                        log::error!("Flycheck failed to run {}: {}", self.config, err);
+                    }
+                    if self.run_next_command() {
+                        continue;
+                    }
+                    self.checked_packages = None;
+                    self.progress(Progress::DidFinish(res.map(drop)));
                 }
-                Event::CheckEvent(Some(message)) => match message {
+                Event::CheckEvent(Some(CheckMessage::Rustc(diagnostic))) => {
+                    self.send(Message::AddDiagnostic {
+                        workspace_root: self.workspace_root.clone(),
+                        package_id: self.rustc_crate_name().unwrap_or_default(),
+                        diagnostic,
+                    });
+                }
+                Event::CheckEvent(Some(CheckMessage::Cargo(message))) => match message {
                     cargo_metadata::Message::CompilerArtifact(msg) => {
                         self.progress(Progress::DidCheckCrate(msg.target.name));
                     }
@@ -173,6 +300,7 @@ impl FlycheckActor {
                     cargo_metadata::Message::CompilerMessage(msg) => {
                         self.send(Message::AddDiagnostic {
                             workspace_root: self.workspace_root.clone(),
+                            package_id: msg.package_id.repr,
                             diagnostic: msg.message,
                         });
                     }
@@ -188,12 +316,79 @@ impl FlycheckActor {
         self.cancel_check_process();
     }
     fn cancel_check_process(&mut self) {
-        if self.cargo_handle.take().is_some() {
+        self.pending_commands.clear();
+        let cancelled = self.cargo_handle.take().is_some();
+        if let Some(rustc_run) = &mut self.rustc_run {
+            rustc_run.cancel();
+        }
+        if cancelled {
+            self.checked_packages = None;
             self.progress(Progress::DidCancel);
         }
     }
-    fn check_command(&self) -> Command {
-        let mut cmd = match &self.config {
+    /// Starts checking the next crate of the `rustc` run, returning `false`
+    /// if there is none left.
+    fn check_next_crate(&mut self) -> bool {
+        let (crates, target_triple) = match &self.config {
+            FlycheckConfig::RustcCommand { crates, target_triple } => (crates, target_triple),
+            _ => return false,
+        };
+        let rustc_run = match &mut self.rustc_run {
+            Some(it) => it,
+            None => return false,
+        };
+        while let Some((idx, mut command)) =
+            rustc_run.next_command(crates, target_triple.as_deref())
+        {
+            command.current_dir(&self.workspace_root);
+            log::info!("flycheck crate {:?}", command);
+            command.stdout(Stdio::null()).stderr(Stdio::piped()).stdin(Stdio::null());
+            match command.spawn().map(JodChild) {
+                Ok(child) => {
+                    self.cargo_handle = Some(CargoHandle::spawn(child, OutputFormat::Rustc));
+                    let name = crates[idx].name.clone();
+                    (self.sender)(Message::Progress {
+                        id: self.id,
+                        progress: Progress::DidCheckCrate(name),
+                    });
+                    return true;
+                }
+                Err(err) => {
+                    log::error!("Flycheck failed to run {:?}: {}", command, err);
+                    rustc_run.finish_current(false);
+                }
+            }
+        }
+        false
+    }
+    /// The name of the crate being checked with `rustc`.
+    fn rustc_crate_name(&self) -> Option<String> {
+        let crates = match &self.config {
+            FlycheckConfig::RustcCommand { crates, .. } => crates,
+            _ => return None,
+        };
+        Some(crates[self.rustc_run.as_ref()?.current()?].name.clone())
+    }
+    /// Spawns the next of the pending commands, returning `false` if there is
+    /// none left.
+    fn run_next_command(&mut self) -> bool {
+        while let Some(mut command) = self.pending_commands.pop_front() {
+            log::info!("restart flycheck {:?}", command);
+            command.stdout(Stdio::piped()).stderr(Stdio::null()).stdin(Stdio::null());
+            match command.spawn().map(JodChild) {
+                Ok(child) => {
+                    self.cargo_handle = Some(CargoHandle::spawn(child, OutputFormat::Cargo));
+                    return true;
+                }
+                Err(err) => log::error!("Flycheck failed to run {:?}: {}", command, err),
+            }
+        }
+        false
+    }
+    /// The commands checking `packages`, or the whole workspace if `None`.
+    /// Packages with overrides are checked by commands of their own.
+    fn check_commands(&self, packages: Option<&[String]>) -> Vec<Command> {
+        let mut commands = match &self.config {
             FlycheckConfig::CargoCommand {
                 command,
                 target_triple,
@@ -202,40 +397,103 @@ impl FlycheckActor {
                 all_features,
                 extra_args,
                 features,
+                package_overrides,
             } => {
-                let mut cmd = Command::new(toolchain::cargo());
-                cmd.arg(command);
-                cmd.args(&["--workspace", "--message-format=json", "--manifest-path"])
-                    .arg(self.workspace_root.join("Cargo.toml"));
+                let cargo_command =
+                    |packages: Option<&[String]>,
+                     excluded: &[String],
+                     package_override: Option<&CargoPackageOverride>| {
+                        let mut cmd = Command::new(toolchain::cargo());
+                        cmd.arg(command);
+                        match packages {
+                            Some(packages) => {
+                                for package in packages {
+                                    cmd.args(&["-p", package.as_str()]);
+                                }
+                            }
+                            None => {
+                                cmd.arg("--workspace");
+                                for package in excluded {
+                                    cmd.args(&["--exclude", package.as_str()]);
+                                }
+                            }
+                        }
+                        cmd.args(&["--message-format=json", "--manifest-path"])
+                            .arg(self.workspace_root.join("Cargo.toml"));
 
-                if let Some(target) = target_triple {
-                    cmd.args(&["--target", target.as_str()]);
-                }
-                if *all_targets {
-                    cmd.arg("--all-targets");
-                }
-                if *all_features {
-                    cmd.arg("--all-features");
-                } else {
-                    if *no_default_features {
-                        cmd.arg("--no-default-features");
+                        let target_triple = package_override
+                            .and_then(|it| it.target_triple.as_ref())
+                            .or_else(|| target_triple.as_ref());
+                        if let Some(target) = target_triple {
+                            cmd.args(&["--target", target.as_str()]);
+                        }
+                        if *all_targets {
+                            cmd.arg("--all-targets");
+                        }
+                        let features = package_override
+                            .and_then(|it| it.features.as_ref())
+                            .unwrap_or(features);
+                        let no_default_features = *no_default_features
+                            || package_override.map_or(false, |it| it.no_default_features);
+                        if *all_features
+                            && package_override.map_or(true, |it| it.features.is_none())
+                        {
+                            cmd.arg("--all-features");
+                        } else {
+                            if no_default_features {
+                                cmd.arg("--no-default-features");
+                            }
+                            if !features.is_empty() {
+                                cmd.arg("--features");
+                                cmd.arg(features.join(" "));
+                            }
+                        }
+                        cmd.args(extra_args);
+                        cmd
+                    };
+
+                let is_overridden =
+                    |package: &String| package_overrides.iter().any(|it| &it.package == package);
+                let mut commands = Vec::new();
+                match packages {
+                    Some(packages) => {
+                        let rest: Vec<String> =
+                            packages.iter().filter(|it| !is_overridden(it)).cloned().collect();
+                        if !rest.is_empty() {
+                            commands.push(cargo_command(Some(&rest), &[], None));
+                        }
+                    }
+                    None => {
+                        let excluded: Vec<String> =
+                            package_overrides.iter().map(|it| it.package.clone()).collect();
+                        commands.push(cargo_command(None, &excluded, None));
                     }
-                    if !features.is_empty() {
-                        cmd.arg("--features");
-                        cmd.arg(features.join(" "));
+                }
+                for package_override in package_overrides {
+                    let is_checked = packages.map_or(true, |packages| {
+                        packages.iter().any(|it| it == &package_override.package)
+                    });
+                    if is_checked {
+                        let package = [package_override.package.clone()];
+                        commands.push(cargo_command(Some(&package), &[], Some(package_override)));
                     }
                 }
-                cmd.args(extra_args);
-                cmd
+                commands
             }
             FlycheckConfig::CustomCommand { command, args } => {
                 let mut cmd = Command::new(command);
                 cmd.args(args);
-                cmd
+                vec![cmd]
+            }
+            FlycheckConfig::RustcCommand { .. } => {
+                // Crates are checked one at a time, see `check_next_crate`.
+                Vec::new()
             }
         };
-        cmd.current_dir(&self.workspace_root);
-        cmd
+        for cmd in commands.iter_mut() {
+            cmd.current_dir(&self.workspace_root);
+        }
+        commands
     }
 
     fn send(&self, check_task: Message) {
@@ -243,22 +501,47 @@ impl FlycheckActor {
     }
 }
 
+/// Combines the packages of two checks, `None` standing for the whole
+/// workspace.
+fn merge_packages(packages: Option<Vec<String>>, more: Option<Vec<String>>) -> Option<Vec<String>> {
+    let (mut packages, more) = (packages?, more?);
+    for package in more {
+        if !packages.contains(&package) {
+            packages.push(package);
+        }
+    }
+    Some(packages)
+}
+
+/// How the check command reports diagnostics.
+#[derive(Clone, Copy)]
+enum OutputFormat {
+    /// Messages of `cargo --message-format=json`, on stdout.
+    Cargo,
+    /// Diagnostics of `rustc --error-format=json`, on stderr.
+    Rustc,
+}
+
 struct CargoHandle {
     child: JodChild,
     #[allow(unused)]
     thread: jod_thread::JoinHandle<io::Result<bool>>,
-    receiver: Receiver<cargo_metadata::Message>,
+    receiver: Receiver<CheckMessage>,
 }
 
 impl CargoHandle {
-    fn spawn(mut child: JodChild) -> CargoHandle {
-        let child_stdout = child.stdout.take().unwrap();
+    fn spawn(mut child: JodChild, format: OutputFormat) -> CargoHandle {
+        let output: Box<dyn Read + Send> = match format {
+            OutputFormat::Cargo => Box::new(child.stdout.take().unwrap()),
+            OutputFormat::Rustc => Box::new(child.stderr.take().unwrap()),
+        };
         let (sender, receiver) = unbounded();
-        let actor = CargoActor::new(child_stdout, sender);
+        let actor = CargoActor::new(output, format, sender);
         let thread = jod_thread::spawn(move || actor.run());
         CargoHandle { child, thread, receiver }
     }
-    fn join(mut self) -> io::Result<()> {
+    /// Waits for the command to exit, returning whether it succeeded.
+    fn join(mut self) -> io::Result<bool> {
         // It is okay to ignore the result, as it only errors if the process is already dead
         let _ = self.child.kill();
         let exit_status = self.child.wait()?;
@@ -274,23 +557,29 @@ impl CargoHandle {
                 ),
             ));
         }
-        Ok(())
+        Ok(exit_status.success())
     }
 }
 
 struct CargoActor {
-    child_stdout: process::ChildStdout,
-    sender: Sender<cargo_metadata::Message>,
+    output: Box<dyn Read + Send>,
+    format: OutputFormat,
+    sender: Sender<CheckMessage>,
 }
 
 impl CargoActor {
     fn new(
-        child_stdout: process::ChildStdout,
-        sender: Sender<cargo_metadata::Message>,
+        output: Box<dyn Read + Send>,
+        format: OutputFormat,
+        sender: Sender<CheckMessage>,
     ) -> CargoActor {
-        CargoActor { child_stdout, sender }
+        CargoActor { output, format, sender }
     }
     fn run(self) -> io::Result<bool> {
+        if let OutputFormat::Rustc = self.format {
+            return self.run_rustc();
+        }
+
         // We manually read a line at a time, instead of using serde's
         // stream deserializers, because the deserializer cannot recover
         // from an error, resulting in it getting stuck, because we try to
@@ -299,7 +588,7 @@ impl CargoActor {
         // Because cargo only outputs one JSON object per line, we can
         // simply skip a line if it doesn't parse, which just ignores any
         // erroneus output.
-        let stdout = BufReader::new(self.child_stdout);
+        let stdout = BufReader::new(self.output);
         let mut read_at_least_one_message = false;
         for message in cargo_metadata::Message::parse_stream(stdout) {
             let message = match message {
@@ -317,7 +606,21 @@ impl CargoActor {
                 cargo_metadata::Message::CompilerArtifact(artifact) if artifact.fresh => (),
                 cargo_metadata::Message::BuildScriptExecuted(_)
                 | cargo_metadata::Message::Unknown => (),
-                _ => self.sender.send(message).unwrap(),
+                _ => self.sender.send(CheckMessage::Cargo(message)).unwrap(),
+            }
+        }
+        Ok(read_at_least_one_message)
+    }
+    fn run_rustc(self) -> io::Result<bool> {
+        // Besides diagnostics, rustc may print artifact notifications and
+        // plain text, which are skipped.
+        let stderr = BufReader::new(self.output);
+        let mut read_at_least_one_message = false;
+        for line in stderr.lines() {
+            let line = line?;
+            if let Ok(diagnostic) = serde_json::from_str::<Diagnostic>(&line) {
+                read_at_least_one_message = true;
+                self.sender.send(CheckMessage::Rustc(diagnostic)).unwrap();
             }
         }
         Ok(read_at_least_one_message)
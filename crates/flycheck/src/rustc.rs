@@ -0,0 +1,259 @@
+//! Checking of projects without cargo, by invoking `rustc` on each crate.
+//!
+//! Crates are checked in dependency order, emitting only their metadata, which
+//! the crates depending on them are then pointed to with `--extern`. The
+//! metadata is kept in a temporary directory between runs, so that a run only
+//! rechecks the changed crates and the crates depending on them. The directory
+//! is removed with the flycheck actor.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A crate to check with `rustc`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RustcCrate {
+    /// The name passed to `--crate-name`.
+    pub name: String,
+    pub root_module: PathBuf,
+    pub edition: String,
+    /// The arguments of the `--cfg` flags.
+    pub cfgs: Vec<String>,
+    /// The dependencies, as the name they are imported with and their index in
+    /// the list of crates.
+    pub deps: Vec<(String, usize)>,
+    pub env: Vec<(String, String)>,
+    pub target: Option<String>,
+    /// For proc-macro crates, the already built library, which dependents use.
+    pub proc_macro_dylib_path: Option<PathBuf>,
+    /// Lints are only reported for workspace members.
+    pub is_workspace_member: bool,
+    pub kind: RustcCrateKind,
+}
+
+/// What `rustc` is asked to build out of a crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RustcCrateKind {
+    Lib,
+    ProcMacro,
+    Bin,
+    /// A test harness, built with `--test`.
+    Test,
+}
+
+/// The state of checking the crates, one at a time, across runs.
+pub(crate) struct RustcRun {
+    out_dir: PathBuf,
+    /// The crates left to check in the current run, the next one last.
+    pending: Vec<usize>,
+    current: Option<usize>,
+    /// Whether the metadata of each crate is up to date with its sources and
+    /// the metadata of its dependencies.
+    fresh: Vec<bool>,
+    failed: Vec<bool>,
+}
+
+impl RustcRun {
+    pub(crate) fn new(crates: &[RustcCrate], out_dir: PathBuf) -> RustcRun {
+        RustcRun {
+            out_dir,
+            pending: Vec::new(),
+            current: None,
+            fresh: vec![false; crates.len()],
+            failed: vec![false; crates.len()],
+        }
+    }
+
+    /// Starts a run rechecking the crates named `changed`, or all of the
+    /// workspace members if `None`, along with the crates depending on them
+    /// and the crates whose metadata is missing.
+    ///
+    /// Returns the names of the crates to check, or `None` if the whole
+    /// workspace is checked.
+    pub(crate) fn start(
+        &mut self,
+        crates: &[RustcCrate],
+        changed: Option<&[String]>,
+    ) -> Option<Vec<String>> {
+        let mut stale: Vec<usize> = (0..crates.len())
+            .filter(|&idx| match changed {
+                Some(names) => names.contains(&crates[idx].name),
+                None => crates[idx].is_workspace_member,
+            })
+            .collect();
+        let dependents = reverse_dependencies(crates);
+        let mut visited = vec![false; crates.len()];
+        while let Some(idx) = stale.pop() {
+            if visited[idx] {
+                continue;
+            }
+            visited[idx] = true;
+            self.fresh[idx] = false;
+            stale.extend(dependents[idx].iter().copied());
+        }
+
+        let mut pending: Vec<usize> =
+            topological_order(crates).into_iter().filter(|&idx| !self.fresh[idx]).collect();
+        let names = changed.map(|_| {
+            let mut names: Vec<String> = Vec::new();
+            for &idx in pending.iter() {
+                if !names.contains(&crates[idx].name) {
+                    names.push(crates[idx].name.clone());
+                }
+            }
+            names
+        });
+        pending.reverse();
+        self.pending = pending;
+        self.current = None;
+        names
+    }
+
+    /// Stops the current run. The crates it didn't check are checked by the
+    /// next one.
+    pub(crate) fn cancel(&mut self) {
+        self.pending.clear();
+        self.current = None;
+    }
+
+    /// Returns the index of the next crate to check and the command checking
+    /// it, skipping the crates with a dependency that failed to check.
+    pub(crate) fn next_command(
+        &mut self,
+        crates: &[RustcCrate],
+        target_triple: Option<&str>,
+    ) -> Option<(usize, Command)> {
+        while let Some(idx) = self.pending.pop() {
+            if crates[idx].deps.iter().any(|&(_, dep)| self.failed[dep]) {
+                log::info!("not checking {}, a dependency failed to check", crates[idx].name);
+                self.failed[idx] = true;
+                continue;
+            }
+            if let Err(err) = fs::create_dir_all(&self.out_dir) {
+                log::error!("failed to create {}: {}", self.out_dir.display(), err);
+                return None;
+            }
+            self.current = Some(idx);
+            return Some((idx, self.command(crates, idx, target_triple)));
+        }
+        None
+    }
+
+    /// The crate being checked.
+    pub(crate) fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Records the outcome of checking the last crate returned by
+    /// `next_command`.
+    pub(crate) fn finish_current(&mut self, success: bool) {
+        if let Some(idx) = self.current.take() {
+            self.failed[idx] = !success;
+            self.fresh[idx] = success;
+        }
+    }
+
+    fn command(&self, crates: &[RustcCrate], idx: usize, target_triple: Option<&str>) -> Command {
+        let krate = &crates[idx];
+        let mut cmd = Command::new(toolchain::rustc());
+        cmd.args(&["--crate-name", &krate.name]);
+        match krate.kind {
+            RustcCrateKind::Lib => cmd.args(&["--crate-type", "lib"]),
+            RustcCrateKind::ProcMacro => cmd.args(&["--crate-type", "proc-macro"]),
+            RustcCrateKind::Bin => cmd.args(&["--crate-type", "bin"]),
+            RustcCrateKind::Test => cmd.arg("--test"),
+        };
+        cmd.args(&["--edition", &krate.edition, "--error-format=json"]);
+        cmd.arg("--emit").arg(format!("metadata={}", self.metadata_path(crates, idx).display()));
+        if let Some(target) = krate.target.as_deref().or(target_triple) {
+            cmd.args(&["--target", target]);
+        }
+        for cfg in krate.cfgs.iter() {
+            cmd.args(&["--cfg", cfg]);
+        }
+        if krate.kind == RustcCrateKind::ProcMacro {
+            cmd.args(&["--extern", "proc_macro"]);
+        }
+        for (name, dep) in krate.deps.iter() {
+            let path = match &crates[*dep].proc_macro_dylib_path {
+                Some(it) => it.clone(),
+                None => self.metadata_path(crates, *dep),
+            };
+            cmd.arg("--extern").arg(format!("{}={}", name, path.display()));
+        }
+        cmd.arg("-L").arg(format!("dependency={}", self.out_dir.display()));
+        if !krate.is_workspace_member {
+            cmd.args(&["--cap-lints", "allow"]);
+        }
+        cmd.envs(krate.env.iter().map(|(k, v)| (k, v)));
+        cmd.arg(&krate.root_module);
+        cmd
+    }
+
+    fn metadata_path(&self, crates: &[RustcCrate], idx: usize) -> PathBuf {
+        self.out_dir.join(format!("lib{}-{}.rmeta", crates[idx].name, idx))
+    }
+}
+
+impl Drop for RustcRun {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.out_dir) {
+            if err.kind() != io::ErrorKind::NotFound {
+                log::warn!("failed to remove {}: {}", self.out_dir.display(), err);
+            }
+        }
+    }
+}
+
+/// The directory holding the metadata of the checked crates.
+///
+/// Each call returns a new directory, as the directory of a replaced flycheck
+/// actor is only removed once its thread is over.
+pub(crate) fn out_dir(id: usize, workspace_root: &Path) -> PathBuf {
+    static GENERATION: AtomicUsize = AtomicUsize::new(0);
+    let generation = GENERATION.fetch_add(1, Ordering::Relaxed);
+    let name = workspace_root.file_name().and_then(|it| it.to_str()).unwrap_or("project");
+    std::env::temp_dir().join(format!(
+        "rust-analyzer-check-{}-{}-{}-{}",
+        std::process::id(),
+        id,
+        generation,
+        name
+    ))
+}
+
+/// For each crate, the crates depending on it.
+fn reverse_dependencies(crates: &[RustcCrate]) -> Vec<Vec<usize>> {
+    let mut res = vec![Vec::new(); crates.len()];
+    for (idx, krate) in crates.iter().enumerate() {
+        for &(_, dep) in krate.deps.iter() {
+            res[dep].push(idx);
+        }
+    }
+    res
+}
+
+/// Orders the crates so that each one comes after its dependencies, ignoring
+/// the dependencies forming cycles.
+fn topological_order(crates: &[RustcCrate]) -> Vec<usize> {
+    fn visit(crates: &[RustcCrate], idx: usize, visited: &mut Vec<bool>, res: &mut Vec<usize>) {
+        if visited[idx] {
+            return;
+        }
+        visited[idx] = true;
+        for &(_, dep) in crates[idx].deps.iter() {
+            visit(crates, dep, visited, res);
+        }
+        res.push(idx);
+    }
+
+    let mut visited = vec![false; crates.len()];
+    let mut res = Vec::with_capacity(crates.len());
+    for idx in 0..crates.len() {
+        visit(crates, idx, &mut visited, &mut res);
+    }
+    res
+}
@@ -2,14 +2,14 @@
 use std::{iter, sync::Arc};
 
 use arrayvec::ArrayVec;
-use base_db::{CrateDisplayName, CrateId, Edition, FileId};
+use base_db::{CrateDisplayName, CrateId, Edition, FileId, ProcMacroKind};
 use either::Either;
 use hir_def::{
     adt::ReprKind,
     adt::StructKind,
     adt::VariantData,
     builtin_type::BuiltinType,
-    expr::{BindingAnnotation, Pat, PatId},
+    expr::{BindingAnnotation, LabelId, Pat, PatId},
     import_map,
     item_tree::ItemTreeNode,
     lang_item::LangItemTarget,
@@ -18,10 +18,10 @@ use hir_def::{
     resolver::{HasResolver, Resolver},
     src::HasSource as _,
     type_ref::{Mutability, TypeRef},
-    AdtId, AssocContainerId, AssocItemId, AssocItemLoc, AttrDefId, ConstId, DefWithBodyId, EnumId,
-    FunctionId, GenericDefId, HasModule, ImplId, LifetimeParamId, LocalEnumVariantId, LocalFieldId,
-    LocalModuleId, Lookup, ModuleId, StaticId, StructId, TraitId, TypeAliasId, TypeParamId,
-    UnionId,
+    AdtId, AssocContainerId, AssocItemId, AssocItemLoc, AttrDefId, ConstId, ConstParamId,
+    DefWithBodyId, EnumId, FunctionId, GenericDefId, HasModule, ImplId, LifetimeParamId,
+    LocalEnumVariantId, LocalFieldId, LocalModuleId, Lookup, ModuleId, StaticId, StructId, TraitId,
+    TypeAliasId, TypeParamId, UnionId,
 };
 use hir_def::{find_path::PrefixKind, item_scope::ItemInNs, visibility::Visibility};
 use hir_expand::{
@@ -36,7 +36,7 @@ use hir_ty::{
     traits::{FnTrait, Solution, SolutionVariables},
     ApplicationTy, BoundVar, CallableDefId, Canonical, DebruijnIndex, FnSig, GenericPredicate,
     InEnvironment, Obligation, ProjectionPredicate, ProjectionTy, Substs, TraitEnvironment, Ty,
-    TyDefId, TyKind, TypeCtor,
+    TyDefId, TyKind, TypeCtor, TypeWalk,
 };
 use rustc_hash::FxHashSet;
 use stdx::impl_from;
@@ -108,6 +108,18 @@ impl Crate {
         db.crate_graph()[self.id].display_name.clone()
     }
 
+    /// Returns the values of the cfg option `key` the crate could be compiled
+    /// with, like all the features declared in its `Cargo.toml`.
+    pub fn potential_cfg_values(self, db: &dyn HirDatabase, key: &str) -> Vec<String> {
+        let mut values: Vec<String> = db.crate_graph()[self.id]
+            .potential_cfg_options
+            .get_cfg_values(key)
+            .map(|it| it.to_string())
+            .collect();
+        values.sort();
+        values
+    }
+
     pub fn query_external_importables(
         self,
         db: &dyn DefDatabase,
@@ -687,6 +699,10 @@ impl VariantDef {
         }
     }
 
+    pub fn kind(self, db: &dyn HirDatabase) -> StructKind {
+        self.variant_data(db).kind()
+    }
+
     pub(crate) fn variant_data(self, db: &dyn HirDatabase) -> Arc<VariantData> {
         match self {
             VariantDef::Struct(it) => it.variant_data(db),
@@ -767,6 +783,42 @@ impl Function {
             })
             .collect()
     }
+    pub fn ret_type(self, db: &dyn HirDatabase) -> Type {
+        let resolver = self.id.resolver(db.upcast());
+        let ctx = hir_ty::TyLoweringContext::new(db, &resolver);
+        let environment = TraitEnvironment::lower(db, &resolver);
+        let ret_type = &db.function_data(self.id).ret_type;
+        Type {
+            krate: self.id.lookup(db.upcast()).container.module(db.upcast()).krate,
+            ty: InEnvironment { value: Ty::from_hir_ext(&ctx, ret_type).0, environment },
+        }
+    }
+
+    /// Whether the return type could be `expected` for some choice of the
+    /// generic parameters of the function and of its impl.
+    pub fn ret_type_could_unify_with(self, db: &dyn HirDatabase, expected: &Type) -> bool {
+        let mut generic_defs = vec![GenericDefId::from(self.id)];
+        if let AssocContainerId::ImplId(it) = self.id.lookup(db.upcast()).container {
+            generic_defs.push(it.into());
+        }
+        let mut params: Vec<TypeParamId> = Vec::new();
+        let ret_type = self.ret_type(db).ty.value.fold(&mut |ty| match ty {
+            Ty::Placeholder(id) if generic_defs.contains(&id.parent) => {
+                let index = match params.iter().position(|it| *it == id) {
+                    Some(it) => it,
+                    None => {
+                        params.push(id);
+                        params.len() - 1
+                    }
+                };
+                Ty::Bound(BoundVar::new(DebruijnIndex::INNERMOST, index))
+            }
+            ty => ty,
+        });
+        let ret_type = Canonical::new(ret_type, params.iter().map(|_| TyKind::General));
+        hir_ty::could_unify(&ret_type, &expected.ty.value)
+    }
+
     pub fn method_params(self, db: &dyn HirDatabase) -> Option<Vec<Param>> {
         if self.self_param(db).is_none() {
             return None;
@@ -995,7 +1047,16 @@ impl MacroDef {
 
     /// Indicate it is a derive macro
     pub fn is_derive_macro(&self) -> bool {
-        matches!(self.id.kind, MacroDefKind::ProcMacro(_) | MacroDefKind::BuiltInDerive(_))
+        match self.id.kind {
+            MacroDefKind::ProcMacro(it) => it.kind == ProcMacroKind::CustomDerive,
+            MacroDefKind::BuiltInDerive(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Indicate it is an attribute macro
+    pub fn is_attr_macro(&self) -> bool {
+        matches!(self.id.kind, MacroDefKind::ProcMacro(it) if it.kind == ProcMacroKind::Attr)
     }
 }
 
@@ -1121,7 +1182,12 @@ impl GenericDef {
                 id: LifetimeParamId { parent: self.into(), local_id },
             })
             .map(GenericParam::LifetimeParam);
-        ty_params.chain(lt_params).collect()
+        let const_params = generics
+            .consts
+            .iter()
+            .map(|(local_id, _)| ConstParam { id: ConstParamId { parent: self.into(), local_id } })
+            .map(GenericParam::ConstParam);
+        ty_params.chain(lt_params).chain(const_params).collect()
     }
 
     pub fn type_params(self, db: &dyn HirDatabase) -> Vec<TypeParam> {
@@ -1134,6 +1200,34 @@ impl GenericDef {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Label {
+    pub(crate) parent: DefWithBodyId,
+    pub(crate) label_id: LabelId,
+}
+
+impl Label {
+    pub fn module(self, db: &dyn HirDatabase) -> Module {
+        self.parent(db).module(db)
+    }
+
+    pub fn parent(self, _db: &dyn HirDatabase) -> DefWithBody {
+        self.parent.into()
+    }
+
+    pub fn name(self, db: &dyn HirDatabase) -> Name {
+        let body = db.body(self.parent);
+        body[self.label_id].name.clone()
+    }
+
+    pub fn source(self, db: &dyn HirDatabase) -> InFile<ast::Label> {
+        let (_body, source_map) = db.body_with_source_map(self.parent);
+        let src = source_map.label_syntax(self.label_id);
+        let root = src.file_syntax(db.upcast());
+        src.map(|ast| ast.to_node(&root))
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Local {
     pub(crate) parent: DefWithBodyId,
@@ -1205,8 +1299,9 @@ impl Local {
 pub enum GenericParam {
     TypeParam(TypeParam),
     LifetimeParam(LifetimeParam),
+    ConstParam(ConstParam),
 }
-impl_from!(TypeParam, LifetimeParam for GenericParam);
+impl_from!(TypeParam, LifetimeParam, ConstParam for GenericParam);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct TypeParam {
@@ -1268,6 +1363,26 @@ impl LifetimeParam {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ConstParam {
+    pub(crate) id: ConstParamId,
+}
+
+impl ConstParam {
+    pub fn name(self, db: &dyn HirDatabase) -> Name {
+        let params = db.generic_params(self.id.parent);
+        params.consts[self.id.local_id].name.clone()
+    }
+
+    pub fn module(self, db: &dyn HirDatabase) -> Module {
+        self.id.parent.module(db.upcast()).into()
+    }
+
+    pub fn parent(self, _db: &dyn HirDatabase) -> GenericDef {
+        self.id.parent.into()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Impl {
     pub(crate) id: ImplId,
@@ -1393,6 +1508,17 @@ impl Type {
         matches!(self.ty.value, Ty::Apply(ApplicationTy { ctor: TypeCtor::Bool, .. }))
     }
 
+    /// Whether this is `bool`, `char` or a numeric type.
+    pub fn is_scalar(&self) -> bool {
+        matches!(
+            self.ty.value,
+            Ty::Apply(ApplicationTy {
+                ctor: TypeCtor::Bool | TypeCtor::Char | TypeCtor::Int(_) | TypeCtor::Float(_),
+                ..
+            })
+        )
+    }
+
     pub fn is_mutable_reference(&self) -> bool {
         matches!(
             self.ty.value,
@@ -1412,6 +1538,13 @@ impl Type {
         matches!(self.ty.value, Ty::Unknown)
     }
 
+    /// Whether a value of this type could be used where `other` is expected,
+    /// modulo unknown types.
+    pub fn could_unify_with(&self, other: &Type) -> bool {
+        let ty = Canonical::new(self.ty.value.clone(), iter::empty());
+        hir_ty::could_unify(&ty, &other.ty.value)
+    }
+
     /// Checks that particular type `ty` implements `std::future::Future`.
     /// This function is used in `.await` syntax completion.
     pub fn impls_future(&self, db: &dyn HirDatabase) -> bool {
@@ -1701,6 +1834,14 @@ impl Type {
         Some(adt.into())
     }
 
+    /// The generic arguments of an ADT, like `i32` in `Option<i32>`.
+    pub fn type_arguments(&self) -> Vec<Type> {
+        match self.ty.value.as_adt() {
+            Some((_adt, substs)) => substs.iter().map(|ty| self.derived(ty.clone())).collect(),
+            None => Vec::new(),
+        }
+    }
+
     pub fn as_dyn_trait(&self) -> Option<Trait> {
         self.ty.value.dyn_trait().map(Into::into)
     }
@@ -1903,10 +2044,11 @@ impl Callable {
 pub enum ScopeDef {
     ModuleDef(ModuleDef),
     MacroDef(MacroDef),
-    GenericParam(TypeParam),
+    GenericParam(GenericParam),
     ImplSelfType(Impl),
     AdtSelfType(Adt),
     Local(Local),
+    Label(Label),
     Unknown,
 }
 
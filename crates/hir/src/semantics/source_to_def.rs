@@ -1,14 +1,15 @@
 //! Maps *syntax* of various definitions to their semantic ids.
 
-use base_db::FileId;
+use base_db::{CrateId, FileId};
 use hir_def::{
+    attr::Attrs,
     child_by_source::ChildBySource,
     dyn_map::DynMap,
-    expr::PatId,
+    expr::{LabelId, PatId},
     keys::{self, Key},
-    ConstId, DefWithBodyId, EnumId, EnumVariantId, FieldId, FunctionId, GenericDefId, ImplId,
-    LifetimeParamId, ModuleId, StaticId, StructId, TraitId, TypeAliasId, TypeParamId, UnionId,
-    VariantId,
+    ConstId, ConstParamId, DefWithBodyId, EnumId, EnumVariantId, FieldId, FunctionId, GenericDefId,
+    ImplId, LifetimeParamId, ModuleId, StaticId, StructId, TraitId, TypeAliasId, TypeParamId,
+    UnionId, VariantId,
 };
 use hir_expand::{name::AsName, AstId, MacroDefKind};
 use rustc_hash::FxHashMap;
@@ -25,12 +26,24 @@ pub(super) type SourceToDefCache = FxHashMap<ChildContainer, DynMap>;
 pub(super) struct SourceToDefCtx<'a, 'b> {
     pub(super) db: &'b dyn HirDatabase,
     pub(super) cache: &'a mut SourceToDefCache,
+    /// When a file is part of several instances of a crate, analyzed with
+    /// different cfgs, the instance in which the looked up node is active.
+    pub(super) krate: Option<CrateId>,
 }
 
 impl SourceToDefCtx<'_, '_> {
     pub(super) fn file_to_def(&mut self, file: FileId) -> Option<ModuleId> {
         let _p = profile::span("SourceBinder::to_module_def");
-        let (krate, local_id) = self.db.relevant_crates(file).iter().find_map(|&crate_id| {
+        let crate_graph = self.db.crate_graph();
+        let mut crates: Vec<CrateId> = self.db.relevant_crates(file).iter().copied().collect();
+        // Prefer the primary instances of crates over the alternative ones.
+        crates.sort_by_key(|&krate| crate_graph[krate].alternative_of.is_some());
+        if let Some(krate) = self.krate {
+            if let Some(idx) = crates.iter().position(|&it| it == krate) {
+                crates.swap(0, idx);
+            }
+        }
+        let (krate, local_id) = crates.into_iter().find_map(|crate_id| {
             let crate_def_map = self.db.crate_def_map(crate_id);
             let local_id = crate_def_map.modules_for_file(file).next()?;
             Some((crate_id, local_id))
@@ -38,8 +51,65 @@ impl SourceToDefCtx<'_, '_> {
         Some(ModuleId { krate, local_id })
     }
 
+    pub(super) fn file_to_defs(&mut self, file: FileId) -> Vec<ModuleId> {
+        let crate_graph = self.db.crate_graph();
+        let mut crates: Vec<CrateId> = self.db.relevant_crates(file).iter().copied().collect();
+        crates.sort_by_key(|&krate| crate_graph[krate].alternative_of.is_some());
+        crates
+            .into_iter()
+            .filter_map(|krate| {
+                let local_id = self.db.crate_def_map(krate).modules_for_file(file).next()?;
+                Some(ModuleId { krate, local_id })
+            })
+            .collect()
+    }
+
+    /// If the file of `src` is part of alternative instances of a crate, picks
+    /// the first instance in which all of the `#[cfg]`s around `src` are
+    /// enabled.
+    fn pick_crate(&mut self, src: InFile<&SyntaxNode>) {
+        if self.krate.is_some() {
+            return;
+        }
+        let file = src.file_id.original_file(self.db.upcast());
+        let crate_graph = self.db.crate_graph();
+        let relevant_crates = self.db.relevant_crates(file);
+        if relevant_crates.iter().all(|&krate| crate_graph[krate].alternative_of.is_none()) {
+            return;
+        }
+        let mut crates: Vec<CrateId> = relevant_crates
+            .iter()
+            .copied()
+            .filter(|&krate| self.db.crate_def_map(krate).modules_for_file(file).next().is_some())
+            .collect();
+        crates.sort_by_key(|&krate| crate_graph[krate].alternative_of.is_some());
+        let db = self.db;
+        self.krate = crates.into_iter().find(|&krate| {
+            let cfg_options = &crate_graph[krate].cfg_options;
+            src.cloned().ancestors_with_macros(db.upcast()).all(|node| {
+                let is_enabled = |owner: &dyn ast::AttrsOwner| {
+                    Attrs::of_node(db.upcast(), node.with_value(owner), krate)
+                        .is_cfg_enabled(cfg_options)
+                };
+                match_ast! {
+                    match (node.value) {
+                        ast::Item(it) => is_enabled(&it),
+                        ast::Variant(it) => is_enabled(&it),
+                        ast::RecordField(it) => is_enabled(&it),
+                        ast::TupleField(it) => is_enabled(&it),
+                        ast::MatchArm(it) => is_enabled(&it),
+                        ast::ExprStmt(it) => is_enabled(&it),
+                        ast::LetStmt(it) => is_enabled(&it),
+                        _ => true,
+                    }
+                }
+            })
+        });
+    }
+
     pub(super) fn module_to_def(&mut self, src: InFile<ast::Module>) -> Option<ModuleId> {
         let _p = profile::span("module_to_def");
+        self.pick_crate(src.as_ref().map(|it| it.syntax()));
         let parent_declaration = src
             .as_ref()
             .map(|it| it.syntax())
@@ -114,6 +184,15 @@ impl SourceToDefCtx<'_, '_> {
         let pat_id = source_map.node_pat(src.as_ref())?;
         Some((container, pat_id))
     }
+    pub(super) fn label_to_def(
+        &mut self,
+        src: InFile<ast::Label>,
+    ) -> Option<(DefWithBodyId, LabelId)> {
+        let container = self.find_pat_container(src.as_ref().map(|it| it.syntax()))?;
+        let (_body, source_map) = self.db.body_with_source_map(container);
+        let label_id = source_map.node_label(src.as_ref())?;
+        Some((container, label_id))
+    }
 
     fn to_def<Ast: AstNode + 'static, ID: Copy + 'static>(
         &mut self,
@@ -148,11 +227,24 @@ impl SourceToDefCtx<'_, '_> {
         dyn_map[keys::LIFETIME_PARAM].get(&src).copied()
     }
 
+    pub(super) fn const_param_to_def(
+        &mut self,
+        src: InFile<ast::ConstParam>,
+    ) -> Option<ConstParamId> {
+        let container: ChildContainer =
+            self.find_generic_param_container(src.as_ref().map(|it| it.syntax()))?.into();
+        let db = self.db;
+        let dyn_map =
+            &*self.cache.entry(container).or_insert_with(|| container.child_by_source(db));
+        dyn_map[keys::CONST_PARAM].get(&src).copied()
+    }
+
     // FIXME: use DynMap as well?
     pub(super) fn macro_rules_to_def(
         &mut self,
         src: InFile<ast::MacroRules>,
     ) -> Option<MacroDefId> {
+        self.pick_crate(src.as_ref().map(|it| it.syntax()));
         let kind = MacroDefKind::Declarative;
         let file_id = src.file_id.original_file(self.db.upcast());
         let krate = self.file_to_def(file_id)?.krate;
@@ -162,6 +254,7 @@ impl SourceToDefCtx<'_, '_> {
     }
 
     pub(super) fn find_container(&mut self, src: InFile<&SyntaxNode>) -> Option<ChildContainer> {
+        self.pick_crate(src);
         for container in src.cloned().ancestors_with_macros(self.db.upcast()).skip(1) {
             let res: ChildContainer = match_ast! {
                 match (container.value) {
@@ -220,6 +313,7 @@ impl SourceToDefCtx<'_, '_> {
     }
 
     fn find_generic_param_container(&mut self, src: InFile<&SyntaxNode>) -> Option<GenericDefId> {
+        self.pick_crate(src);
         for container in src.cloned().ancestors_with_macros(self.db.upcast()).skip(1) {
             let res: GenericDefId = match_ast! {
                 match (container.value) {
@@ -238,6 +332,7 @@ impl SourceToDefCtx<'_, '_> {
     }
 
     fn find_pat_container(&mut self, src: InFile<&SyntaxNode>) -> Option<DefWithBodyId> {
+        self.pick_crate(src);
         for container in src.cloned().ancestors_with_macros(self.db.upcast()).skip(1) {
             let res: DefWithBodyId = match_ast! {
                 match (container.value) {
@@ -10,7 +10,7 @@ use std::{iter::once, sync::Arc};
 use hir_def::{
     body::{
         scope::{ExprScopes, ScopeId},
-        Body, BodySourceMap,
+        Body, BodySourceMap, ExprSource,
     },
     expr::{ExprId, Pat, PatId},
     path::{ModPath, Path, PathKind},
@@ -388,13 +388,7 @@ fn scope_for_offset(
         .iter()
         .filter_map(|(id, scope)| {
             let source = source_map.expr_syntax(*id).ok()?;
-            // FIXME: correctly handle macro expansion
-            if source.file_id != offset.file_id {
-                return None;
-            }
-            let root = source.file_syntax(db.upcast());
-            let node = source.value.to_node(&root);
-            Some((node.syntax().text_range(), scope))
+            Some((original_expr_range(db, source, offset.file_id)?, scope))
         })
         // find containing scope
         .min_by_key(|(expr_range, _scope)| {
@@ -408,6 +402,29 @@ fn scope_for_offset(
         })
 }
 
+/// The range of the expression in `file_id`. A macro call expression is
+/// lowered from the root of its expansion, so that maps back to the call.
+fn original_expr_range(
+    db: &dyn HirDatabase,
+    source: ExprSource,
+    file_id: HirFileId,
+) -> Option<TextRange> {
+    let root = source.file_syntax(db.upcast());
+    let node = source.value.to_node(&root);
+    if source.file_id == file_id {
+        return Some(node.syntax().text_range());
+    }
+    // FIXME: correctly handle expressions nested in macro expansions
+    if node.syntax().text_range() != root.text_range() {
+        return None;
+    }
+    let call = source.file_id.call_node(db.upcast())?;
+    if call.file_id != file_id {
+        return None;
+    }
+    Some(call.value.text_range())
+}
+
 // XXX: during completion, cursor might be outside of any particular
 // expression. Try to figure out the correct scope...
 fn adjust(
@@ -422,13 +439,7 @@ fn adjust(
         .iter()
         .filter_map(|(id, scope)| {
             let source = source_map.expr_syntax(*id).ok()?;
-            // FIXME: correctly handle macro expansion
-            if source.file_id != offset.file_id {
-                return None;
-            }
-            let root = source.file_syntax(db.upcast());
-            let node = source.value.to_node(&root);
-            Some((node.syntax().text_range(), scope))
+            Some((original_expr_range(db, source, offset.file_id)?, scope))
         })
         .filter(|&(range, _)| {
             range.start() <= offset.value && expr_range.contains_range(range) && range != expr_range
@@ -240,6 +240,12 @@ impl Attrs {
         raw_attrs.filter(db, def.krate(db))
     }
 
+    /// The attributes of an arbitrary syntax node, with the `cfg_attr`s
+    /// processed as in `krate`.
+    pub fn of_node(db: &dyn DefDatabase, owner: InFile<&dyn AttrsOwner>, krate: CrateId) -> Attrs {
+        RawAttrs::from_attrs_owner(db, owner).filter(db, krate)
+    }
+
     pub fn by_key(&self, key: &'static str) -> AttrQuery<'_> {
         AttrQuery { attrs: self, key }
     }
@@ -252,7 +258,7 @@ impl Attrs {
             _ => Some(CfgExpr::All(cfgs)),
         }
     }
-    pub(crate) fn is_cfg_enabled(&self, cfg_options: &CfgOptions) -> bool {
+    pub fn is_cfg_enabled(&self, cfg_options: &CfgOptions) -> bool {
         match self.cfg() {
             None => true,
             Some(cfg) => cfg_options.check(&cfg) != Some(false),
@@ -244,6 +244,7 @@ pub struct Query {
     lowercased: String,
     anchor_end: bool,
     case_sensitive: bool,
+    assoc_items_only: bool,
     limit: usize,
     exclude_import_kinds: FxHashSet<ImportKind>,
 }
@@ -255,6 +256,7 @@ impl Query {
             query: query.to_string(),
             anchor_end: false,
             case_sensitive: false,
+            assoc_items_only: false,
             limit: usize::max_value(),
             exclude_import_kinds: FxHashSet::default(),
         }
@@ -276,6 +278,12 @@ impl Query {
         Self { case_sensitive: true, ..self }
     }
 
+    /// Only search the associated items of exported traits, matching their names against the
+    /// query instead of requiring them to be equal.
+    pub fn assoc_items_only(self) -> Self {
+        Self { assoc_items_only: true, ..self }
+    }
+
     /// Do not include imports of the specified kind in the search results.
     pub fn exclude_import_kind(mut self, import_kind: ImportKind) -> Self {
         self.exclude_import_kinds.insert(import_kind);
@@ -297,6 +305,10 @@ pub fn search_dependencies<'a>(
     let import_maps: Vec<_> =
         graph[krate].dependencies.iter().map(|dep| db.import_map(dep.crate_id)).collect();
 
+    if query.assoc_items_only {
+        return search_assoc_items(&import_maps, &query);
+    }
+
     let automaton = fst::automaton::Subsequence::new(&query.lowercased);
 
     let mut op = fst::map::OpBuilder::new();
@@ -356,19 +368,50 @@ pub fn search_dependencies<'a>(
     // Add all exported associated items whose names match the query (exactly).
     for map in &import_maps {
         if let Some(v) = map.assoc_map.get(&*query.query) {
-            res.extend(v.iter().map(|&assoc| {
-                ItemInNs::Types(match assoc {
-                    AssocItemId::FunctionId(it) => it.into(),
-                    AssocItemId::ConstId(it) => it.into(),
-                    AssocItemId::TypeAliasId(it) => it.into(),
-                })
-            }));
+            res.extend(v.iter().map(|&assoc| assoc_item_to_item_in_ns(assoc)));
         }
     }
 
     res
 }
 
+fn search_assoc_items(import_maps: &[Arc<ImportMap>], query: &Query) -> Vec<ItemInNs> {
+    let mut names = import_maps
+        .iter()
+        .flat_map(|map| map.assoc_map.iter())
+        .filter(|(name, _)| {
+            let name = if query.case_sensitive { name.to_string() } else { name.to_lowercase() };
+            let text = if query.case_sensitive { &query.query } else { &query.lowercased };
+            if query.anchor_end {
+                &name == text
+            } else {
+                is_subsequence(text, &name)
+            }
+        })
+        .collect::<Vec<_>>();
+    names.sort_by_key(|&(name, _)| name);
+
+    names
+        .into_iter()
+        .flat_map(|(_, items)| items.iter())
+        .map(|&assoc| assoc_item_to_item_in_ns(assoc))
+        .take(query.limit)
+        .collect()
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|it| it == c))
+}
+
+fn assoc_item_to_item_in_ns(assoc: AssocItemId) -> ItemInNs {
+    ItemInNs::Types(match assoc {
+        AssocItemId::FunctionId(it) => it.into(),
+        AssocItemId::ConstId(it) => it.into(),
+        AssocItemId::TypeAliasId(it) => it.into(),
+    })
+}
+
 fn item_import_kind(item: ItemInNs) -> Option<ImportKind> {
     Some(match item.as_module_def_id()? {
         ModuleDefId::ModuleId(_) => ImportKind::Module,
@@ -830,4 +873,45 @@ mod tests {
             expect![[r#""#]],
         );
     }
+
+    #[test]
+    fn search_assoc_items() {
+        let ra_fixture = r#"
+            //- /main.rs crate:main deps:dep
+            //- /dep.rs crate:dep
+            pub mod io {
+                pub trait Write {
+                    fn write(&mut self);
+                    fn flush(&mut self);
+                }
+            }
+            pub struct Writer;
+            trait Private {
+                fn write_all(&self);
+            }
+        "#;
+
+        check_search(
+            ra_fixture,
+            "main",
+            Query::new("wr").assoc_items_only(),
+            expect![[r#"
+                dep::io::Write (t)
+            "#]],
+        );
+        check_search(
+            ra_fixture,
+            "main",
+            Query::new("FLUSH").assoc_items_only(),
+            expect![[r#"
+                dep::io::Write (t)
+            "#]],
+        );
+        check_search(
+            ra_fixture,
+            "main",
+            Query::new("flu").assoc_items_only().anchor_end(),
+            expect![[r#""#]],
+        );
+    }
 }
@@ -5,13 +5,13 @@
 
 use std::iter;
 
-use base_db::{CrateId, FileId, ProcMacroId};
+use base_db::{CrateId, Edition, FileId, ProcMacroId, ProcMacroKind};
 use cfg::{CfgExpr, CfgOptions};
 use hir_expand::{
     ast_id_map::FileAstId,
     builtin_derive::find_builtin_derive,
     builtin_macro::find_builtin_macro,
-    name::{AsName, Name},
+    name::{name, AsName, Name},
     proc_macro::ProcMacroExpander,
     HirFileId, MacroCallId, MacroDefId, MacroDefKind,
 };
@@ -45,6 +45,17 @@ const GLOB_RECURSION_LIMIT: usize = 100;
 const EXPANSION_DEPTH_LIMIT: usize = 128;
 const FIXED_POINT_LIMIT: usize = 8192;
 
+/// Finds the edition-specific prelude next to `prelude`, like the
+/// `std::prelude::rust_2021` sibling of `std::prelude::v1`. Falls back to
+/// `prelude` itself if there is no such module.
+fn edition_prelude(db: &dyn DefDatabase, prelude: ModuleId, edition_module: &Name) -> ModuleId {
+    let def_map = db.crate_def_map(prelude.krate);
+    def_map[prelude.local_id]
+        .parent
+        .and_then(|parent| def_map[parent].children.get(edition_module))
+        .map_or(prelude, |&local_id| ModuleId { krate: prelude.krate, local_id })
+}
+
 pub(super) fn collect_defs(db: &dyn DefDatabase, mut def_map: CrateDefMap) -> CrateDefMap {
     let crate_graph = db.crate_graph();
 
@@ -65,6 +76,11 @@ pub(super) fn collect_defs(db: &dyn DefDatabase, mut def_map: CrateDefMap) -> Cr
             def_map.prelude = dep_def_map.prelude;
         }
     }
+    if crate_graph[def_map.krate].edition >= Edition::Edition2021 {
+        if let Some(prelude) = def_map.prelude {
+            def_map.prelude = Some(edition_prelude(db, prelude, &name![rust_2021]));
+        }
+    }
 
     let cfg_options = &crate_graph[def_map.krate].cfg_options;
     let proc_macros = &crate_graph[def_map.krate].proc_macro;
@@ -74,7 +90,8 @@ pub(super) fn collect_defs(db: &dyn DefDatabase, mut def_map: CrateDefMap) -> Cr
         .map(|(idx, it)| {
             // FIXME: a hacky way to create a Name from string.
             let name = tt::Ident { text: it.name.clone(), id: tt::TokenId::unspecified() };
-            (name.as_name(), ProcMacroExpander::new(def_map.krate, ProcMacroId(idx as u32)))
+            let expander = ProcMacroExpander::new(def_map.krate, ProcMacroId(idx as u32), it.kind);
+            (name.as_name(), expander)
         })
         .collect();
 
@@ -320,7 +337,7 @@ impl DefCollector<'_> {
     /// use a dummy expander that always errors. This comes with the drawback of macros potentially
     /// going out of sync with what the build system sees (since we resolve using VFS state, but
     /// Cargo builds only on-disk files). We could and probably should add diagnostics for that.
-    fn resolve_proc_macro(&mut self, name: &Name) {
+    fn resolve_proc_macro(&mut self, name: &Name, kind: ProcMacroKind) {
         self.exports_proc_macros = true;
         let macro_def = match self.proc_macros.iter().find(|(n, _)| n == name) {
             Some((_, expander)) => MacroDefId {
@@ -332,7 +349,7 @@ impl DefCollector<'_> {
             None => MacroDefId {
                 ast_id: None,
                 krate: self.def_map.krate,
-                kind: MacroDefKind::ProcMacro(ProcMacroExpander::dummy(self.def_map.krate)),
+                kind: MacroDefKind::ProcMacro(ProcMacroExpander::dummy(self.def_map.krate, kind)),
                 local_inner: false,
             },
         };
@@ -1310,16 +1327,15 @@ impl ModCollector<'_, '_> {
     /// If `attrs` registers a procedural macro, collects its definition.
     fn collect_proc_macro_def(&mut self, func_name: &Name, attrs: &Attrs) {
         // FIXME: this should only be done in the root module of `proc-macro` crates, not everywhere
-        // FIXME: distinguish the type of macro
-        let macro_name = if attrs.by_key("proc_macro").exists()
-            || attrs.by_key("proc_macro_attribute").exists()
-        {
-            func_name.clone()
+        let (macro_name, kind) = if attrs.by_key("proc_macro").exists() {
+            (func_name.clone(), ProcMacroKind::FuncLike)
+        } else if attrs.by_key("proc_macro_attribute").exists() {
+            (func_name.clone(), ProcMacroKind::Attr)
         } else {
             let derive = attrs.by_key("proc_macro_derive");
             if let Some(arg) = derive.tt_values().next() {
                 if let [TokenTree::Leaf(Leaf::Ident(trait_name)), ..] = &*arg.token_trees {
-                    trait_name.as_name()
+                    (trait_name.as_name(), ProcMacroKind::CustomDerive)
                 } else {
                     log::trace!("malformed `#[proc_macro_derive]`: {}", arg);
                     return;
@@ -1329,7 +1345,7 @@ impl ModCollector<'_, '_> {
             }
         };
 
-        self.def_collector.resolve_proc_macro(&macro_name);
+        self.def_collector.resolve_proc_macro(&macro_name, kind);
     }
 
     fn collect_macro_rules(&mut self, id: FileItemTreeId<MacroRules>) {
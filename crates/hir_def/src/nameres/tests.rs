@@ -474,6 +474,58 @@ mod prelude {
     );
 }
 
+#[test]
+fn edition_2021_prelude() {
+    check(
+        r#"
+//- /main.rs crate:main edition:2021 deps:std
+use {Foo, TryFrom};
+
+//- /std.rs crate:std
+#[prelude_import]
+pub use self::prelude::v1::*;
+pub mod prelude {
+    pub mod v1 {
+        pub struct Foo;
+    }
+    pub mod rust_2021 {
+        pub use super::v1::*;
+        pub trait TryFrom {}
+    }
+}
+"#,
+        expect![[r#"
+            crate
+            Foo: t v
+            TryFrom: t
+        "#]],
+    );
+    check(
+        r#"
+//- /main.rs crate:main edition:2018 deps:std
+use {Foo, TryFrom};
+
+//- /std.rs crate:std
+#[prelude_import]
+pub use self::prelude::v1::*;
+pub mod prelude {
+    pub mod v1 {
+        pub struct Foo;
+    }
+    pub mod rust_2021 {
+        pub use super::v1::*;
+        pub trait TryFrom {}
+    }
+}
+"#,
+        expect![[r#"
+            crate
+            Foo: t v
+            TryFrom: _
+        "#]],
+    );
+}
+
 #[test]
 fn cfg_not_test() {
     check(
@@ -13,16 +13,17 @@ use crate::{
     body::Body,
     builtin_type::BuiltinType,
     db::DefDatabase,
-    expr::{ExprId, PatId},
+    expr::{ExprId, LabelId, PatId},
     generics::GenericParams,
     item_scope::{BuiltinShadowMode, BUILTIN_SCOPE},
     nameres::CrateDefMap,
     path::{ModPath, PathKind},
     per_ns::PerNs,
     visibility::{RawVisibility, Visibility},
-    AdtId, AssocContainerId, ConstId, ContainerId, DefWithBodyId, EnumId, EnumVariantId,
-    FunctionId, GenericDefId, HasModule, ImplId, LocalModuleId, Lookup, ModuleDefId, ModuleId,
-    StaticId, StructId, TraitId, TypeAliasId, TypeParamId, VariantId,
+    AdtId, AssocContainerId, ConstId, ConstParamId, ContainerId, DefWithBodyId, EnumId,
+    EnumVariantId, FunctionId, GenericDefId, GenericParamId, HasModule, ImplId, LifetimeParamId,
+    LocalModuleId, Lookup, ModuleDefId, ModuleId, StaticId, StructId, TraitId, TypeAliasId,
+    TypeParamId, VariantId,
 };
 
 #[derive(Debug, Clone, Default)]
@@ -399,8 +400,13 @@ impl Resolver {
     }
 
     pub fn process_all_names(&self, db: &dyn DefDatabase, f: &mut dyn FnMut(Name, ScopeDef)) {
+        let mut labels_visible = true;
         for scope in self.scopes.iter().rev() {
-            scope.process_names(db, f);
+            scope.process_names(db, labels_visible, f);
+            if let Scope::ExprScope(scope) = scope {
+                // Labels outside of a closure can't be used inside of it.
+                labels_visible &= !scope.expr_scopes.is_closure(scope.scope_id);
+            }
         }
     }
 
@@ -477,12 +483,18 @@ pub enum ScopeDef {
     PerNs(PerNs),
     ImplSelfType(ImplId),
     AdtSelfType(AdtId),
-    GenericParam(TypeParamId),
+    GenericParam(GenericParamId),
     Local(PatId),
+    Label(LabelId),
 }
 
 impl Scope {
-    fn process_names(&self, db: &dyn DefDatabase, f: &mut dyn FnMut(Name, ScopeDef)) {
+    fn process_names(
+        &self,
+        db: &dyn DefDatabase,
+        labels_visible: bool,
+        f: &mut dyn FnMut(Name, ScopeDef),
+    ) {
         match self {
             Scope::ModuleScope(m) => {
                 // FIXME: should we provide `self` here?
@@ -514,15 +526,31 @@ impl Scope {
             Scope::LocalItemsScope(body) => body.item_scope.entries().for_each(|(name, def)| {
                 f(name.clone(), ScopeDef::PerNs(def));
             }),
-            Scope::GenericParams { params, def } => {
+            Scope::GenericParams { params, def: parent } => {
                 for (local_id, param) in params.types.iter() {
                     if let Some(name) = &param.name {
                         f(
                             name.clone(),
-                            ScopeDef::GenericParam(TypeParamId { local_id, parent: *def }),
+                            ScopeDef::GenericParam(
+                                TypeParamId { local_id, parent: *parent }.into(),
+                            ),
                         )
                     }
                 }
+                for (local_id, param) in params.consts.iter() {
+                    f(
+                        param.name.clone(),
+                        ScopeDef::GenericParam(ConstParamId { local_id, parent: *parent }.into()),
+                    )
+                }
+                for (local_id, param) in params.lifetimes.iter() {
+                    f(
+                        param.name.clone(),
+                        ScopeDef::GenericParam(
+                            LifetimeParamId { local_id, parent: *parent }.into(),
+                        ),
+                    )
+                }
             }
             Scope::ImplDefScope(i) => {
                 f(name![Self], ScopeDef::ImplSelfType(*i));
@@ -531,6 +559,11 @@ impl Scope {
                 f(name![Self], ScopeDef::AdtSelfType(*i));
             }
             Scope::ExprScope(scope) => {
+                if labels_visible {
+                    if let Some((label, name)) = scope.expr_scopes.label(scope.scope_id) {
+                        f(name, ScopeDef::Label(label))
+                    }
+                }
                 scope.expr_scopes.entries(scope.scope_id).iter().for_each(|e| {
                     f(e.name().clone(), ScopeDef::Local(e.pat()));
                 });
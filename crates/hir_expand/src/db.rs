@@ -2,7 +2,7 @@
 
 use std::sync::Arc;
 
-use base_db::{salsa, SourceDatabase};
+use base_db::{salsa, Edition, SourceDatabase};
 use mbe::{ExpandError, ExpandResult, MacroRules};
 use parser::FragmentKind;
 use syntax::{algo::diff, AstNode, GreenNode, Parse, SyntaxKind::*, SyntaxNode};
@@ -138,7 +138,8 @@ fn macro_def(db: &dyn AstDatabase, id: MacroDefId) -> Option<Arc<(TokenExpander,
                 log::warn!("fail on macro_def to token tree: {:#?}", arg);
                 None
             })?;
-            let rules = match MacroRules::parse(&tt) {
+            let is_2021 = db.crate_graph()[id.krate].edition >= Edition::Edition2021;
+            let rules = match MacroRules::parse(&tt, is_2021) {
                 Ok(it) => it,
                 Err(err) => {
                     log::warn!("fail on macro_def parse: error: {:#?} {:#?}", err, tt);
@@ -164,6 +164,7 @@ pub mod known {
         future,
         result,
         boxed,
+        rust_2021,
         // Components of known path (type name)
         Iterator,
         IntoIterator,
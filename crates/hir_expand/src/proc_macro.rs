@@ -1,13 +1,14 @@
 //! Proc Macro Expander stub
 
 use crate::{db::AstDatabase, LazyMacroId};
-use base_db::{CrateId, ProcMacroId};
+use base_db::{CrateId, ProcMacroId, ProcMacroKind};
 use tt::buffer::{Cursor, TokenBuffer};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct ProcMacroExpander {
     krate: CrateId,
     proc_macro_id: Option<ProcMacroId>,
+    pub kind: ProcMacroKind,
 }
 
 macro_rules! err {
@@ -20,13 +21,13 @@ macro_rules! err {
 }
 
 impl ProcMacroExpander {
-    pub fn new(krate: CrateId, proc_macro_id: ProcMacroId) -> Self {
-        Self { krate, proc_macro_id: Some(proc_macro_id) }
+    pub fn new(krate: CrateId, proc_macro_id: ProcMacroId, kind: ProcMacroKind) -> Self {
+        Self { krate, proc_macro_id: Some(proc_macro_id), kind }
     }
 
-    pub fn dummy(krate: CrateId) -> Self {
+    pub fn dummy(krate: CrateId, kind: ProcMacroKind) -> Self {
         // FIXME: Should store the name for better errors
-        Self { krate, proc_macro_id: None }
+        Self { krate, proc_macro_id: None, kind }
     }
 
     pub fn expand(
@@ -44,6 +44,9 @@ use crate::{
     db::HirDatabase, infer::diagnostics::InferenceDiagnostic, lower::ImplTraitLoweringMode,
 };
 
+pub(crate) use closure::closure_captures_query;
+pub use closure::{CaptureKind, CapturedItem, ClosureCaptures};
+pub use unify::could_unify;
 pub(crate) use unify::unify;
 
 macro_rules! ty_app {
@@ -60,6 +63,7 @@ mod path;
 mod expr;
 mod pat;
 mod coerce;
+mod closure;
 
 /// The entry point of type inference.
 pub(crate) fn infer_query(db: &dyn HirDatabase, def: DefWithBodyId) -> Arc<InferenceResult> {
@@ -0,0 +1,322 @@
+//! Computes the places captured by closures, and how they are captured.
+//!
+//! Since Rust 2021, closures capture disjoint fields: a closure using `a.b`
+//! only captures the field `b` of `a`, not all of `a`. Before that, closures
+//! always capture whole locals.
+
+use std::sync::Arc;
+
+use base_db::{CrateId, Edition};
+use hir_def::{
+    body::Body,
+    expr::{BinaryOp, Expr, ExprId, PatId, Statement, UnaryOp},
+    lang_item::LangItemTarget,
+    resolver::{resolver_for_expr, HasResolver, ValueNs},
+    type_ref::{Mutability, TypeRef},
+    DefWithBodyId, FieldId, FunctionId, VariantId,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::{InferenceResult, Obligation};
+use crate::{
+    db::HirDatabase, Canonical, InEnvironment, Substs, TraitEnvironment, TraitRef, Ty, TypeCtor,
+};
+
+/// How a closure captures a place.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CaptureKind {
+    ByRef,
+    ByMutRef,
+    ByValue,
+}
+
+/// A place captured by a closure: a local, or a field path on a local like
+/// `a.b.c`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CapturedItem {
+    pub local: PatId,
+    pub fields: Vec<FieldId>,
+    pub kind: CaptureKind,
+}
+
+/// The captures of all closures in a body.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct ClosureCaptures {
+    captures: FxHashMap<ExprId, Vec<CapturedItem>>,
+}
+
+impl ClosureCaptures {
+    pub fn captures(&self, closure: ExprId) -> &[CapturedItem] {
+        self.captures.get(&closure).map_or(&[], |it| it.as_slice())
+    }
+}
+
+pub(crate) fn closure_captures_query(
+    db: &dyn HirDatabase,
+    def: DefWithBodyId,
+) -> Arc<ClosureCaptures> {
+    let _p = profile::span("closure_captures_query");
+    let body = db.body(def);
+    let closures: Vec<_> = body
+        .exprs
+        .iter()
+        .filter_map(|(expr, data)| match data {
+            Expr::Lambda { body, is_move, .. } => Some((expr, *body, *is_move)),
+            _ => None,
+        })
+        .collect();
+    if closures.is_empty() {
+        return Arc::new(ClosureCaptures::default());
+    }
+
+    let resolver = def.resolver(db.upcast());
+    let ctx = CaptureContext {
+        db,
+        owner: def,
+        trait_env: TraitEnvironment::lower(db, &resolver),
+        krate: resolver.krate(),
+        infer: db.infer(def),
+        body,
+    };
+    let mut res = ClosureCaptures::default();
+    for (closure, closure_body, is_move) in closures {
+        let mut collector = CaptureCollector {
+            ctx: &ctx,
+            inner_bindings: FxHashSet::default(),
+            captures: Vec::new(),
+        };
+        collector.collect_bindings(closure);
+        collector.walk_expr(closure_body, CaptureKind::ByValue);
+        let mut captures = collector.captures;
+        if is_move {
+            captures.iter_mut().for_each(|it| it.kind = CaptureKind::ByValue);
+        }
+        res.captures.insert(closure, captures);
+    }
+    Arc::new(res)
+}
+
+struct CaptureContext<'a> {
+    db: &'a dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: Arc<Body>,
+    infer: Arc<InferenceResult>,
+    trait_env: Arc<TraitEnvironment>,
+    krate: Option<CrateId>,
+}
+
+impl CaptureContext<'_> {
+    fn captures_disjoint_fields(&self) -> bool {
+        match self.krate {
+            Some(krate) => self.db.crate_graph()[krate].edition >= Edition::Edition2021,
+            None => false,
+        }
+    }
+
+    fn is_copy(&self, expr: ExprId) -> bool {
+        let ty = self.infer[expr].clone();
+        match &ty {
+            Ty::Unknown => return true,
+            Ty::Apply(a_ty) => match a_ty.ctor {
+                TypeCtor::Bool
+                | TypeCtor::Char
+                | TypeCtor::Int(_)
+                | TypeCtor::Float(_)
+                | TypeCtor::Never
+                | TypeCtor::RawPtr(_)
+                | TypeCtor::Ref(Mutability::Shared)
+                | TypeCtor::FnDef(_)
+                | TypeCtor::FnPtr { .. } => return true,
+                _ => {}
+            },
+            _ => {}
+        }
+        let krate = match self.krate {
+            Some(krate) => krate,
+            None => return false,
+        };
+        let copy_trait = match self.db.lang_item(krate, "copy".into()) {
+            Some(LangItemTarget::TraitId(it)) => it,
+            _ => return false,
+        };
+        // The types in the inference result are fully resolved, so there is
+        // nothing to canonicalize.
+        let trait_ref = TraitRef { trait_: copy_trait, substs: Substs::single(ty) };
+        let goal = Canonical {
+            value: InEnvironment::new(self.trait_env.clone(), Obligation::Trait(trait_ref)),
+            kinds: Arc::new([]),
+        };
+        self.db.trait_solve(krate, goal).is_some()
+    }
+}
+
+/// The capture kind needed for the receiver of a call of `method`.
+fn receiver_capture_kind(ctx: &CaptureContext, method: FunctionId) -> CaptureKind {
+    let data = ctx.db.function_data(method);
+    match data.params.first() {
+        Some(TypeRef::Reference(_, _, Mutability::Shared)) => CaptureKind::ByRef,
+        Some(TypeRef::Reference(_, _, Mutability::Mut)) => CaptureKind::ByMutRef,
+        _ => CaptureKind::ByValue,
+    }
+}
+
+struct CaptureCollector<'a, 'b> {
+    ctx: &'a CaptureContext<'b>,
+    /// The patterns of the closure itself, which are not captured.
+    inner_bindings: FxHashSet<PatId>,
+    captures: Vec<CapturedItem>,
+}
+
+impl CaptureCollector<'_, '_> {
+    fn collect_bindings(&mut self, expr: ExprId) {
+        let body = self.ctx.body.clone();
+        let mut pats = Vec::new();
+        match &body[expr] {
+            Expr::Lambda { args, .. } => pats.extend(args.iter().copied()),
+            Expr::For { pat, .. } => pats.push(*pat),
+            Expr::Match { arms, .. } => pats.extend(arms.iter().map(|arm| arm.pat)),
+            Expr::Block { statements, .. } => {
+                pats.extend(statements.iter().filter_map(|stmt| match stmt {
+                    Statement::Let { pat, .. } => Some(*pat),
+                    Statement::Expr(_) => None,
+                }))
+            }
+            _ => {}
+        }
+        while let Some(pat) = pats.pop() {
+            self.inner_bindings.insert(pat);
+            body[pat].walk_child_pats(|it| pats.push(it));
+        }
+        body[expr].walk_child_exprs(|it| self.collect_bindings(it));
+    }
+
+    /// Records the captures needed to use `expr` as `kind`.
+    fn walk_expr(&mut self, expr: ExprId, kind: CaptureKind) {
+        if let Some((local, fields)) = self.place_of(expr) {
+            let kind = if kind == CaptureKind::ByValue && self.ctx.is_copy(expr) {
+                CaptureKind::ByRef
+            } else {
+                kind
+            };
+            self.capture(local, fields, kind);
+            return;
+        }
+
+        let body = self.ctx.body.clone();
+        match &body[expr] {
+            Expr::Ref { expr, mutability, .. } => {
+                let kind = match mutability {
+                    Mutability::Shared => CaptureKind::ByRef,
+                    Mutability::Mut => CaptureKind::ByMutRef,
+                };
+                self.walk_expr(*expr, kind)
+            }
+            Expr::UnaryOp { expr, op: UnaryOp::Deref } => {
+                let kind = match kind {
+                    CaptureKind::ByMutRef => CaptureKind::ByMutRef,
+                    _ => CaptureKind::ByRef,
+                };
+                self.walk_expr(*expr, kind)
+            }
+            Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::Assignment { .. }) } => {
+                self.walk_expr(*lhs, CaptureKind::ByMutRef);
+                self.walk_expr(*rhs, CaptureKind::ByValue);
+            }
+            // Comparisons take their operands by reference.
+            Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::CmpOp(_)) } => {
+                self.walk_expr(*lhs, CaptureKind::ByRef);
+                self.walk_expr(*rhs, CaptureKind::ByRef);
+            }
+            Expr::MethodCall { receiver, args, .. } => {
+                let receiver_kind = match self.ctx.infer.method_resolution(expr) {
+                    Some(method) => receiver_capture_kind(self.ctx, method),
+                    None => CaptureKind::ByRef,
+                };
+                self.walk_expr(*receiver, receiver_kind);
+                args.iter().for_each(|arg| self.walk_expr(*arg, CaptureKind::ByValue));
+            }
+            Expr::Field { expr, .. } => self.walk_expr(*expr, kind),
+            Expr::Match { expr, arms } => {
+                self.walk_expr(*expr, CaptureKind::ByRef);
+                for arm in arms {
+                    if let Some(guard) = arm.guard {
+                        self.walk_expr(guard, CaptureKind::ByValue);
+                    }
+                    self.walk_expr(arm.expr, kind);
+                }
+            }
+            Expr::Block { statements, tail, .. } => {
+                for stmt in statements {
+                    match stmt {
+                        Statement::Let { initializer: Some(expr), .. } | Statement::Expr(expr) => {
+                            self.walk_expr(*expr, CaptureKind::ByValue)
+                        }
+                        Statement::Let { initializer: None, .. } => {}
+                    }
+                }
+                if let Some(tail) = tail {
+                    self.walk_expr(*tail, kind);
+                }
+            }
+            Expr::If { condition, then_branch, else_branch } => {
+                self.walk_expr(*condition, CaptureKind::ByValue);
+                self.walk_expr(*then_branch, kind);
+                if let Some(else_branch) = else_branch {
+                    self.walk_expr(*else_branch, kind);
+                }
+            }
+            it => it.walk_child_exprs(|child| self.walk_expr(child, CaptureKind::ByValue)),
+        }
+    }
+
+    /// If `expr` is a place outside of the closure, returns its local and the
+    /// fields accessed on it.
+    fn place_of(&mut self, expr: ExprId) -> Option<(PatId, Vec<FieldId>)> {
+        let body = self.ctx.body.clone();
+        match &body[expr] {
+            Expr::Path(path) => {
+                let resolver = resolver_for_expr(self.ctx.db.upcast(), self.ctx.owner, expr);
+                match resolver.resolve_path_in_value_ns_fully(self.ctx.db.upcast(), path.mod_path())
+                {
+                    Some(ValueNs::LocalBinding(pat)) if !self.inner_bindings.contains(&pat) => {
+                        Some((pat, Vec::new()))
+                    }
+                    _ => None,
+                }
+            }
+            Expr::Field { expr: receiver, .. } => {
+                let (local, mut fields) = self.place_of(*receiver)?;
+                if let Some(field) = self.ctx.infer.field_resolution(expr) {
+                    fields.push(field);
+                }
+                Some((local, fields))
+            }
+            _ => None,
+        }
+    }
+
+    fn capture(&mut self, local: PatId, mut fields: Vec<FieldId>, mut kind: CaptureKind) {
+        if !self.ctx.captures_disjoint_fields() {
+            fields.clear();
+        }
+        // Fields of unions can't be borrowed separately.
+        if let Some(idx) = fields.iter().position(|it| matches!(it.parent, VariantId::UnionId(_))) {
+            fields.truncate(idx);
+        }
+
+        let covering =
+            self.captures.iter_mut().find(|it| it.local == local && fields.starts_with(&it.fields));
+        if let Some(covering) = covering {
+            covering.kind = covering.kind.max(kind);
+            return;
+        }
+        self.captures.retain(|it| {
+            let is_covered = it.local == local && it.fields.starts_with(&fields);
+            if is_covered {
+                kind = kind.max(it.kind);
+            }
+            !is_covered
+        });
+        self.captures.push(CapturedItem { local, fields, kind });
+    }
+}
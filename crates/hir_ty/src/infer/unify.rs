@@ -168,6 +168,16 @@ impl<T> Canonicalized<T> {
     }
 }
 
+/// Checks whether the two types can be made equal. Unknown types unify with
+/// anything, type parameters only with themselves. The bound variables of `t1`
+/// are replaced by fresh inference variables, so they unify with any type.
+pub fn could_unify(t1: &Canonical<Ty>, t2: &Ty) -> bool {
+    let mut table = InferenceTable::new();
+    let vars = Substs(t1.kinds.iter().map(|_| table.new_type_var()).collect());
+    let t1 = t1.value.clone().subst_bound_vars(&vars);
+    table.unify(&t1, t2)
+}
+
 pub(crate) fn unify(tys: &Canonical<(Ty, Ty)>) -> Option<Substs> {
     let mut table = InferenceTable::new();
     let vars = Substs(
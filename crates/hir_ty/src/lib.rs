@@ -42,7 +42,9 @@ use crate::{
 };
 
 pub use autoderef::autoderef;
-pub use infer::{InferTy, InferenceResult};
+pub use infer::{
+    could_unify, CaptureKind, CapturedItem, ClosureCaptures, InferTy, InferenceResult,
+};
 pub use lower::CallableDefId;
 pub use lower::{
     associated_type_shorthand_candidates, callable_item_sig, ImplTraitLoweringMode, TyDefId,
@@ -0,0 +1,140 @@
+use base_db::{fixture::WithFixture, FileRange, SourceDatabase};
+use hir_def::{child_by_source::ChildBySource, db::DefDatabase, expr::Pat, keys};
+use hir_expand::InFile;
+use itertools::Itertools;
+use syntax::{
+    algo,
+    ast::{self, AstNode},
+};
+
+use crate::{db::HirDatabase, test_db::TestDB, utils::variant_data, CaptureKind};
+
+/// Checks the captures of the closures annotated with `//^^^`, written like
+/// `a.b by ref, c by value`.
+fn check_closure_captures(ra_fixture: &str) {
+    let db = TestDB::with_files(ra_fixture);
+    let mut checked_one = false;
+    for (file_id, annotations) in db.extract_annotations() {
+        for (range, expected) in annotations {
+            let actual = captures_at_range(&db, FileRange { file_id, range });
+            assert_eq!(expected, actual);
+            checked_one = true;
+        }
+    }
+    assert!(checked_one, "no `//^` annotations found");
+}
+
+fn captures_at_range(db: &TestDB, pos: FileRange) -> String {
+    let file = db.parse(pos.file_id).ok().unwrap();
+    let closure =
+        algo::find_node_at_offset::<ast::ClosureExpr>(file.syntax(), pos.range.start()).unwrap();
+    let fn_def = closure.syntax().ancestors().find_map(ast::Fn::cast).unwrap();
+    let module = db.module_for_file(pos.file_id);
+    let func = *module.child_by_source(db)[keys::FUNCTION]
+        .get(&InFile::new(pos.file_id.into(), fn_def))
+        .unwrap();
+
+    let (body, source_map) = db.body_with_source_map(func.into());
+    let closure = ast::Expr::from(closure);
+    let expr = source_map.node_expr(InFile::new(pos.file_id.into(), &closure)).unwrap();
+    db.closure_captures(func.into())
+        .captures(expr)
+        .iter()
+        .map(|capture| {
+            let mut place = match &body[capture.local] {
+                Pat::Bind { name, .. } => name.to_string(),
+                _ => "?".to_string(),
+            };
+            for field in &capture.fields {
+                let data = variant_data(db, field.parent);
+                place.push('.');
+                place.push_str(&data.fields()[field.local_id].name.to_string());
+            }
+            let kind = match capture.kind {
+                CaptureKind::ByRef => "by ref",
+                CaptureKind::ByMutRef => "by mut ref",
+                CaptureKind::ByValue => "by value",
+            };
+            format!("{} {}", place, kind)
+        })
+        .join(", ")
+}
+
+#[test]
+fn captures_locals() {
+    check_closure_captures(
+        r#"
+struct S;
+fn consume(s: S) {}
+fn main() {
+    let a = 1;
+    let mut b = 2;
+    let c = S;
+    let d = S;
+    let f = |x: i32| { b += x + a; consume(c); &d; };
+          //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ b by mut ref, a by ref, c by value, d by ref
+}
+"#,
+    );
+}
+
+#[test]
+fn captures_by_value_in_move_closures() {
+    check_closure_captures(
+        r#"
+fn main() {
+    let a = 1;
+    let f = move || a;
+          //^^^^^^^^^ a by value
+}
+"#,
+    );
+}
+
+#[test]
+fn captures_receivers_of_methods() {
+    check_closure_captures(
+        r#"
+struct Counter { count: u32 }
+impl Counter {
+    fn get(&self) -> u32 { self.count }
+    fn incr(&mut self) {}
+}
+fn main() {
+    let c = Counter { count: 0 };
+    let mut d = Counter { count: 0 };
+    let f = || { c.get(); d.incr(); };
+          //^^^^^^^^^^^^^^^^^^^^^^^^^ c by ref, d by mut ref
+}
+"#,
+    );
+}
+
+#[test]
+fn captures_disjoint_fields_in_2021() {
+    check_closure_captures(
+        r#"
+//- /main.rs crate:main edition:2021
+struct Inner { x: u32, y: u32 }
+struct Point { inner: Inner, z: u32 }
+fn main() {
+    let mut p = Point { inner: Inner { x: 0, y: 0 }, z: 0 };
+    let f = || { p.inner.x += 1; p.z; };
+          //^^^^^^^^^^^^^^^^^^^^^^^^^^^ p.inner.x by mut ref, p.z by ref
+    let g = || { p.inner.x; p.inner; };
+          //^^^^^^^^^^^^^^^^^^^^^^^^^^ p.inner by value
+}
+"#,
+    );
+    check_closure_captures(
+        r#"
+//- /main.rs crate:main edition:2018
+struct Point { x: u32, z: u32 }
+fn main() {
+    let mut p = Point { x: 0, z: 0 };
+    let f = || { p.x += 1; p.z; };
+          //^^^^^^^^^^^^^^^^^^^^^ p by mut ref
+}
+"#,
+    );
+}
@@ -6,6 +6,7 @@
 
 mod fixes;
 mod field_shorthand;
+mod format_args;
 
 use std::cell::RefCell;
 
@@ -13,7 +14,7 @@ use hir::{
     diagnostics::{Diagnostic as _, DiagnosticCode, DiagnosticSinkBuilder},
     Semantics,
 };
-use ide_db::base_db::SourceDatabase;
+use ide_db::base_db::{Edition, SourceDatabase};
 use ide_db::RootDatabase;
 use itertools::Itertools;
 use rustc_hash::FxHashSet;
@@ -112,10 +113,22 @@ pub(crate) fn diagnostics(
             .take(128)
             .map(|err| Diagnostic::error(err.range(), format!("Syntax Error: {}", err))),
     );
+    let edition = sema.to_module_def(file_id).map(|module| module.krate().edition(db));
+    if edition >= Some(Edition::Edition2021) {
+        res.extend(
+            parse
+                .tree()
+                .reserved_prefix_errors()
+                .iter()
+                .map(|err| Diagnostic::error(err.range(), format!("Syntax Error: {}", err))),
+        );
+    }
 
-    for node in parse.tree().syntax().descendants() {
+    // Parsed through `sema`, so that the nodes can be resolved.
+    for node in sema.parse(file_id).syntax().descendants() {
         check_unnecessary_braces_in_use_statement(&mut res, file_id, &node);
         field_shorthand::check(&mut res, file_id, &node);
+        format_args::check(&mut res, &sema, &node);
     }
     let res = RefCell::new(res);
     let sink_builder = DiagnosticSinkBuilder::new()
@@ -173,11 +186,55 @@ pub(crate) fn diagnostics(
             );
         });
 
-    if let Some(m) = sema.to_module_def(file_id) {
+    // The file may be analyzed as part of several instances of the same crate
+    // with different cfg sets, so collect the diagnostics of each of them.
+    let mut instance_ranges = Vec::new();
+    for m in sema.to_module_defs(file_id) {
+        let start = res.borrow().len();
         m.diagnostics(db, &mut sink);
-    };
+        instance_ranges.push(start..res.borrow().len());
+    }
     drop(sink);
-    res.into_inner()
+    let mut res = res.into_inner();
+    if instance_ranges.len() > 1 {
+        res = merge_instance_diagnostics(res, &instance_ranges);
+    }
+    // The syntactic checks above don't go through the sink's filter.
+    res.retain(|d| d.code.as_ref().map_or(true, |code| !config.disabled.contains(code.as_str())));
+    res
+}
+
+/// Merges the diagnostics reported for the different instances of a crate:
+/// duplicates are reported once, and code is only marked as inactive if it is
+/// inactive in every instance.
+fn merge_instance_diagnostics(
+    mut res: Vec<Diagnostic>,
+    instance_ranges: &[std::ops::Range<usize>],
+) -> Vec<Diagnostic> {
+    let is_inactive_code =
+        |d: &Diagnostic| d.code.as_ref().map(|code| code.as_str()) == Some("inactive-code");
+    let inactive_in_all = |d: &Diagnostic| {
+        instance_ranges.iter().all(|range| {
+            res[range.clone()].iter().any(|it| is_inactive_code(it) && it.range == d.range)
+        })
+    };
+    let first = instance_ranges[0].start;
+    let keep: Vec<bool> = res
+        .iter()
+        .enumerate()
+        .map(|(idx, d)| {
+            if idx < first {
+                return true;
+            }
+            if is_inactive_code(d) && !inactive_in_all(d) {
+                return false;
+            }
+            !res[first..idx].iter().any(|it| it.range == d.range && it.message == d.message)
+        })
+        .collect();
+    let mut keep = keep.into_iter();
+    res.retain(|_| keep.next().unwrap_or(true));
+    res
 }
 
 fn diagnostic_with_fix<D: DiagnosticWithFix>(d: &D, sema: &Semantics<RootDatabase>) -> Diagnostic {
@@ -341,7 +398,7 @@ mod tests {
         assert_eq!(diagnostics.len(), 0, "unexpected diagnostics:\n{:#?}", diagnostics);
     }
 
-    fn check_expect(ra_fixture: &str, expect: Expect) {
+    pub(crate) fn check_expect(ra_fixture: &str, expect: Expect) {
         let (analysis, file_id) = fixture::file(ra_fixture);
         let diagnostics = analysis.diagnostics(&DiagnosticsConfig::default(), file_id).unwrap();
         expect.assert_debug_eq(&diagnostics)
@@ -799,6 +856,156 @@ struct Foo {
         assert!(!diagnostics.is_empty());
     }
 
+    #[test]
+    fn diagnostics_of_alternative_crate_instances() {
+        check_expect(
+            r#"
+//- /lib.rs crate:foo cfg:unix alt_cfg:windows
+struct S { x: u32 }
+#[cfg(unix)]
+fn f() { S {}; }
+#[cfg(windows)]
+fn g() { S {}; }
+#[cfg(target_os = "redox")]
+fn h() {}
+"#,
+            expect![[r##"
+                [
+                    Diagnostic {
+                        message: "code is inactive due to #[cfg] directives: target_os = \"redox\" is disabled",
+                        range: 83..120,
+                        severity: WeakWarning,
+                        fix: None,
+                        unused: true,
+                        code: Some(
+                            DiagnosticCode(
+                                "inactive-code",
+                            ),
+                        ),
+                    },
+                    Diagnostic {
+                        message: "Missing structure fields:\n- x\n",
+                        range: 42..43,
+                        severity: Error,
+                        fix: Some(
+                            Fix {
+                                label: "Fill struct fields",
+                                source_change: SourceChange {
+                                    source_file_edits: [
+                                        SourceFileEdit {
+                                            file_id: FileId(
+                                                0,
+                                            ),
+                                            edit: TextEdit {
+                                                indels: [
+                                                    Indel {
+                                                        insert: " ",
+                                                        delete: 45..45,
+                                                    },
+                                                    Indel {
+                                                        insert: "x: ()",
+                                                        delete: 45..45,
+                                                    },
+                                                ],
+                                            },
+                                        },
+                                    ],
+                                    file_system_edits: [],
+                                    is_snippet: false,
+                                },
+                                fix_trigger_range: 42..46,
+                            },
+                        ),
+                        unused: false,
+                        code: Some(
+                            DiagnosticCode(
+                                "missing-structure-fields",
+                            ),
+                        ),
+                    },
+                    Diagnostic {
+                        message: "Missing structure fields:\n- x\n",
+                        range: 75..76,
+                        severity: Error,
+                        fix: Some(
+                            Fix {
+                                label: "Fill struct fields",
+                                source_change: SourceChange {
+                                    source_file_edits: [
+                                        SourceFileEdit {
+                                            file_id: FileId(
+                                                0,
+                                            ),
+                                            edit: TextEdit {
+                                                indels: [
+                                                    Indel {
+                                                        insert: " ",
+                                                        delete: 78..78,
+                                                    },
+                                                    Indel {
+                                                        insert: "x: ()",
+                                                        delete: 78..78,
+                                                    },
+                                                ],
+                                            },
+                                        },
+                                    ],
+                                    file_system_edits: [],
+                                    is_snippet: false,
+                                },
+                                fix_trigger_range: 75..79,
+                            },
+                        ),
+                        unused: false,
+                        code: Some(
+                            DiagnosticCode(
+                                "missing-structure-fields",
+                            ),
+                        ),
+                    },
+                ]
+            "##]],
+        );
+    }
+
+    #[test]
+    fn test_reserved_prefixes() {
+        check_expect(
+            r##"
+//- /main.rs crate:main edition:2021
+macro_rules! m { ($($t:tt)*) => {} }
+m!(f"{x}" k#x r#"raw"# b'x' x #y);
+"##,
+            expect![[r#"
+                [
+                    Diagnostic {
+                        message: "Syntax Error: prefix `f` is unknown",
+                        range: 40..41,
+                        severity: Error,
+                        fix: None,
+                        unused: false,
+                        code: None,
+                    },
+                    Diagnostic {
+                        message: "Syntax Error: prefix `k` is unknown",
+                        range: 47..48,
+                        severity: Error,
+                        fix: None,
+                        unused: false,
+                        code: None,
+                    },
+                ]
+            "#]],
+        );
+        check_no_diagnostics(
+            r#"
+//- /main.rs crate:main edition:2018
+macro_rules! m { ($($t:tt)*) => {} }
+m!(f"{x}" k#x);
+"#,
+        );
+    }
+
     #[test]
     fn test_rename_incorrect_case() {
         check_fixes(
@@ -0,0 +1,230 @@
+//! Checks that the positional `{}` placeholders of a `format!`-like macro call
+//! of the standard library match the arguments passed to it.
+
+use hir::{diagnostics::DiagnosticCode, Semantics};
+use ide_db::{
+    helpers::format_string::{placeholders, FormatCall, PlaceholderArg},
+    RootDatabase,
+};
+use syntax::{ast, AstNode, AstToken, SyntaxNode};
+
+use crate::Diagnostic;
+
+// Diagnostic: format-args-count
+//
+// This diagnostic is triggered if the number of positional arguments of a format macro of the
+// standard library, like `println!`, doesn't match its format string.
+pub(super) fn check(acc: &mut Vec<Diagnostic>, sema: &Semantics<RootDatabase>, node: &SyntaxNode) {
+    let macro_call = match ast::MacroCall::cast(node.clone()) {
+        Some(it) => it,
+        None => return,
+    };
+    let call = match FormatCall::from_macro_call(&macro_call) {
+        Some(it) => it,
+        None => return,
+    };
+    if !is_std_macro(sema, &macro_call) {
+        return;
+    }
+    let placeholders = match placeholders(call.string.text()) {
+        Some(it) => it,
+        None => return,
+    };
+
+    let mut next = 0;
+    let mut expected = 0;
+    for placeholder in &placeholders {
+        // `{:1$}` and `{:.*}` take their width and precision from the arguments too.
+        if placeholder.spec.contains(|c| c == '$' || c == '*') {
+            return;
+        }
+        match placeholder.arg {
+            PlaceholderArg::Next => {
+                next += 1;
+                expected = expected.max(next);
+            }
+            PlaceholderArg::Index(idx) => expected = expected.max(idx + 1),
+            PlaceholderArg::Name(_) => (),
+        }
+    }
+
+    let positional = call.args.iter().filter(|it| it.name.is_none()).count();
+    // Named arguments can be referred to by their position as well.
+    if positional <= expected && expected <= call.args.len() {
+        return;
+    }
+    let message = format!(
+        "{} in format string, but {}",
+        plural(expected, "positional argument"),
+        match call.args.len() {
+            0 => "no arguments were given".to_string(),
+            1 => "there is 1 argument".to_string(),
+            n => format!("there are {} arguments", n),
+        }
+    );
+    acc.push(
+        Diagnostic::error(call.string.syntax().text_range(), message)
+            .with_code(Some(DiagnosticCode("format-args-count"))),
+    );
+}
+
+/// Other macros might share the names of the format macros, but not their
+/// syntax.
+fn is_std_macro(sema: &Semantics<RootDatabase>, macro_call: &ast::MacroCall) -> bool {
+    let krate = match sema.resolve_macro_call(macro_call).and_then(|it| it.module(sema.db)) {
+        Some(module) => module.krate(),
+        None => return false,
+    };
+    matches!(krate.display_name(sema.db).as_deref(), Some("core") | Some("alloc") | Some("std"))
+}
+
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 {
+        format!("1 {}", word)
+    } else {
+        format!("{} {}s", n, word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use crate::diagnostics::tests::{check_expect, check_no_diagnostics};
+
+    #[test]
+    fn format_args_match() {
+        check_no_diagnostics(
+            r#"
+//- /main.rs crate:main deps:std
+use std::{println, write};
+
+fn main() {
+    let (a, b) = (1, 2);
+    println!("{} {:?} {{}} {a} {b:>8}", a, b);
+    println!("{0} {0} {1:.3}", a, b);
+    println!("{x} {}", x = a);
+    println!("{:.*}", 3, a);
+    write!(f, "{}\n", a);
+}
+//- /std.rs crate:std
+#[macro_export]
+macro_rules! println { ($($t:tt)*) => {} }
+#[macro_export]
+macro_rules! write { ($($t:tt)*) => {} }
+"#,
+        );
+    }
+
+    #[test]
+    fn format_args_with_generic_args() {
+        check_expect(
+            r#"
+//- /main.rs crate:main deps:std
+use std::println;
+
+fn main() {
+    let (a, b) = (1, 2);
+    println!("{:?}", HashMap::<u32, u32>::new());
+    println!("{} {}", a < b, a > b);
+    println!("{}", Vec::<(u8, u8)>::new(), a);
+}
+//- /std.rs crate:std
+#[macro_export]
+macro_rules! println { ($($t:tt)*) => {} }
+#[macro_export]
+macro_rules! write { ($($t:tt)*) => {} }
+"#,
+            expect![[r#"
+                [
+                    Diagnostic {
+                        message: "1 positional argument in format string, but there are 2 arguments",
+                        range: 156..160,
+                        severity: Error,
+                        fix: None,
+                        unused: false,
+                        code: Some(
+                            DiagnosticCode(
+                                "format-args-count",
+                            ),
+                        ),
+                    },
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn format_args_mismatch() {
+        check_expect(
+            r#"
+//- /main.rs crate:main deps:std
+use std::println;
+
+fn main() {
+    let a = 1;
+    println!("{} {}", a);
+    println!("{}");
+    println!("{0}", a, a);
+}
+//- /std.rs crate:std
+#[macro_export]
+macro_rules! println { ($($t:tt)*) => {} }
+#[macro_export]
+macro_rules! write { ($($t:tt)*) => {} }
+"#,
+            expect![[r#"
+                [
+                    Diagnostic {
+                        message: "2 positional arguments in format string, but there is 1 argument",
+                        range: 59..66,
+                        severity: Error,
+                        fix: None,
+                        unused: false,
+                        code: Some(
+                            DiagnosticCode(
+                                "format-args-count",
+                            ),
+                        ),
+                    },
+                    Diagnostic {
+                        message: "1 positional argument in format string, but no arguments were given",
+                        range: 85..89,
+                        severity: Error,
+                        fix: None,
+                        unused: false,
+                        code: Some(
+                            DiagnosticCode(
+                                "format-args-count",
+                            ),
+                        ),
+                    },
+                    Diagnostic {
+                        message: "1 positional argument in format string, but there are 2 arguments",
+                        range: 105..110,
+                        severity: Error,
+                        fix: None,
+                        unused: false,
+                        code: Some(
+                            DiagnosticCode(
+                                "format-args-count",
+                            ),
+                        ),
+                    },
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn format_args_of_other_macros() {
+        check_no_diagnostics(
+            r#"
+macro_rules! println { ($($t:tt)*) => {} }
+fn main() {
+    println!("{} {}", 1);
+}
+"#,
+        );
+    }
+}
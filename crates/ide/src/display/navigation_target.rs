@@ -25,6 +25,7 @@ pub enum SymbolKind {
     Field,
     TypeParam,
     LifetimeParam,
+    Label,
     ValueParam,
     SelfParam,
     Local,
@@ -223,6 +224,7 @@ impl TryToNav for Definition {
             Definition::Local(it) => Some(it.to_nav(db)),
             Definition::TypeParam(it) => Some(it.to_nav(db)),
             Definition::LifetimeParam(it) => Some(it.to_nav(db)),
+            Definition::Label(it) => Some(it.to_nav(db)),
         }
     }
 }
@@ -462,6 +464,24 @@ impl ToNav for hir::LifetimeParam {
     }
 }
 
+impl ToNav for hir::Label {
+    fn to_nav(&self, db: &RootDatabase) -> NavigationTarget {
+        let src = self.source(db);
+        let full_range = src.value.syntax().text_range();
+        let focus_range = src.value.lifetime().map(|it| it.syntax().text_range());
+        NavigationTarget {
+            file_id: src.file_id.original_file(db),
+            name: self.name(db).to_string().into(),
+            kind: Some(SymbolKind::Label),
+            full_range,
+            focus_range,
+            container_name: None,
+            description: None,
+            docs: None,
+        }
+    }
+}
+
 /// Get a description of a symbol.
 ///
 /// e.g. `struct Name`, `enum Name`, `fn Name`
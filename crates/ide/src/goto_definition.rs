@@ -1102,6 +1102,23 @@ fn foo<T>() where for<'a<|>> T: Foo<&'a (u8, u16)>, {}
             r#"trait Foo<T> {}
 fn foo<T>() where T: for<'a> Foo<&'a<|> (u8, u16)>, {}
                        //^^
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_in_code_inactive_for_host() {
+        check(
+            r#"
+//- /lib.rs crate:foo cfg:unix alt_cfg:windows
+#[cfg(windows)]
+mod win {
+    fn foo() {
+        let x = 92;
+          //^
+        x<|>;
+    }
+}
 "#,
         );
     }
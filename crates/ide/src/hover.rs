@@ -2,8 +2,9 @@ use hir::{
     Adt, AsAssocItem, AssocItemContainer, FieldSource, HasAttrs, HasSource, HirDisplay, Module,
     ModuleDef, ModuleSource, Semantics,
 };
-use ide_db::base_db::SourceDatabase;
+use ide_db::base_db::{FileLoader, SourceDatabase};
 use ide_db::{
+    cargo_toml::{is_cargo_toml, manifest_position, ManifestContext, Table, TABLES},
     defs::{Definition, NameClass, NameRefClass},
     RootDatabase,
 };
@@ -97,6 +98,10 @@ pub(crate) fn hover(
     links_in_hover: bool,
     markdown: bool,
 ) -> Option<RangeInfo<HoverResult>> {
+    if is_cargo_toml(db, position.file_id) {
+        return hover_cargo_toml(db, position, markdown);
+    }
+
     let sema = Semantics::new(db);
     let file = sema.parse(position.file_id).syntax().clone();
     let token = pick_best(file.token_at_offset(position.offset))?;
@@ -263,6 +268,57 @@ fn goto_type_action(db: &RootDatabase, def: Definition) -> Option<HoverAction> {
     }
 }
 
+/// Describes the table, key or dependency at the cursor in a `Cargo.toml`.
+fn hover_cargo_toml(
+    db: &RootDatabase,
+    position: FilePosition,
+    markdown: bool,
+) -> Option<RangeInfo<HoverResult>> {
+    let text = db.file_text(position.file_id);
+    let manifest_position = manifest_position(&text, position.offset)?;
+    let range = manifest_position.range;
+    let word = &text[range];
+    if word.is_empty() {
+        return None;
+    }
+
+    let mut markup = String::new();
+    match manifest_position.context {
+        ManifestContext::TableHeader => {
+            let table = TABLES.iter().find(|it| it.name == word)?;
+            format_to!(markup, "```toml\n[{}]\n```\n\n{}", table.name, table.description);
+        }
+        ManifestContext::Key(Table::Dependencies) => {
+            let package_index = db.package_index();
+            let features = package_index.features.get(word);
+            let latest = package_index.registry.get(word).and_then(|it| it.last());
+            if features.is_none() && latest.is_none() {
+                return None;
+            }
+            match latest {
+                Some(latest) => format_to!(markup, "```toml\n{} = \"{}\"\n```", word, latest),
+                None => format_to!(markup, "```toml\n{}\n```", word),
+            }
+            if let Some(features) = features.filter(|it| !it.is_empty()) {
+                let features =
+                    features.iter().format_with(", ", |it, f| f(&format_args!("`{}`", it)));
+                format_to!(markup, "\n\nFeatures: {}", features);
+            }
+        }
+        ManifestContext::Key(table) => {
+            let key = table.keys().iter().find(|it| it.name == word)?;
+            format_to!(markup, "```toml\n{}\n```\n\n{}", key.name, key.description);
+        }
+        ManifestContext::DependencyVersion(_) | ManifestContext::DependencyFeature(_) => {
+            return None
+        }
+    }
+
+    let markup = if markdown { markup } else { remove_markdown(&markup) };
+    let res = HoverResult { markup: Markup::from(markup), actions: Vec::new() };
+    Some(RangeInfo::new(range, res))
+}
+
 fn hover_markup(
     docs: Option<String>,
     desc: Option<String>,
@@ -370,6 +426,7 @@ fn hover_for_definition(db: &RootDatabase, def: Definition) -> Option<Markup> {
                 Adt::Enum(it) => from_def_source(db, it, mod_path),
             })
         }
+        Definition::Label(it) => return Some(Markup::fenced_block(&it.name(db))),
         Definition::TypeParam(_) | Definition::LifetimeParam(_) => {
             // FIXME: Hover for generic param
             None
@@ -3221,4 +3278,41 @@ fn no_hover() {
 "#,
         );
     }
+
+    #[test]
+    fn hover_cargo_toml_keys_and_dependencies() {
+        check(
+            r#"
+//- /Cargo.toml
+[package]
+edi<|>tion = "2018"
+//- /lib.rs
+"#,
+            expect![[r#"
+                *edition*
+                ```toml
+                edition
+                ```
+
+                The Rust edition, `2015`, `2018` or `2021`.
+            "#]],
+        );
+        check(
+            r#"
+//- /Cargo.toml
+[dependencies]
+ser<|>de = { version = "1.0", features = ["derive"] }
+//- /lib.rs crate:main deps:serde
+//- /serde.rs crate:serde cfg:feature=derive,feature=std
+"#,
+            expect![[r#"
+                *serde*
+                ```toml
+                serde
+                ```
+
+                Features: `derive`, `std`
+            "#]],
+        );
+    }
 }
@@ -81,8 +81,8 @@ pub use crate::{
 };
 pub use assists::{Assist, AssistConfig, AssistId, AssistKind, ResolvedAssist};
 pub use completion::{
-    CompletionConfig, CompletionItem, CompletionItemKind, CompletionResolveCapability,
-    CompletionScore, ImportEdit, InsertTextFormat,
+    CompletionConfig, CompletionItem, CompletionItemKind, CompletionRelevance,
+    CompletionResolveCapability, ImportEdit, InsertTextFormat, Locality, Snippet, SnippetScope,
 };
 pub use hir::{Documentation, Semantics};
 pub use ide_db::base_db::{
@@ -130,7 +130,7 @@ pub(crate) fn find_all_refs(
                 kind = ReferenceKind::FieldShorthandForLocal;
             }
         }
-    } else if let Definition::LifetimeParam(_) = def {
+    } else if let Definition::LifetimeParam(_) | Definition::Label(_) = def {
         kind = ReferenceKind::Lifetime;
     };
 
@@ -1064,6 +1064,31 @@ type Foo<'a, T> where T: 'a<|> = &'a T;
         );
     }
 
+    #[test]
+    fn test_find_labels() {
+        check(
+            r#"
+fn foo() {
+    'outer: loop {
+        'inner: loop {
+            break 'outer;
+        }
+        continue 'outer<|>;
+    }
+    'outer: loop {
+        break 'outer;
+    }
+}
+"#,
+            expect![[r#"
+                'outer Label FileId(0) 15..22 15..21 Lifetime
+
+                FileId(0) 71..77 Lifetime
+                FileId(0) 106..112 Lifetime
+            "#]],
+        );
+    }
+
     #[test]
     fn test_find_lifetimes_trait_impl() {
         check(
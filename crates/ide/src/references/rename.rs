@@ -6,9 +6,10 @@ use std::{
 };
 
 use hir::{Module, ModuleDef, ModuleSource, Semantics};
-use ide_db::base_db::{AnchoredPathBuf, FileRange, SourceDatabaseExt};
+use ide_db::base_db::{AnchoredPathBuf, Edition, FileRange, SourceDatabaseExt};
 use ide_db::{
     defs::{Definition, NameClass, NameRefClass},
+    helpers::is_keyword,
     RootDatabase,
 };
 use syntax::{
@@ -72,9 +73,14 @@ pub(crate) fn rename_with_semantics(
     position: FilePosition,
     new_name: &str,
 ) -> Result<RangeInfo<SourceChange>, RenameError> {
+    let edition = sema
+        .to_module_def(position.file_id)
+        .map_or(Edition::Edition2018, |module| module.krate().edition(sema.db));
     let is_lifetime_name = match lex_single_syntax_kind(new_name) {
         Some(res) => match res {
             (SyntaxKind::IDENT, _) => false,
+            // Like `async` in Rust 2015.
+            (kind, _) if kind.is_keyword() && !is_keyword(new_name, edition) => false,
             (SyntaxKind::UNDERSCORE, _) => false,
             (SyntaxKind::SELF_KW, _) => return rename_to_self(&sema, position),
             (SyntaxKind::LIFETIME_IDENT, _) if new_name != "'static" && new_name != "'_" => true,
@@ -502,6 +508,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rename_to_edition_keyword() {
+        check(
+            "async",
+            r#"
+//- /main.rs crate:main edition:2015
+fn main() { let i<|> = 1; }
+"#,
+            r#"
+fn main() { let async = 1; }
+"#,
+        );
+        check(
+            "async",
+            r#"
+//- /main.rs crate:main edition:2018
+fn main() { let i<|> = 1; }
+"#,
+            "error: Invalid name `async`: not an identifier",
+        );
+    }
+
     #[test]
     fn test_rename_to_invalid_identifier3() {
         check(
@@ -1489,6 +1517,29 @@ impl<'yeeee> Foo<'yeeee> for &'yeeee () {
         )
     }
 
+    #[test]
+    fn test_rename_labels() {
+        check(
+            "'new",
+            r#"
+fn main() {
+    'outer<|>: loop {
+        let f = || 'outer: loop { break 'outer };
+        continue 'outer;
+    }
+}
+"#,
+            r#"
+fn main() {
+    'new: loop {
+        let f = || 'outer: loop { break 'outer };
+        continue 'new;
+    }
+}
+"#,
+        )
+    }
+
     #[test]
     fn test_rename_bind_pat() {
         check(
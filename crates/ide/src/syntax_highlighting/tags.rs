@@ -78,6 +78,7 @@ impl HighlightTag {
                 SymbolKind::TypeAlias => "type_alias",
                 SymbolKind::TypeParam => "type_param",
                 SymbolKind::LifetimeParam => "lifetime",
+                SymbolKind::Label => "label",
                 SymbolKind::Macro => "macro",
                 SymbolKind::Local => "variable",
                 SymbolKind::ValueParam => "value_param",
@@ -89,6 +89,7 @@ impl RootDatabase {
 
         hir::db::ExprScopesQuery.in_db(self).sweep(sweep);
         hir::db::InferQueryQuery.in_db(self).sweep(sweep);
+        hir::db::ClosureCapturesQuery.in_db(self).sweep(sweep);
         hir::db::BodyQuery.in_db(self).sweep(sweep);
     }
 
@@ -132,6 +133,7 @@ impl RootDatabase {
             // SourceDatabase
             base_db::ParseQuery
             base_db::CrateGraphQuery
+            base_db::PackageIndexQuery
 
             // SourceDatabaseExt
             base_db::FileTextQuery
@@ -169,6 +171,7 @@ impl RootDatabase {
 
             // HirDatabase
             hir::db::InferQueryQuery
+            hir::db::ClosureCapturesQuery
             hir::db::TyQuery
             hir::db::ValueTyQuery
             hir::db::ImplSelfTyQuery
@@ -0,0 +1,373 @@
+//! Understands just enough of `Cargo.toml` files to complete and hover in
+//! them.
+//!
+//! We don't have a TOML parser: manifests are short, and a small scanner which
+//! tracks strings, brackets and the current table is enough to tell which key
+//! or value the cursor is at, even in a half-written file.
+use base_db::{FileId, SourceDatabaseExt};
+use syntax::{TextRange, TextSize};
+
+use crate::RootDatabase;
+
+/// Whether the file is a `Cargo.toml` manifest rather than a Rust file.
+pub fn is_cargo_toml(db: &RootDatabase, file_id: FileId) -> bool {
+    let source_root = db.source_root(db.file_source_root(file_id));
+    let path = match source_root.path_for_file(&file_id) {
+        Some(it) => it,
+        None => return false,
+    };
+    path.name_and_extension() == Some(("Cargo", Some("toml")))
+}
+
+/// A key of a manifest table, or a table itself.
+#[derive(Debug)]
+pub struct ManifestKey {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+const fn key(name: &'static str, description: &'static str) -> ManifestKey {
+    ManifestKey { name, description }
+}
+
+pub const TABLES: &[ManifestKey] = &[
+    key("package", "Defines a package."),
+    key("lib", "Library target settings."),
+    key("bin", "Binary target settings."),
+    key("example", "Example target settings."),
+    key("test", "Test target settings."),
+    key("bench", "Benchmark target settings."),
+    key("dependencies", "Package library dependencies."),
+    key("dev-dependencies", "Dependencies for examples, tests, and benchmarks."),
+    key("build-dependencies", "Dependencies for build scripts."),
+    key("target", "Platform-specific dependencies."),
+    key("features", "Conditional compilation features."),
+    key("patch", "Override dependencies."),
+    key("profile.dev", "Compiler settings of the `dev` profile, used by `cargo build`."),
+    key("profile.release", "Compiler settings of the `release` profile, used by `--release`."),
+    key("profile.test", "Compiler settings of the `test` profile, used by `cargo test`."),
+    key("profile.bench", "Compiler settings of the `bench` profile, used by `cargo bench`."),
+    key("workspace", "The workspace definition."),
+    key("badges", "Badges to display on a registry."),
+];
+
+pub const PACKAGE_KEYS: &[ManifestKey] = &[
+    key("name", "The name of the package."),
+    key("version", "The version of the package."),
+    key("authors", "The authors of the package."),
+    key("edition", "The Rust edition, `2015`, `2018` or `2021`."),
+    key("description", "A description of the package."),
+    key("documentation", "URL of the package documentation."),
+    key("readme", "Path to the package's README file."),
+    key("homepage", "URL of the package homepage."),
+    key("repository", "URL of the package source repository."),
+    key("license", "The package license, as an SPDX 2.1 license expression."),
+    key("license-file", "Path to the text of the license."),
+    key("keywords", "Keywords for the package, used to search it in the registry."),
+    key("categories", "Categories of the package in the registry."),
+    key("workspace", "Path to the workspace of the package."),
+    key("build", "Path to the package build script."),
+    key("links", "Name of the native library the package links with."),
+    key("exclude", "Files to exclude when publishing."),
+    key("include", "Files to include when publishing."),
+    key("publish", "Can be used to prevent publishing the package."),
+    key("metadata", "Extra settings for external tools."),
+    key("default-run", "The default binary picked by `cargo run`."),
+    key("autobins", "Disables binary auto discovery."),
+    key("autoexamples", "Disables example auto discovery."),
+    key("autotests", "Disables test auto discovery."),
+    key("autobenches", "Disables bench auto discovery."),
+    key("resolver", "Sets the dependency resolver to use."),
+];
+
+pub const PROFILE_KEYS: &[ManifestKey] = &[
+    key("opt-level", "The optimization level, `0` to `3`, `\"s\"` or `\"z\"`."),
+    key("debug", "The amount of debug information, `0`, `1` or `2`."),
+    key("debug-assertions", "Whether `cfg(debug_assertions)` is enabled."),
+    key("overflow-checks", "Whether integer overflows panic."),
+    key("lto", "Link time optimizations, `false`, `true`, `\"thin\"` or `\"off\"`."),
+    key("panic", "The panic strategy, `\"unwind\"` or `\"abort\"`."),
+    key("incremental", "Whether incremental compilation is enabled."),
+    key("codegen-units", "How many code generation units a crate is split into."),
+    key("rpath", "Whether rpath is enabled."),
+];
+
+pub const DEPENDENCY_KEYS: &[ManifestKey] = &[
+    key("version", "The version requirement of the dependency."),
+    key("path", "Path to a local dependency."),
+    key("git", "URL of a git repository with the dependency."),
+    key("branch", "The git branch to use."),
+    key("tag", "The git tag to use."),
+    key("rev", "The git revision to use."),
+    key("features", "Features of the dependency to enable."),
+    key("default-features", "Whether the default features of the dependency are enabled."),
+    key("optional", "Makes the dependency optional, enabled by a feature of the same name."),
+    key("package", "The name of the package, when it is renamed."),
+    key("registry", "The registry to use for the dependency."),
+];
+
+/// The table the cursor is in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Table {
+    Package,
+    Profile,
+    /// `[dependencies]`, `[dev-dependencies]` and the like.
+    Dependencies,
+    /// `[dependencies.serde]`, or the inline table of a dependency.
+    Dependency(String),
+    Other,
+}
+
+impl Table {
+    /// The keys which are known in the table.
+    pub fn keys(&self) -> &'static [ManifestKey] {
+        match self {
+            Table::Package => PACKAGE_KEYS,
+            Table::Profile => PROFILE_KEYS,
+            Table::Dependency(_) => DEPENDENCY_KEYS,
+            Table::Dependencies | Table::Other => &[],
+        }
+    }
+
+    fn from_header(header: &str) -> Table {
+        let header = header.trim_matches(|c| c == '[' || c == ']').trim();
+        if header == "package" {
+            return Table::Package;
+        }
+        if header.starts_with("profile.") {
+            return Table::Profile;
+        }
+        let segments: Vec<&str> = header.split('.').map(|it| it.trim().trim_matches('"')).collect();
+        match segments.as_slice() {
+            [.., table] if is_dependencies(table) => Table::Dependencies,
+            [.., table, name] if is_dependencies(table) => Table::Dependency(name.to_string()),
+            _ => Table::Other,
+        }
+    }
+}
+
+fn is_dependencies(table: &str) -> bool {
+    matches!(table.trim(), "dependencies" | "dev-dependencies" | "build-dependencies")
+}
+
+/// What is written at the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestContext {
+    /// The name of a table, `[pack<|>]`.
+    TableHeader,
+    /// A key of a table, like `edi<|>` in `[package]` or `ser<|>` in
+    /// `[dependencies]`.
+    Key(Table),
+    /// The version of a dependency, `serde = "<|>"`.
+    DependencyVersion(String),
+    /// A feature of a dependency, `features = ["<|>"]`.
+    DependencyFeature(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestPosition {
+    pub context: ManifestContext,
+    /// The range of the word at the cursor, without the quotes.
+    pub range: TextRange,
+    /// Whether the cursor is inside of a string.
+    pub in_string: bool,
+}
+
+/// Figures out what is written at `offset`.
+pub fn manifest_position(text: &str, offset: TextSize) -> Option<ManifestPosition> {
+    let offset = usize::from(offset);
+    if offset > text.len() {
+        return None;
+    }
+    let line_start = text[..offset].rfind('\n').map_or(0, |it| it + 1);
+    if text[line_start..offset].trim_start().starts_with('[') {
+        let range = word_range(text, offset, |c| is_key_char(c) || c == '.');
+        return Some(ManifestPosition {
+            context: ManifestContext::TableHeader,
+            range,
+            in_string: false,
+        });
+    }
+
+    // Find the header of the table.
+    let mut table = Table::Other;
+    let mut table_start = 0;
+    let mut line_start = line_start;
+    while line_start > 0 {
+        let prev_line_start = text[..line_start - 1].rfind('\n').map_or(0, |it| it + 1);
+        let line = text[prev_line_start..line_start].trim();
+        if line.starts_with('[') {
+            let header = line.split('#').next().unwrap_or_default();
+            table = Table::from_header(header);
+            table_start = line_start;
+            break;
+        }
+        line_start = prev_line_start;
+    }
+
+    let scan = Scan::new(&text[table_start..offset]);
+    let (context, in_string) = match (&table, scan.path.as_slice()) {
+        (_, []) if scan.frames.is_empty() => (ManifestContext::Key(table), false),
+        (Table::Dependencies, [(name, None)]) => {
+            (ManifestContext::DependencyVersion(name.clone()), scan.in_string)
+        }
+        (Table::Dependencies, [(name, Some('{'))]) if scan.key.is_none() => {
+            (ManifestContext::Key(Table::Dependency(name.clone())), false)
+        }
+        (Table::Dependencies, [(name, Some('{')), (key, _)])
+        | (Table::Dependency(name), [(key, _)])
+            if key == "version" && scan.frames.last() != Some(&'[') =>
+        {
+            (ManifestContext::DependencyVersion(name.clone()), scan.in_string)
+        }
+        (Table::Dependencies, [(name, Some('{')), (key, Some('['))])
+        | (Table::Dependency(name), [(key, Some('['))])
+            if key == "features" =>
+        {
+            (ManifestContext::DependencyFeature(name.clone()), scan.in_string)
+        }
+        _ => return None,
+    };
+    let range = if in_string {
+        word_range(text, offset, |c| c != '"' && c != '\n')
+    } else {
+        word_range(text, offset, is_key_char)
+    };
+    Some(ManifestPosition { context, range, in_string })
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+fn word_range(text: &str, offset: usize, is_word_char: impl Fn(char) -> bool) -> TextRange {
+    let start = text[..offset]
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| !is_word_char(c))
+        .map_or(0, |(idx, c)| idx + c.len_utf8());
+    let end = text[offset..]
+        .char_indices()
+        .find(|&(_, c)| !is_word_char(c))
+        .map_or(text.len(), |(idx, _)| offset + idx);
+    TextRange::new((start as u32).into(), (end as u32).into())
+}
+
+/// The state of the scanner at the cursor.
+#[derive(Debug, Default)]
+struct Scan {
+    /// Kinds of the open brackets, `{` or `[`.
+    frames: Vec<char>,
+    /// The keys of the values the cursor is in, with the bracket opening each
+    /// value, if any.
+    path: Vec<(String, Option<char>)>,
+    /// The key of the current entry, if its `=` was already seen.
+    key: Option<String>,
+    in_string: bool,
+}
+
+impl Scan {
+    fn new(text: &str) -> Scan {
+        let mut scan = Scan::default();
+        let mut entry_start = 0;
+        let mut in_comment = false;
+        for (idx, c) in text.char_indices() {
+            if in_comment {
+                if c == '\n' {
+                    in_comment = false;
+                } else {
+                    continue;
+                }
+            }
+            if scan.in_string {
+                if c == '"' || c == '\n' {
+                    scan.in_string = false;
+                }
+                continue;
+            }
+            let in_table = scan.frames.last() != Some(&'[');
+            match c {
+                '"' => scan.in_string = true,
+                '#' => in_comment = true,
+                '=' if in_table && scan.key.is_none() => {
+                    let key = text[entry_start..idx].trim().trim_matches('"').to_string();
+                    scan.key = Some(key.clone());
+                    scan.path.push((key, None));
+                }
+                '{' | '[' => {
+                    if let Some((_, bracket @ None)) = scan.path.last_mut() {
+                        if scan.key.is_some() {
+                            *bracket = Some(c);
+                        }
+                    }
+                    scan.frames.push(c);
+                    scan.key = None;
+                    entry_start = idx + 1;
+                }
+                '}' | ']' => {
+                    scan.frames.pop();
+                    scan.path.truncate(scan.frames.len());
+                    scan.key = Some(String::new());
+                }
+                ',' if in_table && !scan.frames.is_empty() => {
+                    scan.path.truncate(scan.frames.len());
+                    scan.key = None;
+                    entry_start = idx + 1;
+                }
+                '\n' if scan.frames.is_empty() => {
+                    scan.path.clear();
+                    scan.key = None;
+                    entry_start = idx + 1;
+                }
+                _ => {}
+            }
+        }
+        scan
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_utils::extract_offset;
+
+    use super::*;
+
+    fn check(ra_fixture: &str, expected: Option<ManifestContext>) {
+        let (offset, text) = extract_offset(ra_fixture);
+        let actual = manifest_position(&text, offset).map(|it| it.context);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn finds_manifest_context() {
+        check("[pack<|>]", Some(ManifestContext::TableHeader));
+        check("[package]\nedi<|>", Some(ManifestContext::Key(Table::Package)));
+        check("[profile.release]\nopt<|>", Some(ManifestContext::Key(Table::Profile)));
+        check("[package]\nedition = \"20<|>\"", None);
+        check("[dependencies]\nser<|>", Some(ManifestContext::Key(Table::Dependencies)));
+        check(
+            "[dependencies]\nserde = \"1.<|>\"",
+            Some(ManifestContext::DependencyVersion("serde".to_string())),
+        );
+        check(
+            "[dependencies]\nserde = { ver<|> }",
+            Some(ManifestContext::Key(Table::Dependency("serde".to_string()))),
+        );
+        check(
+            "[dependencies]\nserde = { version = \"1\", features = [\"derive\", \"s<|>\"] }",
+            Some(ManifestContext::DependencyFeature("serde".to_string())),
+        );
+        check(
+            "[dependencies]\nserde = { features = [\"derive\"], <|> }",
+            Some(ManifestContext::Key(Table::Dependency("serde".to_string()))),
+        );
+        check(
+            "[target.'cfg(unix)'.dependencies.libc]\nfeatures = [\n  \"std\",\n  <|>\n]",
+            Some(ManifestContext::DependencyFeature("libc".to_string())),
+        );
+        check(
+            "[dev-dependencies]\n# a comment = [\nrand = { version = \"0.<|>\" }",
+            Some(ManifestContext::DependencyVersion("rand".to_string())),
+        );
+    }
+}
@@ -1,8 +1,13 @@
 //! A module with ide helpers for high-level ide features.
 use crate::RootDatabase;
-use hir::{Crate, Enum, Module, ScopeDef, Semantics, Trait};
-use syntax::ast::{self, make};
-
+use base_db::Edition;
+use hir::{Crate, Enum, Module, ScopeDef, Semantics, Struct, Trait};
+use syntax::{
+    ast::{self, make},
+    SyntaxKind, T,
+};
+
+pub mod format_string;
 pub mod insert_use;
 
 /// Converts the mod path struct into its ast representation.
@@ -29,6 +34,18 @@ pub fn mod_path_to_ast(path: &hir::ModPath) -> ast::Path {
     make::path_from_segments(segments, is_abs)
 }
 
+/// Checks if `name` is a keyword in the given edition. `async`, `await`, `dyn`
+/// and `try` are only keywords since Rust 2018.
+pub fn is_keyword(name: &str, edition: Edition) -> bool {
+    match SyntaxKind::from_keyword(name) {
+        Some(T![async]) | Some(T![await]) | Some(T![dyn]) | Some(T![try]) => {
+            edition >= Edition::Edition2018
+        }
+        Some(_) => true,
+        None => false,
+    }
+}
+
 /// Helps with finding well-know things inside the standard library. This is
 /// somewhat similar to the known paths infra inside hir, but it different; We
 /// want to make sure that IDE specific paths don't become interesting inside
@@ -77,6 +94,9 @@ pub mod iter {
             pub trait IntoIterator {
                 type Item;
             }
+            impl<I: super::iterator::Iterator> IntoIterator for I {
+                type Item = I::Item;
+            }
         }
     }
 
@@ -146,6 +166,14 @@ pub use prelude::*;
         self.find_trait("core:iter:traits:iterator:Iterator")
     }
 
+    pub fn core_iter_IntoIterator(&self) -> Option<Trait> {
+        self.find_trait("core:iter:traits:collect:IntoIterator")
+    }
+
+    pub fn std_sync_Mutex(&self) -> Option<Struct> {
+        self.find_struct("std:sync:Mutex")
+    }
+
     pub fn core_iter(&self) -> Option<Module> {
         self.find_module("core:iter")
     }
@@ -157,6 +185,13 @@ pub use prelude::*;
         }
     }
 
+    fn find_struct(&self, path: &str) -> Option<Struct> {
+        match self.find_def(path)? {
+            hir::ScopeDef::ModuleDef(hir::ModuleDef::Adt(hir::Adt::Struct(it))) => Some(it),
+            _ => None,
+        }
+    }
+
     fn find_enum(&self, path: &str) -> Option<Enum> {
         match self.find_def(path)? {
             hir::ScopeDef::ModuleDef(hir::ModuleDef::Adt(hir::Adt::Enum(it))) => Some(it),
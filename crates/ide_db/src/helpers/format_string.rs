@@ -0,0 +1,259 @@
+//! Tools to work with the format strings of `format!`-like macros: finding the
+//! format string and the arguments of a macro call and parsing the `{}`
+//! placeholders of the string.
+use syntax::{
+    ast::{self, AstNode, AstToken},
+    NodeOrToken, SyntaxElement,
+    SyntaxKind::{COMMA, EQ, IDENT, STRING},
+    TextRange, TextSize,
+};
+
+/// Format-like macros, with the index of their format string argument.
+const FORMAT_MACROS: &[(&str, usize)] = &[
+    ("format", 0),
+    ("format_args", 0),
+    ("format_args_nl", 0),
+    ("print", 0),
+    ("println", 0),
+    ("eprint", 0),
+    ("eprintln", 0),
+    ("write", 1),
+    ("writeln", 1),
+];
+
+/// A call of a format-like macro, like `println!("{} {}", a, b)`.
+#[derive(Debug)]
+pub struct FormatCall {
+    pub string: ast::String,
+    /// The arguments after the format string.
+    pub args: Vec<FormatArg>,
+    /// The offset of the closing delimiter of the macro call.
+    pub end: TextSize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct FormatArg {
+    pub range: TextRange,
+    /// The name of a `name = value` argument.
+    pub name: Option<String>,
+}
+
+impl FormatCall {
+    pub fn from_macro_call(macro_call: &ast::MacroCall) -> Option<FormatCall> {
+        let name = macro_call.path()?.segment()?.name_ref()?;
+        let &(_, string_index) =
+            FORMAT_MACROS.iter().find(|(macro_name, _)| name.text() == *macro_name)?;
+        let token_tree = macro_call.token_tree()?;
+        let r_delim = token_tree.right_delimiter_token()?;
+
+        let mut segments: Vec<Vec<SyntaxElement>> = vec![Vec::new()];
+        let elements = token_tree.syntax().children_with_tokens().skip(1);
+        for element in
+            elements.filter(|it| !it.kind().is_trivia() && it.as_token() != Some(&r_delim))
+        {
+            let segment = segments.last_mut()?;
+            // `<` and `>` don't delimit token trees, so a comma might separate
+            // generic arguments, like in `HashMap::<u32, u32>::new()`. It only
+            // ends the argument if the argument is an expression by then.
+            if element.kind() == COMMA && (segment.is_empty() || is_expr(segment)) {
+                segments.push(Vec::new());
+            } else {
+                segment.push(element);
+            }
+        }
+
+        let string = match segments.get(string_index)?.as_slice() {
+            [NodeOrToken::Token(token)] if token.kind() == STRING => {
+                ast::String::cast(token.clone())?
+            }
+            _ => return None,
+        };
+        let args = segments
+            .iter()
+            .skip(string_index + 1)
+            .filter_map(|segment| {
+                let range = segment.first()?.text_range().cover(segment.last()?.text_range());
+                let name = match segment.as_slice() {
+                    [name, eq, _, ..] if name.kind() == IDENT && eq.kind() == EQ => {
+                        Some(element_text(name))
+                    }
+                    _ => None,
+                };
+                Some(FormatArg { range, name })
+            })
+            .collect();
+        Some(FormatCall { string, args, end: r_delim.text_range().start() })
+    }
+
+    /// Finds the call whose format string is `string`.
+    pub fn from_string(string: &ast::String) -> Option<FormatCall> {
+        let token_tree = ast::TokenTree::cast(string.syntax().parent())?;
+        let macro_call = token_tree.syntax().parent().and_then(ast::MacroCall::cast)?;
+        let call = FormatCall::from_macro_call(&macro_call)?;
+        if call.string.syntax() != string.syntax() {
+            return None;
+        }
+        Some(call)
+    }
+}
+
+fn is_expr(elements: &[SyntaxElement]) -> bool {
+    let (first, last) = match (elements.first(), elements.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return false,
+    };
+    let range = first.text_range().cover(last.text_range());
+    let root = match first.parent() {
+        Some(it) => it,
+        None => return false,
+    };
+    let text = root.text().slice(range - root.text_range().start()).to_string();
+    ast::Expr::parse(&text).is_ok()
+}
+
+fn element_text(element: &SyntaxElement) -> String {
+    match element {
+        NodeOrToken::Node(node) => node.text().to_string(),
+        NodeOrToken::Token(token) => token.text().to_string(),
+    }
+}
+
+/// A `{...}` placeholder of a format string.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Placeholder {
+    pub arg: PlaceholderArg,
+    /// The format spec after the `:`, like `#?` or `>8`.
+    pub spec: String,
+    /// The range of the placeholder, braces included, relative to the start
+    /// of the string literal.
+    pub range: TextRange,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PlaceholderArg {
+    /// `{}`, the next positional argument.
+    Next,
+    /// `{0}`
+    Index(usize),
+    /// `{name}`, a named argument or a captured variable.
+    Name(String),
+}
+
+/// Parses the placeholders of the string literal `text`, quotes included.
+/// Returns `None` if the string is not a valid format string.
+pub fn placeholders(text: &str) -> Option<Vec<Placeholder>> {
+    let mut res = Vec::new();
+    let mut scanner = Scanner::new(text);
+    while let Some(start) = scanner.next_placeholder()? {
+        let len = text[start..].find('}')?;
+        let contents = &text[start + 1..start + len];
+        let (arg, spec) = match contents.find(':') {
+            Some(idx) => (&contents[..idx], &contents[idx + 1..]),
+            None => (contents, ""),
+        };
+        let arg = if arg.is_empty() {
+            PlaceholderArg::Next
+        } else if let Ok(idx) = arg.parse() {
+            PlaceholderArg::Index(idx)
+        } else if is_ident(arg) {
+            PlaceholderArg::Name(arg.to_string())
+        } else {
+            return None;
+        };
+        let range = TextRange::at(TextSize::from(start as u32), TextSize::from(len as u32 + 1));
+        res.push(Placeholder { arg, spec: spec.to_string(), range });
+        scanner.pos = start + len + 1;
+    }
+    Some(res)
+}
+
+/// If `prefix`, the start of a string literal, ends inside of a placeholder,
+/// returns the offset just after its `{`.
+pub fn open_placeholder(prefix: &str) -> Option<usize> {
+    let mut scanner = Scanner::new(prefix);
+    let mut open = None;
+    while let Some(Some(start)) = scanner.next_placeholder() {
+        match prefix[start..].find('}') {
+            Some(len) => scanner.pos = start + len + 1,
+            None => {
+                open = Some(start + 1);
+                break;
+            }
+        }
+    }
+    open
+}
+
+fn is_ident(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!(chars.next(), Some(c) if c == '_' || c.is_alphabetic())
+        && chars.all(|c| c == '_' || c.is_alphanumeric())
+}
+
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Scanner<'a> {
+        // Skip the opening quote.
+        Scanner { text, pos: text.find('"').map_or(text.len(), |it| it + 1) }
+    }
+
+    /// Returns the offset of the `{` of the next placeholder, skipping
+    /// escapes and `{{`. The outer `None` means an unmatched `}`.
+    fn next_placeholder(&mut self) -> Option<Option<usize>> {
+        let bytes = self.text.as_bytes();
+        while self.pos < bytes.len() {
+            let pos = self.pos;
+            let next = bytes.get(pos + 1).copied();
+            match (bytes[pos], next) {
+                (b'\\', Some(b'u')) => match self.text[pos..].find('}') {
+                    Some(len) => self.pos += len + 1,
+                    None => self.pos = bytes.len(),
+                },
+                (b'\\', _) | (b'{', Some(b'{')) | (b'}', Some(b'}')) => self.pos += 2,
+                (b'{', _) => return Some(Some(pos)),
+                (b'}', _) => return None,
+                _ => self.pos += 1,
+            }
+        }
+        Some(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(text: &str, expected: Vec<(PlaceholderArg, &str)>) {
+        let actual: Vec<_> =
+            placeholders(text).unwrap().into_iter().map(|it| (it.arg, it.spec)).collect();
+        let expected: Vec<_> =
+            expected.into_iter().map(|(arg, spec)| (arg, spec.to_string())).collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parses_placeholders() {
+        check(
+            r#""{} {{}} {0:?} {name:>8} \u{1F600} {:.3}""#,
+            vec![
+                (PlaceholderArg::Next, ""),
+                (PlaceholderArg::Index(0), "?"),
+                (PlaceholderArg::Name("name".to_string()), ">8"),
+                (PlaceholderArg::Next, ".3"),
+            ],
+        );
+        assert_eq!(placeholders(r#""{} }""#), None);
+        assert_eq!(placeholders(r#""{a.b}""#), None);
+    }
+
+    #[test]
+    fn finds_open_placeholder() {
+        assert_eq!(open_placeholder(r#""{} {na"#), Some(5));
+        assert_eq!(open_placeholder(r#""{} {{na"#), None);
+        assert_eq!(open_placeholder(r#""{x:"#), Some(2));
+    }
+}
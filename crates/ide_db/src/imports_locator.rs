@@ -1,7 +1,9 @@
 //! This module contains an import search funcionality that is provided to the assists module.
 //! Later, this should be moved away to a separate crate that is accessible from the assists module.
 
-use hir::{import_map, Crate, MacroDef, ModuleDef, Semantics};
+use hir::{
+    import_map, AsAssocItem, AssocItem, AssocItemContainer, Crate, MacroDef, ModuleDef, Semantics,
+};
 use syntax::{ast, AstNode, SyntaxKind::NAME};
 
 use crate::{
@@ -55,6 +57,32 @@ pub fn find_similar_imports<'a>(
     find_imports(sema, krate, local_query, external_query)
 }
 
+/// Finds the associated items of traits, whose names are similar to `name`.
+pub fn find_similar_trait_assoc_items<'a>(
+    sema: &'a Semantics<'a, RootDatabase>,
+    krate: Crate,
+    limit: Option<usize>,
+    name: &str,
+) -> impl Iterator<Item = AssocItem> + 'a {
+    let _p = profile::span("find_similar_trait_assoc_items");
+
+    let mut external_query = import_map::Query::new(name).assoc_items_only();
+    let mut local_query = symbol_index::Query::new(name.to_string());
+    if let Some(limit) = limit {
+        local_query.limit(limit);
+        external_query = external_query.limit(limit);
+    }
+
+    find_imports(sema, krate, local_query, external_query)
+        .filter_map(move |candidate| match candidate.left()? {
+            ModuleDef::Function(it) => it.as_assoc_item(sema.db),
+            ModuleDef::Const(it) => it.as_assoc_item(sema.db),
+            ModuleDef::TypeAlias(it) => it.as_assoc_item(sema.db),
+            _ => None,
+        })
+        .filter(move |item| matches!(item.container(sema.db), AssocItemContainer::Trait(_)))
+}
+
 fn find_imports<'a>(
     sema: &Semantics<'a, RootDatabase>,
     krate: Crate,
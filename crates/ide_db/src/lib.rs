@@ -14,6 +14,7 @@ pub mod ty_filter;
 pub mod traits;
 pub mod call_info;
 pub mod helpers;
+pub mod cargo_toml;
 
 use std::{fmt, sync::Arc};
 
@@ -105,6 +106,7 @@ impl RootDatabase {
     pub fn new(lru_capacity: Option<usize>) -> RootDatabase {
         let mut db = RootDatabase { storage: salsa::Storage::default() };
         db.set_crate_graph_with_durability(Default::default(), Durability::HIGH);
+        db.set_package_index_with_durability(Default::default(), Durability::HIGH);
         db.set_local_roots_with_durability(Default::default(), Durability::HIGH);
         db.set_library_roots_with_durability(Default::default(), Durability::HIGH);
         db.update_lru_capacity(lru_capacity);
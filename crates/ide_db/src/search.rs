@@ -119,8 +119,13 @@ impl Definition {
         let module_src = module.definition_source(db);
         let file_id = module_src.file_id.original_file(db);
 
-        if let Definition::Local(var) = self {
-            let range = match var.parent(db) {
+        let body_parent = match self {
+            Definition::Local(var) => Some(var.parent(db)),
+            Definition::Label(label) => Some(label.parent(db)),
+            _ => None,
+        };
+        if let Some(parent) = body_parent {
+            let range = match parent {
                 DefWithBody::Function(f) => f.source(db).value.syntax().text_range(),
                 DefWithBody::Const(c) => c.source(db).value.syntax().text_range(),
                 DefWithBody::Static(s) => s.source(db).value.syntax().text_range(),
@@ -8,7 +8,7 @@ use std::iter;
 use syntax::ast::{self, make};
 
 /// Enum types that implement `std::ops::Try` trait.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum TryEnum {
     Result,
     Option,
@@ -74,6 +74,9 @@ pub struct MacroRules {
     rules: Vec<Rule>,
     /// Highest id of the token we have in TokenMap
     shift: Shift,
+    /// Whether the macro is defined in a Rust 2021 crate, where `pat`
+    /// fragments match top-level or-patterns.
+    is_2021: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -156,7 +159,7 @@ pub enum Origin {
 }
 
 impl MacroRules {
-    pub fn parse(tt: &tt::Subtree) -> Result<MacroRules, ParseError> {
+    pub fn parse(tt: &tt::Subtree, is_2021: bool) -> Result<MacroRules, ParseError> {
         // Note: this parsing can be implemented using mbe machinery itself, by
         // matching against `$($lhs:tt => $rhs:tt);*` pattern, but implementing
         // manually seems easier.
@@ -177,7 +180,7 @@ impl MacroRules {
             validate(&rule.lhs)?;
         }
 
-        Ok(MacroRules { rules, shift: Shift::new(tt) })
+        Ok(MacroRules { rules, shift: Shift::new(tt), is_2021 })
     }
 
     pub fn expand(&self, tt: &tt::Subtree) -> ExpandResult<tt::Subtree> {
@@ -11,13 +11,17 @@ use syntax::SmolStr;
 use crate::{ExpandError, ExpandResult};
 
 pub(crate) fn expand(rules: &crate::MacroRules, input: &tt::Subtree) -> ExpandResult<tt::Subtree> {
-    expand_rules(&rules.rules, input)
+    expand_rules(&rules.rules, input, rules.is_2021)
 }
 
-fn expand_rules(rules: &[crate::Rule], input: &tt::Subtree) -> ExpandResult<tt::Subtree> {
+fn expand_rules(
+    rules: &[crate::Rule],
+    input: &tt::Subtree,
+    is_2021: bool,
+) -> ExpandResult<tt::Subtree> {
     let mut match_: Option<(matcher::Match, &crate::Rule)> = None;
     for rule in rules {
-        let new_match = match matcher::match_(&rule.lhs, input) {
+        let new_match = match matcher::match_(&rule.lhs, input, is_2021) {
             Ok(m) => m,
             Err(_e) => {
                 // error in pattern parsing
@@ -167,7 +171,7 @@ mod tests {
 
         let (definition_tt, _) =
             ast_to_token_tree(&macro_definition.token_tree().unwrap()).unwrap();
-        crate::MacroRules::parse(&definition_tt).unwrap()
+        crate::MacroRules::parse(&definition_tt, false).unwrap()
     }
 
     fn expand_first(rules: &crate::MacroRules, invocation: &str) -> ExpandResult<tt::Subtree> {
@@ -178,6 +182,6 @@ mod tests {
         let (invocation_tt, _) =
             ast_to_token_tree(&macro_invocation.token_tree().unwrap()).unwrap();
 
-        expand_rules(&rules.rules, &invocation_tt)
+        expand_rules(&rules.rules, &invocation_tt, false)
     }
 }
@@ -83,13 +83,17 @@ impl Match {
 // sense to try using it. Matching errors are added to the `Match`. It might
 // make sense to make pattern parsing a separate step?
 
-pub(super) fn match_(pattern: &tt::Subtree, src: &tt::Subtree) -> Result<Match, ExpandError> {
+pub(super) fn match_(
+    pattern: &tt::Subtree,
+    src: &tt::Subtree,
+    is_2021: bool,
+) -> Result<Match, ExpandError> {
     assert!(pattern.delimiter == None);
 
     let mut res = Match::default();
     let mut src = TtIter::new(src);
 
-    match_subtree(&mut res, pattern, &mut src)?;
+    match_subtree(&mut res, pattern, &mut src, is_2021)?;
 
     if src.len() > 0 {
         res.unmatched_tts += src.len();
@@ -103,6 +107,7 @@ fn match_subtree(
     res: &mut Match,
     pattern: &tt::Subtree,
     src: &mut TtIter,
+    is_2021: bool,
 ) -> Result<(), ExpandError> {
     for op in parse_pattern(pattern) {
         match op? {
@@ -145,7 +150,7 @@ fn match_subtree(
                     continue;
                 }
                 let mut src = TtIter::new(rhs);
-                match_subtree(res, lhs, &mut src)?;
+                match_subtree(res, lhs, &mut src, is_2021)?;
                 if src.len() > 0 {
                     res.add_err(err!("leftover tokens"));
                 }
@@ -159,7 +164,7 @@ fn match_subtree(
                     }
                 };
                 let ExpandResult { value: matched, err: match_err } =
-                    match_meta_var(kind.as_str(), src);
+                    match_meta_var(kind.as_str(), src, is_2021);
                 match matched {
                     Some(fragment) => {
                         res.bindings.inner.insert(name.clone(), Binding::Fragment(fragment));
@@ -172,7 +177,7 @@ fn match_subtree(
                 }
             }
             Op::Repeat { subtree, kind, separator } => {
-                match_repeat(res, subtree, kind, separator, src)?;
+                match_repeat(res, subtree, kind, separator, src, is_2021)?;
             }
         }
     }
@@ -376,6 +381,7 @@ pub(super) fn match_repeat(
     kind: RepeatKind,
     separator: Option<Separator>,
     src: &mut TtIter,
+    is_2021: bool,
 ) -> Result<(), ExpandError> {
     // Dirty hack to make macro-expansion terminate.
     // This should be replaced by a propper macro-by-example implementation
@@ -392,7 +398,7 @@ pub(super) fn match_repeat(
         }
 
         let mut nested = Match::default();
-        match_subtree(&mut nested, pattern, &mut fork)?;
+        match_subtree(&mut nested, pattern, &mut fork, is_2021)?;
         if nested.err.is_none() {
             limit -= 1;
             if limit == 0 {
@@ -438,12 +444,13 @@ pub(super) fn match_repeat(
     Ok(())
 }
 
-fn match_meta_var(kind: &str, input: &mut TtIter) -> ExpandResult<Option<Fragment>> {
+fn match_meta_var(kind: &str, input: &mut TtIter, is_2021: bool) -> ExpandResult<Option<Fragment>> {
     let fragment = match kind {
         "path" => Path,
         "expr" => Expr,
         "ty" => Type,
-        "pat" => Pattern,
+        "pat" if is_2021 => PatternTop,
+        "pat" | "pat_param" => Pattern,
         "stmt" => Statement,
         "block" => Block,
         "meta" => MetaItem,
@@ -52,7 +52,7 @@ mod rule_parsing {
 
         let (definition_tt, _) =
             ast_to_token_tree(&macro_definition.token_tree().unwrap()).unwrap();
-        crate::MacroRules::parse(&definition_tt)
+        crate::MacroRules::parse(&definition_tt, false)
     }
 }
 
@@ -809,6 +809,25 @@ fn test_pat_() {
     .assert_expand_items("foo! { (a, b) }", "fn foo () {let (a , b) ;}");
 }
 
+#[test]
+fn test_pat_2021() {
+    let definition = r#"
+        macro_rules! foo {
+            ($ i:pat) => { fn foo() { match 0 { $ i => () } } }
+        }
+"#;
+    parse_macro_2021(definition)
+        .assert_expand_items("foo! { 1 | 2 }", "fn foo () {match 0 {1 | 2 => ()}}");
+    parse_macro(definition).assert_expand_err(
+        "foo! { 1 | 2 }",
+        &ExpandError::BindingError("leftover tokens".to_string()),
+    );
+    parse_macro_2021(&definition.replace(":pat", ":pat_param")).assert_expand_err(
+        "foo! { 1 | 2 }",
+        &ExpandError::BindingError("leftover tokens".to_string()),
+    );
+}
+
 #[test]
 fn test_stmt() {
     parse_macro(
@@ -1708,14 +1727,20 @@ fn parse_macro_to_tt(ra_fixture: &str) -> tt::Subtree {
 
 pub(crate) fn parse_macro(ra_fixture: &str) -> MacroFixture {
     let definition_tt = parse_macro_to_tt(ra_fixture);
-    let rules = MacroRules::parse(&definition_tt).unwrap();
+    let rules = MacroRules::parse(&definition_tt, false).unwrap();
+    MacroFixture { rules }
+}
+
+fn parse_macro_2021(ra_fixture: &str) -> MacroFixture {
+    let definition_tt = parse_macro_to_tt(ra_fixture);
+    let rules = MacroRules::parse(&definition_tt, true).unwrap();
     MacroFixture { rules }
 }
 
 pub(crate) fn parse_macro_error(ra_fixture: &str) -> ParseError {
     let definition_tt = parse_macro_to_tt(ra_fixture);
 
-    match MacroRules::parse(&definition_tt) {
+    match MacroRules::parse(&definition_tt, false) {
         Ok(_) => panic!("Expect error"),
         Err(err) => err,
     }
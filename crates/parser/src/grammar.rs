@@ -62,6 +62,10 @@ pub(crate) mod fragments {
         let _ = expressions::expr(p);
     }
 
+    pub(crate) fn pattern_top(p: &mut Parser) {
+        patterns::pattern_top(p)
+    }
+
     pub(crate) fn stmt(p: &mut Parser) {
         expressions::stmt(p, expressions::StmtWithSemi::No)
     }
@@ -90,6 +90,9 @@ pub enum FragmentKind {
     Statement,
     Type,
     Pattern,
+    /// A pattern which may be an or-pattern, as matched by `pat` fragments in
+    /// Rust 2021.
+    PatternTop,
     Item,
     Block,
     Visibility,
@@ -113,6 +116,7 @@ pub fn parse_fragment(
         FragmentKind::Expr => grammar::fragments::expr,
         FragmentKind::Type => grammar::fragments::type_,
         FragmentKind::Pattern => grammar::fragments::pattern_single,
+        FragmentKind::PatternTop => grammar::fragments::pattern_top,
         FragmentKind::Item => grammar::fragments::item,
         FragmentKind::Block => grammar::fragments::block_expr,
         FragmentKind::Visibility => grammar::fragments::opt_visibility,
@@ -0,0 +1,232 @@
+//! Handles build script specific information: `OUT_DIR`, `cfg`s and
+//! environment variables emitted by build scripts, and the dylibs of
+//! proc-macro crates.
+//!
+//! Collecting this information requires running `cargo check`, which is slow,
+//! so it is done separately from loading the workspace itself.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    ffi::OsStr,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::Result;
+use cargo_metadata::{BuildScript, Message};
+use paths::AbsPathBuf;
+use rustc_hash::FxHashMap;
+
+use crate::{cfg_flag::CfgFlag, CargoConfig, CargoWorkspace, Package, TargetKind};
+
+/// The results of running the build scripts and building the proc-macros of a
+/// single package.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildData {
+    /// Directory where the build script might place its output.
+    pub out_dir: Option<AbsPathBuf>,
+    /// `cfg`s set with `cargo:rustc-cfg`.
+    pub cfgs: Vec<CfgFlag>,
+    /// Environment variables set with `cargo:rustc-env`.
+    pub envs: Vec<(String, String)>,
+    /// Path to the proc-macro library file, if the package is a proc-macro.
+    pub proc_macro_dylib_path: Option<AbsPathBuf>,
+}
+
+/// Build data of all the packages of a `CargoWorkspace`, keyed by package id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceBuildData {
+    per_package: FxHashMap<String, BuildData>,
+    fingerprint: u64,
+}
+
+impl WorkspaceBuildData {
+    /// Runs `cargo check` on the workspace and collects the build data of its
+    /// packages.
+    ///
+    /// Build failures are not fatal: whatever cargo managed to report is kept.
+    ///
+    /// Packages whose features or target are overridden are checked again on
+    /// their own, with the overridden settings.
+    pub fn collect(cargo: &CargoWorkspace, config: &CargoConfig) -> Result<WorkspaceBuildData> {
+        let fingerprint = WorkspaceBuildData::fingerprint(cargo, config);
+        let mut res = WorkspaceBuildData { per_package: FxHashMap::default(), fingerprint };
+
+        let cmd = check_command(cargo, config, None);
+        res.collect_from(cmd, None)?;
+
+        for pkg in cargo.packages() {
+            let data = &cargo[pkg];
+            let overrides = &data.overrides;
+            if overrides.features.is_none()
+                && !overrides.no_default_features
+                && overrides.target.is_none()
+            {
+                continue;
+            }
+            let cmd = check_command(cargo, config, Some(pkg));
+            res.collect_from(cmd, Some(&data.id))?;
+        }
+        Ok(res)
+    }
+
+    /// Runs `cmd` and records the build data it reports, only for the package
+    /// `only` if given.
+    fn collect_from(&mut self, mut cmd: Command, only: Option<&str>) -> Result<()> {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::null()).stdin(Stdio::null());
+
+        log::info!("Collecting build data by {:?}", cmd);
+        let output = cmd.output()?;
+        if !output.status.success() {
+            log::warn!("{:?} failed ({}), build data may be incomplete", cmd, output.status);
+        }
+
+        let is_collected = |package_id: &str| only.map_or(true, |it| it == package_id);
+        for message in Message::parse_stream(output.stdout.as_slice()) {
+            let message = match message {
+                Ok(it) => it,
+                Err(err) => {
+                    log::error!("Invalid json from cargo check: {}", err);
+                    continue;
+                }
+            };
+            match message {
+                Message::BuildScriptExecuted(BuildScript {
+                    package_id,
+                    out_dir,
+                    cfgs,
+                    env,
+                    ..
+                }) => {
+                    if !is_collected(&package_id.repr) {
+                        continue;
+                    }
+                    let data = self.per_package.entry(package_id.repr).or_default();
+                    // cargo_metadata crate returns default (empty) path for
+                    // older cargos, which is not absolute, so work around that.
+                    if out_dir != PathBuf::default() {
+                        data.out_dir = Some(AbsPathBuf::assert(out_dir));
+                    }
+                    data.cfgs = cfgs
+                        .iter()
+                        .filter_map(|cfg| match cfg.parse::<CfgFlag>() {
+                            Ok(it) => Some(it),
+                            Err(err) => {
+                                log::error!("Invalid cfg from build script: {}", err);
+                                None
+                            }
+                        })
+                        .collect();
+                    data.envs = env;
+                }
+                Message::CompilerArtifact(message) => {
+                    if !is_collected(&message.package_id.repr) {
+                        continue;
+                    }
+                    if message.target.kind.contains(&"proc-macro".to_string()) {
+                        // Skip rmeta file
+                        if let Some(filename) = message.filenames.iter().find(|name| is_dylib(name))
+                        {
+                            let filename = AbsPathBuf::assert(filename.clone());
+                            let data = self.per_package.entry(message.package_id.repr).or_default();
+                            data.proc_macro_dylib_path = Some(filename);
+                        }
+                    }
+                }
+                Message::CompilerMessage(_)
+                | Message::Unknown
+                | Message::BuildFinished(_)
+                | Message::TextLine(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Hashes everything the build data depends on: the relevant parts of the
+    /// config and of the package overrides, `Cargo.lock` and the sources of
+    /// the build scripts.
+    ///
+    /// Build data with the same fingerprint can be reused without rerunning
+    /// `cargo check`.
+    pub fn fingerprint(cargo: &CargoWorkspace, config: &CargoConfig) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        config.no_default_features.hash(&mut hasher);
+        config.all_features.hash(&mut hasher);
+        config.features.hash(&mut hasher);
+        config.target.hash(&mut hasher);
+
+        let lock_file = cargo.workspace_root().join("Cargo.lock");
+        fs::read(&lock_file).ok().hash(&mut hasher);
+        for pkg in cargo.packages() {
+            let overrides = &cargo[pkg].overrides;
+            overrides.features.hash(&mut hasher);
+            overrides.no_default_features.hash(&mut hasher);
+            overrides.target.hash(&mut hasher);
+            for &tgt in cargo[pkg].targets.iter() {
+                if cargo[tgt].kind == TargetKind::BuildScript {
+                    cargo[tgt].root.hash(&mut hasher);
+                    fs::read(&cargo[tgt].root).ok().hash(&mut hasher);
+                }
+            }
+        }
+        hasher.finish()
+    }
+
+    pub fn is_fresh(&self, cargo: &CargoWorkspace, config: &CargoConfig) -> bool {
+        self.fingerprint == WorkspaceBuildData::fingerprint(cargo, config)
+    }
+
+    pub fn get(&self, package_id: &str) -> Option<&BuildData> {
+        self.per_package.get(package_id)
+    }
+}
+
+/// The `cargo check` command collecting the build data of the workspace, or of
+/// the single package `package` with its overrides applied.
+fn check_command(
+    cargo: &CargoWorkspace,
+    config: &CargoConfig,
+    package: Option<Package>,
+) -> Command {
+    let mut cmd = Command::new(toolchain::cargo());
+    cmd.args(&["check", "--message-format=json", "--manifest-path"])
+        .arg(cargo.manifest_path().as_ref() as &Path);
+
+    let overrides = package.map(|pkg| &cargo[pkg].overrides);
+    if let Some(pkg) = package {
+        cmd.arg("-p").arg(cargo.package_flag(&cargo[pkg]));
+    }
+
+    let target = overrides.and_then(|it| it.target.as_ref()).or_else(|| config.target.as_ref());
+    if let Some(target) = target {
+        cmd.args(&["--target", target]);
+    }
+
+    let features = overrides.and_then(|it| it.features.as_ref()).unwrap_or(&config.features);
+    let no_default_features =
+        config.no_default_features || overrides.map_or(false, |it| it.no_default_features);
+    if config.all_features && overrides.map_or(true, |it| it.features.is_none()) {
+        cmd.arg("--all-features");
+    } else {
+        if no_default_features {
+            // FIXME: `NoDefaultFeatures` is mutual exclusive with `SomeFeatures`
+            // https://github.com/oli-obk/cargo_metadata/issues/79
+            cmd.arg("--no-default-features");
+        }
+        if !features.is_empty() {
+            cmd.arg("--features");
+            cmd.arg(features.join(" "));
+        }
+    }
+    cmd
+}
+
+// FIXME: File a better way to know if it is a dylib
+fn is_dylib(path: &Path) -> bool {
+    match path.extension().and_then(OsStr::to_str).map(|it| it.to_string().to_lowercase()) {
+        None => false,
+        Some(ext) => matches!(ext.as_str(), "dll" | "dylib" | "so"),
+    }
+}
@@ -1,22 +1,22 @@
 //! FIXME: write short doc here
 
-use std::{
-    ffi::OsStr,
-    ops,
-    path::{Path, PathBuf},
-    process::Command,
-};
+use std::{ops, process::Command};
 
 use anyhow::{Context, Result};
 use arena::{Arena, Idx};
 use base_db::Edition;
-use cargo_metadata::{BuildScript, CargoOpt, Message, MetadataCommand, PackageId};
+use cargo_metadata::{CargoOpt, MetadataCommand};
+use cfg::CfgDiff;
 use itertools::Itertools;
 use paths::{AbsPath, AbsPathBuf};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use stdx::split_once;
 
-use crate::cfg_flag::CfgFlag;
-use crate::utf8_stdout;
+use crate::{
+    build_data::{BuildData, WorkspaceBuildData},
+    cfg_flag::CfgFlag,
+    utf8_stdout,
+};
 
 /// `CargoWorkspace` represents the logical structure of, well, a Cargo
 /// workspace. It pretty closely mirrors `cargo metadata` output.
@@ -33,6 +33,9 @@ pub struct CargoWorkspace {
     packages: Arena<PackageData>,
     targets: Arena<TargetData>,
     workspace_root: AbsPathBuf,
+    manifest_path: AbsPathBuf,
+    /// The workspace members depending directly on each package.
+    rev_deps: FxHashMap<Package, Vec<Package>>,
 }
 
 impl ops::Index<Package> for CargoWorkspace {
@@ -61,7 +64,8 @@ pub struct CargoConfig {
     /// This will be ignored if `cargo_all_features` is true.
     pub features: Vec<String>,
 
-    /// Runs cargo check on launch to figure out the correct values of OUT_DIR
+    /// Runs cargo check in the background after loading the workspace to figure
+    /// out the build data (OUT_DIR, build script cfgs, proc-macro dylibs).
     pub load_out_dirs_from_check: bool,
 
     /// rustc target
@@ -73,6 +77,26 @@ pub struct CargoConfig {
 
     /// rustc private crate source
     pub rustc_source: Option<AbsPathBuf>,
+
+    /// Settings replacing the ones above for single packages, keyed by package
+    /// name.
+    pub package_overrides: FxHashMap<String, PackageOverride>,
+}
+
+/// Cargo settings that replace the workspace-wide ones for a single package.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct PackageOverride {
+    /// Features to activate instead of the ones resolved by `cargo metadata`.
+    pub features: Option<Vec<String>>,
+    /// Do not activate the `default` feature.
+    pub no_default_features: bool,
+    /// rustc target
+    pub target: Option<String>,
+    /// Extra cfgs to enable.
+    pub cfgs: Vec<CfgFlag>,
+    /// Changes to the cfgs of the package, each creating an additional
+    /// instance of its crates to also analyze code inactive with the main cfgs.
+    pub cfg_alternatives: Vec<CfgDiff>,
 }
 
 pub type Package = Idx<PackageData>;
@@ -81,6 +105,8 @@ pub type Target = Idx<TargetData>;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct PackageData {
+    /// The package id, as reported by `cargo metadata`.
+    pub id: String,
     pub version: String,
     pub name: String,
     pub manifest: AbsPathBuf,
@@ -88,11 +114,20 @@ pub struct PackageData {
     pub is_member: bool,
     pub dependencies: Vec<PackageDependency>,
     pub edition: Edition,
+    /// Features enabled for this build.
     pub features: Vec<String>,
-    pub cfgs: Vec<CfgFlag>,
+    /// Features declared in the manifest, including optional dependencies.
+    pub declared_features: Vec<String>,
+    /// What each feature of the `[features]` table enables.
+    pub feature_dependencies: FxHashMap<String, Vec<String>>,
+    /// Environment variables cargo sets for the package.
     pub envs: Vec<(String, String)>,
-    pub out_dir: Option<AbsPathBuf>,
-    pub proc_macro_dylib_path: Option<AbsPathBuf>,
+    /// Outputs of the build script and the proc-macro build, filled in by
+    /// `CargoWorkspace::set_build_data` once they are known.
+    pub build_data: BuildData,
+    /// Settings overriding the workspace-wide ones, set with
+    /// `CargoWorkspace::set_package_overrides`.
+    pub overrides: PackageOverride,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -118,6 +153,7 @@ pub enum TargetKind {
     Example,
     Test,
     Bench,
+    BuildScript,
     Other,
 }
 
@@ -129,6 +165,7 @@ impl TargetKind {
                 "test" => TargetKind::Test,
                 "bench" => TargetKind::Bench,
                 "example" => TargetKind::Example,
+                "custom-build" => TargetKind::BuildScript,
                 "proc-macro" => TargetKind::Lib,
                 _ if kind.contains("lib") => TargetKind::Lib,
                 _ => continue,
@@ -142,6 +179,44 @@ impl PackageData {
     pub fn root(&self) -> &AbsPath {
         self.manifest.parent().unwrap()
     }
+
+    /// Features enabled for this package, taking its overrides into account.
+    pub fn active_features(&self) -> Vec<String> {
+        if self.overrides.features.is_none() && !self.overrides.no_default_features {
+            return self.features.clone();
+        }
+
+        let mut stack: Vec<&str> =
+            self.overrides.features.iter().flatten().map(|it| it.as_str()).collect();
+        if !self.overrides.no_default_features {
+            stack.push("default");
+        }
+        let mut res = Vec::new();
+        while let Some(feature) = stack.pop() {
+            // `dep/feature` enables the optional dependency `dep`, unless it is
+            // a weak `dep?/feature`. A namespaced `dep:dep` only enables the
+            // dependency, without the `dep` feature.
+            if feature.starts_with("dep:") {
+                continue;
+            }
+            let feature = match split_once(feature, '/') {
+                Some((dep, _)) if dep.ends_with('?') => continue,
+                Some((dep, _)) => dep,
+                None => feature,
+            };
+            if !self.declared_features.iter().any(|it| it == feature)
+                || res.iter().any(|it| it == feature)
+            {
+                continue;
+            }
+            res.push(feature.to_string());
+            if let Some(implied) = self.feature_dependencies.get(feature) {
+                stack.extend(implied.iter().map(|it| it.as_str()));
+            }
+        }
+        res.sort();
+        res
+    }
 }
 
 impl CargoWorkspace {
@@ -200,18 +275,6 @@ impl CargoWorkspace {
             format!("Failed to run `cargo metadata --manifest-path {}`", cargo_toml.display())
         })?;
 
-        let mut out_dir_by_id = FxHashMap::default();
-        let mut cfgs = FxHashMap::default();
-        let mut envs = FxHashMap::default();
-        let mut proc_macro_dylib_paths = FxHashMap::default();
-        if config.load_out_dirs_from_check {
-            let resources = load_extern_resources(cargo_toml, config)?;
-            out_dir_by_id = resources.out_dirs;
-            cfgs = resources.cfgs;
-            envs = resources.env;
-            proc_macro_dylib_paths = resources.proc_dylib_paths;
-        }
-
         let mut pkg_by_id = FxHashMap::default();
         let mut packages = Arena::default();
         let mut targets = Arena::default();
@@ -220,9 +283,24 @@ impl CargoWorkspace {
 
         meta.packages.sort_by(|a, b| a.id.cmp(&b.id));
         for meta_pkg in meta.packages {
-            let id = meta_pkg.id.clone();
-            inject_cargo_env(&meta_pkg, envs.entry(id).or_default());
-
+            let mut envs = Vec::new();
+            inject_cargo_env(&meta_pkg, &mut envs);
+
+            let mut declared_features: Vec<String> = meta_pkg.features.keys().cloned().collect();
+            declared_features.extend(
+                meta_pkg
+                    .dependencies
+                    .iter()
+                    .filter(|dep| dep.optional)
+                    .map(|dep| dep.rename.clone().unwrap_or_else(|| dep.name.clone())),
+            );
+            declared_features.sort();
+            declared_features.dedup();
+            let feature_dependencies = meta_pkg
+                .features
+                .iter()
+                .map(|(name, implied)| (name.clone(), implied.clone()))
+                .collect();
             let cargo_metadata::Package { id, edition, name, manifest_path, version, .. } =
                 meta_pkg;
             let is_member = ws_members.contains(&id);
@@ -230,6 +308,7 @@ impl CargoWorkspace {
                 .parse::<Edition>()
                 .with_context(|| format!("Failed to parse edition {}", edition))?;
             let pkg = packages.alloc(PackageData {
+                id: id.repr.clone(),
                 name,
                 version: version.to_string(),
                 manifest: AbsPathBuf::assert(manifest_path),
@@ -238,10 +317,11 @@ impl CargoWorkspace {
                 edition,
                 dependencies: Vec::new(),
                 features: Vec::new(),
-                cfgs: cfgs.get(&id).cloned().unwrap_or_default(),
-                envs: envs.get(&id).cloned().unwrap_or_default(),
-                out_dir: out_dir_by_id.get(&id).cloned(),
-                proc_macro_dylib_path: proc_macro_dylib_paths.get(&id).cloned(),
+                declared_features,
+                feature_dependencies,
+                envs,
+                build_data: BuildData::default(),
+                overrides: PackageOverride::default(),
             });
             let pkg_data = &mut packages[pkg];
             pkg_by_id.insert(id, pkg);
@@ -288,7 +368,14 @@ impl CargoWorkspace {
         }
 
         let workspace_root = AbsPathBuf::assert(meta.workspace_root);
-        Ok(CargoWorkspace { packages, targets, workspace_root: workspace_root })
+        let manifest_path = cargo_toml.to_path_buf();
+        let mut rev_deps: FxHashMap<Package, Vec<Package>> = FxHashMap::default();
+        for (pkg, data) in packages.iter().filter(|(_, data)| data.is_member) {
+            for dep in data.dependencies.iter() {
+                rev_deps.entry(dep.pkg).or_default().push(pkg);
+            }
+        }
+        Ok(CargoWorkspace { packages, targets, workspace_root, manifest_path, rev_deps })
     }
 
     pub fn packages<'a>(&'a self) -> impl Iterator<Item = Package> + ExactSizeIterator + 'a {
@@ -306,114 +393,63 @@ impl CargoWorkspace {
         &self.workspace_root
     }
 
-    pub fn package_flag(&self, package: &PackageData) -> String {
-        if self.is_unique(&*package.name) {
-            package.name.clone()
-        } else {
-            format!("{}:{}", package.name, package.version)
-        }
-    }
-
-    fn is_unique(&self, name: &str) -> bool {
-        self.packages.iter().filter(|(_, v)| v.name == name).count() == 1
+    /// The manifest the workspace was loaded from.
+    pub fn manifest_path(&self) -> &AbsPath {
+        &self.manifest_path
     }
-}
-
-#[derive(Debug, Clone, Default)]
-pub(crate) struct ExternResources {
-    out_dirs: FxHashMap<PackageId, AbsPathBuf>,
-    proc_dylib_paths: FxHashMap<PackageId, AbsPathBuf>,
-    cfgs: FxHashMap<PackageId, Vec<CfgFlag>>,
-    env: FxHashMap<PackageId, Vec<(String, String)>>,
-}
-
-pub(crate) fn load_extern_resources(
-    cargo_toml: &Path,
-    cargo_features: &CargoConfig,
-) -> Result<ExternResources> {
-    let mut cmd = Command::new(toolchain::cargo());
-    cmd.args(&["check", "--message-format=json", "--manifest-path"]).arg(cargo_toml);
 
-    if let Some(target) = &cargo_features.target {
-        cmd.args(&["--target", target]);
+    /// Attaches the collected build data to the packages, replacing any
+    /// previous one.
+    pub fn set_build_data(&mut self, build_data: &WorkspaceBuildData) {
+        let packages: Vec<_> = self.packages().collect();
+        for pkg in packages {
+            let data = build_data.get(&self[pkg].id).cloned().unwrap_or_default();
+            self.packages[pkg].build_data = data;
+        }
     }
 
-    if cargo_features.all_features {
-        cmd.arg("--all-features");
-    } else {
-        if cargo_features.no_default_features {
-            // FIXME: `NoDefaultFeatures` is mutual exclusive with `SomeFeatures`
-            // https://github.com/oli-obk/cargo_metadata/issues/79
-            cmd.arg("--no-default-features");
-        }
-        if !cargo_features.features.is_empty() {
-            cmd.arg("--features");
-            cmd.arg(cargo_features.features.join(" "));
+    /// Sets the overrides of the packages, by package name. Packages without
+    /// an entry get the default, empty, override.
+    pub fn set_package_overrides(&mut self, overrides: &FxHashMap<String, PackageOverride>) {
+        let packages: Vec<_> = self.packages().collect();
+        for pkg in packages {
+            let data = overrides.get(&self[pkg].name).cloned().unwrap_or_default();
+            self.packages[pkg].overrides = data;
         }
     }
 
-    let output = cmd.output()?;
-
-    let mut res = ExternResources::default();
-
-    for message in cargo_metadata::Message::parse_stream(output.stdout.as_slice()) {
-        if let Ok(message) = message {
-            match message {
-                Message::BuildScriptExecuted(BuildScript {
-                    package_id,
-                    out_dir,
-                    cfgs,
-                    env,
-                    ..
-                }) => {
-                    let cfgs = {
-                        let mut acc = Vec::new();
-                        for cfg in cfgs {
-                            match cfg.parse::<CfgFlag>() {
-                                Ok(it) => acc.push(it),
-                                Err(err) => {
-                                    anyhow::bail!("invalid cfg from cargo-metadata: {}", err)
-                                }
-                            };
-                        }
-                        acc
-                    };
-                    // cargo_metadata crate returns default (empty) path for
-                    // older cargos, which is not absolute, so work around that.
-                    if out_dir != PathBuf::default() {
-                        let out_dir = AbsPathBuf::assert(out_dir);
-                        res.out_dirs.insert(package_id.clone(), out_dir);
-                        res.cfgs.insert(package_id.clone(), cfgs);
-                    }
+    pub fn package_by_name(&self, name: &str) -> Option<Package> {
+        self.packages().find(|&pkg| self[pkg].name == name)
+    }
 
-                    res.env.insert(package_id, env);
-                }
-                Message::CompilerArtifact(message) => {
-                    if message.target.kind.contains(&"proc-macro".to_string()) {
-                        let package_id = message.package_id;
-                        // Skip rmeta file
-                        if let Some(filename) = message.filenames.iter().find(|name| is_dylib(name))
-                        {
-                            let filename = AbsPathBuf::assert(filename.clone());
-                            res.proc_dylib_paths.insert(package_id, filename);
-                        }
-                    }
+    /// Returns `package` and the workspace members depending on it, directly
+    /// or transitively.
+    pub fn reverse_dependencies(&self, package: Package) -> Vec<Package> {
+        let mut res = vec![package];
+        let mut seen: FxHashSet<Package> = res.iter().copied().collect();
+        let mut idx = 0;
+        while idx < res.len() {
+            let dependency = res[idx];
+            idx += 1;
+            for &pkg in self.rev_deps.get(&dependency).into_iter().flatten() {
+                if seen.insert(pkg) {
+                    res.push(pkg);
                 }
-                Message::CompilerMessage(_) => (),
-                Message::Unknown => (),
-                Message::BuildFinished(_) => {}
-                Message::TextLine(_) => {}
             }
         }
+        res
+    }
+
+    pub fn package_flag(&self, package: &PackageData) -> String {
+        if self.is_unique(&*package.name) {
+            package.name.clone()
+        } else {
+            format!("{}:{}", package.name, package.version)
+        }
     }
-    Ok(res)
-}
 
-// FIXME: File a better way to know if it is a dylib
-fn is_dylib(path: &Path) -> bool {
-    match path.extension().and_then(OsStr::to_str).map(|it| it.to_string().to_lowercase()) {
-        None => false,
-        Some(ext) => matches!(ext.as_str(), "dll" | "dylib" | "so"),
+    fn is_unique(&self, name: &str) -> bool {
+        self.packages.iter().filter(|(_, v)| v.name == name).count() == 1
     }
 }
 
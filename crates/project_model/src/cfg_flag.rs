@@ -1,9 +1,9 @@
 //! Parsing of CfgFlags as command line arguments, as in
 //!
 //! rustc main.rs --cfg foo --cfg 'feature="bar"'
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
-use cfg::CfgOptions;
+use cfg::{CfgAtom, CfgOptions};
 use stdx::split_once;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -30,6 +30,15 @@ impl FromStr for CfgFlag {
     }
 }
 
+impl fmt::Display for CfgFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CfgFlag::Atom(atom) => f.write_str(atom),
+            CfgFlag::KeyValue { key, value } => write!(f, "{}=\"{}\"", key, value),
+        }
+    }
+}
+
 impl<'de> serde::Deserialize<'de> for CfgFlag {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -49,3 +58,14 @@ impl Extend<CfgFlag> for CfgOptions {
         }
     }
 }
+
+impl From<CfgFlag> for CfgAtom {
+    fn from(cfg_flag: CfgFlag) -> Self {
+        match cfg_flag {
+            CfgFlag::Atom(it) => CfgAtom::Flag(it.into()),
+            CfgFlag::KeyValue { key, value } => {
+                CfgAtom::KeyValue { key: key.into(), value: value.into() }
+            }
+        }
+    }
+}
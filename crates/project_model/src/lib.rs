@@ -1,7 +1,10 @@
 //! FIXME: write short doc here
 
+mod build_data;
 mod cargo_workspace;
+mod local_registry;
 mod project_json;
+mod script;
 mod sysroot;
 mod cfg_flag;
 mod workspace;
@@ -17,8 +20,11 @@ use paths::{AbsPath, AbsPathBuf};
 use rustc_hash::FxHashSet;
 
 pub use crate::{
-    cargo_workspace::{CargoConfig, CargoWorkspace, Package, Target, TargetKind},
-    project_json::{ProjectJson, ProjectJsonData},
+    build_data::{BuildData, WorkspaceBuildData},
+    cargo_workspace::{CargoConfig, CargoWorkspace, Package, PackageOverride, Target, TargetKind},
+    cfg_flag::CfgFlag,
+    local_registry::local_registry_crates,
+    project_json::{Build, BuildRunnable, BuildRunnableKind, ProjectJson, ProjectJsonData},
     sysroot::Sysroot,
     workspace::{PackageRoot, ProjectWorkspace},
 };
@@ -0,0 +1,116 @@
+//! Lists the crates available offline in Cargo's registry cache.
+//!
+//! Cargo keeps the downloaded `.crate` files in `$CARGO_HOME/registry/cache/*`
+//! and unpacks them to `$CARGO_HOME/registry/src/*`, both named like
+//! `serde-1.0.118`. The versions of the crates Cargo looked up, downloaded or
+//! not, are in the index cache, `$CARGO_HOME/registry/index/*/.cache`. We don't
+//! read the full index: only the crates resolved before are likely to be
+//! depended on again.
+use std::{fs, path::Path};
+
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+
+/// Returns the versions of the crates in the local registry cache, by crate
+/// name, oldest first.
+pub fn local_registry_crates() -> FxHashMap<String, Vec<String>> {
+    let mut res = FxHashMap::default();
+    let registry = match toolchain::cargo_home() {
+        Some(cargo_home) => cargo_home.join("registry"),
+        None => return res,
+    };
+    for dir in ["cache", "src"].iter() {
+        for registry_dir in read_dir_names(&registry.join(dir)) {
+            for entry in read_dir_names(&registry.join(dir).join(registry_dir)) {
+                let entry = entry.strip_suffix(".crate").unwrap_or(&entry);
+                if let Some((name, version)) = split_name_and_version(entry) {
+                    res.entry(name.to_string()).or_insert_with(Vec::new).push(version.to_string());
+                }
+            }
+        }
+    }
+    for index_dir in read_dir_names(&registry.join("index")) {
+        read_index_cache(&registry.join("index").join(index_dir).join(".cache"), &mut res);
+    }
+    for versions in res.values_mut() {
+        versions.sort_by_cached_key(|version| version_key(version));
+        versions.dedup();
+    }
+    res
+}
+
+/// Reads the versions of the crates in the index cache of a registry, which
+/// has a file per crate, like `se/rd/serde`. Yanked versions are skipped.
+fn read_index_cache(dir: &Path, res: &mut FxHashMap<String, Vec<String>>) {
+    for name in read_dir_names(dir) {
+        let path = dir.join(name);
+        if path.is_dir() {
+            read_index_cache(&path, res);
+            continue;
+        }
+        let bytes = match fs::read(&path) {
+            Ok(it) => it,
+            Err(_) => continue,
+        };
+        for entry in index_cache_entries(&bytes) {
+            if !entry.yanked {
+                res.entry(entry.name).or_insert_with(Vec::new).push(entry.vers);
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct IndexEntry {
+    name: String,
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// Parses an index cache file: the cache version as a byte, the index format
+/// version as 4 bytes since cache version 2, then the index version and pairs
+/// of a crate version and its index entry in JSON, each ending with a NUL.
+fn index_cache_entries(bytes: &[u8]) -> Vec<IndexEntry> {
+    let rest = match bytes.split_first() {
+        Some((1, rest)) => rest,
+        Some((2, rest)) | Some((3, rest)) => rest.get(4..).unwrap_or_default(),
+        _ => return Vec::new(),
+    };
+    let mut fields = rest.split(|&byte| byte == 0).skip(1);
+    let mut res = Vec::new();
+    while let (Some(_version), Some(json)) = (fields.next(), fields.next()) {
+        if let Ok(entry) = serde_json::from_slice(json) {
+            res.push(entry);
+        }
+    }
+    res
+}
+
+fn read_dir_names(dir: &Path) -> Vec<String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(it) => it,
+        Err(_) => return Vec::new(),
+    };
+    entries.filter_map(|entry| entry.ok()?.file_name().into_string().ok()).collect()
+}
+
+/// Splits `serde_derive-1.0.118` into `serde_derive` and `1.0.118`. Crate
+/// names may contain dashes, but never one followed by a digit.
+fn split_name_and_version(entry: &str) -> Option<(&str, &str)> {
+    let idx = entry
+        .match_indices('-')
+        .map(|(idx, _)| idx)
+        .find(|&idx| entry[idx + 1..].starts_with(|c: char| c.is_ascii_digit()))?;
+    Some((&entry[..idx], &entry[idx + 1..]))
+}
+
+/// Orders `1.0.9` before `1.0.10`, and pre-releases before the release.
+fn version_key(version: &str) -> (Vec<u64>, bool, String) {
+    let (numbers, pre) = match version.find(|c| c == '-' || c == '+') {
+        Some(idx) => (&version[..idx], &version[idx..]),
+        None => (version, ""),
+    };
+    let numbers = numbers.split('.').map(|it| it.parse().unwrap_or(0)).collect();
+    (numbers, pre.is_empty(), pre.to_string())
+}
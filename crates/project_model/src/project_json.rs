@@ -7,31 +7,70 @@ use paths::{AbsPath, AbsPathBuf};
 use rustc_hash::FxHashMap;
 use serde::{de, Deserialize};
 
-use crate::cfg_flag::CfgFlag;
+use crate::{cfg_flag::CfgFlag, Sysroot, TargetKind};
 
 /// Roots and crates that compose this Rust project.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ProjectJson {
     pub(crate) sysroot_src: Option<AbsPathBuf>,
+    /// The sysroot crates listed explicitly, used instead of the ones found in
+    /// `sysroot_src`.
+    pub(crate) sysroot: Option<Sysroot>,
     project_root: AbsPathBuf,
     crates: Vec<Crate>,
+    runnables: Vec<BuildRunnable>,
+    /// Files of the build system the project was generated from.
+    build_files: Vec<AbsPathBuf>,
 }
 
 /// A crate points to the root module of a crate and lists the dependencies of the crate. This is
 /// useful in creating the crate graph.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Crate {
-    pub(crate) display_name: Option<CrateDisplayName>,
-    pub(crate) root_module: AbsPathBuf,
-    pub(crate) edition: Edition,
-    pub(crate) deps: Vec<Dependency>,
-    pub(crate) cfg: Vec<CfgFlag>,
-    pub(crate) target: Option<String>,
-    pub(crate) env: FxHashMap<String, String>,
-    pub(crate) proc_macro_dylib_path: Option<AbsPathBuf>,
-    pub(crate) is_workspace_member: bool,
+    pub display_name: Option<CrateDisplayName>,
+    pub root_module: AbsPathBuf,
+    pub edition: Edition,
+    pub deps: Vec<Dependency>,
+    pub cfg: Vec<CfgFlag>,
+    pub target: Option<String>,
+    pub env: FxHashMap<String, String>,
+    pub proc_macro_dylib_path: Option<AbsPathBuf>,
+    pub is_workspace_member: bool,
     pub(crate) include: Vec<AbsPathBuf>,
     pub(crate) exclude: Vec<AbsPathBuf>,
+    /// The build system target the crate comes from.
+    pub build: Option<Build>,
+}
+
+/// The build system target of a crate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Build {
+    /// The name of the target in the build system, like `//foo:bar`.
+    pub label: String,
+    /// The file defining the target.
+    pub build_file: AbsPathBuf,
+    pub target_kind: TargetKind,
+}
+
+/// A build system command, used to run or check the targets of the project.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BuildRunnable {
+    pub program: String,
+    /// The arguments, where `{label}` stands for the label of the target, and
+    /// `{test_id}` for the path of the test to run.
+    pub args: Vec<String>,
+    pub cwd: AbsPathBuf,
+    pub kind: BuildRunnableKind,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BuildRunnableKind {
+    /// Checks a whole target.
+    Check,
+    /// Runs a binary target.
+    Run,
+    /// Runs a single test of a target.
+    TestOne,
 }
 
 impl ProjectJson {
@@ -45,7 +84,26 @@ impl ProjectJson {
     pub fn new(base: &AbsPath, data: ProjectJsonData) -> ProjectJson {
         ProjectJson {
             sysroot_src: data.sysroot_src.map(|it| base.join(it)),
+            sysroot: data.sysroot.map(|crates| {
+                Sysroot::from_crates(
+                    crates
+                        .into_iter()
+                        .map(|it| (it.name, base.join(it.root_module).normalize(), it.deps))
+                        .collect(),
+                )
+            }),
             project_root: base.to_path_buf(),
+            runnables: data
+                .runnables
+                .into_iter()
+                .map(|it| BuildRunnable {
+                    program: it.program,
+                    args: it.args,
+                    cwd: it.cwd.map_or_else(|| base.to_path_buf(), |cwd| base.join(cwd)),
+                    kind: it.kind.into(),
+                })
+                .collect(),
+            build_files: data.build_files.into_iter().map(|it| base.join(it)).collect(),
             crates: data
                 .crates
                 .into_iter()
@@ -56,17 +114,20 @@ impl ProjectJson {
                             || crate_data.root_module.starts_with(base)
                     });
                     let root_module = base.join(crate_data.root_module).normalize();
-                    let (include, exclude) = match crate_data.source {
-                        Some(src) => {
-                            let absolutize = |dirs: Vec<PathBuf>| {
-                                dirs.into_iter()
-                                    .map(|it| base.join(it).normalize())
-                                    .collect::<Vec<_>>()
-                            };
-                            (absolutize(src.include_dirs), absolutize(src.exclude_dirs))
-                        }
-                        None => (vec![root_module.parent().unwrap().to_path_buf()], Vec::new()),
+                    let absolutize = |dirs: Vec<PathBuf>| {
+                        dirs.into_iter().map(|it| base.join(it).normalize()).collect::<Vec<_>>()
                     };
+                    let source = crate_data.source.unwrap_or_default();
+                    let mut include = absolutize(source.include_dirs);
+                    if include.is_empty() {
+                        // Like for cargo packages, the generated sources are
+                        // loaded along with the ones of the crate.
+                        include.push(root_module.parent().unwrap().to_path_buf());
+                        include.extend(
+                            crate_data.env.get("OUT_DIR").map(|it| base.join(it).normalize()),
+                        );
+                    }
+                    let exclude = absolutize(source.exclude_dirs);
 
                     Crate {
                         display_name: crate_data
@@ -91,6 +152,11 @@ impl ProjectJson {
                         is_workspace_member,
                         include,
                         exclude,
+                        build: crate_data.build.map(|build| Build {
+                            label: build.label,
+                            build_file: base.join(build.build_file),
+                            target_kind: build.target_kind.into(),
+                        }),
                     }
                 })
                 .collect::<Vec<_>>(),
@@ -108,12 +174,82 @@ impl ProjectJson {
     pub fn path(&self) -> &AbsPath {
         &self.project_root
     }
+    /// Returns the build system commands running the targets of the project.
+    pub fn runnables(&self) -> &[BuildRunnable] {
+        &self.runnables
+    }
+    /// Returns the build files the project was generated from, which should be
+    /// watched to regenerate it.
+    pub fn build_files(&self) -> &[AbsPathBuf] {
+        &self.build_files
+    }
+    /// Adds the crates of `other` to the project. Crates with the same root
+    /// module and cfgs in both projects are only kept once.
+    pub fn merge(&mut self, other: ProjectJson) {
+        if self.sysroot_src.is_none() {
+            self.sysroot_src = other.sysroot_src;
+        }
+        if self.sysroot.is_none() {
+            self.sysroot = other.sysroot;
+        }
+        for runnable in other.runnables {
+            if !self.runnables.contains(&runnable) {
+                self.runnables.push(runnable);
+            }
+        }
+        for build_file in other.build_files {
+            if !self.build_files.contains(&build_file) {
+                self.build_files.push(build_file);
+            }
+        }
+
+        let mut new_crates = Vec::new();
+        let ids: Vec<u32> = other
+            .crates
+            .iter()
+            .map(|krate| {
+                let existing = self
+                    .crates
+                    .iter()
+                    .position(|it| it.root_module == krate.root_module && it.cfg == krate.cfg);
+                match existing {
+                    Some(idx) => idx as u32,
+                    None => {
+                        new_crates.push(krate.clone());
+                        (self.crates.len() + new_crates.len() - 1) as u32
+                    }
+                }
+            })
+            .collect();
+        for mut krate in new_crates {
+            for dep in krate.deps.iter_mut() {
+                if let Some(&id) = ids.get(dep.crate_id.0 as usize) {
+                    dep.crate_id = CrateId(id);
+                }
+            }
+            self.crates.push(krate);
+        }
+    }
 }
 
 #[derive(Deserialize)]
 pub struct ProjectJsonData {
     sysroot_src: Option<PathBuf>,
+    sysroot: Option<Vec<SysrootCrateData>>,
     crates: Vec<CrateData>,
+    #[serde(default)]
+    runnables: Vec<RunnableData>,
+    #[serde(default)]
+    build_files: Vec<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct SysrootCrateData {
+    name: String,
+    root_module: PathBuf,
+    /// The names of the sysroot crates this one depends on.
+    #[serde(default)]
+    deps: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -130,6 +266,59 @@ struct CrateData {
     proc_macro_dylib_path: Option<PathBuf>,
     is_workspace_member: Option<bool>,
     source: Option<CrateSource>,
+    build: Option<BuildData>,
+}
+
+#[derive(Deserialize)]
+struct BuildData {
+    label: String,
+    build_file: PathBuf,
+    target_kind: TargetKindData,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum TargetKindData {
+    Bin,
+    Lib,
+    Test,
+}
+
+impl From<TargetKindData> for TargetKind {
+    fn from(data: TargetKindData) -> Self {
+        match data {
+            TargetKindData::Bin => TargetKind::Bin,
+            TargetKindData::Lib => TargetKind::Lib,
+            TargetKindData::Test => TargetKind::Test,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RunnableData {
+    program: String,
+    #[serde(default)]
+    args: Vec<String>,
+    cwd: Option<PathBuf>,
+    kind: RunnableKindData,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RunnableKindData {
+    Check,
+    Run,
+    TestOne,
+}
+
+impl From<RunnableKindData> for BuildRunnableKind {
+    fn from(data: RunnableKindData) -> Self {
+        match data {
+            RunnableKindData::Check => BuildRunnableKind::Check,
+            RunnableKindData::Run => BuildRunnableKind::Run,
+            RunnableKindData::TestOne => BuildRunnableKind::TestOne,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -139,6 +328,8 @@ enum EditionData {
     Edition2015,
     #[serde(rename = "2018")]
     Edition2018,
+    #[serde(rename = "2021")]
+    Edition2021,
 }
 
 impl From<EditionData> for Edition {
@@ -146,6 +337,7 @@ impl From<EditionData> for Edition {
         match data {
             EditionData::Edition2015 => Edition::Edition2015,
             EditionData::Edition2018 => Edition::Edition2018,
+            EditionData::Edition2021 => Edition::Edition2021,
         }
     }
 }
@@ -159,9 +351,11 @@ struct DepData {
     name: CrateName,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 struct CrateSource {
+    #[serde(default)]
     include_dirs: Vec<PathBuf>,
+    #[serde(default)]
     exclude_dirs: Vec<PathBuf>,
 }
 
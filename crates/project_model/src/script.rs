@@ -0,0 +1,153 @@
+//! Standalone Rust files with a `cargo-script` style embedded manifest.
+//!
+//! The manifest is a fenced code block in the leading doc comment of the file:
+//!
+//! ```text
+//! //! ```cargo
+//! //! [dependencies]
+//! //! time = "0.1.25"
+//! //! ```
+//! fn main() {}
+//! ```
+//!
+//! As cargo can't load such a file by itself, we write a package with the file
+//! as its only binary target to a temporary directory, and load that one.
+use std::{
+    collections::hash_map::DefaultHasher,
+    convert::TryFrom,
+    fs,
+    hash::{Hash, Hasher},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use base_db::Edition;
+use paths::{AbsPath, AbsPathBuf};
+
+/// Returns the manifest embedded in the leading doc comment of `text`, if any.
+pub(crate) fn embedded_manifest(text: &str) -> Option<String> {
+    let lines = text.lines().skip_while(|line| line.starts_with("#!"));
+    let mut manifest: Option<String> = None;
+    for line in lines {
+        let line = match line.trim_start().strip_prefix("//!") {
+            Some(it) => it.strip_prefix(' ').unwrap_or(it),
+            None => return None,
+        };
+        match &mut manifest {
+            None if line.trim_end() == "```cargo" => manifest = Some(String::new()),
+            None => (),
+            Some(manifest) if line.trim_end() == "```" => return Some(manifest.clone()),
+            Some(manifest) => {
+                manifest.push_str(line);
+                manifest.push('\n');
+            }
+        }
+    }
+    None
+}
+
+/// Writes the package of the `script` with the given embedded `manifest`, and
+/// returns the path of its `Cargo.toml`. The package has the given `edition`,
+/// unless the manifest has a `[package]` section of its own.
+pub(crate) fn write_package(
+    script: &AbsPath,
+    manifest: &str,
+    edition: Edition,
+) -> Result<AbsPathBuf> {
+    let name = package_name(script);
+    let dir = {
+        let mut hasher = DefaultHasher::new();
+        AsRef::<Path>::as_ref(script).hash(&mut hasher);
+        let dir = std::env::temp_dir().join(format!(
+            "rust-analyzer-script-{}-{:x}",
+            name,
+            hasher.finish()
+        ));
+        AbsPathBuf::try_from(dir).map_err(|it| {
+            anyhow::format_err!("temporary directory is not absolute: {}", it.display())
+        })?
+    };
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+
+    let script_dir = script.parent().unwrap();
+    let manifest = absolutize_paths(manifest, script_dir);
+    let mut contents = String::new();
+    if !manifest.lines().any(|line| line.trim() == "[package]") {
+        contents.push_str(&format!(
+            "[package]\nname = {:?}\nversion = \"0.0.0\"\nedition = \"{}\"\n\n",
+            name, edition
+        ));
+    }
+    contents.push_str(&manifest);
+    contents.push_str(&format!(
+        "\n[[bin]]\nname = {:?}\npath = {:?}\n\n[workspace]\n",
+        name,
+        script.display().to_string()
+    ));
+
+    let cargo_toml = dir.join("Cargo.toml");
+    // Keep the file untouched if possible, not to invalidate cargo's caches.
+    if fs::read_to_string(&cargo_toml).ok().as_deref() != Some(contents.as_str()) {
+        fs::write(&cargo_toml, contents)
+            .with_context(|| format!("failed to write {}", cargo_toml.display()))?;
+    }
+    Ok(cargo_toml)
+}
+
+/// A valid package name for the file stem of `script`.
+fn package_name(script: &AbsPath) -> String {
+    let stem = script.file_stem().and_then(|it| it.to_str()).unwrap_or("script");
+    let mut name: String =
+        stem.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' }).collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert_str(0, "script_");
+    }
+    name
+}
+
+/// Makes the relative `path = "..."` values of the manifest relative to the
+/// directory of the script, as the manifest itself is written elsewhere.
+fn absolutize_paths(manifest: &str, script_dir: &AbsPath) -> String {
+    let mut res = String::with_capacity(manifest.len());
+    let mut rest = manifest;
+    while let Some(idx) = find_path_value(rest) {
+        let (before, after) = rest.split_at(idx);
+        res.push_str(before);
+        rest = after;
+        let end = match after.find('"') {
+            Some(it) => it,
+            None => break,
+        };
+        let value = &after[..end];
+        if Path::new(value).is_relative() {
+            res.push_str(&script_dir.join(value).display().to_string().replace('\\', "\\\\"));
+        } else {
+            res.push_str(value);
+        }
+        rest = &after[end..];
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Returns the offset of the value of the first `path = "` key in `text`.
+fn find_path_value(text: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(idx) = text[offset..].find("path") {
+        let start = offset + idx;
+        let is_key_start = match text[..start].chars().next_back() {
+            Some(c) => c.is_whitespace() || c == '{' || c == ',',
+            None => true,
+        };
+        let after_key = text[start + "path".len()..].trim_start();
+        if is_key_start {
+            if let Some(after_eq) = after_key.strip_prefix('=') {
+                if let Some(value) = after_eq.trim_start().strip_prefix('"') {
+                    return Some(text.len() - value.len());
+                }
+            }
+        }
+        offset = start + "path".len();
+    }
+    None
+}
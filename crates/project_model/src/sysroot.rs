@@ -104,6 +104,26 @@ impl Sysroot {
         Ok(sysroot)
     }
 
+    /// Creates a sysroot from an explicit list of crates, given as their name,
+    /// root module and the names of their dependencies.
+    pub fn from_crates(crates: Vec<(String, AbsPathBuf, Vec<String>)>) -> Sysroot {
+        let mut sysroot = Sysroot { crates: Arena::default() };
+        let mut deps = Vec::new();
+        for (name, root, crate_deps) in crates {
+            let id = sysroot.crates.alloc(SysrootCrateData { name, root, deps: Vec::new() });
+            deps.push((id, crate_deps));
+        }
+        for (id, crate_deps) in deps {
+            for dep in crate_deps {
+                match sysroot.by_name(&dep) {
+                    Some(dep) => sysroot.crates[id].deps.push(dep),
+                    None => log::warn!("unknown sysroot dependency {:?}", dep),
+                }
+            }
+        }
+        sysroot
+    }
+
     fn by_name(&self, name: &str) -> Option<SysrootCrate> {
         let (id, _data) = self.crates.iter().find(|(_id, data)| data.name == name)?;
         Some(id)
@@ -16,8 +16,8 @@ use proc_macro_api::ProcMacroClient;
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::{
-    cargo_workspace, cfg_flag::CfgFlag, sysroot::SysrootCrate, utf8_stdout, CargoConfig,
-    CargoWorkspace, ProjectJson, ProjectManifest, Sysroot, TargetKind,
+    build_data::BuildData, cargo_workspace, cfg_flag::CfgFlag, script, sysroot::SysrootCrate,
+    utf8_stdout, CargoConfig, CargoWorkspace, ProjectJson, ProjectManifest, Sysroot, TargetKind,
 };
 
 /// `PackageRoot` describes a package root folder.
@@ -37,6 +37,9 @@ pub enum ProjectWorkspace {
     Cargo { cargo: CargoWorkspace, sysroot: Sysroot, rustc: Option<CargoWorkspace> },
     /// Project workspace was manually specified using a `rust-project.json` file.
     Json { project: ProjectJson, sysroot: Option<Sysroot> },
+    /// Standalone files outside of any project, each one being a crate
+    /// depending on the sysroot.
+    DetachedFiles { files: Vec<AbsPathBuf>, sysroot: Sysroot, edition: Edition },
 }
 
 impl fmt::Debug for ProjectWorkspace {
@@ -59,6 +62,12 @@ impl fmt::Debug for ProjectWorkspace {
                 }
                 debug_struct.finish()
             }
+            ProjectWorkspace::DetachedFiles { files, sysroot, edition } => f
+                .debug_struct("DetachedFiles")
+                .field("n_files", &files.len())
+                .field("n_sysroot_crates", &sysroot.crates().len())
+                .field("edition", edition)
+                .finish(),
         }
     }
 }
@@ -120,14 +129,64 @@ impl ProjectWorkspace {
     }
 
     pub fn load_inline(project_json: ProjectJson) -> Result<ProjectWorkspace> {
-        let sysroot = match &project_json.sysroot_src {
-            Some(path) => Some(Sysroot::load(path)?),
-            None => None,
+        let sysroot = match (&project_json.sysroot, &project_json.sysroot_src) {
+            (Some(sysroot), _) => Some(sysroot.clone()),
+            (None, Some(path)) => Some(Sysroot::load(path)?),
+            (None, None) => None,
         };
 
         Ok(ProjectWorkspace::Json { project: project_json, sysroot })
     }
 
+    /// Loads standalone files which don't belong to any project, with the
+    /// given `edition`. The files with an embedded manifest are loaded as cargo
+    /// packages of their own, the other ones together as detached files.
+    pub fn load_detached_files(
+        files: Vec<AbsPathBuf>,
+        edition: Edition,
+        config: &CargoConfig,
+    ) -> Vec<Result<ProjectWorkspace>> {
+        let mut res = Vec::new();
+        let mut detached_files = Vec::new();
+        for file in files {
+            let text = match fs::read_to_string(&file) {
+                Ok(it) => it,
+                Err(err) => {
+                    res.push(
+                        Err(err).with_context(|| format!("Failed to read {}", file.display())),
+                    );
+                    continue;
+                }
+            };
+            match script::embedded_manifest(&text) {
+                Some(manifest) => res.push(
+                    script::write_package(&file, &manifest, edition).and_then(|cargo_toml| {
+                        ProjectWorkspace::load(ProjectManifest::CargoToml(cargo_toml), config)
+                    }),
+                ),
+                None => detached_files.push(file),
+            }
+        }
+        if !detached_files.is_empty() {
+            let sysroot = if config.no_sysroot {
+                Ok(Sysroot::default())
+            } else {
+                Sysroot::discover(&detached_files[0]).with_context(|| {
+                    format!(
+                        "Failed to find sysroot for file {}. Is rust-src installed?",
+                        detached_files[0].display()
+                    )
+                })
+            };
+            res.push(sysroot.map(|sysroot| ProjectWorkspace::DetachedFiles {
+                files: detached_files,
+                sysroot,
+                edition,
+            }));
+        }
+        res
+    }
+
     /// Returns the roots for the current `ProjectWorkspace`
     /// The return type contains the path and whether or not
     /// the root is a member of the current workspace
@@ -157,7 +216,17 @@ impl ProjectWorkspace {
                     let pkg_root = cargo[pkg].root().to_path_buf();
 
                     let mut include = vec![pkg_root.clone()];
-                    include.extend(cargo[pkg].out_dir.clone());
+                    include.extend(cargo[pkg].build_data.out_dir.clone());
+                    // Targets may live outside of the package, like the
+                    // scripts with an embedded manifest.
+                    include.extend(
+                        cargo[pkg]
+                            .targets
+                            .iter()
+                            .map(|&target| &cargo[target].root)
+                            .filter(|root| !root.starts_with(&pkg_root))
+                            .cloned(),
+                    );
 
                     let mut exclude = vec![pkg_root.join(".git")];
                     if is_member {
@@ -182,12 +251,28 @@ impl ProjectWorkspace {
                     })
                 }))
                 .collect(),
+            ProjectWorkspace::DetachedFiles { files, sysroot, .. } => files
+                .iter()
+                .map(|file| PackageRoot {
+                    is_member: true,
+                    include: vec![file.clone()],
+                    exclude: Vec::new(),
+                })
+                .chain(sysroot.crates().map(|krate| PackageRoot {
+                    is_member: false,
+                    include: vec![sysroot[krate].root_dir().to_path_buf()],
+                    exclude: Vec::new(),
+                }))
+                .collect(),
         }
     }
 
     pub fn n_packages(&self) -> usize {
         match self {
             ProjectWorkspace::Json { project, .. } => project.n_crates(),
+            ProjectWorkspace::DetachedFiles { files, sysroot, .. } => {
+                files.len() + sysroot.crates().len()
+            }
             ProjectWorkspace::Cargo { cargo, sysroot, rustc } => {
                 let rustc_package_len = rustc.as_ref().map_or(0, |rc| rc.packages().len());
                 cargo.packages().len() + sysroot.crates().len() + rustc_package_len
@@ -195,6 +280,17 @@ impl ProjectWorkspace {
         }
     }
 
+    /// Returns the features declared by each package loaded with `cargo metadata`.
+    pub fn package_features(&self) -> Vec<(String, Vec<String>)> {
+        match self {
+            ProjectWorkspace::Json { .. } | ProjectWorkspace::DetachedFiles { .. } => Vec::new(),
+            ProjectWorkspace::Cargo { cargo, .. } => cargo
+                .packages()
+                .map(|pkg| (cargo[pkg].name.clone(), cargo[pkg].declared_features.clone()))
+                .collect(),
+        }
+    }
+
     pub fn to_crate_graph(
         &self,
         target: Option<&str>,
@@ -213,6 +309,9 @@ impl ProjectWorkspace {
             ProjectWorkspace::Cargo { cargo, sysroot, rustc } => {
                 cargo_to_crate_graph(target, &proc_macro_loader, load, cargo, sysroot, rustc)
             }
+            ProjectWorkspace::DetachedFiles { files, sysroot, edition } => {
+                detached_files_to_crate_graph(target, load, files, sysroot, *edition)
+            }
         };
         if crate_graph.patch_cfg_if() {
             log::debug!("Patched std to depend on cfg-if")
@@ -285,6 +384,42 @@ fn project_json_to_crate_graph(
     crate_graph
 }
 
+fn detached_files_to_crate_graph(
+    target: Option<&str>,
+    load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
+    files: &[AbsPathBuf],
+    sysroot: &Sysroot,
+    edition: Edition,
+) -> CrateGraph {
+    let mut crate_graph = CrateGraph::default();
+    let (public_deps, _libproc_macro) =
+        sysroot_to_crate_graph(&mut crate_graph, sysroot, target, load);
+
+    let cfg_options = cargo_cfg_options(target);
+    for file in files {
+        let file_id = match load(file) {
+            Some(it) => it,
+            None => continue,
+        };
+        let display_name = file
+            .file_stem()
+            .and_then(|it| it.to_str())
+            .map(|it| CrateDisplayName::from_canonical_name(it.to_string()));
+        let krate = crate_graph.add_crate_root(
+            file_id,
+            edition,
+            display_name,
+            cfg_options.clone(),
+            Env::default(),
+            Vec::new(),
+        );
+        for (name, to) in public_deps.iter() {
+            add_dep(&mut crate_graph, krate, name.clone(), *to);
+        }
+    }
+    crate_graph
+}
+
 fn cargo_to_crate_graph(
     target: Option<&str>,
     proc_macro_loader: &dyn Fn(&Path) -> Vec<ProcMacro>,
@@ -297,26 +432,40 @@ fn cargo_to_crate_graph(
     let (public_deps, libproc_macro) =
         sysroot_to_crate_graph(&mut crate_graph, sysroot, target, load);
 
-    let mut cfg_options = CfgOptions::default();
-    cfg_options.extend(get_rustc_cfg_options(target));
+    let cfg_options = cargo_cfg_options(target);
+    // Packages overriding the target get the cfgs of their own target.
+    let mut cfg_options_by_target = FxHashMap::default();
 
     let mut pkg_to_lib_crate = FxHashMap::default();
 
-    // Add test cfg for non-sysroot crates
-    cfg_options.insert_atom("test".into());
-    cfg_options.insert_atom("debug_assertions".into());
-
     let mut pkg_crates = FxHashMap::default();
 
     // Next, create crates for each package, target pair
     for pkg in cargo.packages() {
+        let overrides = &cargo[pkg].overrides;
+        let mut pkg_cfg_options = match overrides.target.as_deref() {
+            Some(pkg_target) if Some(pkg_target) != target => cfg_options_by_target
+                .entry(pkg_target)
+                .or_insert_with(|| cargo_cfg_options(Some(pkg_target)))
+                .clone(),
+            _ => cfg_options.clone(),
+        };
+        pkg_cfg_options.extend(overrides.cfgs.iter().cloned());
+
         let mut lib_tgt = None;
         for &tgt in cargo[pkg].targets.iter() {
             if let Some(file_id) = load(&cargo[tgt].root) {
+                // Build script outputs apply to the package's targets, not to
+                // the build script itself.
+                let build_data = match cargo[tgt].kind {
+                    TargetKind::BuildScript => None,
+                    _ => Some(&cargo[pkg].build_data),
+                };
                 let crate_id = add_target_crate_root(
                     &mut crate_graph,
                     &cargo[pkg],
-                    &cfg_options,
+                    build_data,
+                    &pkg_cfg_options,
                     proc_macro_loader,
                     file_id,
                 );
@@ -324,25 +473,44 @@ fn cargo_to_crate_graph(
                     lib_tgt = Some((crate_id, cargo[tgt].name.clone()));
                     pkg_to_lib_crate.insert(pkg, crate_id);
                 }
-                if cargo[tgt].is_proc_macro {
-                    if let Some(proc_macro) = libproc_macro {
-                        add_dep(
-                            &mut crate_graph,
-                            crate_id,
-                            CrateName::new("proc_macro").unwrap(),
-                            proc_macro,
-                        );
+
+                let mut target_crates = vec![crate_id];
+                for cfg_diff in overrides.cfg_alternatives.iter() {
+                    let mut alternative_cfg_options = pkg_cfg_options.clone();
+                    alternative_cfg_options.apply_diff(cfg_diff.clone());
+                    let alternative = add_target_crate_root(
+                        &mut crate_graph,
+                        &cargo[pkg],
+                        build_data,
+                        &alternative_cfg_options,
+                        proc_macro_loader,
+                        file_id,
+                    );
+                    crate_graph.set_alternative_of(alternative, crate_id);
+                    target_crates.push(alternative);
+                }
+
+                for &crate_id in target_crates.iter() {
+                    if cargo[tgt].is_proc_macro {
+                        if let Some(proc_macro) = libproc_macro {
+                            add_dep(
+                                &mut crate_graph,
+                                crate_id,
+                                CrateName::new("proc_macro").unwrap(),
+                                proc_macro,
+                            );
+                        }
                     }
                 }
 
-                pkg_crates.entry(pkg).or_insert_with(Vec::new).push(crate_id);
+                pkg_crates.entry(pkg).or_insert_with(Vec::new).extend(target_crates);
             }
         }
 
         // Set deps to the core, std and to the lib target of the current package
         for &from in pkg_crates.get(&pkg).into_iter().flatten() {
             if let Some((to, name)) = lib_tgt.clone() {
-                if to != from {
+                if to != from && crate_graph[from].alternative_of != Some(to) {
                     // For root projects with dashes in their name,
                     // cargo metadata does not do any normalization,
                     // so we do it ourselves currently
@@ -392,6 +560,7 @@ fn cargo_to_crate_graph(
                     let crate_id = add_target_crate_root(
                         &mut crate_graph,
                         &rustc_workspace[pkg],
+                        Some(&rustc_workspace[pkg].build_data),
                         &cfg_options,
                         proc_macro_loader,
                         file_id,
@@ -440,25 +609,34 @@ fn cargo_to_crate_graph(
 fn add_target_crate_root(
     crate_graph: &mut CrateGraph,
     pkg: &cargo_workspace::PackageData,
+    build_data: Option<&BuildData>,
     cfg_options: &CfgOptions,
     proc_macro_loader: &dyn Fn(&Path) -> Vec<ProcMacro>,
     file_id: FileId,
 ) -> CrateId {
     let edition = pkg.edition;
+    let build_data = build_data.cloned().unwrap_or_default();
     let cfg_options = {
         let mut opts = cfg_options.clone();
-        for feature in pkg.features.iter() {
+        for feature in pkg.active_features() {
+            opts.insert_key_value("feature".into(), feature.into());
+        }
+        opts.extend(build_data.cfgs);
+        opts
+    };
+    let potential_cfg_options = {
+        let mut opts = cfg_options.clone();
+        for feature in pkg.declared_features.iter() {
             opts.insert_key_value("feature".into(), feature.into());
         }
-        opts.extend(pkg.cfgs.iter().cloned());
         opts
     };
 
     let mut env = Env::default();
-    for (k, v) in &pkg.envs {
+    for (k, v) in pkg.envs.iter().chain(&build_data.envs) {
         env.set(k, v.clone());
     }
-    if let Some(out_dir) = &pkg.out_dir {
+    if let Some(out_dir) = &build_data.out_dir {
         // NOTE: cargo and rustc seem to hide non-UTF-8 strings from env! and option_env!()
         if let Some(out_dir) = out_dir.to_str().map(|s| s.to_owned()) {
             env.set("OUT_DIR", out_dir);
@@ -466,7 +644,7 @@ fn add_target_crate_root(
     }
 
     let proc_macro =
-        pkg.proc_macro_dylib_path.as_ref().map(|it| proc_macro_loader(&it)).unwrap_or_default();
+        build_data.proc_macro_dylib_path.map(|it| proc_macro_loader(&it)).unwrap_or_default();
 
     let display_name = CrateDisplayName::from_canonical_name(pkg.name.clone());
     let crate_id = crate_graph.add_crate_root(
@@ -477,6 +655,7 @@ fn add_target_crate_root(
         env,
         proc_macro,
     );
+    crate_graph.set_potential_cfg_options(crate_id, potential_cfg_options);
 
     crate_id
 }
@@ -527,6 +706,16 @@ fn sysroot_to_crate_graph(
     (public_deps, libproc_macro)
 }
 
+/// The cfgs of non-sysroot crates built for `target`.
+fn cargo_cfg_options(target: Option<&str>) -> CfgOptions {
+    let mut cfg_options = CfgOptions::default();
+    cfg_options.extend(get_rustc_cfg_options(target));
+    // Add test cfg for non-sysroot crates
+    cfg_options.insert_atom("test".into());
+    cfg_options.insert_atom("debug_assertions".into());
+    cfg_options
+}
+
 fn get_rustc_cfg_options(target: Option<&str>) -> Vec<CfgFlag> {
     let mut res = Vec::new();
 
@@ -90,7 +90,7 @@ pub fn server_capabilities(client_caps: &ClientCapabilities) -> ServerCapabiliti
             "onEnter": true,
             "parentModule": true,
             "runnables": {
-                "kinds": [ "cargo" ],
+                "kinds": [ "cargo", "rustc" ],
             },
         })),
     }
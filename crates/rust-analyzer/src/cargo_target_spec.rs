@@ -1,8 +1,8 @@
 //! See `CargoTargetSpec`
 
 use cfg::{CfgAtom, CfgExpr};
-use ide::{FileId, RunnableKind, TestId};
-use project_model::{self, TargetKind};
+use ide::{Edition, FileId, RunnableKind, TestId};
+use project_model::{self, BuildRunnable, BuildRunnableKind, TargetKind};
 use vfs::AbsPathBuf;
 
 use crate::{global_state::GlobalStateSnapshot, Result};
@@ -20,6 +20,24 @@ pub(crate) struct CargoTargetSpec {
     pub(crate) target_kind: TargetKind,
 }
 
+/// Build system target of a `rust-project.json` crate.
+///
+/// Unlike for cargo, the commands to run the target come from the project.
+#[derive(Clone)]
+pub(crate) struct ProjectJsonTargetSpec {
+    pub(crate) label: String,
+    pub(crate) target_kind: TargetKind,
+    pub(crate) runnables: Vec<BuildRunnable>,
+}
+
+/// Standalone file outside of any project, compiled and run with `rustc`
+/// directly.
+#[derive(Clone)]
+pub(crate) struct DetachedFileSpec {
+    pub(crate) path: AbsPathBuf,
+    pub(crate) edition: Edition,
+}
+
 impl CargoTargetSpec {
     pub(crate) fn runnable_args(
         snap: &GlobalStateSnapshot,
@@ -158,11 +176,72 @@ impl CargoTargetSpec {
             TargetKind::Lib => {
                 buf.push("--lib".to_string());
             }
-            TargetKind::Other => (),
+            TargetKind::BuildScript | TargetKind::Other => (),
         }
     }
 }
 
+impl ProjectJsonTargetSpec {
+    pub(crate) fn for_file(
+        global_state_snapshot: &GlobalStateSnapshot,
+        file_id: FileId,
+    ) -> Result<Option<ProjectJsonTargetSpec>> {
+        let crate_id = match global_state_snapshot.analysis.crate_for(file_id)?.first() {
+            Some(crate_id) => *crate_id,
+            None => return Ok(None),
+        };
+        let (project, build) =
+            match global_state_snapshot.project_json_target_for_crate_root(crate_id) {
+                Some(it) => it,
+                None => return Ok(None),
+            };
+
+        let res = ProjectJsonTargetSpec {
+            label: build.label.clone(),
+            target_kind: build.target_kind,
+            runnables: project.runnables().to_vec(),
+        };
+
+        Ok(Some(res))
+    }
+
+    /// Returns the build system command running `kind`, with its arguments, if
+    /// the project has one.
+    pub(crate) fn runnable_args(
+        &self,
+        kind: &RunnableKind,
+    ) -> Option<(&BuildRunnable, Vec<String>)> {
+        match kind {
+            RunnableKind::Test { test_id, .. }
+            | RunnableKind::Bench { test_id }
+            | RunnableKind::DocTest { test_id } => {
+                self.command(BuildRunnableKind::TestOne, &test_id.to_string())
+            }
+            RunnableKind::TestMod { path } => self.command(BuildRunnableKind::TestOne, path),
+            RunnableKind::Bin => self.command(BuildRunnableKind::Run, ""),
+        }
+    }
+
+    /// Returns the build system command checking the whole target.
+    pub(crate) fn check_args(&self) -> Option<(&BuildRunnable, Vec<String>)> {
+        self.command(BuildRunnableKind::Check, "")
+    }
+
+    fn command(
+        &self,
+        kind: BuildRunnableKind,
+        test_id: &str,
+    ) -> Option<(&BuildRunnable, Vec<String>)> {
+        let runnable = self.runnables.iter().find(|it| it.kind == kind)?;
+        let args = runnable
+            .args
+            .iter()
+            .map(|arg| arg.replace("{label}", &self.label).replace("{test_id}", test_id))
+            .collect();
+        Some((runnable, args))
+    }
+}
+
 /// Fill minimal features needed
 fn required_features(cfg_expr: &CfgExpr, features: &mut Vec<String>) {
     match cfg_expr {
@@ -185,6 +264,49 @@ fn required_features(cfg_expr: &CfgExpr, features: &mut Vec<String>) {
     }
 }
 
+impl DetachedFileSpec {
+    pub(crate) fn for_file(
+        global_state_snapshot: &GlobalStateSnapshot,
+        file_id: FileId,
+    ) -> Result<Option<DetachedFileSpec>> {
+        let crate_id = match global_state_snapshot.analysis.crate_for(file_id)?.first() {
+            Some(crate_id) => *crate_id,
+            None => return Ok(None),
+        };
+        let file = global_state_snapshot.detached_file_for_crate_root(crate_id);
+        Ok(file.map(|(path, edition)| DetachedFileSpec { path, edition }))
+    }
+
+    /// The name of the compiled executable.
+    pub(crate) fn name(&self) -> String {
+        self.path.file_stem().and_then(|it| it.to_str()).unwrap_or("main").to_string()
+    }
+
+    /// Returns the arguments of the `rustc` invocation compiling the file, and
+    /// the executable it builds. Only binaries can be run this way.
+    pub(crate) fn runnable_args(&self, kind: &RunnableKind) -> Option<(Vec<String>, String)> {
+        match kind {
+            RunnableKind::Bin => (),
+            RunnableKind::Test { .. }
+            | RunnableKind::TestMod { .. }
+            | RunnableKind::Bench { .. }
+            | RunnableKind::DocTest { .. } => return None,
+        }
+        let executable = std::env::temp_dir()
+            .join(format!("rust-analyzer-run-{}", self.name()))
+            .display()
+            .to_string();
+        let args = vec![
+            "--edition".to_string(),
+            self.edition.to_string(),
+            self.path.display().to_string(),
+            "-o".to_string(),
+            executable.clone(),
+        ];
+        Some((args, executable))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -213,6 +335,31 @@ mod tests {
         assert_eq!(features, expected_features);
     }
 
+    #[test]
+    fn test_project_json_runnable_args() {
+        let runnable = |kind, args: &[&str]| BuildRunnable {
+            program: "buck".to_string(),
+            args: args.iter().map(|it| it.to_string()).collect(),
+            cwd: AbsPathBuf::assert("/project".into()),
+            kind,
+        };
+        let spec = ProjectJsonTargetSpec {
+            label: "//foo:bar".to_string(),
+            target_kind: TargetKind::Test,
+            runnables: vec![
+                runnable(BuildRunnableKind::Check, &["build", "{label}"]),
+                runnable(BuildRunnableKind::TestOne, &["test", "{label}", "--", "{test_id}"]),
+            ],
+        };
+
+        let kind = RunnableKind::TestMod { path: "tests::nested".to_string() };
+        let (_, args) = spec.runnable_args(&kind).unwrap();
+        assert_eq!(args, ["test", "//foo:bar", "--", "tests::nested"]);
+        let (_, args) = spec.check_args().unwrap();
+        assert_eq!(args, ["build", "//foo:bar"]);
+        assert!(spec.runnable_args(&RunnableKind::Bin).is_none());
+    }
+
     #[test]
     fn test_cfg_expr_minimal_features_needed() {
         check(r#"#![cfg(feature = "baz")]"#, &["baz"]);
@@ -6,7 +6,9 @@ use anyhow::Result;
 use crossbeam_channel::{unbounded, Receiver};
 use ide::{AnalysisHost, Change};
 use ide_db::base_db::CrateGraph;
-use project_model::{CargoConfig, ProcMacroClient, ProjectManifest, ProjectWorkspace};
+use project_model::{
+    CargoConfig, ProcMacroClient, ProjectManifest, ProjectWorkspace, WorkspaceBuildData,
+};
 use vfs::{loader::Handle, AbsPath, AbsPathBuf};
 
 use crate::reload::{ProjectFolders, SourceRootConfig};
@@ -18,10 +20,14 @@ pub fn load_cargo(
 ) -> Result<(AnalysisHost, vfs::Vfs)> {
     let root = AbsPathBuf::assert(std::env::current_dir()?.join(root));
     let root = ProjectManifest::discover_single(&root)?;
-    let ws = ProjectWorkspace::load(
-        root,
-        &CargoConfig { load_out_dirs_from_check, ..Default::default() },
-    )?;
+    let config = CargoConfig { load_out_dirs_from_check, ..Default::default() };
+    let mut ws = ProjectWorkspace::load(root, &config)?;
+    if load_out_dirs_from_check {
+        if let ProjectWorkspace::Cargo { cargo, .. } = &mut ws {
+            let build_data = WorkspaceBuildData::collect(cargo, &config)?;
+            cargo.set_build_data(&build_data);
+        }
+    }
 
     let (sender, receiver) = unbounded();
     let mut vfs = vfs::Vfs::default();
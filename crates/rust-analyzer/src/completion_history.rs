@@ -0,0 +1,94 @@
+//! Keeps track of the completions the user accepted recently, so that they
+//! can be ranked higher the next time.
+//!
+//! LSP doesn't tell the server which completion item was accepted, so we
+//! remember the items offered by the last completion request, and look for a
+//! document change inserting one of them.
+use std::collections::VecDeque;
+
+use lsp_types::{TextDocumentContentChangeEvent, Url};
+use rustc_hash::FxHashSet;
+
+#[derive(Debug, Default)]
+pub(crate) struct CompletionHistory {
+    /// The document and the lookup strings of the last offered completions.
+    offered: Option<(Url, Vec<String>)>,
+    /// Most recently accepted first.
+    accepted: VecDeque<String>,
+}
+
+impl CompletionHistory {
+    const CAPACITY: usize = 32;
+
+    pub(crate) fn offered(&mut self, url: Url, lookups: Vec<String>) {
+        self.offered = Some((url, lookups));
+    }
+
+    pub(crate) fn on_change(&mut self, url: &Url, changes: &[TextDocumentContentChangeEvent]) {
+        let lookups = match &self.offered {
+            Some((offered_url, lookups)) if offered_url == url => lookups,
+            _ => return,
+        };
+        let accepted = changes.iter().find_map(|change| {
+            // Typing inserts one char at a time, accepting a completion inserts
+            // the whole item at once.
+            if change.range.is_none() || change.text.chars().nth(1).is_none() {
+                return None;
+            }
+            lookups.iter().find(|lookup| inserts(&change.text, lookup)).cloned()
+        });
+        if let Some(accepted) = accepted {
+            self.offered = None;
+            self.accepted.retain(|it| it != &accepted);
+            self.accepted.push_front(accepted);
+            self.accepted.truncate(Self::CAPACITY);
+        }
+    }
+
+    /// The lookup strings of the recently accepted completions.
+    pub(crate) fn recently_accepted(&self) -> FxHashSet<String> {
+        self.accepted.iter().cloned().collect()
+    }
+}
+
+/// Whether `text` is the insert text of an item with the given lookup, like
+/// `foo(${1:bar})` for `foo`.
+fn inserts(text: &str, lookup: &str) -> bool {
+    if lookup.is_empty() || !text.starts_with(lookup) {
+        return false;
+    }
+    match text[lookup.len()..].chars().next() {
+        Some(c) => !(c.is_alphanumeric() || c == '_'),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, Range};
+
+    use super::*;
+
+    fn change(text: &str) -> TextDocumentContentChangeEvent {
+        let range = Range::new(Position::new(0, 0), Position::new(0, 2));
+        TextDocumentContentChangeEvent { range: Some(range), range_length: None, text: text.into() }
+    }
+
+    #[test]
+    fn records_accepted_completions() {
+        let url = Url::parse("file:///main.rs").unwrap();
+        let other_url = Url::parse("file:///lib.rs").unwrap();
+        let mut history = CompletionHistory::default();
+        history.offered(url.clone(), vec!["foo".to_string(), "foobar".to_string()]);
+
+        history.on_change(&other_url, &[change("foo")]);
+        history.on_change(&url, &[change("f")]);
+        history.on_change(&url, &[change("fooba")]);
+        assert!(history.recently_accepted().is_empty());
+
+        history.on_change(&url, &[change("foobar($0)")]);
+        let recently_accepted = history.recently_accepted();
+        assert!(recently_accepted.contains("foobar"));
+        assert!(!recently_accepted.contains("foo"));
+    }
+}
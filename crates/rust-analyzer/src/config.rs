@@ -7,17 +7,23 @@
 //! configure the server itself, feature flags are passed into analysis, and
 //! tweak things like automatic insertion of `()` in completions.
 
-use std::{convert::TryFrom, ffi::OsString, path::PathBuf};
+use std::{collections::BTreeMap, convert::TryFrom, ffi::OsString, path::PathBuf};
 
+use cfg::CfgDiff;
 use flycheck::FlycheckConfig;
 use hir::PrefixKind;
-use ide::{AssistConfig, CompletionConfig, DiagnosticsConfig, HoverConfig, InlayHintsConfig};
-use ide_db::helpers::insert_use::MergeBehavior;
+use ide::{
+    AssistConfig, CompletionConfig, DiagnosticsConfig, HoverConfig, InlayHintsConfig, Snippet,
+    SnippetScope,
+};
+use ide_db::{base_db::Edition, helpers::insert_use::MergeBehavior};
 use itertools::Itertools;
 use lsp_types::{ClientCapabilities, MarkupKind};
-use project_model::{CargoConfig, ProjectJson, ProjectJsonData, ProjectManifest};
-use rustc_hash::FxHashSet;
-use serde::{de::DeserializeOwned, Deserialize};
+use project_model::{
+    CargoConfig, CfgFlag, PackageOverride, ProjectJson, ProjectJsonData, ProjectManifest,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
 use vfs::AbsPathBuf;
 
 use crate::{caps::enabled_completions_resolve_capabilities, diagnostics::DiagnosticsMapConfig};
@@ -39,8 +45,8 @@ config_data! {
         cargo_allFeatures: bool          = "false",
         /// List of features to activate.
         cargo_features: Vec<String>      = "[]",
-        /// Run `cargo check` on startup to get the correct value for package
-        /// OUT_DIRs.
+        /// Run `cargo check` in the background on startup to get the correct
+        /// value for package OUT_DIRs, build script cfgs and proc-macros.
         cargo_loadOutDirsFromCheck: bool = "false",
         /// Do not activate the `default` feature.
         cargo_noDefaultFeatures: bool    = "false",
@@ -48,8 +54,18 @@ config_data! {
         cargo_target: Option<String>     = "null",
         /// Internal config for debugging, disables loading of sysroot crates.
         cargo_noSysroot: bool            = "false",
+        /// Per-package overrides of the cargo settings, keyed by package name.
+        /// An override can set `features`, `noDefaultFeatures`, the `target`
+        /// triple and extra `cfgs`, like `foo` or `key="value"`.
+        /// `cfgAlternatives` lists sets of cfgs to enable, or to disable when
+        /// prefixed with `!`, to additionally analyze the package with, like
+        /// `[["windows", "!unix"]]`.
+        cargo_packageOverrides: FxHashMap<String, PackageOverrideDef> = "{}",
 
         /// Run specified `cargo check` command for diagnostics on save.
+        /// The crates of `rust-project.json` projects are checked by
+        /// invoking `rustc` on each of them instead, unless
+        /// `#rust-analyzer.checkOnSave.overrideCommand#` is set.
         checkOnSave_enable: bool                         = "true",
         /// Check with all features (will be passed as `--all-features`).
         /// Defaults to `rust-analyzer.cargo.allFeatures`.
@@ -82,6 +98,16 @@ config_data! {
         /// Toggles the additional completions that automatically add imports when completed.
         /// Note that your client have to specify the `additionalTextEdits` LSP client capability to truly have this feature enabled.
         completion_autoimport_enable: bool       = "true",
+        /// User-defined completion snippets, keyed by name. A snippet has
+        /// `prefix` and/or `postfix` triggers, a `body` (postfix snippets
+        /// refer to the receiver as `${receiver}`), an optional `description`,
+        /// a `scope` (`expr`, `item` or `type`) and a list of paths it
+        /// `requires`, which have to resolve and are imported on completion.
+        completion_snippets: BTreeMap<String, SnippetDef> = "{}",
+
+        /// Edition of the Rust files opened outside of any project, and of the
+        /// packages of the scripts among them without a `[package]` section.
+        detachedFiles_edition: String = "\"2018\"",
 
         /// Whether to show native rust-analyzer diagnostics.
         diagnostics_enable: bool                = "true",
@@ -99,6 +125,15 @@ config_data! {
         /// will not show up in the problems panel.
         diagnostics_warningsAsInfo: Vec<String> = "[]",
 
+        /// Command generating a `rust-project.json` for the files opened
+        /// outside of the known projects, for build systems other than cargo.
+        /// It is run in the workspace root with the path of the file appended,
+        /// and should print the project for just the relevant targets on
+        /// stdout. The projects of all the opened files are merged, and
+        /// regenerated when one of the files listed in their `build_files`
+        /// changes.
+        discoverProjectCommand: Option<Vec<String>> = "null",
+
         /// Controls file watching implementation.
         files_watcher: String = "\"client\"",
 
@@ -201,6 +236,8 @@ pub struct Config {
     pub code_lens_refresh: bool,
 
     pub linked_projects: Vec<LinkedProject>,
+    pub discover_project_command: Option<Vec<String>>,
+    pub detached_files_edition: Edition,
     pub root_path: AbsPathBuf,
 }
 
@@ -326,6 +363,7 @@ impl Config {
                 all_features: false,
                 extra_args: Vec::new(),
                 features: Vec::new(),
+                package_overrides: Vec::new(),
             }),
             runnables: RunnablesConfig::default(),
 
@@ -343,6 +381,8 @@ impl Config {
             semantic_tokens_refresh: false,
             code_lens_refresh: false,
             linked_projects: Vec::new(),
+            discover_project_command: None,
+            detached_files_edition: Edition::Edition2018,
             root_path,
         };
         res.do_update(serde_json::json!({}));
@@ -396,6 +436,42 @@ impl Config {
             target: data.cargo_target.clone(),
             rustc_source: rustc_source,
             no_sysroot: data.cargo_noSysroot,
+            package_overrides: data
+                .cargo_packageOverrides
+                .iter()
+                .map(|(name, def)| {
+                    let cfgs = def
+                        .cfgs
+                        .iter()
+                        .filter_map(|cfg| match cfg.parse::<CfgFlag>() {
+                            Ok(it) => Some(it),
+                            Err(err) => {
+                                log::error!("invalid cfg in override of {}: {}", name, err);
+                                None
+                            }
+                        })
+                        .collect();
+                    let cfg_alternatives = def
+                        .cfg_alternatives
+                        .iter()
+                        .filter_map(|alternative| {
+                            let res = parse_cfg_diff(alternative);
+                            if let Err(err) = &res {
+                                log::error!("invalid cfg alternative for {}: {}", name, err);
+                            }
+                            res.ok()
+                        })
+                        .collect();
+                    let package_override = PackageOverride {
+                        features: def.features.clone(),
+                        no_default_features: def.no_default_features,
+                        target: def.target.clone(),
+                        cfgs,
+                        cfg_alternatives,
+                    };
+                    (name.clone(), package_override)
+                })
+                .collect(),
         };
         self.runnables = RunnablesConfig {
             override_cargo: data.runnables_overrideCargo,
@@ -432,6 +508,8 @@ impl Config {
                     all_features: data.checkOnSave_allFeatures.unwrap_or(data.cargo_allFeatures),
                     features: data.checkOnSave_features.unwrap_or(data.cargo_features),
                     extra_args: data.checkOnSave_extraArgs,
+                    // Filled in per workspace, see `GlobalState::reload_flycheck`.
+                    package_overrides: Vec::new(),
                 },
             };
             Some(flycheck_config)
@@ -462,6 +540,29 @@ impl Config {
         self.completion.add_call_parenthesis = data.completion_addCallParenthesis;
         self.completion.add_call_argument_snippets = data.completion_addCallArgumentSnippets;
         self.completion.merge = self.assist.insert_use.merge;
+        self.completion.snippets = data
+            .completion_snippets
+            .iter()
+            .filter_map(|(name, def)| {
+                let scope = match def.scope {
+                    SnippetScopeDef::Expr => SnippetScope::Expr,
+                    SnippetScopeDef::Item => SnippetScope::Item,
+                    SnippetScopeDef::Type => SnippetScope::Type,
+                };
+                let snippet = Snippet::new(
+                    &def.prefix,
+                    &def.postfix,
+                    &def.body,
+                    def.description.as_deref().unwrap_or(name),
+                    &def.requires,
+                    scope,
+                );
+                if snippet.is_none() {
+                    log::warn!("invalid snippet {:?}: {:?}", name, def);
+                }
+                snippet
+            })
+            .collect();
 
         self.call_info_full = data.callInfo_full;
 
@@ -494,6 +595,18 @@ impl Config {
             }
         }
 
+        self.discover_project_command = match data.discoverProjectCommand {
+            Some(command) if !command.is_empty() => Some(command),
+            Some(_) | None => None,
+        };
+        self.detached_files_edition = match data.detachedFiles_edition.parse() {
+            Ok(it) => it,
+            Err(err) => {
+                log::error!("invalid detachedFiles.edition: {}", err);
+                Edition::Edition2018
+            }
+        };
+
         self.hover = HoverConfig {
             implementations: data.hoverActions_enable && data.hoverActions_implementations,
             run: data.hoverActions_enable && data.hoverActions_run,
@@ -604,6 +717,75 @@ enum ManifestOrProjectJson {
     ProjectJson(ProjectJsonData),
 }
 
+#[derive(Deserialize, Debug)]
+struct SnippetDef {
+    #[serde(deserialize_with = "single_or_array", default)]
+    prefix: Vec<String>,
+    #[serde(deserialize_with = "single_or_array", default)]
+    postfix: Vec<String>,
+    #[serde(deserialize_with = "single_or_array")]
+    body: Vec<String>,
+    description: Option<String>,
+    #[serde(default)]
+    requires: Vec<String>,
+    #[serde(default)]
+    scope: SnippetScopeDef,
+}
+
+/// Parses a list of cfgs to enable, or to disable when prefixed with `!`.
+fn parse_cfg_diff(cfgs: &[String]) -> Result<CfgDiff, String> {
+    let mut enable = Vec::new();
+    let mut disable = Vec::new();
+    for cfg in cfgs {
+        match cfg.strip_prefix('!') {
+            Some(cfg) => disable.push(cfg.parse::<CfgFlag>()?.into()),
+            None => enable.push(cfg.parse::<CfgFlag>()?.into()),
+        }
+    }
+    CfgDiff::new(enable, disable).ok_or_else(|| format!("duplicate cfgs in {:?}", cfgs))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PackageOverrideDef {
+    features: Option<Vec<String>>,
+    #[serde(default)]
+    no_default_features: bool,
+    target: Option<String>,
+    #[serde(default)]
+    cfgs: Vec<String>,
+    #[serde(default)]
+    cfg_alternatives: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum SnippetScopeDef {
+    Expr,
+    Item,
+    Type,
+}
+
+impl Default for SnippetScopeDef {
+    fn default() -> Self {
+        SnippetScopeDef::Expr
+    }
+}
+
+fn single_or_array<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SingleOrArray {
+        Single(String),
+        Array(Vec<String>),
+    }
+
+    Ok(match SingleOrArray::deserialize(deserializer)? {
+        SingleOrArray::Single(it) => vec![it],
+        SingleOrArray::Array(it) => it,
+    })
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "snake_case")]
 enum MergeBehaviorDef {
@@ -762,6 +944,12 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
                 "Force import paths to be absolute by always starting them with `crate` or the crate name they refer to."
             ],
         },
+        "BTreeMap<String, SnippetDef>" => set! {
+            "type": "object",
+        },
+        "FxHashMap<String, PackageOverrideDef>" => set! {
+            "type": "object",
+        },
         "Vec<ManifestOrProjectJson>" => set! {
             "type": "array",
             "items": { "type": ["string", "object"] },
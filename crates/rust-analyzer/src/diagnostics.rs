@@ -8,7 +8,8 @@ use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::lsp_ext;
 
-pub(crate) type CheckFixes = Arc<FxHashMap<FileId, Vec<Fix>>>;
+/// Fixes of the check diagnostics, by package id and file.
+pub(crate) type CheckFixes = Arc<FxHashMap<String, FxHashMap<FileId, Vec<Fix>>>>;
 
 #[derive(Debug, Default, Clone)]
 pub struct DiagnosticsMapConfig {
@@ -21,7 +22,9 @@ pub(crate) struct DiagnosticCollection {
     // FIXME: should be FxHashMap<FileId, Vec<ra_id::Diagnostic>>
     pub(crate) native: FxHashMap<FileId, Vec<lsp_types::Diagnostic>>,
     // FIXME: should be Vec<flycheck::Diagnostic>
-    pub(crate) check: FxHashMap<FileId, Vec<lsp_types::Diagnostic>>,
+    /// Diagnostics of the check command, by package id and file, so that
+    /// checking a package doesn't clear the diagnostics of the others.
+    pub(crate) check: FxHashMap<String, FxHashMap<FileId, Vec<lsp_types::Diagnostic>>>,
     pub(crate) check_fixes: CheckFixes,
     changes: FxHashSet<FileId>,
 }
@@ -35,16 +38,27 @@ pub(crate) struct Fix {
 impl DiagnosticCollection {
     pub(crate) fn clear_check(&mut self) {
         Arc::make_mut(&mut self.check_fixes).clear();
-        self.changes.extend(self.check.drain().map(|(key, _value)| key))
+        for (_package_id, diagnostics) in self.check.drain() {
+            self.changes.extend(diagnostics.into_iter().map(|(key, _value)| key));
+        }
+    }
+
+    pub(crate) fn clear_check_for_package(&mut self, package_id: &str) {
+        Arc::make_mut(&mut self.check_fixes).remove(package_id);
+        if let Some(diagnostics) = self.check.remove(package_id) {
+            self.changes.extend(diagnostics.into_iter().map(|(key, _value)| key));
+        }
     }
 
     pub(crate) fn add_check_diagnostic(
         &mut self,
+        package_id: String,
         file_id: FileId,
         diagnostic: lsp_types::Diagnostic,
         fixes: Vec<lsp_ext::CodeAction>,
     ) {
-        let diagnostics = self.check.entry(file_id).or_default();
+        let diagnostics =
+            self.check.entry(package_id.clone()).or_default().entry(file_id).or_default();
         for existing_diagnostic in diagnostics.iter() {
             if are_diagnostics_equal(&existing_diagnostic, &diagnostic) {
                 return;
@@ -53,6 +67,8 @@ impl DiagnosticCollection {
 
         let check_fixes = Arc::make_mut(&mut self.check_fixes);
         check_fixes
+            .entry(package_id)
+            .or_default()
             .entry(file_id)
             .or_default()
             .extend(fixes.into_iter().map(|action| Fix { range: diagnostic.range, action }));
@@ -74,7 +90,7 @@ impl DiagnosticCollection {
         file_id: FileId,
     ) -> impl Iterator<Item = &lsp_types::Diagnostic> {
         let native = self.native.get(&file_id).into_iter().flatten();
-        let check = self.check.get(&file_id).into_iter().flatten();
+        let check = self.check.values().filter_map(move |it| it.get(&file_id)).flatten();
         native.chain(check)
     }
 
@@ -8,21 +8,26 @@ use std::{sync::Arc, time::Instant};
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use flycheck::FlycheckHandle;
 use ide::{Analysis, AnalysisHost, Change, FileId};
-use ide_db::base_db::{CrateId, VfsPath};
+use ide_db::base_db::{CrateId, Edition, VfsPath};
+use itertools::Itertools;
 use lsp_types::{SemanticTokens, Url};
 use parking_lot::{Mutex, RwLock};
-use project_model::{CargoWorkspace, ProcMacroClient, ProjectWorkspace, Target};
+use project_model::{
+    Build, CargoWorkspace, PackageOverride, ProcMacroClient, ProjectJson, ProjectWorkspace, Target,
+    WorkspaceBuildData,
+};
 use rustc_hash::FxHashMap;
-use vfs::AnchoredPathBuf;
+use vfs::{AbsPathBuf, AnchoredPathBuf};
 
 use crate::{
+    completion_history::CompletionHistory,
     config::Config,
     diagnostics::{CheckFixes, DiagnosticCollection},
     document::DocumentData,
     from_proto,
     line_endings::LineEndings,
     main_loop::Task,
-    reload::SourceRootConfig,
+    reload::{rustc_crates, SourceRootConfig},
     request_metrics::{LatestRequests, RequestMetrics},
     thread_pool::TaskPool,
     to_proto::url_from_abs_path,
@@ -72,12 +77,40 @@ pub(crate) struct GlobalState {
     pub(crate) diagnostics: DiagnosticCollection,
     pub(crate) mem_docs: FxHashMap<VfsPath, DocumentData>,
     pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, SemanticTokens>>>,
+    pub(crate) completion_history: Arc<Mutex<CompletionHistory>>,
     pub(crate) vfs: Arc<RwLock<(vfs::Vfs, FxHashMap<FileId, LineEndings>)>>,
     pub(crate) shutdown_requested: bool,
     pub(crate) status: Status,
     pub(crate) source_root_config: SourceRootConfig,
     pub(crate) proc_macro_client: Option<ProcMacroClient>,
     pub(crate) workspaces: Arc<Vec<ProjectWorkspace>>,
+    /// Build data of the cargo workspaces, keyed by their manifest path.
+    pub(crate) build_data: FxHashMap<AbsPathBuf, WorkspaceBuildData>,
+    /// Package overrides set at runtime, taking precedence over the ones from
+    /// the config.
+    pub(crate) package_overrides: FxHashMap<String, PackageOverride>,
+    /// Projects generated by the discovery command, keyed by the file they were
+    /// generated for. `None` while the command runs.
+    pub(crate) discovered_projects: FxHashMap<AbsPathBuf, Option<ProjectJson>>,
+    /// Files opened outside of any project, loaded on their own.
+    pub(crate) detached_files: Vec<AbsPathBuf>,
+    /// Whether `detached_files` changed since they were last loaded.
+    pub(crate) detached_files_changed: bool,
+    /// The number of workspaces of the detached files, which come after the
+    /// ones of the projects in `workspaces`.
+    pub(crate) n_detached_workspaces: usize,
+    /// Whether the projects are being loaded.
+    pub(crate) fetching_workspaces: bool,
+    /// Whether the detached files are being loaded.
+    pub(crate) fetching_detached_files: bool,
+    /// Whether the build data is being collected.
+    pub(crate) fetching_build_data: bool,
+    /// Whether the build data has to be collected again once the current run
+    /// is done.
+    pub(crate) fetch_build_data_queued: bool,
+    /// Versions of the crates in the local registry cache, by crate name, as of
+    /// the last scan.
+    pub(crate) local_registry: FxHashMap<String, Vec<String>>,
     latest_requests: Arc<RwLock<LatestRequests>>,
 }
 
@@ -89,6 +122,7 @@ pub(crate) struct GlobalStateSnapshot {
     pub(crate) latest_requests: Arc<RwLock<LatestRequests>>,
     mem_docs: FxHashMap<VfsPath, DocumentData>,
     pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, SemanticTokens>>>,
+    pub(crate) completion_history: Arc<Mutex<CompletionHistory>>,
     vfs: Arc<RwLock<(vfs::Vfs, FxHashMap<FileId, LineEndings>)>>,
     pub(crate) workspaces: Arc<Vec<ProjectWorkspace>>,
 }
@@ -124,12 +158,24 @@ impl GlobalState {
             diagnostics: Default::default(),
             mem_docs: FxHashMap::default(),
             semantic_tokens_cache: Arc::new(Default::default()),
+            completion_history: Arc::new(Default::default()),
             vfs: Arc::new(RwLock::new((vfs::Vfs::default(), FxHashMap::default()))),
             shutdown_requested: false,
             status: Status::default(),
             source_root_config: SourceRootConfig::default(),
             proc_macro_client: None,
             workspaces: Arc::new(Vec::new()),
+            build_data: FxHashMap::default(),
+            package_overrides: FxHashMap::default(),
+            discovered_projects: FxHashMap::default(),
+            detached_files: Vec::new(),
+            detached_files_changed: false,
+            n_detached_workspaces: 0,
+            fetching_workspaces: false,
+            fetching_detached_files: false,
+            fetching_build_data: false,
+            fetch_build_data_queued: false,
+            local_registry: FxHashMap::default(),
             latest_requests: Default::default(),
         }
     }
@@ -192,6 +238,61 @@ impl GlobalState {
             check_fixes: Arc::clone(&self.diagnostics.check_fixes),
             mem_docs: self.mem_docs.clone(),
             semantic_tokens_cache: Arc::clone(&self.semantic_tokens_cache),
+            completion_history: Arc::clone(&self.completion_history),
+        }
+    }
+
+    /// The `-p` specs of the packages of the `ws`th workspace affected by a
+    /// change to `file_id`: the packages the file belongs to and the workspace
+    /// members depending on them.
+    ///
+    /// For `rust-project.json` workspaces, checked with `rustc`, these are the
+    /// names of the crates the file belongs to, flycheck rechecking the crates
+    /// depending on them itself.
+    pub(crate) fn packages_to_check(&self, ws: usize, file_id: FileId) -> Vec<String> {
+        let analysis = self.analysis_host.analysis();
+        let crates = analysis.crate_for(file_id).unwrap_or_default();
+        let vfs = &self.vfs.read().0;
+        let roots: Vec<AbsPathBuf> = crates
+            .into_iter()
+            .filter_map(|krate| {
+                let path = vfs.file_path(analysis.crate_root(krate).ok()?);
+                Some(path.as_path()?.to_path_buf())
+            })
+            .collect();
+        match self.workspaces.get(ws) {
+            Some(ProjectWorkspace::Cargo { cargo, .. }) => roots
+                .iter()
+                .filter_map(|root| cargo.target_by_root(root))
+                .flat_map(|target| cargo.reverse_dependencies(cargo[target].package))
+                .unique()
+                .map(|pkg| cargo.package_flag(&cargo[pkg]))
+                .collect(),
+            Some(ProjectWorkspace::Json { project, .. }) => rustc_crates(project)
+                .into_iter()
+                .filter(|krate| {
+                    roots.iter().any(|root| krate.root_module == root.as_path().as_ref())
+                })
+                .map(|krate| krate.name)
+                .unique()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Maps the `-p` specs of a check of the `ws`th workspace back to the ids
+    /// of the packages.
+    pub(crate) fn package_ids(&self, ws: usize, specs: &[String]) -> Vec<String> {
+        match self.workspaces.get(ws) {
+            Some(ProjectWorkspace::Cargo { cargo, .. }) => cargo
+                .packages()
+                .filter(|&pkg| specs.contains(&cargo.package_flag(&cargo[pkg])))
+                .map(|pkg| cargo[pkg].id.clone())
+                .collect(),
+            // The diagnostics of `rustc` checks are recorded under the crate
+            // names.
+            Some(ProjectWorkspace::Json { .. }) => specs.to_vec(),
+            _ => Vec::new(),
         }
     }
 
@@ -288,7 +389,44 @@ impl GlobalStateSnapshot {
             ProjectWorkspace::Cargo { cargo, .. } => {
                 cargo.target_by_root(&path).map(|it| (cargo, it))
             }
-            ProjectWorkspace::Json { .. } => None,
+            ProjectWorkspace::Json { .. } | ProjectWorkspace::DetachedFiles { .. } => None,
+        })
+    }
+
+    /// Returns the `rust-project.json` project of the crate, and the build
+    /// system target it comes from.
+    pub(crate) fn project_json_target_for_crate_root(
+        &self,
+        crate_id: CrateId,
+    ) -> Option<(&ProjectJson, &Build)> {
+        let file_id = self.analysis.crate_root(crate_id).ok()?;
+        let path = self.vfs.read().0.file_path(file_id);
+        let path = path.as_path()?;
+        self.workspaces.iter().find_map(|ws| match ws {
+            ProjectWorkspace::Json { project, .. } => project
+                .crates()
+                .find(|(_, krate)| &*krate.root_module == path)
+                .and_then(|(_, krate)| krate.build.as_ref())
+                .map(|build| (project, build)),
+            ProjectWorkspace::Cargo { .. } | ProjectWorkspace::DetachedFiles { .. } => None,
+        })
+    }
+
+    /// Returns the path of the crate root and the edition it was loaded with,
+    /// if it is a detached file.
+    pub(crate) fn detached_file_for_crate_root(
+        &self,
+        crate_id: CrateId,
+    ) -> Option<(AbsPathBuf, Edition)> {
+        let file_id = self.analysis.crate_root(crate_id).ok()?;
+        let path = self.vfs.read().0.file_path(file_id);
+        let path = path.as_path()?;
+        self.workspaces.iter().find_map(|ws| match ws {
+            ProjectWorkspace::DetachedFiles { files, edition, .. } => {
+                let file = files.iter().find(|file| file.as_path() == path)?;
+                Some((file.clone(), *edition))
+            }
+            ProjectWorkspace::Cargo { .. } | ProjectWorkspace::Json { .. } => None,
         })
     }
 }
@@ -23,14 +23,14 @@ use lsp_types::{
     SemanticTokensRangeParams, SemanticTokensRangeResult, SemanticTokensResult, SymbolInformation,
     SymbolTag, TextDocumentIdentifier, TextDocumentPositionParams, Url, WorkspaceEdit,
 };
-use project_model::TargetKind;
+use project_model::{ProjectWorkspace, TargetKind};
 use serde::{Deserialize, Serialize};
 use serde_json::to_value;
 use stdx::{format_to, split_once};
 use syntax::{algo, ast, AstNode, TextRange, TextSize};
 
 use crate::{
-    cargo_target_spec::CargoTargetSpec,
+    cargo_target_spec::{CargoTargetSpec, DetachedFileSpec, ProjectJsonTargetSpec},
     config::RustfmtConfig,
     from_json, from_proto,
     global_state::{GlobalState, GlobalStateSnapshot},
@@ -90,6 +90,32 @@ pub(crate) fn handle_memory_usage(state: &mut GlobalState, _: ()) -> Result<Stri
     Ok(out)
 }
 
+pub(crate) fn handle_set_package_features(
+    state: &mut GlobalState,
+    params: lsp_ext::SetPackageFeaturesParams,
+) -> Result<()> {
+    let _p = profile::span("handle_set_package_features");
+    let is_known = state.workspaces.iter().any(|ws| match ws {
+        ProjectWorkspace::Cargo { cargo, .. } => cargo.package_by_name(&params.package).is_some(),
+        ProjectWorkspace::Json { .. } | ProjectWorkspace::DetachedFiles { .. } => false,
+    });
+    if !is_known {
+        return Err(LspError::new(
+            ErrorCode::InvalidParams as i32,
+            format!("unknown package: {}", params.package),
+        )
+        .into());
+    }
+
+    let config_override =
+        state.config.cargo.package_overrides.get(&params.package).cloned().unwrap_or_default();
+    let package_override = state.package_overrides.entry(params.package).or_insert(config_override);
+    package_override.features = Some(params.features);
+    package_override.no_default_features = params.no_default_features;
+    state.refresh_workspaces();
+    Ok(())
+}
+
 pub(crate) fn handle_syntax_tree(
     snap: GlobalStateSnapshot,
     params: lsp_ext::SyntaxTreeParams,
@@ -465,6 +491,9 @@ pub(crate) fn handle_runnables(
     let line_index = snap.analysis.file_line_index(file_id)?;
     let offset = params.position.map(|it| from_proto::offset(&line_index, it));
     let cargo_spec = CargoTargetSpec::for_file(&snap, file_id)?;
+    let json_spec = ProjectJsonTargetSpec::for_file(&snap, file_id)?;
+    let detached_spec = DetachedFileSpec::for_file(&snap, file_id)?;
+    let target_kind = target_kind(cargo_spec.as_ref(), json_spec.as_ref(), detached_spec.as_ref());
 
     let expect_test = match offset {
         Some(offset) => {
@@ -483,7 +512,7 @@ pub(crate) fn handle_runnables(
                 continue;
             }
         }
-        if should_skip_target(&runnable, cargo_spec.as_ref()) {
+        if should_skip_target(&runnable, target_kind) {
             continue;
         }
         let mut runnable = to_proto::runnable(&snap, file_id, runnable)?;
@@ -515,12 +544,29 @@ pub(crate) fn handle_runnables(
                         cargo_extra_args: config.cargo_extra_args.clone(),
                         executable_args: Vec::new(),
                         expect_test: None,
+                        executable: None,
                     },
                 })
             }
         }
-        None => {
-            res.push(lsp_ext::Runnable {
+        None => match json_spec.as_ref().and_then(|it| it.check_args()) {
+            Some((command, args)) => res.push(lsp_ext::Runnable {
+                label: format!("{} {}", command.program, args.join(" ")),
+                location: None,
+                kind: lsp_ext::RunnableKind::Cargo,
+                args: lsp_ext::CargoRunnable {
+                    workspace_root: Some(command.cwd.clone().into()),
+                    override_cargo: Some(command.program.clone()),
+                    cargo_args: args,
+                    cargo_extra_args: Vec::new(),
+                    executable_args: Vec::new(),
+                    expect_test: None,
+                    executable: None,
+                },
+            }),
+            // There is no project to check for a detached file.
+            None if detached_spec.is_some() => (),
+            None => res.push(lsp_ext::Runnable {
                 label: "cargo check --workspace".to_string(),
                 location: None,
                 kind: lsp_ext::RunnableKind::Cargo,
@@ -531,9 +577,10 @@ pub(crate) fn handle_runnables(
                     cargo_extra_args: config.cargo_extra_args.clone(),
                     executable_args: Vec::new(),
                     expect_test: None,
+                    executable: None,
                 },
-            });
-        }
+            }),
+        },
     }
     Ok(res)
 }
@@ -574,9 +621,20 @@ pub(crate) fn handle_completion(
     let line_index = snap.analysis.file_line_index(position.file_id)?;
     let line_endings = snap.file_line_endings(position.file_id);
 
+    // Other requests and notifications wait on the lock, so it isn't held
+    // while the items are converted.
+    let recently_accepted = {
+        let mut completion_history = snap.completion_history.lock();
+        completion_history.offered(
+            text_document_position.text_document.uri.clone(),
+            items.iter().map(|item| item.lookup().to_string()).collect(),
+        );
+        completion_history.recently_accepted()
+    };
     let items: Vec<CompletionItem> = items
         .into_iter()
-        .flat_map(|item| {
+        .flat_map(|mut item| {
+            item.set_recently_accepted(recently_accepted.contains(item.lookup()));
             let mut new_completion_items =
                 to_proto::completion_item(&line_index, line_endings, item.clone());
 
@@ -905,7 +963,7 @@ fn handle_fixes(
         res.push(action);
     }
 
-    for fix in snap.check_fixes.get(&file_id).into_iter().flatten() {
+    for fix in snap.check_fixes.values().filter_map(|it| it.get(&file_id)).flatten() {
         let fix_range = from_proto::text_range(&line_index, fix.range);
         if fix_range.intersect(range).is_none() {
             continue;
@@ -1003,11 +1061,14 @@ pub(crate) fn handle_code_lens(
     let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
     let line_index = snap.analysis.file_line_index(file_id)?;
     let cargo_spec = CargoTargetSpec::for_file(&snap, file_id)?;
+    let json_spec = ProjectJsonTargetSpec::for_file(&snap, file_id)?;
+    let detached_spec = DetachedFileSpec::for_file(&snap, file_id)?;
+    let target_kind = target_kind(cargo_spec.as_ref(), json_spec.as_ref(), detached_spec.as_ref());
 
     if snap.config.lens.runnable() {
         // Gather runnables
         for runnable in snap.analysis.runnables(file_id)? {
-            if should_skip_target(&runnable, cargo_spec.as_ref()) {
+            if should_skip_target(&runnable, target_kind) {
                 continue;
             }
 
@@ -1023,7 +1084,7 @@ pub(crate) fn handle_code_lens(
                 lenses.push(lens);
             }
 
-            if action.debugee && snap.config.lens.debug {
+            if action.debugee && r.kind.is_debuggable() && snap.config.lens.debug {
                 let debug_lens =
                     CodeLens { range, command: Some(debug_single_command(&r)), data: None };
                 lenses.push(debug_lens);
@@ -1543,7 +1604,10 @@ fn runnable_action_links(
     runnable: Runnable,
 ) -> Option<lsp_ext::CommandLinkGroup> {
     let cargo_spec = CargoTargetSpec::for_file(&snap, file_id).ok()?;
-    if !snap.config.hover.runnable() || should_skip_target(&runnable, cargo_spec.as_ref()) {
+    let json_spec = ProjectJsonTargetSpec::for_file(&snap, file_id).ok()?;
+    let detached_spec = DetachedFileSpec::for_file(&snap, file_id).ok()?;
+    let target_kind = target_kind(cargo_spec.as_ref(), json_spec.as_ref(), detached_spec.as_ref());
+    if !snap.config.hover.runnable() || should_skip_target(&runnable, target_kind) {
         return None;
     }
 
@@ -1556,7 +1620,7 @@ fn runnable_action_links(
             group.commands.push(to_command_link(run_command, r.label.clone()));
         }
 
-        if snap.config.hover.debug {
+        if snap.config.hover.debug && r.kind.is_debuggable() {
             let dbg_command = debug_single_command(&r);
             group.commands.push(to_command_link(dbg_command, r.label));
         }
@@ -1604,15 +1668,27 @@ fn prepare_hover_actions(
         .collect()
 }
 
-fn should_skip_target(runnable: &Runnable, cargo_spec: Option<&CargoTargetSpec>) -> bool {
+/// The kind of the cargo or `rust-project.json` target of a file. Detached
+/// files are run as binaries.
+fn target_kind(
+    cargo_spec: Option<&CargoTargetSpec>,
+    json_spec: Option<&ProjectJsonTargetSpec>,
+    detached_spec: Option<&DetachedFileSpec>,
+) -> Option<TargetKind> {
+    cargo_spec
+        .map(|it| it.target_kind)
+        .or_else(|| json_spec.map(|it| it.target_kind))
+        .or_else(|| detached_spec.map(|_| TargetKind::Bin))
+}
+
+fn should_skip_target(runnable: &Runnable, target_kind: Option<TargetKind>) -> bool {
     match runnable.kind {
         RunnableKind::Bin => {
             // Do not suggest binary run on other target than binary
-            match &cargo_spec {
-                Some(spec) => !matches!(
-                    spec.target_kind,
-                    TargetKind::Bin | TargetKind::Example | TargetKind::Test
-                ),
+            match target_kind {
+                Some(kind) => {
+                    !matches!(kind, TargetKind::Bin | TargetKind::Example | TargetKind::Test)
+                }
                 None => true,
             }
         }
@@ -38,6 +38,31 @@ impl Request for ReloadWorkspace {
     const METHOD: &'static str = "rust-analyzer/reloadWorkspace";
 }
 
+pub enum CancelFlycheck {}
+
+impl Request for CancelFlycheck {
+    type Params = ();
+    type Result = ();
+    const METHOD: &'static str = "rust-analyzer/cancelFlycheck";
+}
+
+pub enum SetPackageFeatures {}
+
+impl Request for SetPackageFeatures {
+    type Params = SetPackageFeaturesParams;
+    type Result = ();
+    const METHOD: &'static str = "rust-analyzer/setPackageFeatures";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SetPackageFeaturesParams {
+    pub package: String,
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub no_default_features: bool,
+}
+
 pub enum SyntaxTree {}
 
 impl Request for SyntaxTree {
@@ -150,6 +175,15 @@ pub struct Runnable {
 #[serde(rename_all = "lowercase")]
 pub enum RunnableKind {
     Cargo,
+    /// Compiles a single file with `rustc`, then runs the executable.
+    Rustc,
+}
+
+impl RunnableKind {
+    /// Whether the client can start a debug session for the runnable.
+    pub fn is_debuggable(&self) -> bool {
+        matches!(self, RunnableKind::Cargo)
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -167,6 +201,10 @@ pub struct CargoRunnable {
     pub executable_args: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expect_test: Option<bool>,
+    // for the `rustc` kind, the executable built by the command, which is then
+    // run with the executable args
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub executable: Option<String>,
 }
 
 pub enum InlayHints {}
@@ -2,10 +2,10 @@
 use std::{error::Error, ops::Range};
 
 use ide::LineIndex;
-use ide_db::base_db::Canceled;
-use lsp_server::Notification;
+use ide_db::base_db::{Canceled, VfsPath};
+use lsp_server::{Notification, Request};
 
-use crate::{from_proto, global_state::GlobalState};
+use crate::{from_proto, global_state::GlobalState, lsp_ext};
 
 pub(crate) fn is_canceled(e: &(dyn Error + 'static)) -> bool {
     e.downcast_ref::<Canceled>().is_some()
@@ -17,6 +17,22 @@ pub(crate) fn notification_is<N: lsp_types::notification::Notification>(
     notification.method == N::METHOD
 }
 
+pub(crate) fn is_cargo_toml(path: &VfsPath) -> bool {
+    path.name_and_extension() == Some(("Cargo", Some("toml")))
+}
+
+/// `Cargo.toml` files are only completed and hovered, the other requests
+/// expect Rust files.
+pub(crate) fn is_unsupported_manifest_request(request: &Request) -> bool {
+    if request.method == <lsp_types::request::Completion as lsp_types::request::Request>::METHOD
+        || request.method == <lsp_ext::HoverRequest as lsp_types::request::Request>::METHOD
+    {
+        return false;
+    }
+    let uri = request.params.get("textDocument").and_then(|it| it.get("uri"));
+    matches!(uri.and_then(|it| it.as_str()), Some(uri) if uri.ends_with("/Cargo.toml"))
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub(crate) enum Progress {
     Begin,
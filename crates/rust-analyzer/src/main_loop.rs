@@ -1,7 +1,7 @@
 //! The main loop of `rust-analyzer` responsible for dispatching LSP
 //! requests/replies and notifications back to the client.
 use std::{
-    env, fmt,
+    env, fmt, mem,
     time::{Duration, Instant},
 };
 
@@ -11,8 +11,9 @@ use ide::{Canceled, FileId};
 use ide_db::base_db::VfsPath;
 use lsp_server::{Connection, Notification, Request, Response};
 use lsp_types::notification::Notification as _;
-use project_model::ProjectWorkspace;
-use vfs::ChangeKind;
+use project_model::{ProjectJson, ProjectWorkspace};
+use rustc_hash::FxHashMap;
+use vfs::{AbsPathBuf, ChangeKind};
 
 use crate::{
     config::Config,
@@ -21,7 +22,11 @@ use crate::{
     from_proto,
     global_state::{file_id_to_url, url_to_file_id, GlobalState, Status},
     handlers, lsp_ext,
-    lsp_utils::{apply_document_changes, is_canceled, notification_is, Progress},
+    lsp_utils::{
+        apply_document_changes, is_canceled, is_cargo_toml, is_unsupported_manifest_request,
+        notification_is, Progress,
+    },
+    reload::BuildDataProgress,
     Result,
 };
 
@@ -61,7 +66,14 @@ enum Event {
 pub(crate) enum Task {
     Response(Response),
     Diagnostics(Vec<(FileId, Vec<lsp_types::Diagnostic>)>),
-    Workspaces(Vec<anyhow::Result<ProjectWorkspace>>),
+    /// Loaded workspaces, with the versions of the crates in the local
+    /// registry cache by crate name.
+    Workspaces(Vec<anyhow::Result<ProjectWorkspace>>, FxHashMap<String, Vec<String>>),
+    /// Workspaces of the files opened outside of any project.
+    DetachedFiles(Vec<anyhow::Result<ProjectWorkspace>>),
+    BuildData(BuildDataProgress),
+    /// Projects generated by the discovery command, for the given files.
+    DiscoveredProjects(Vec<(AbsPathBuf, anyhow::Result<ProjectJson>)>),
     PrimeCaches(PrimeCachesProgress),
 }
 
@@ -99,7 +111,9 @@ impl fmt::Debug for Event {
 
 impl GlobalState {
     fn run(mut self, inbox: Receiver<lsp_server::Message>) -> Result<()> {
-        if self.config.linked_projects.is_empty() && self.config.notifications.cargo_toml_not_found
+        if self.config.linked_projects.is_empty()
+            && self.config.discover_project_command.is_none()
+            && self.config.notifications.cargo_toml_not_found
         {
             self.show_message(
                 lsp_types::MessageType::Error,
@@ -201,7 +215,45 @@ impl GlobalState {
                                 self.diagnostics.set_native_diagnostics(file_id, diagnostics)
                             }
                         }
-                        Task::Workspaces(workspaces) => self.switch_workspaces(workspaces),
+                        Task::Workspaces(workspaces, local_registry) => {
+                            self.fetching_workspaces = false;
+                            self.set_local_registry(local_registry);
+                            self.switch_workspaces(workspaces);
+                            self.fetch_build_data();
+                        }
+                        Task::DetachedFiles(workspaces) => {
+                            self.fetching_detached_files = false;
+                            self.set_detached_files(workspaces);
+                            self.fetch_build_data();
+                        }
+                        Task::DiscoveredProjects(projects) => {
+                            self.set_discovered_projects(projects)
+                        }
+                        Task::BuildData(progress) => {
+                            let (state, message) = match progress {
+                                BuildDataProgress::Begin => (Some(Progress::Begin), None),
+                                BuildDataProgress::Report(msg) => {
+                                    (Some(Progress::Report), Some(msg))
+                                }
+                                BuildDataProgress::End(build_data) => {
+                                    self.fetching_build_data = false;
+                                    // Nothing was reported if all of the build data was fresh.
+                                    let state = if build_data.is_empty() {
+                                        None
+                                    } else {
+                                        self.set_build_data(build_data);
+                                        Some(Progress::End)
+                                    };
+                                    if mem::take(&mut self.fetch_build_data_queued) {
+                                        self.fetch_build_data();
+                                    }
+                                    (state, None)
+                                }
+                            };
+                            if let Some(state) = state {
+                                self.report_progress("build scripts", state, message, None);
+                            }
+                        }
                         Task::PrimeCaches(progress) => match progress {
                             PrimeCachesProgress::Started => prime_caches_progress.push(progress),
                             PrimeCachesProgress::StartedOnCrate { .. } => {
@@ -293,7 +345,11 @@ impl GlobalState {
                 let _p = profile::span("GlobalState::handle_event/flycheck");
                 loop {
                     match task {
-                        flycheck::Message::AddDiagnostic { workspace_root, diagnostic } => {
+                        flycheck::Message::AddDiagnostic {
+                            workspace_root,
+                            package_id,
+                            diagnostic,
+                        } => {
                             let diagnostics =
                                 crate::diagnostics::to_proto::map_rust_diagnostic_to_lsp(
                                     &self.config.diagnostics_map,
@@ -303,6 +359,7 @@ impl GlobalState {
                             for diag in diagnostics {
                                 match url_to_file_id(&self.vfs.read().0, &diag.url) {
                                     Ok(file_id) => self.diagnostics.add_check_diagnostic(
+                                        package_id.clone(),
                                         file_id,
                                         diag.diagnostic,
                                         diag.fixes,
@@ -319,8 +376,16 @@ impl GlobalState {
 
                         flycheck::Message::Progress { id, progress } => {
                             let (state, message) = match progress {
-                                flycheck::Progress::DidStart => {
-                                    self.diagnostics.clear_check();
+                                flycheck::Progress::DidStart { packages } => {
+                                    match packages {
+                                        Some(packages) => {
+                                            for package_id in self.package_ids(id, &packages) {
+                                                self.diagnostics
+                                                    .clear_check_for_package(&package_id);
+                                            }
+                                        }
+                                        None => self.diagnostics.clear_check(),
+                                    }
                                     (Progress::Begin, None)
                                 }
                                 flycheck::Progress::DidCheckCrate(target) => {
@@ -354,6 +419,8 @@ impl GlobalState {
             }
         }
 
+        self.maybe_fetch_detached_files();
+
         let state_changed = self.process_changes();
         if prev_status == Status::Loading && self.status == Status::Ready {
             for flycheck in &self.flycheck {
@@ -426,8 +493,20 @@ impl GlobalState {
             return Ok(());
         }
 
+        if is_unsupported_manifest_request(&req) {
+            self.respond(Response::new_ok(req.id, serde_json::Value::Null));
+            return Ok(());
+        }
+
         RequestDispatcher { req: Some(req), global_state: self }
             .on_sync::<lsp_ext::ReloadWorkspace>(|s, ()| Ok(s.fetch_workspaces()))?
+            .on_sync::<lsp_ext::CancelFlycheck>(|s, ()| {
+                for flycheck in &s.flycheck {
+                    flycheck.cancel();
+                }
+                Ok(())
+            })?
+            .on_sync::<lsp_ext::SetPackageFeatures>(handlers::handle_set_package_features)?
             .on_sync::<lsp_ext::JoinLines>(|s, p| handlers::handle_join_lines(s.snapshot(), p))?
             .on_sync::<lsp_ext::OnEnter>(|s, p| handlers::handle_on_enter(s.snapshot(), p))?
             .on_sync::<lsp_types::request::Shutdown>(|s, ()| {
@@ -524,6 +603,9 @@ impl GlobalState {
                         this.maybe_update_diagnostics();
                     }
                 }
+                if let Ok(path) = from_proto::abs_path(&params.text_document.uri) {
+                    this.maybe_load_opened_file(path);
+                }
                 Ok(())
             })?
             .on::<lsp_types::notification::DidChangeTextDocument>(|this, params| {
@@ -538,6 +620,9 @@ impl GlobalState {
                     let vfs = &mut this.vfs.write().0;
                     let file_id = vfs.file_id(&path).unwrap();
                     let mut text = String::from_utf8(vfs.file_contents(file_id).to_vec()).unwrap();
+                    this.completion_history
+                        .lock()
+                        .on_change(&params.text_document.uri, &params.content_changes);
                     apply_document_changes(&mut text, params.content_changes);
 
                     // The version passed in DidChangeTextDocument is the version after all edits are applied
@@ -560,6 +645,7 @@ impl GlobalState {
 
                     if let Some(path) = path.as_path() {
                         this.loader.handle.invalidate(path.to_path_buf());
+                        this.maybe_unload_closed_file(path);
                     }
                 }
 
@@ -576,10 +662,34 @@ impl GlobalState {
                 Ok(())
             })?
             .on::<lsp_types::notification::DidSaveTextDocument>(|this, params| {
-                for flycheck in &this.flycheck {
-                    flycheck.update();
+                // Only check the packages affected by the saved file, if we know
+                // them, and the whole workspaces otherwise.
+                //
+                // FIXME: unsaved changes are not checked, as cargo only sees the
+                // files on disk. Checking them would need the in-memory
+                // contents written to a shadow copy of the workspace.
+                let file_id = url_to_file_id(&this.vfs.read().0, &params.text_document.uri).ok();
+                let packages: Vec<Vec<String>> = this
+                    .flycheck
+                    .iter()
+                    .map(|flycheck| match file_id {
+                        Some(file_id) => this.packages_to_check(flycheck.id(), file_id),
+                        None => Vec::new(),
+                    })
+                    .collect();
+                if packages.iter().all(|it| it.is_empty()) {
+                    for flycheck in &this.flycheck {
+                        flycheck.update();
+                    }
+                } else {
+                    for (flycheck, packages) in this.flycheck.iter().zip(packages) {
+                        if !packages.is_empty() {
+                            flycheck.update_packages(packages);
+                        }
+                    }
                 }
                 if let Ok(abs_path) = from_proto::abs_path(&params.text_document.uri) {
+                    this.maybe_rediscover_projects(&[abs_path.clone()]);
                     this.maybe_refresh(&[(abs_path, ChangeKind::Modify)]);
                 }
                 Ok(())
@@ -621,11 +731,14 @@ impl GlobalState {
                 return Ok(());
             })?
             .on::<lsp_types::notification::DidChangeWatchedFiles>(|this, params| {
+                let mut changed = Vec::new();
                 for change in params.changes {
                     if let Ok(path) = from_proto::abs_path(&change.uri) {
-                        this.loader.handle.invalidate(path);
+                        this.loader.handle.invalidate(path.clone());
+                        changed.push(path);
                     }
                 }
+                this.maybe_rediscover_projects(&changed);
                 Ok(())
             })?
             .finish();
@@ -652,6 +765,7 @@ impl GlobalState {
         let subscriptions = self
             .mem_docs
             .keys()
+            .filter(|path| !is_cargo_toml(path))
             .map(|path| self.vfs.read().0.file_id(&path).unwrap())
             .collect::<Vec<_>>();
 
@@ -1,10 +1,16 @@
 //! Project loading & configuration updates
-use std::{mem, sync::Arc};
+use std::{mem, path::Path, process::Command, sync::Arc};
 
-use flycheck::{FlycheckConfig, FlycheckHandle};
+use anyhow::Context;
+
+use flycheck::{CargoPackageOverride, FlycheckConfig, FlycheckHandle, RustcCrate, RustcCrateKind};
 use ide::Change;
-use ide_db::base_db::{CrateGraph, SourceRoot, VfsPath};
-use project_model::{ProcMacroClient, ProjectWorkspace};
+use ide_db::base_db::{CrateGraph, PackageIndex, SourceRoot, VfsPath};
+use project_model::{
+    CargoWorkspace, PackageRoot, ProcMacroClient, ProjectJson, ProjectJsonData, ProjectWorkspace,
+    TargetKind, WorkspaceBuildData,
+};
+use rustc_hash::FxHashMap;
 use vfs::{file_set::FileSetConfig, AbsPath, AbsPathBuf, ChangeKind};
 
 use crate::{
@@ -15,6 +21,15 @@ use crate::{
 };
 use lsp_ext::StatusParams;
 
+#[derive(Debug)]
+pub(crate) enum BuildDataProgress {
+    Begin,
+    Report(String),
+    /// The freshly collected build data, keyed by the manifest path of the
+    /// workspace. Empty, and not preceded by `Begin`, if all of it was fresh.
+    End(Vec<(AbsPathBuf, anyhow::Result<WorkspaceBuildData>)>),
+}
+
 impl GlobalState {
     pub(crate) fn update_configuration(&mut self, config: Config) {
         let _p = profile::span("GlobalState::update_configuration");
@@ -24,11 +39,22 @@ impl GlobalState {
         }
         if self.config.linked_projects != old_config.linked_projects {
             self.fetch_workspaces()
-        } else if self.config.flycheck != old_config.flycheck {
-            self.reload_flycheck();
+        } else {
+            if self.config.cargo.package_overrides != old_config.cargo.package_overrides {
+                self.refresh_workspaces()
+            }
+            if self.config.flycheck != old_config.flycheck {
+                self.reload_flycheck();
+            }
+        }
+        if self.config.detached_files_edition != old_config.detached_files_edition {
+            self.detached_files_changed = true;
         }
     }
     pub(crate) fn maybe_refresh(&mut self, changes: &[(AbsPathBuf, ChangeKind)]) {
+        if changes.iter().any(|(path, _)| self.is_build_script(path)) {
+            self.fetch_build_data();
+        }
         if !changes.iter().any(|(path, kind)| is_interesting(path, *kind)) {
             return;
         }
@@ -93,8 +119,12 @@ impl GlobalState {
     }
     pub(crate) fn fetch_workspaces(&mut self) {
         log::info!("will fetch workspaces");
+        self.fetching_workspaces = true;
         self.task_pool.handle.spawn({
-            let linked_projects = self.config.linked_projects.clone();
+            let mut linked_projects = self.config.linked_projects.clone();
+            if let Some(project) = self.discovered_project() {
+                linked_projects.push(LinkedProject::InlineJsonProject(project));
+            }
             let cargo_config = self.config.cargo.clone();
             move || {
                 let workspaces = linked_projects
@@ -109,16 +139,339 @@ impl GlobalState {
                     })
                     .collect::<Vec<_>>();
                 log::info!("did fetch workspaces {:?}", workspaces);
-                Task::Workspaces(workspaces)
+                // Scanned after loading the workspaces, which might have
+                // downloaded new crates, so that the package index is set
+                // along with the crate graph.
+                let local_registry = project_model::local_registry_crates();
+                Task::Workspaces(workspaces, local_registry)
+            }
+        });
+    }
+    /// Loads the project of a freshly opened Rust file which doesn't belong to
+    /// any of the loaded workspaces, with the project discovery command if
+    /// there is one, or as a detached file otherwise.
+    pub(crate) fn maybe_load_opened_file(&mut self, path: AbsPathBuf) {
+        if path.extension() != Some("rs".as_ref())
+            || self.discovered_projects.contains_key(&path)
+            || self.detached_files.contains(&path)
+        {
+            return;
+        }
+        let roots = self.workspaces.iter().flat_map(ProjectWorkspace::to_roots).collect::<Vec<_>>();
+        if is_in_roots(&roots, &path) {
+            return;
+        }
+        if self.config.discover_project_command.is_some() {
+            self.discover_projects(vec![path]);
+        } else {
+            self.detached_files.push(path);
+            self.detached_files_changed = true;
+        }
+    }
+    /// Unloads a closed file if it was detached.
+    pub(crate) fn maybe_unload_closed_file(&mut self, path: &AbsPath) {
+        let n_files = self.detached_files.len();
+        self.detached_files.retain(|it| it != path);
+        if self.detached_files.len() != n_files {
+            self.detached_files_changed = true;
+        }
+    }
+    /// Reloads the detached files if they changed. This waits for the projects
+    /// to be loaded, since the files opened in the meantime might belong to
+    /// them, and batches the files opened while the detached files load.
+    pub(crate) fn maybe_fetch_detached_files(&mut self) {
+        if self.fetching_workspaces || self.fetching_detached_files {
+            return;
+        }
+        let roots = self
+            .project_workspaces()
+            .iter()
+            .flat_map(ProjectWorkspace::to_roots)
+            .collect::<Vec<_>>();
+        let n_files = self.detached_files.len();
+        self.detached_files.retain(|file| !is_in_roots(&roots, file));
+        if self.detached_files.len() != n_files {
+            self.detached_files_changed = true;
+        }
+        if !mem::take(&mut self.detached_files_changed) {
+            return;
+        }
+        log::info!("will fetch detached files");
+        self.fetching_detached_files = true;
+        self.task_pool.handle.spawn({
+            let detached_files = self.detached_files.clone();
+            let edition = self.config.detached_files_edition;
+            let cargo_config = self.config.cargo.clone();
+            move || {
+                let workspaces =
+                    ProjectWorkspace::load_detached_files(detached_files, edition, &cargo_config);
+                log::info!("did fetch detached files {:?}", workspaces);
+                Task::DetachedFiles(workspaces)
             }
         });
     }
+    /// Replaces the loaded detached files, keeping the projects.
+    pub(crate) fn set_detached_files(&mut self, detached: Vec<anyhow::Result<ProjectWorkspace>>) {
+        // Failing to load a detached file shouldn't prevent the others from
+        // being loaded.
+        let mut detached = detached
+            .into_iter()
+            .filter_map(|res| {
+                res.map_err(|err| log::error!("failed to load detached files: {:#}", err)).ok()
+            })
+            .collect::<Vec<_>>();
+        self.set_cargo_data(&mut detached);
+        self.load_workspaces(self.project_workspaces().to_vec(), detached);
+    }
+    /// The workspaces of the loaded projects, without the detached files.
+    fn project_workspaces(&self) -> &[ProjectWorkspace] {
+        &self.workspaces[..self.workspaces.len() - self.n_detached_workspaces]
+    }
+    /// The workspaces of the detached files.
+    fn detached_workspaces(&self) -> &[ProjectWorkspace] {
+        &self.workspaces[self.workspaces.len() - self.n_detached_workspaces..]
+    }
+    /// Reruns the project discovery command for the discovered projects
+    /// listing one of the `changed` files as a build file.
+    pub(crate) fn maybe_rediscover_projects(&mut self, changed: &[AbsPathBuf]) {
+        let paths = self
+            .discovered_projects
+            .iter()
+            .filter(|(_, project)| match project {
+                Some(project) => changed.iter().any(|it| project.build_files().contains(it)),
+                None => false,
+            })
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        if !paths.is_empty() {
+            self.discover_projects(paths);
+        }
+    }
+    fn discover_projects(&mut self, paths: Vec<AbsPathBuf>) {
+        let command = match &self.config.discover_project_command {
+            Some(it) => it.clone(),
+            None => return,
+        };
+        for path in paths.iter() {
+            self.discovered_projects.entry(path.clone()).or_insert(None);
+        }
+        log::info!("will discover projects for {:?}", paths);
+        self.task_pool.handle.spawn({
+            let root_path = self.config.root_path.clone();
+            move || {
+                let projects = paths
+                    .into_iter()
+                    .map(|path| {
+                        let project = discover_project(&command, &root_path, &path);
+                        (path, project)
+                    })
+                    .collect::<Vec<_>>();
+                log::info!("did discover projects {:?}", projects);
+                Task::DiscoveredProjects(projects)
+            }
+        });
+    }
+    pub(crate) fn set_discovered_projects(
+        &mut self,
+        projects: Vec<(AbsPathBuf, anyhow::Result<ProjectJson>)>,
+    ) {
+        let mut changed = false;
+        for (path, project) in projects {
+            match project {
+                Ok(project) => {
+                    changed = true;
+                    self.discovered_projects.insert(path, Some(project));
+                }
+                Err(err) => {
+                    log::error!("failed to discover the project of {}: {:#}", path.display(), err);
+                    // Keep the previously discovered project, if any, but allow
+                    // another attempt when the file is opened again otherwise.
+                    if let Some(None) = self.discovered_projects.get(&path) {
+                        self.discovered_projects.remove(&path);
+                    }
+                    self.show_message(
+                        lsp_types::MessageType::Error,
+                        format!("rust-analyzer failed to discover the project: {:#}", err),
+                    );
+                }
+            }
+        }
+        if changed {
+            self.fetch_workspaces();
+        }
+    }
+    /// All of the discovered projects, merged into a single one.
+    fn discovered_project(&self) -> Option<ProjectJson> {
+        let mut paths = self
+            .discovered_projects
+            .iter()
+            .filter(|(_, project)| project.is_some())
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        // Sort the projects to assign the same crate ids on each reload.
+        paths.sort();
+        let mut projects =
+            paths.into_iter().filter_map(|path| self.discovered_projects[path].clone());
+        let mut res = projects.next()?;
+        projects.for_each(|project| res.merge(project));
+        Some(res)
+    }
+    /// Runs `cargo check` in the background to collect the build data of the
+    /// cargo workspaces whose cached build data is missing or outdated. Only
+    /// one run happens at a time, another one follows if requested meanwhile.
+    pub(crate) fn fetch_build_data(&mut self) {
+        if !self.config.cargo.load_out_dirs_from_check {
+            return;
+        }
+        if self.fetching_build_data {
+            self.fetch_build_data_queued = true;
+            return;
+        }
+        let workspaces = self
+            .workspaces
+            .iter()
+            .filter_map(|ws| match ws {
+                ProjectWorkspace::Cargo { cargo, .. } => {
+                    let cached = self.build_data.get(&cargo.manifest_path().to_path_buf()).cloned();
+                    Some((cargo.clone(), cached))
+                }
+                ProjectWorkspace::Json { .. } | ProjectWorkspace::DetachedFiles { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        if workspaces.is_empty() {
+            return;
+        }
+        log::info!("will fetch build data");
+        self.fetching_build_data = true;
+        self.task_pool.handle.spawn_with_sender({
+            let cargo_config = self.config.cargo.clone();
+            move |sender| {
+                let mut res = Vec::new();
+                for (cargo, cached) in workspaces {
+                    if let Some(cached) = cached {
+                        if cached.is_fresh(&cargo, &cargo_config) {
+                            continue;
+                        }
+                    }
+                    if res.is_empty() {
+                        sender.send(Task::BuildData(BuildDataProgress::Begin)).unwrap();
+                    }
+                    let name = cargo.workspace_root().display().to_string();
+                    sender.send(Task::BuildData(BuildDataProgress::Report(name))).unwrap();
+                    let build_data = WorkspaceBuildData::collect(&cargo, &cargo_config);
+                    res.push((cargo.manifest_path().to_path_buf(), build_data));
+                }
+                log::info!("did fetch build data");
+                sender.send(Task::BuildData(BuildDataProgress::End(res))).unwrap();
+            }
+        });
+    }
+    fn is_build_script(&self, path: &AbsPath) -> bool {
+        self.workspaces.iter().any(|ws| match ws {
+            ProjectWorkspace::Cargo { cargo, .. } => cargo
+                .target_by_root(path)
+                .map_or(false, |tgt| cargo[tgt].kind == TargetKind::BuildScript),
+            ProjectWorkspace::Json { .. } | ProjectWorkspace::DetachedFiles { .. } => false,
+        })
+    }
+    /// Attaches the collected build data to the current workspaces. Only the
+    /// crate graph is rebuilt, unless an `OUT_DIR` changed and has to be loaded.
+    pub(crate) fn set_build_data(
+        &mut self,
+        build_data: Vec<(AbsPathBuf, anyhow::Result<WorkspaceBuildData>)>,
+    ) {
+        for (manifest_path, res) in build_data {
+            match res {
+                Ok(it) => {
+                    self.build_data.insert(manifest_path, it);
+                }
+                Err(err) => {
+                    log::error!(
+                        "failed to load build data for {}: {:#}",
+                        manifest_path.display(),
+                        err
+                    );
+                }
+            }
+        }
+
+        let mut workspaces = (*self.workspaces).clone();
+        let mut out_dirs_changed = false;
+        for ws in workspaces.iter_mut() {
+            if let ProjectWorkspace::Cargo { cargo, .. } = ws {
+                let build_data = match self.build_data.get(&cargo.manifest_path().to_path_buf()) {
+                    Some(it) => it,
+                    None => continue,
+                };
+                let old_out_dirs = cargo
+                    .packages()
+                    .map(|pkg| cargo[pkg].build_data.out_dir.clone())
+                    .collect::<Vec<_>>();
+                cargo.set_build_data(build_data);
+                out_dirs_changed |= cargo
+                    .packages()
+                    .map(|pkg| cargo[pkg].build_data.out_dir.clone())
+                    .ne(old_out_dirs);
+            }
+        }
+        if &*self.workspaces == &workspaces {
+            return;
+        }
+        if out_dirs_changed {
+            let detached = workspaces.split_off(workspaces.len() - self.n_detached_workspaces);
+            self.load_workspaces(workspaces, detached);
+            return;
+        }
+
+        log::info!("will apply build data");
+        let mut change = Change::new();
+        change.set_crate_graph(self.crate_graph(&workspaces));
+        self.workspaces = Arc::new(workspaces);
+        self.analysis_host.apply_change(change);
+        self.process_changes();
+    }
+    /// Applies changed package overrides to the current workspaces. Only the
+    /// crate graph is rebuilt, and only the flycheck of the workspaces whose
+    /// overrides changed is restarted.
+    pub(crate) fn refresh_workspaces(&mut self) {
+        let mut workspaces = (*self.workspaces).clone();
+        self.set_cargo_data(&mut workspaces);
+        let changed = self
+            .workspaces
+            .iter()
+            .zip(workspaces.iter())
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        if changed.is_empty() {
+            return;
+        }
+
+        log::info!("will refresh workspaces {:?}", changed);
+        let mut change = Change::new();
+        change.set_crate_graph(self.crate_graph(&workspaces));
+        self.workspaces = Arc::new(workspaces);
+        self.analysis_host.apply_change(change);
+        self.process_changes();
+
+        for idx in 0..self.flycheck.len() {
+            let id = self.flycheck[idx].id();
+            if changed.contains(&id) {
+                if let Some(flycheck) = self.spawn_flycheck(id) {
+                    self.flycheck[idx] = flycheck;
+                }
+            }
+        }
+        self.fetch_build_data();
+    }
+    /// Switches to the freshly loaded projects, keeping the detached files.
     pub(crate) fn switch_workspaces(&mut self, workspaces: Vec<anyhow::Result<ProjectWorkspace>>) {
         let _p = profile::span("GlobalState::switch_workspaces");
         log::info!("will switch workspaces: {:?}", workspaces);
 
         let mut has_errors = false;
-        let workspaces = workspaces
+        let mut workspaces = workspaces
             .into_iter()
             .filter_map(|res| {
                 res.map_err(|err| {
@@ -134,8 +487,9 @@ impl GlobalState {
                 .ok()
             })
             .collect::<Vec<_>>();
+        self.set_cargo_data(&mut workspaces);
 
-        if &*self.workspaces == &workspaces {
+        if self.project_workspaces() == &workspaces[..] {
             return;
         }
 
@@ -143,6 +497,36 @@ impl GlobalState {
             return;
         }
 
+        self.load_workspaces(workspaces, self.detached_workspaces().to_vec());
+    }
+    /// Applies the package overrides and the collected build data to the cargo
+    /// workspaces.
+    fn set_cargo_data(&self, workspaces: &mut [ProjectWorkspace]) {
+        let mut package_overrides = self.config.cargo.package_overrides.clone();
+        package_overrides.extend(self.package_overrides.clone());
+        for ws in workspaces.iter_mut() {
+            if let ProjectWorkspace::Cargo { cargo, .. } = ws {
+                cargo.set_package_overrides(&package_overrides);
+                if let Some(build_data) = self.build_data.get(&cargo.manifest_path().to_path_buf())
+                {
+                    cargo.set_build_data(build_data);
+                }
+            }
+        }
+    }
+    /// Loads the workspaces of the projects, followed by the ones of the
+    /// detached files.
+    fn load_workspaces(
+        &mut self,
+        mut workspaces: Vec<ProjectWorkspace>,
+        detached: Vec<ProjectWorkspace>,
+    ) {
+        let n_detached_workspaces = detached.len();
+        workspaces.extend(detached);
+        if &*self.workspaces == &workspaces && self.n_detached_workspaces == n_detached_workspaces {
+            return;
+        }
+
         if let FilesWatcher::Client = self.config.files.watcher {
             let registration_options = lsp_types::DidChangeWatchedFilesRegistrationOptions {
                 watchers: workspaces
@@ -152,6 +536,16 @@ impl GlobalState {
                     .flat_map(|root| {
                         root.include.into_iter().map(|it| format!("{}/**/*.rs", it.display()))
                     })
+                    .chain(
+                        workspaces
+                            .iter()
+                            .filter_map(|ws| match ws {
+                                ProjectWorkspace::Json { project, .. } => Some(project),
+                                _ => None,
+                            })
+                            .flat_map(|project| project.build_files())
+                            .map(|it| it.display().to_string()),
+                    )
                     .map(|glob_pattern| lsp_types::FileSystemWatcher { glob_pattern, kind: None })
                     .collect(),
             };
@@ -191,38 +585,13 @@ impl GlobalState {
         };
         self.loader.handle.set_config(vfs::loader::Config { load: project_folders.load, watch });
 
-        // Create crate graph from all the workspaces
-        let crate_graph = {
-            let mut crate_graph = CrateGraph::default();
-            let vfs = &mut self.vfs.write().0;
-            let loader = &mut self.loader;
-            let mem_docs = &self.mem_docs;
-            let mut load = |path: &AbsPath| {
-                let vfs_path = vfs::VfsPath::from(path.to_path_buf());
-                if !mem_docs.contains_key(&vfs_path) {
-                    let contents = loader.handle.load_sync(path);
-                    vfs.set_file_contents(vfs_path.clone(), contents);
-                }
-                let res = vfs.file_id(&vfs_path);
-                if res.is_none() {
-                    log::warn!("failed to load {}", path.display())
-                }
-                res
-            };
-            for ws in workspaces.iter() {
-                crate_graph.extend(ws.to_crate_graph(
-                    self.config.cargo.target.as_deref(),
-                    self.proc_macro_client.as_ref(),
-                    &mut load,
-                ));
-            }
+        change.set_crate_graph(self.crate_graph(&workspaces));
 
-            crate_graph
-        };
-        change.set_crate_graph(crate_graph);
+        change.set_package_index(self.package_index(&workspaces));
 
         self.source_root_config = project_folders.source_root_config;
         self.workspaces = Arc::new(workspaces);
+        self.n_detached_workspaces = n_detached_workspaces;
 
         self.analysis_host.apply_change(change);
         self.process_changes();
@@ -230,44 +599,190 @@ impl GlobalState {
         log::info!("did switch workspaces");
     }
 
-    fn reload_flycheck(&mut self) {
-        let config = match self.config.flycheck.clone() {
-            Some(it) => it,
-            None => {
-                self.flycheck = Vec::new();
-                return;
+    /// Creates the crate graph from all the workspaces.
+    fn crate_graph(&mut self, workspaces: &[ProjectWorkspace]) -> CrateGraph {
+        let mut crate_graph = CrateGraph::default();
+        let vfs = &mut self.vfs.write().0;
+        let loader = &mut self.loader;
+        let mem_docs = &self.mem_docs;
+        let mut load = |path: &AbsPath| {
+            let vfs_path = vfs::VfsPath::from(path.to_path_buf());
+            if !mem_docs.contains_key(&vfs_path) {
+                let contents = loader.handle.load_sync(path);
+                vfs.set_file_contents(vfs_path.clone(), contents);
             }
+            let res = vfs.file_id(&vfs_path);
+            if res.is_none() {
+                log::warn!("failed to load {}", path.display())
+            }
+            res
         };
+        for ws in workspaces.iter() {
+            crate_graph.extend(ws.to_crate_graph(
+                self.config.cargo.target.as_deref(),
+                self.proc_macro_client.as_ref(),
+                &mut load,
+            ));
+        }
+        crate_graph
+    }
 
-        let sender = self.flycheck_sender.clone();
-        self.flycheck = self
-            .workspaces
-            .iter()
-            .enumerate()
-            .filter_map(|(id, w)| match w {
-                ProjectWorkspace::Cargo { cargo, sysroot: _, rustc: _ } => {
-                    Some((id, cargo.workspace_root()))
+    fn package_index(&self, workspaces: &[ProjectWorkspace]) -> PackageIndex {
+        let mut package_index = PackageIndex::default();
+        for ws in workspaces.iter() {
+            package_index.features.extend(ws.package_features());
+        }
+        package_index.registry = self.local_registry.clone();
+        package_index
+    }
+
+    /// Updates the local registry crates, which are only applied right away
+    /// if the workspaces are already loaded.
+    pub(crate) fn set_local_registry(&mut self, registry: FxHashMap<String, Vec<String>>) {
+        if registry == self.local_registry {
+            return;
+        }
+        self.local_registry = registry;
+        if self.workspaces.is_empty() {
+            return;
+        }
+        let mut change = Change::new();
+        change.set_package_index(self.package_index(&self.workspaces));
+        self.analysis_host.apply_change(change);
+    }
+
+    fn reload_flycheck(&mut self) {
+        self.flycheck =
+            (0..self.workspaces.len()).filter_map(|id| self.spawn_flycheck(id)).collect();
+    }
+    /// Starts the flycheck of the `id`th workspace, if it has one.
+    fn spawn_flycheck(&self, id: usize) -> Option<FlycheckHandle> {
+        let config = self.config.flycheck.clone()?;
+        let (root, config) = match &self.workspaces[id] {
+            ProjectWorkspace::Cargo { cargo, sysroot: _, rustc: _ } => {
+                let mut config = config;
+                if let FlycheckConfig::CargoCommand { package_overrides, .. } = &mut config {
+                    *package_overrides = cargo_package_overrides(cargo);
                 }
-                ProjectWorkspace::Json { project, .. } => {
-                    // Enable flychecks for json projects if a custom flycheck command was supplied
-                    // in the workspace configuration.
-                    match config {
-                        FlycheckConfig::CustomCommand { .. } => Some((id, project.path())),
-                        _ => None,
-                    }
+                (cargo.workspace_root(), config)
+            }
+            ProjectWorkspace::Json { project, .. } => match config {
+                // Use the custom flycheck command if one was supplied in the workspace
+                // configuration, and check the crates with rustc directly otherwise.
+                FlycheckConfig::CustomCommand { .. } => (project.path(), config),
+                FlycheckConfig::CargoCommand { target_triple, .. }
+                | FlycheckConfig::RustcCommand { target_triple, .. } => {
+                    let config = FlycheckConfig::RustcCommand {
+                        crates: rustc_crates(project),
+                        target_triple,
+                    };
+                    (project.path(), config)
                 }
+            },
+            ProjectWorkspace::DetachedFiles { .. } => return None,
+        };
+        let sender = self.flycheck_sender.clone();
+        Some(FlycheckHandle::spawn(
+            id,
+            Box::new(move |msg| sender.send(msg).unwrap()),
+            config,
+            root.to_path_buf().into(),
+        ))
+    }
+}
+
+/// The packages of `cargo` whose features or target are overridden, to be
+/// checked by flycheck with their own settings.
+fn cargo_package_overrides(cargo: &CargoWorkspace) -> Vec<CargoPackageOverride> {
+    cargo
+        .packages()
+        .filter(|&pkg| cargo[pkg].is_member)
+        .filter_map(|pkg| {
+            let overrides = &cargo[pkg].overrides;
+            if overrides.features.is_none()
+                && !overrides.no_default_features
+                && overrides.target.is_none()
+            {
+                return None;
+            }
+            Some(CargoPackageOverride {
+                package: cargo.package_flag(&cargo[pkg]),
+                features: overrides.features.clone(),
+                no_default_features: overrides.no_default_features,
+                target_triple: overrides.target.clone(),
             })
-            .map(|(id, root)| {
-                let sender = sender.clone();
-                FlycheckHandle::spawn(
-                    id,
-                    Box::new(move |msg| sender.send(msg).unwrap()),
-                    config.clone(),
-                    root.to_path_buf().into(),
-                )
-            })
-            .collect();
+        })
+        .collect()
+}
+
+/// Runs the project discovery `command` with `path` appended, reading the
+/// discovered `rust-project.json` from its output.
+fn discover_project(
+    command: &[String],
+    root_path: &AbsPath,
+    path: &AbsPath,
+) -> anyhow::Result<ProjectJson> {
+    let mut cmd = Command::new(&command[0]);
+    cmd.args(&command[1..]).arg(path.as_ref() as &Path).current_dir(root_path);
+    let output = cmd.output().with_context(|| format!("failed to run {:?}", cmd))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{:?} failed, {}\nstderr:\n{}",
+            cmd,
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
     }
+    let data: ProjectJsonData = serde_json::from_slice(&output.stdout)
+        .with_context(|| format!("failed to deserialize the output of {:?}", cmd))?;
+    Ok(ProjectJson::new(root_path, data))
+}
+
+/// Whether `path` is inside one of the `roots`, and not excluded from it.
+fn is_in_roots(roots: &[PackageRoot], path: &AbsPath) -> bool {
+    roots.iter().any(|root| {
+        root.include.iter().any(|dir| path.starts_with(dir))
+            && !root.exclude.iter().any(|dir| path.starts_with(dir))
+    })
+}
+
+/// The crates of a `rust-project.json`, as checked by flycheck with `rustc`.
+pub(crate) fn rustc_crates(project: &ProjectJson) -> Vec<RustcCrate> {
+    let n_crates = project.n_crates();
+    project
+        .crates()
+        .map(|(crate_id, krate)| RustcCrate {
+            name: match &krate.display_name {
+                Some(name) => name.to_string(),
+                None => format!("crate{}", crate_id.0),
+            },
+            root_module: krate.root_module.clone().into(),
+            edition: krate.edition.to_string(),
+            cfgs: krate.cfg.iter().map(|cfg| cfg.to_string()).collect(),
+            deps: krate
+                .deps
+                .iter()
+                .map(|dep| (dep.name.to_string(), dep.crate_id.0 as usize))
+                .filter(|&(_, idx)| idx < n_crates)
+                .collect(),
+            env: krate.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            target: krate.target.clone(),
+            proc_macro_dylib_path: krate.proc_macro_dylib_path.clone().map(Into::into),
+            is_workspace_member: krate.is_workspace_member,
+            kind: if krate.proc_macro_dylib_path.is_some() {
+                RustcCrateKind::ProcMacro
+            } else {
+                match krate.build.as_ref().map(|build| build.target_kind) {
+                    Some(TargetKind::Bin) | Some(TargetKind::Example) => RustcCrateKind::Bin,
+                    Some(TargetKind::Test) | Some(TargetKind::Bench) => RustcCrateKind::Test,
+                    Some(TargetKind::Lib)
+                    | Some(TargetKind::BuildScript)
+                    | Some(TargetKind::Other)
+                    | None => RustcCrateKind::Lib,
+                }
+            },
+        })
+        .collect()
 }
 
 #[derive(Default)]
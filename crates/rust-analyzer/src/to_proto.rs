@@ -5,17 +5,19 @@ use std::{
 };
 
 use ide::{
-    Assist, AssistKind, CallInfo, CompletionItem, CompletionItemKind, Documentation, FileId,
-    FileRange, FileSystemEdit, Fold, FoldKind, Highlight, HighlightModifier, HighlightTag,
-    HighlightedRange, Indel, InlayHint, InlayKind, InsertTextFormat, LineIndex, Markup,
-    NavigationTarget, ReferenceAccess, ResolvedAssist, Runnable, Severity, SourceChange,
+    Assist, AssistKind, CallInfo, CompletionItem, CompletionItemKind, CompletionRelevance,
+    Documentation, FileId, FileRange, FileSystemEdit, Fold, FoldKind, Highlight, HighlightModifier,
+    HighlightTag, HighlightedRange, Indel, InlayHint, InlayKind, InsertTextFormat, LineIndex,
+    Markup, NavigationTarget, ReferenceAccess, ResolvedAssist, Runnable, Severity, SourceChange,
     SourceFileEdit, SymbolKind, TextEdit, TextRange, TextSize,
 };
 use itertools::Itertools;
 
 use crate::{
-    cargo_target_spec::CargoTargetSpec, global_state::GlobalStateSnapshot,
-    line_endings::LineEndings, lsp_ext, semantic_tokens, Result,
+    cargo_target_spec::{CargoTargetSpec, DetachedFileSpec, ProjectJsonTargetSpec},
+    global_state::GlobalStateSnapshot,
+    line_endings::LineEndings,
+    lsp_ext, semantic_tokens, Result,
 };
 
 pub(crate) fn position(line_index: &LineIndex, offset: TextSize) -> lsp_types::Position {
@@ -46,6 +48,7 @@ pub(crate) fn symbol_kind(symbol_kind: SymbolKind) -> lsp_types::SymbolKind {
         SymbolKind::Local
         | SymbolKind::SelfParam
         | SymbolKind::LifetimeParam
+        | SymbolKind::Label
         | SymbolKind::ValueParam => lsp_types::SymbolKind::Variable,
         SymbolKind::Union => lsp_types::SymbolKind::Struct,
     }
@@ -102,6 +105,9 @@ pub(crate) fn completion_item_kind(
         CompletionItemKind::Static => lsp_types::CompletionItemKind::Value,
         CompletionItemKind::Method => lsp_types::CompletionItemKind::Method,
         CompletionItemKind::TypeParam => lsp_types::CompletionItemKind::TypeParameter,
+        CompletionItemKind::LifetimeParam => lsp_types::CompletionItemKind::TypeParameter,
+        CompletionItemKind::ConstParam => lsp_types::CompletionItemKind::TypeParameter,
+        CompletionItemKind::Label => lsp_types::CompletionItemKind::Variable,
         CompletionItemKind::Macro => lsp_types::CompletionItemKind::Method,
         CompletionItemKind::Attribute => lsp_types::CompletionItemKind::EnumMember,
         CompletionItemKind::UnresolvedReference => lsp_types::CompletionItemKind::Reference,
@@ -162,10 +168,12 @@ pub(crate) fn completion_item(
     line_endings: LineEndings,
     completion_item: CompletionItem,
 ) -> Vec<lsp_types::CompletionItem> {
-    fn set_score(res: &mut lsp_types::CompletionItem, label: &str) {
-        res.preselect = Some(true);
-        // HACK: sort preselect items first
-        res.sort_text = Some(format!(" {}", label));
+    fn set_relevance(res: &mut lsp_types::CompletionItem, relevance: CompletionRelevance) {
+        if relevance.is_relevant() {
+            res.preselect = Some(true);
+        }
+        // Clients sort by `sortText` in ascending order, so the score is inverted.
+        res.sort_text = Some(format!("{:08x}", u32::MAX - relevance.score()));
     }
 
     let mut additional_text_edits = Vec::new();
@@ -206,9 +214,7 @@ pub(crate) fn completion_item(
         ..Default::default()
     };
 
-    if completion_item.score().is_some() {
-        set_score(&mut res, completion_item.label());
-    }
+    set_relevance(&mut res, completion_item.relevance());
 
     if completion_item.deprecated() {
         res.tags = Some(vec![lsp_types::CompletionItemTag::Deprecated])
@@ -225,9 +231,9 @@ pub(crate) fn completion_item(
     let mut all_results = match completion_item.ref_match() {
         Some(ref_match) => {
             let mut refed = res.clone();
-            let (mutability, _score) = ref_match;
+            let (mutability, relevance) = ref_match;
             let label = format!("&{}{}", mutability.as_keyword_for_ref(), refed.label);
-            set_score(&mut refed, &label);
+            set_relevance(&mut refed, relevance);
             refed.label = label;
             vec![res, refed]
         }
@@ -377,7 +383,7 @@ fn semantic_token_type_and_modifiers(
             SymbolKind::Impl => lsp_types::SemanticTokenType::TYPE,
             SymbolKind::Field => lsp_types::SemanticTokenType::PROPERTY,
             SymbolKind::TypeParam => lsp_types::SemanticTokenType::TYPE_PARAMETER,
-            SymbolKind::LifetimeParam => semantic_tokens::LIFETIME,
+            SymbolKind::LifetimeParam | SymbolKind::Label => semantic_tokens::LIFETIME,
             SymbolKind::ValueParam => lsp_types::SemanticTokenType::PARAMETER,
             SymbolKind::SelfParam => semantic_tokens::SELF_KEYWORD,
             SymbolKind::Local => lsp_types::SemanticTokenType::VARIABLE,
@@ -802,6 +808,51 @@ pub(crate) fn runnable(
 ) -> Result<lsp_ext::Runnable> {
     let config = &snap.config.runnables;
     let spec = CargoTargetSpec::for_file(snap, file_id)?;
+    if spec.is_none() {
+        if let Some(detached_spec) = DetachedFileSpec::for_file(snap, file_id)? {
+            if let Some((args, executable)) = detached_spec.runnable_args(&runnable.kind) {
+                let label = runnable.label(Some(detached_spec.name()));
+                let location = location_link(snap, None, runnable.nav)?;
+                return Ok(lsp_ext::Runnable {
+                    label,
+                    location: Some(location),
+                    kind: lsp_ext::RunnableKind::Rustc,
+                    args: lsp_ext::CargoRunnable {
+                        workspace_root: detached_spec
+                            .path
+                            .parent()
+                            .map(|it| it.to_path_buf().into()),
+                        override_cargo: Some(toolchain::rustc().display().to_string()),
+                        cargo_args: args,
+                        cargo_extra_args: Vec::new(),
+                        executable_args: Vec::new(),
+                        expect_test: None,
+                        executable: Some(executable),
+                    },
+                });
+            }
+        }
+        if let Some(json_spec) = ProjectJsonTargetSpec::for_file(snap, file_id)? {
+            if let Some((command, args)) = json_spec.runnable_args(&runnable.kind) {
+                let label = runnable.label(Some(json_spec.label.clone()));
+                let location = location_link(snap, None, runnable.nav)?;
+                return Ok(lsp_ext::Runnable {
+                    label,
+                    location: Some(location),
+                    kind: lsp_ext::RunnableKind::Cargo,
+                    args: lsp_ext::CargoRunnable {
+                        workspace_root: Some(command.cwd.clone().into()),
+                        override_cargo: Some(command.program.clone()),
+                        cargo_args: args,
+                        cargo_extra_args: Vec::new(),
+                        executable_args: Vec::new(),
+                        expect_test: None,
+                        executable: None,
+                    },
+                });
+            }
+        }
+    }
     let workspace_root = spec.as_ref().map(|it| it.workspace_root.clone());
     let target = spec.as_ref().map(|s| s.target.clone());
     let (cargo_args, executable_args) =
@@ -820,6 +871,7 @@ pub(crate) fn runnable(
             cargo_extra_args: config.cargo_extra_args.clone(),
             executable_args,
             expect_test: None,
+            executable: None,
         },
     })
 }
@@ -864,12 +916,14 @@ mod tests {
             [
                 (
                     "arg",
-                    None,
+                    Some(
+                        "ffffffb8",
+                    ),
                 ),
                 (
                     "&arg",
                     Some(
-                        " &arg",
+                        "ffffff88",
                     ),
                 ),
             ]
@@ -14,14 +14,16 @@ mod support;
 use std::{collections::HashMap, path::PathBuf, time::Instant};
 
 use lsp_types::{
-    notification::DidOpenTextDocument,
+    notification::{DidChangeWatchedFiles, DidOpenTextDocument, DidSaveTextDocument},
     request::{CodeActionRequest, Completion, Formatting, GotoTypeDefinition, HoverRequest},
-    CodeActionContext, CodeActionParams, CompletionParams, DidOpenTextDocumentParams,
-    DocumentFormattingParams, FormattingOptions, GotoDefinitionParams, HoverParams,
-    PartialResultParams, Position, Range, TextDocumentItem, TextDocumentPositionParams,
-    WorkDoneProgressParams,
+    CodeActionContext, CodeActionParams, CompletionParams, DidChangeWatchedFilesParams,
+    DidOpenTextDocumentParams, DidSaveTextDocumentParams, DocumentFormattingParams, FileChangeType,
+    FileEvent, FormattingOptions, GotoDefinitionParams, HoverParams, PartialResultParams, Position,
+    Range, TextDocumentItem, TextDocumentPositionParams, WorkDoneProgressParams,
+};
+use rust_analyzer::lsp_ext::{
+    OnEnter, Runnables, RunnablesParams, SetPackageFeatures, SetPackageFeaturesParams,
 };
-use rust_analyzer::lsp_ext::{OnEnter, Runnables, RunnablesParams};
 use serde_json::json;
 use test_utils::skip_slow_tests;
 
@@ -545,6 +547,12 @@ fn main() {
         r#"pub fn message() -> &'static str { "Hello, World!" }"#,
     )
     .unwrap();
+    fs::write(
+        Path::new(&out_dir).join("generated.rs"),
+        r#"pub fn generated() -> &'static str { "Generated" }"#,
+    )
+    .unwrap();
+    println!("cargo:rustc-env=GENERATED_FILE=generated.rs");
     println!("cargo:rustc-cfg=atom_cfg");
     println!("cargo:rustc-cfg=featlike=\"set\"");
     println!("cargo:rerun-if-changed=build.rs");
@@ -571,14 +579,19 @@ fn main() {
     let vb = B;
     let should_be_str = message();
     let another_str = include_str!("main.rs");
+    let generated_str = generated();
 }
+
+include!(concat!(env!("OUT_DIR"), "/", env!("GENERATED_FILE")));
 "###,
     )
     .with_config(|config| {
         config.cargo.load_out_dirs_from_check = true;
     })
     .server()
-    .wait_until_workspace_is_loaded();
+    .wait_until_build_data_is_loaded();
+    // The out dir of the build script adds a root.
+    server.wait_until_roots_are_scanned(2);
 
     let res = server.send_request::<HoverRequest>(HoverParams {
         text_document_position_params: TextDocumentPositionParams::new(
@@ -598,6 +611,15 @@ fn main() {
     });
     assert!(res.to_string().contains("&str"));
 
+    let res = server.send_request::<HoverRequest>(HoverParams {
+        text_document_position_params: TextDocumentPositionParams::new(
+            server.doc_id("src/main.rs"),
+            Position::new(21, 10),
+        ),
+        work_done_progress_params: Default::default(),
+    });
+    assert!(res.to_string().contains("&str"));
+
     server.request::<GotoTypeDefinition>(
         GotoDefinitionParams {
             text_document_position_params: TextDocumentPositionParams::new(
@@ -726,7 +748,7 @@ pub fn foo(_input: TokenStream) -> TokenStream {
     .root("foo")
     .root("bar")
     .server()
-    .wait_until_workspace_is_loaded();
+    .wait_until_build_data_is_loaded();
 
     let res = server.send_request::<HoverRequest>(HoverParams {
         text_document_position_params: TextDocumentPositionParams::new(
@@ -738,3 +760,461 @@ pub fn foo(_input: TokenStream) -> TokenStream {
     let value = res.get("contents").unwrap().get("value").unwrap().to_string();
     assert_eq!(value, r#""\n```rust\nfoo::Bar\n```\n\n```rust\nfn bar()\n```""#)
 }
+
+#[test]
+fn set_package_features() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let server = Project::with_fixture(
+        r#"
+//- /Cargo.toml
+[package]
+name = "foo"
+version = "0.0.0"
+
+[features]
+default = ["small"]
+small = []
+large = []
+
+//- /src/lib.rs
+#[cfg(feature = "small")]
+fn f() -> u8 { 0 }
+#[cfg(feature = "large")]
+fn f() -> u64 { 0 }
+fn g() { let x = f(); }
+"#,
+    )
+    .server()
+    .wait_until_workspace_is_loaded();
+
+    let hover = || {
+        server
+            .send_request::<HoverRequest>(HoverParams {
+                text_document_position_params: TextDocumentPositionParams::new(
+                    server.doc_id("src/lib.rs"),
+                    Position::new(4, 13),
+                ),
+                work_done_progress_params: Default::default(),
+            })
+            .to_string()
+    };
+    assert!(hover().contains("u8"));
+
+    server.request::<SetPackageFeatures>(
+        SetPackageFeaturesParams {
+            package: "foo".to_string(),
+            features: vec!["large".to_string()],
+            no_default_features: true,
+        },
+        json!(null),
+    );
+    assert!(hover().contains("u64"));
+}
+
+#[test]
+fn flycheck_checks_saved_package() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let server = Project::with_fixture(
+        r#"
+//- /Cargo.toml
+[workspace]
+members = ["a", "b"]
+
+//- /a/Cargo.toml
+[package]
+name = "a"
+version = "0.0.0"
+
+//- /a/src/lib.rs
+fn unused_a() {}
+
+//- /b/Cargo.toml
+[package]
+name = "b"
+version = "0.0.0"
+
+//- /b/src/lib.rs
+fn unused_b() {}
+"#,
+    )
+    .server()
+    .wait_until_workspace_is_loaded();
+
+    server.wait_until_flycheck_is_done(1);
+    server.wait_for_diagnostics("a/src/lib.rs", |diags| diags.len() == 1);
+    server.wait_for_diagnostics("b/src/lib.rs", |diags| diags.len() == 1);
+
+    let start = server.message_count();
+    std::fs::write(server.path().join("b/src/lib.rs"), "pub fn used_b() {}\n").unwrap();
+    server.notification::<DidSaveTextDocument>(DidSaveTextDocumentParams {
+        text_document: server.doc_id("b/src/lib.rs"),
+        text: None,
+    });
+    server.wait_for_diagnostics_after(start, "b/src/lib.rs", |diags| diags.is_empty());
+    server.wait_until_flycheck_is_done_after(start);
+
+    // Only `b` was checked, so the diagnostics of `a` were not cleared.
+    let published_for_a = server.published_diagnostics_after(start, "a/src/lib.rs");
+    assert!(published_for_a.iter().all(|diags| diags.len() == 1));
+}
+
+#[test]
+fn flycheck_in_json_project() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let tmp_dir = TestDir::new();
+    let path = tmp_dir.path();
+
+    let project = json!({
+        "roots": [path],
+        "crates": [
+            {
+                "root_module": path.join("dep/lib.rs"),
+                "deps": [],
+                "edition": "2018",
+                "cfg": ["answer"],
+            },
+            {
+                "root_module": path.join("src/main.rs"),
+                "deps": [{ "crate": 0, "name": "dep" }],
+                "edition": "2018",
+                "build": {
+                    "label": "//:main",
+                    "build_file": path.join("BUILD"),
+                    "target_kind": "bin",
+                },
+            },
+        ]
+    });
+
+    let code = format!(
+        r#"
+//- /rust-project.json
+{PROJECT}
+
+//- /dep/lib.rs
+#[cfg(answer)]
+pub fn answer() -> u32 {{ 92 }}
+
+//- /src/main.rs
+fn main() {{
+    let _x: () = dep::answer();
+}}
+"#,
+        PROJECT = project.to_string(),
+    );
+
+    let server =
+        Project::with_fixture(&code).tmp_dir(tmp_dir).server().wait_until_workspace_is_loaded();
+
+    server.wait_until_flycheck_is_done(1);
+    server.wait_for_diagnostics("src/main.rs", |diags| {
+        diags.iter().any(|diag| diag.message.contains("mismatched types"))
+    });
+    assert!(server.published_diagnostics_after(0, "dep/lib.rs").is_empty());
+    // `main.rs` is checked as a binary, so `main` is not an unused function.
+    let published_for_main = server.published_diagnostics_after(0, "src/main.rs");
+    let last = published_for_main.last().unwrap();
+    assert!(last.iter().all(|diag| !diag.message.contains("never used")));
+}
+
+#[test]
+fn discovers_project_of_opened_file() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let tmp_dir = TestDir::new();
+    let path = tmp_dir.path().to_path_buf();
+    let project = |crates: Vec<serde_json::Value>| {
+        json!({ "crates": crates, "build_files": ["project.gen"] }).to_string()
+    };
+    let main_crate = json!({
+        "root_module": path.join("app/main.rs"),
+        "deps": [{ "crate": 1, "name": "dep" }],
+        "edition": "2018",
+    });
+    let dep_crate = json!({
+        "root_module": path.join("dep/lib.rs"),
+        "deps": [],
+        "edition": "2018",
+    });
+
+    let code = format!(
+        r#"
+//- /other/Cargo.toml
+[package]
+name = "other"
+version = "0.0.0"
+
+//- /other/src/lib.rs
+
+//- /project.gen
+{PROJECT}
+
+//- /dep/lib.rs
+pub fn answer() -> u32 {{ 92 }}
+
+//- /app/main.rs
+fn main() {{
+    dep::answer();
+}}
+"#,
+        PROJECT = project(vec![main_crate.clone()]),
+    );
+
+    let server = Project::with_fixture(&code)
+        .tmp_dir(tmp_dir)
+        .root("other")
+        .with_config(|config| {
+            config.discover_project_command =
+                Some(vec!["sh".into(), "-c".into(), "cat project.gen".into(), "sh".into()]);
+        })
+        .server()
+        .wait_until_workspace_is_loaded();
+
+    let hover = || {
+        server.send_request::<HoverRequest>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                server.doc_id("app/main.rs"),
+                Position::new(1, 10),
+            ),
+            work_done_progress_params: Default::default(),
+        })
+    };
+
+    let start = server.message_count();
+    server.notification::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: server.doc_id("app/main.rs").uri,
+            language_id: "rust".to_string(),
+            version: 0,
+            text: std::fs::read_to_string(path.join("app/main.rs")).unwrap(),
+        },
+    });
+    server.wait_until_reloaded_with(start, || hover().to_string().contains("{unknown}"));
+
+    // The dependency only appears once the project is generated anew.
+    std::fs::write(path.join("project.gen"), project(vec![main_crate, dep_crate])).unwrap();
+    let start = server.message_count();
+    server.notification::<DidChangeWatchedFiles>(DidChangeWatchedFilesParams {
+        changes: vec![FileEvent {
+            uri: server.doc_id("project.gen").uri,
+            typ: FileChangeType::Changed,
+        }],
+    });
+    server.wait_until_reloaded_with(start, || hover().to_string().contains("fn answer() -> u32"));
+}
+
+#[test]
+fn runnables_in_json_project() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let tmp_dir = TestDir::new();
+    let path = tmp_dir.path();
+
+    let project = json!({
+        "crates": [
+            {
+                "root_module": path.join("foo/lib.rs"),
+                "deps": [],
+                "edition": "2018",
+                "build": {
+                    "label": "//foo:foo-test",
+                    "build_file": path.join("foo/BUILD"),
+                    "target_kind": "test",
+                },
+            },
+        ],
+        "runnables": [
+            { "kind": "check", "program": "buck", "args": ["build", "{label}"] },
+            { "kind": "testOne", "program": "buck", "args": ["test", "{label}", "--", "{test_id}"] },
+        ],
+    });
+
+    let code = format!(
+        r#"
+//- /rust-project.json
+{PROJECT}
+
+//- /foo/BUILD
+
+//- /foo/lib.rs
+#[test]
+fn test_eggs() {{}}
+"#,
+        PROJECT = project.to_string(),
+    );
+
+    let server =
+        Project::with_fixture(&code).tmp_dir(tmp_dir).server().wait_until_workspace_is_loaded();
+
+    server.request::<Runnables>(
+        RunnablesParams { text_document: server.doc_id("foo/lib.rs"), position: None },
+        json!([
+          {
+            "args": {
+              "cargoArgs": ["test", "//foo:foo-test", "--", "test_eggs"],
+              "executableArgs": [],
+              "cargoExtraArgs": [],
+              "overrideCargo": "buck",
+              "workspaceRoot": server.path()
+            },
+            "kind": "cargo",
+            "label": "test test_eggs",
+            "location": {
+              "targetRange": {
+                "end": { "character": 17, "line": 1 },
+                "start": { "character": 0, "line": 0 }
+              },
+              "targetSelectionRange": {
+                "end": { "character": 12, "line": 1 },
+                "start": { "character": 3, "line": 1 }
+              },
+              "targetUri": "file:///[..]/foo/lib.rs"
+            }
+          },
+          {
+            "args": {
+              "cargoArgs": ["build", "//foo:foo-test"],
+              "executableArgs": [],
+              "cargoExtraArgs": [],
+              "overrideCargo": "buck",
+              "workspaceRoot": server.path()
+            },
+            "kind": "cargo",
+            "label": "buck build //foo:foo-test"
+          }
+        ]),
+    );
+}
+
+#[test]
+fn loads_detached_file_with_sysroot() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let server = Project::with_fixture(
+        r#"
+//- /other/Cargo.toml
+[package]
+name = "other"
+version = "0.0.0"
+
+//- /other/src/lib.rs
+
+//- /scratch.rs
+fn main() {
+    let s = String::new();
+}
+"#,
+    )
+    .root("other")
+    .with_sysroot(true)
+    .server()
+    .wait_until_workspace_is_loaded();
+
+    let hover = || {
+        server.send_request::<HoverRequest>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                server.doc_id("scratch.rs"),
+                Position::new(1, 9),
+            ),
+            work_done_progress_params: Default::default(),
+        })
+    };
+
+    let start = server.message_count();
+    server.notification::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: server.doc_id("scratch.rs").uri,
+            language_id: "rust".to_string(),
+            version: 0,
+            text: std::fs::read_to_string(server.path().join("scratch.rs")).unwrap(),
+        },
+    });
+    server.wait_until_reloaded_with(start, || hover().to_string().contains("String"));
+
+    let res = server.send_request::<Runnables>(RunnablesParams {
+        text_document: server.doc_id("scratch.rs"),
+        position: None,
+    });
+    let runnables = res.as_array().unwrap();
+    assert_eq!(runnables.len(), 1, "{}", res);
+    assert_eq!(runnables[0]["label"], "run scratch");
+    assert_eq!(runnables[0]["kind"], "rustc");
+    let args = &runnables[0]["args"];
+    let cargo_args = args["cargoArgs"].to_string();
+    assert!(cargo_args.contains("\"--edition\",\"2018\""), "{}", cargo_args);
+    assert!(cargo_args.contains("scratch.rs"), "{}", cargo_args);
+    assert!(args["executable"].is_string(), "{}", args);
+}
+
+#[test]
+fn loads_script_with_embedded_manifest() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let server = Project::with_fixture(
+        r#"
+//- /other/Cargo.toml
+[package]
+name = "other"
+version = "0.0.0"
+
+//- /other/src/lib.rs
+
+//- /dep/Cargo.toml
+[package]
+name = "dep"
+version = "0.0.0"
+
+//- /dep/src/lib.rs
+pub fn answer() -> u32 { 92 }
+
+//- /scripts/script.rs
+//! ```cargo
+//! [dependencies]
+//! dep = { path = "../dep" }
+//! ```
+fn main() {
+    dep::answer();
+}
+"#,
+    )
+    .root("other")
+    .server()
+    .wait_until_workspace_is_loaded();
+
+    let start = server.message_count();
+    server.notification::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: server.doc_id("scripts/script.rs").uri,
+            language_id: "rust".to_string(),
+            version: 0,
+            text: std::fs::read_to_string(server.path().join("scripts/script.rs")).unwrap(),
+        },
+    });
+    server.wait_until_reloaded_with(start, || {
+        let res = server.send_request::<HoverRequest>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                server.doc_id("scripts/script.rs"),
+                Position::new(5, 10),
+            ),
+            work_done_progress_params: Default::default(),
+        });
+        res.to_string().contains("fn answer() -> u32")
+    });
+}
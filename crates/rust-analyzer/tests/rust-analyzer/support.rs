@@ -9,7 +9,9 @@ use std::{
 use crossbeam_channel::{after, select, Receiver};
 use lsp_server::{Connection, Message, Notification, Request};
 use lsp_types::{
-    notification::Exit, request::Shutdown, TextDocumentIdentifier, Url, WorkDoneProgress,
+    notification::{Exit, Notification as _, PublishDiagnostics},
+    request::Shutdown,
+    PublishDiagnosticsParams, TextDocumentIdentifier, Url, WorkDoneProgress,
 };
 use lsp_types::{ProgressParams, ProgressParamsValue};
 use project_model::{CargoConfig, ProjectManifest};
@@ -203,23 +205,112 @@ impl Server {
         panic!("no response");
     }
     pub(crate) fn wait_until_workspace_is_loaded(self) -> Server {
-        self.wait_for_message_cond(1, &|msg: &Message| match msg {
+        self.wait_for_progress_end(1, "rustAnalyzer/roots scanned");
+        self
+    }
+    /// Waits until the build data was collected in the background and
+    /// applied. The roots are only scanned again if it changed the out dirs.
+    pub(crate) fn wait_until_build_data_is_loaded(self) -> Server {
+        self.wait_for_progress_end(1, "rustAnalyzer/build scripts");
+        self
+    }
+    /// Waits until the roots of the `n`th set of loaded workspaces were
+    /// scanned.
+    pub(crate) fn wait_until_roots_are_scanned(&self, n: usize) {
+        self.wait_for_progress_end(n, "rustAnalyzer/roots scanned");
+    }
+    /// Waits until `cond` holds, checking it each time the roots of a set of
+    /// workspaces were scanned after the `start`th message. Unlike counting
+    /// the scans, this isn't thrown off by unrelated reloads.
+    pub(crate) fn wait_until_reloaded_with(&self, start: usize, cond: impl Fn() -> bool) {
+        for n in 1.. {
+            self.wait_for_progress_end_after(start, n, "rustAnalyzer/roots scanned");
+            if cond() {
+                return;
+            }
+        }
+    }
+    /// Waits until the `n`th run of the check command finished.
+    pub(crate) fn wait_until_flycheck_is_done(&self, n: usize) {
+        self.wait_for_progress_end(n, "rustAnalyzer/cargo check");
+    }
+    /// Waits until a run of the check command finished after the `start`th
+    /// message, see `message_count`.
+    pub(crate) fn wait_until_flycheck_is_done_after(&self, start: usize) {
+        self.wait_for_progress_end_after(start, 1, "rustAnalyzer/cargo check");
+    }
+    /// The number of messages received so far. The `_after` methods only look
+    /// at the messages received after such a count.
+    pub(crate) fn message_count(&self) -> usize {
+        self.messages.borrow().len()
+    }
+    /// Waits until diagnostics matching `cond` are published for `rel_path`.
+    pub(crate) fn wait_for_diagnostics(
+        &self,
+        rel_path: &str,
+        cond: impl Fn(&[lsp_types::Diagnostic]) -> bool,
+    ) {
+        self.wait_for_diagnostics_after(0, rel_path, cond)
+    }
+    /// Waits until diagnostics matching `cond` are published for `rel_path`
+    /// after the `start`th message.
+    pub(crate) fn wait_for_diagnostics_after(
+        &self,
+        start: usize,
+        rel_path: &str,
+        cond: impl Fn(&[lsp_types::Diagnostic]) -> bool,
+    ) {
+        let uri = self.doc_id(rel_path).uri;
+        self.wait_for_message_cond_after(start, 1, &|msg: &Message| match msg {
+            Message::Notification(n) if n.method == PublishDiagnostics::METHOD => {
+                let params =
+                    n.clone().extract::<PublishDiagnosticsParams>(PublishDiagnostics::METHOD);
+                let params = params.unwrap();
+                params.uri == uri && cond(&params.diagnostics)
+            }
+            _ => false,
+        });
+    }
+    /// All the diagnostics published for `rel_path` after the `start`th
+    /// message, oldest first.
+    pub(crate) fn published_diagnostics_after(
+        &self,
+        start: usize,
+        rel_path: &str,
+    ) -> Vec<Vec<lsp_types::Diagnostic>> {
+        let uri = self.doc_id(rel_path).uri;
+        self.messages.borrow()[start..]
+            .iter()
+            .filter_map(|msg| match msg {
+                Message::Notification(n) if n.method == PublishDiagnostics::METHOD => {
+                    n.clone().extract::<PublishDiagnosticsParams>(PublishDiagnostics::METHOD).ok()
+                }
+                _ => None,
+            })
+            .filter(|params| params.uri == uri)
+            .map(|params| params.diagnostics)
+            .collect()
+    }
+    fn wait_for_progress_end(&self, n: usize, progress_token: &str) {
+        self.wait_for_progress_end_after(0, n, progress_token)
+    }
+    fn wait_for_progress_end_after(&self, start: usize, n: usize, progress_token: &str) {
+        self.wait_for_message_cond_after(start, n, &|msg: &Message| match msg {
             Message::Notification(n) if n.method == "$/progress" => {
                 match n.clone().extract::<ProgressParams>("$/progress").unwrap() {
                     ProgressParams {
                         token: lsp_types::ProgressToken::String(ref token),
                         value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(_)),
-                    } if token == "rustAnalyzer/roots scanned" => true,
+                    } if token == progress_token => true,
                     _ => false,
                 }
             }
             _ => false,
         });
-        self
     }
-    fn wait_for_message_cond(&self, n: usize, cond: &dyn Fn(&Message) -> bool) {
+    fn wait_for_message_cond_after(&self, start: usize, n: usize, cond: &dyn Fn(&Message) -> bool) {
         let mut total = 0;
-        for msg in self.messages.borrow().iter() {
+        for msg in self.messages.borrow()[start..].iter() {
             if cond(msg) {
                 total += 1
             }
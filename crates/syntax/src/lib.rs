@@ -168,6 +168,13 @@ impl SourceFile {
         assert_eq!(root.kind(), SyntaxKind::SOURCE_FILE);
         Parse { green, errors: Arc::new(errors), _ty: PhantomData }
     }
+
+    /// Errors for the prefixes reserved by Rust 2021, like the `f` of
+    /// `f"..."`. These are not part of the parse errors as the parser does not
+    /// know the edition.
+    pub fn reserved_prefix_errors(&self) -> Vec<SyntaxError> {
+        validation::validate_reserved_prefixes(self.syntax())
+    }
 }
 
 impl ast::Path {
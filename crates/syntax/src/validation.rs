@@ -6,7 +6,7 @@ use crate::{
     algo,
     ast::{self, VisibilityOwner},
     match_ast, AstNode, SyntaxError,
-    SyntaxKind::{CONST, FN, INT_NUMBER, TYPE_ALIAS},
+    SyntaxKind::{BYTE, BYTE_STRING, CHAR, CONST, FN, IDENT, INT_NUMBER, STRING, TYPE_ALIAS},
     SyntaxNode, SyntaxToken, TextSize, T,
 };
 use rowan::Direction;
@@ -163,6 +163,28 @@ fn validate_literal(literal: ast::Literal, acc: &mut Vec<SyntaxError>) {
     }
 }
 
+/// Rust 2021 reserves `prefix#`, `prefix"..."` and `prefix'...'` for future
+/// use, so an identifier immediately followed by a `#` or a quoted literal is
+/// an error there.
+pub(crate) fn validate_reserved_prefixes(root: &SyntaxNode) -> Vec<SyntaxError> {
+    root.descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|token| token.kind() == IDENT)
+        .filter_map(|token| {
+            let next = token.next_token()?;
+            let is_reserved = matches!(next.kind(), T![#] | STRING | BYTE_STRING | CHAR | BYTE)
+                && next.text_range().start() == token.text_range().end();
+            if !is_reserved {
+                return None;
+            }
+            Some(SyntaxError::new(
+                format!("prefix `{}` is unknown", token.text()),
+                token.text_range(),
+            ))
+        })
+        .collect()
+}
+
 pub(crate) fn validate_block_structure(root: &SyntaxNode) {
     let mut stack = Vec::new();
     for node in root.descendants() {
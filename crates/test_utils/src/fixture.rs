@@ -12,6 +12,8 @@ pub struct Fixture {
     pub deps: Vec<String>,
     pub cfg_atoms: Vec<String>,
     pub cfg_key_values: Vec<(String, String)>,
+    pub alt_cfg_atoms: Vec<String>,
+    pub alt_cfg_key_values: Vec<(String, String)>,
     pub edition: Option<String>,
     pub env: FxHashMap<String, String>,
 }
@@ -69,6 +71,8 @@ impl Fixture {
         let mut edition = None;
         let mut cfg_atoms = Vec::new();
         let mut cfg_key_values = Vec::new();
+        let mut alt_cfg_atoms = Vec::new();
+        let mut alt_cfg_key_values = Vec::new();
         let mut env = FxHashMap::default();
         for component in components[1..].iter() {
             let (key, value) = split_once(component, ':').unwrap();
@@ -84,6 +88,14 @@ impl Fixture {
                         }
                     }
                 }
+                "alt_cfg" => {
+                    for entry in value.split(',') {
+                        match split_once(entry, '=') {
+                            Some((k, v)) => alt_cfg_key_values.push((k.to_string(), v.to_string())),
+                            None => alt_cfg_atoms.push(entry.to_string()),
+                        }
+                    }
+                }
                 "env" => {
                     for key in value.split(',') {
                         if let Some((k, v)) = split_once(key, '=') {
@@ -102,6 +114,8 @@ impl Fixture {
             deps,
             cfg_atoms,
             cfg_key_values,
+            alt_cfg_atoms,
+            alt_cfg_key_values,
             edition,
             env,
         }
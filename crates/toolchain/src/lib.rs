@@ -17,6 +17,15 @@ pub fn rustfmt() -> PathBuf {
     get_path_for_executable("rustfmt")
 }
 
+/// Returns the directory Cargo keeps its registry and binaries in, `$CARGO_HOME`
+/// or `~/.cargo`.
+pub fn cargo_home() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("CARGO_HOME") {
+        return Some(path.into());
+    }
+    home::home_dir().map(|path| path.join(".cargo"))
+}
+
 /// Return a `PathBuf` to use for the given executable.
 ///
 /// E.g., `get_path_for_executable("cargo")` may return just `cargo` if that
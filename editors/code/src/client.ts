@@ -45,7 +45,11 @@ export function createClient(serverPath: string, cwd: string): lc.LanguageClient
     );
 
     const clientOptions: lc.LanguageClientOptions = {
-        documentSelector: [{ scheme: 'file', language: 'rust' }],
+        documentSelector: [
+            { scheme: 'file', language: 'rust' },
+            // Only completions and hovers are served for manifests.
+            { scheme: 'file', pattern: '**/Cargo.toml' },
+        ],
         initializationOptions: vscode.workspace.getConfiguration("rust-analyzer"),
         diagnosticCollectionName: "rustc",
         traceOutputChannel,
@@ -59,7 +59,7 @@ export interface RunnablesParams {
 export interface Runnable {
     label: string;
     location?: lc.LocationLink;
-    kind: "cargo";
+    kind: "cargo" | "rustc";
     args: {
         workspaceRoot?: string;
         cargoArgs: string[];
@@ -67,6 +67,7 @@ export interface Runnable {
         executableArgs: string[];
         expectTest?: boolean;
         overrideCargo?: string;
+        executable?: string;
     };
 }
 export const runnables = new lc.RequestType<RunnablesParams, Runnable[], void>("experimental/runnables");
@@ -36,7 +36,7 @@ export async function selectRunnable(ctx: Ctx, prevRunnable?: RunnableQuickPick,
             continue;
         }
 
-        if (debuggeeOnly && (r.label.startsWith('doctest') || r.label.startsWith('cargo'))) {
+        if (debuggeeOnly && (r.kind !== "cargo" || r.label.startsWith('doctest') || r.label.startsWith('cargo'))) {
             continue;
         }
         items.push(new RunnableQuickPick(r));
@@ -121,8 +121,10 @@ export function prepareEnv(runnable: ra.Runnable, runnableEnvCfg: RunnableEnvCfg
 }
 
 export async function createTask(runnable: ra.Runnable, config: Config): Promise<vscode.Task> {
+    if (runnable.kind === "rustc") {
+        return createRustcTask(runnable, config);
+    }
     if (runnable.kind !== "cargo") {
-        // rust-analyzer supports only one kind, "cargo"
         // do not use tasks.TASK_TYPE here, these are completely different meanings.
 
         throw `Unexpected runnable kind: ${runnable.kind}`;
@@ -151,3 +153,34 @@ export async function createTask(runnable: ra.Runnable, config: Config): Promise
 
     return cargoTask;
 }
+
+function createRustcTask(runnable: ra.Runnable, config: Config): vscode.Task {
+    const definition: tasks.CargoTaskDefinition = {
+        type: tasks.TASK_TYPE,
+        cwd: runnable.args.workspaceRoot || ".",
+        env: prepareEnv(runnable, config.runnableEnv),
+    };
+
+    // Compile the file, then run the executable, in a single shell command.
+    const compile = [runnable.args.overrideCargo || "rustc", ...runnable.args.cargoArgs];
+    const run = [runnable.args.executable!, ...runnable.args.executableArgs];
+    const commandLine = `${compile.map(quoteArg).join(" ")} && ${run.map(quoteArg).join(" ")}`;
+    const exec = new vscode.ShellExecution(commandLine, definition);
+
+    const target = vscode.workspace.workspaceFolders![0]; // safe, see main activate()
+    const rustcTask = new vscode.Task(definition, target, runnable.label, tasks.TASK_SOURCE, exec, ['$rustc']);
+    rustcTask.presentationOptions.clear = true;
+
+    return rustcTask;
+}
+
+// Quotes `arg` so that the shell passes it on as is.
+function quoteArg(arg: string): string {
+    if (/^[\w\-.,/:=+@]+$/.test(arg)) {
+        return arg;
+    }
+    if (process.platform === "win32") {
+        return `"${arg.replace(/"/g, '\\"')}"`;
+    }
+    return `'${arg.replace(/'/g, "'\\''")}'`;
+}